@@ -0,0 +1,50 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use rulemorph::{parse_rule_file, transform, transform_parallel};
+use serde_json::json;
+
+const EXTENDED_RULES: &str = include_str!("../tests/fixtures/t13_expr_extended/rules.yaml");
+
+fn bench_sequential_vs_parallel(c: &mut Criterion) {
+    let rule = parse_rule_file(EXTENDED_RULES).expect("failed to parse rules");
+    let input = build_extended_input(20000);
+
+    let mut group = c.benchmark_group("transform_extended_20k");
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            let output = transform(&rule, black_box(&input), None).expect("transform failed");
+            black_box(output);
+        })
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| {
+            let output =
+                transform_parallel(&rule, black_box(&input), None).expect("transform failed");
+            black_box(output);
+        })
+    });
+    group.finish();
+}
+
+fn build_extended_input(count: usize) -> String {
+    let mut records = Vec::with_capacity(count);
+    for i in 0..count {
+        records.push(json!({
+            "text": format!("abc-{}-abc", i),
+            "regex_text": "a1b2c3",
+            "csv": "a,b,c",
+            "pad": "7",
+            "num_a": 80.6,
+            "num_b": "2.5",
+            "num_c": i as i64,
+            "base_value": 255,
+            "date_simple": "2024-01-02 03:04:05",
+            "date_tz": "2024-01-02T03:04:05+09:00",
+            "unix_s": "1970-01-01T00:00:01Z",
+            "unix_ms": "1970-01-01T00:00:00.123Z"
+        }));
+    }
+    serde_json::to_string(&records).expect("failed to serialize input")
+}
+
+criterion_group!(benches, bench_sequential_vs_parallel);
+criterion_main!(benches);
@@ -0,0 +1,53 @@
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use rulemorph::{parse_rule_file, transform};
+use serde_json::json;
+
+// Every step sees the whole array as its pipe value, and the let bindings hold it too.
+const DEEP_PIPE_RULES: &str = r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "total"
+    expr:
+      - "@input.items"
+      - let: { all: "$" }
+      - { op: "slice", args: [0] }
+      - let: { again: "$" }
+      - { op: "slice", args: [0] }
+      - { op: "sort_by", args: ["score"] }
+      - { op: "slice", args: [0] }
+      - map:
+        - let: { score: "@item.score" }
+        - "@score"
+        - { op: "multiply", args: [2] }
+      - { op: "slice", args: [0] }
+      - { op: "length" }
+"#;
+
+fn bench_deep_pipe_over_big_array(c: &mut Criterion) {
+    let rule = parse_rule_file(DEEP_PIPE_RULES).expect("failed to parse rules");
+    let input = build_input(1, 500);
+
+    let mut group = c.benchmark_group("v2_pipe");
+    group.sample_size(10);
+    group.bench_function("deep_pipe_500_items", |b| {
+        b.iter(|| {
+            let output = transform(&rule, black_box(&input), None).expect("transform failed");
+            black_box(output);
+        })
+    });
+    group.finish();
+}
+
+fn build_input(records: usize, items: usize) -> String {
+    let items: Vec<_> = (0..items)
+        .map(|i| json!({ "id": i, "score": (i * 37) % 1000, "name": format!("item-{}", i) }))
+        .collect();
+    let records: Vec<_> = (0..records).map(|_| json!({ "items": items })).collect();
+    serde_json::to_string(&records).expect("failed to serialize input")
+}
+
+criterion_group!(benches, bench_deep_pipe_over_big_array);
+criterion_main!(benches);
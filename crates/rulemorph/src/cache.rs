@@ -1,6 +1,7 @@
 use std::collections::{HashMap, VecDeque};
 use std::hash::Hash;
 
+/// Map that evicts the least recently used entry once `capacity` is reached.
 pub struct LruCache<K, V> {
     map: HashMap<K, V>,
     order: VecDeque<K>,
@@ -49,6 +50,32 @@ where
         self.map.insert(key, value);
     }
 
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.order.clear();
+    }
+
+    /// Change the capacity, evicting least recently used entries that no longer fit.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.map.len() > capacity {
+            match self.order.pop_front() {
+                Some(evicted) => {
+                    self.map.remove(&evicted);
+                }
+                None => break,
+            }
+        }
+    }
+
     fn touch(&mut self, key: &K) {
         if let Some(pos) = self.order.iter().position(|k| k == key) {
             self.order.remove(pos);
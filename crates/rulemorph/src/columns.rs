@@ -0,0 +1,178 @@
+//! Flatten output records into a fixed column set for tabular (CSV) output.
+//!
+//! Nested objects become dotted column names (`user.name`). Arrays of scalars follow
+//! [`ArrayColumns`]; any other array is written as compact JSON in a single cell.
+//! [`records_to_csv`] keeps only top-level fields and writes nested values as JSON.
+
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+use serde_json::Value as JsonValue;
+
+/// Column used for records that are not objects.
+pub const VALUE_COLUMN: &str = "value";
+
+/// How arrays of scalars are laid out in columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArrayColumns {
+    /// One column holding the items joined with the separator.
+    Join(String),
+    /// One column per item, named `key[0]`, `key[1]`, ...
+    Index,
+}
+
+impl Default for ArrayColumns {
+    fn default() -> Self {
+        ArrayColumns::Join(";".to_string())
+    }
+}
+
+/// Records flattened to a shared header.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnTable {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Flatten every record and build the union of their columns.
+///
+/// Columns appear in the order they are first seen across records, so the header is stable
+/// for a given input. Keys within one object are visited in sorted order, as `serde_json`
+/// maps do not keep insertion order. Cells for columns a record does not have are empty
+/// strings.
+pub fn coerce_records_to_columns(records: &[JsonValue], arrays: &ArrayColumns) -> ColumnTable {
+    build_table(
+        records
+            .iter()
+            .map(|record| flatten_record(record, arrays))
+            .collect(),
+    )
+}
+
+/// Render records as CSV text with a header row.
+///
+/// Columns are the union of top-level keys: each record's new keys, sorted, follow those
+/// of earlier records. Nested objects and arrays are written as compact JSON in one cell;
+/// `null` and absent fields are empty.
+pub fn records_to_csv(records: &[JsonValue]) -> io::Result<String> {
+    let table = build_table(records.iter().map(top_level_cells).collect());
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&table.columns)?;
+    for row in &table.rows {
+        writer.write_record(row)?;
+    }
+    let bytes = writer.into_inner().map_err(|err| err.into_error())?;
+    String::from_utf8(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn build_table(flattened: Vec<Vec<(String, String)>>) -> ColumnTable {
+    let mut columns = Vec::new();
+    let mut seen = HashSet::new();
+    for cells in &flattened {
+        for (column, _) in cells {
+            if seen.insert(column.as_str()) {
+                columns.push(column.clone());
+            }
+        }
+    }
+
+    let rows = flattened
+        .into_iter()
+        .map(|cells| row_for_columns(cells, &columns))
+        .collect();
+    ColumnTable { columns, rows }
+}
+
+/// Flatten one record to the given columns, leaving unknown columns empty.
+///
+/// Values that flatten to a column not in `columns` are dropped.
+pub fn coerce_record_to_columns(
+    record: &JsonValue,
+    columns: &[String],
+    arrays: &ArrayColumns,
+) -> Vec<String> {
+    row_for_columns(flatten_record(record, arrays), columns)
+}
+
+fn row_for_columns(cells: Vec<(String, String)>, columns: &[String]) -> Vec<String> {
+    let mut cells: HashMap<String, String> = cells.into_iter().collect();
+    columns
+        .iter()
+        .map(|column| cells.remove(column).unwrap_or_default())
+        .collect()
+}
+
+fn flatten_record(record: &JsonValue, arrays: &ArrayColumns) -> Vec<(String, String)> {
+    let mut cells = Vec::new();
+    match record {
+        JsonValue::Object(map) => {
+            for (key, value) in map {
+                flatten_value(key.clone(), value, arrays, &mut cells);
+            }
+        }
+        other => flatten_value(VALUE_COLUMN.to_string(), other, arrays, &mut cells),
+    }
+    cells
+}
+
+fn top_level_cells(record: &JsonValue) -> Vec<(String, String)> {
+    match record {
+        JsonValue::Object(map) => map
+            .iter()
+            .map(|(key, value)| (key.clone(), json_cell(value)))
+            .collect(),
+        other => vec![(VALUE_COLUMN.to_string(), json_cell(other))],
+    }
+}
+
+fn json_cell(value: &JsonValue) -> String {
+    if is_scalar(value) {
+        scalar_cell(value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn flatten_value(
+    column: String,
+    value: &JsonValue,
+    arrays: &ArrayColumns,
+    cells: &mut Vec<(String, String)>,
+) {
+    match value {
+        JsonValue::Object(map) => {
+            for (key, child) in map {
+                flatten_value(format!("{}.{}", column, key), child, arrays, cells);
+            }
+        }
+        JsonValue::Array(items) if items.iter().all(is_scalar) => match arrays {
+            ArrayColumns::Join(separator) => {
+                let joined = items
+                    .iter()
+                    .map(scalar_cell)
+                    .collect::<Vec<_>>()
+                    .join(separator);
+                cells.push((column, joined));
+            }
+            ArrayColumns::Index => {
+                for (index, item) in items.iter().enumerate() {
+                    cells.push((format!("{}[{}]", column, index), scalar_cell(item)));
+                }
+            }
+        },
+        JsonValue::Array(_) => cells.push((column, value.to_string())),
+        scalar => cells.push((column, scalar_cell(scalar))),
+    }
+}
+
+fn is_scalar(value: &JsonValue) -> bool {
+    !matches!(value, JsonValue::Array(_) | JsonValue::Object(_))
+}
+
+fn scalar_cell(value: &JsonValue) -> String {
+    match value {
+        JsonValue::Null => String::new(),
+        JsonValue::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
@@ -2,8 +2,11 @@ use std::collections::{HashMap, HashSet};
 
 use serde_json::Value as JsonValue;
 
-use crate::model::{Expr, RuleFile};
+use crate::model::{Expr, Mapping, RuleFile};
 use crate::path::{PathToken, parse_path};
+use crate::transform::expr_to_json_for_v2_pipe;
+use crate::v2_model::{V2Expr, V2Pipe, V2Start, V2Step};
+use crate::v2_parser::parse_v2_pipe_from_value;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DtoLanguage {
@@ -14,6 +17,7 @@ pub enum DtoLanguage {
     Java,
     Kotlin,
     Swift,
+    JsonSchema,
 }
 
 #[derive(Debug, Clone)]
@@ -53,6 +57,7 @@ pub fn generate_dto(
         DtoLanguage::Java => render_java(&schema, name),
         DtoLanguage::Kotlin => render_kotlin(&schema, name),
         DtoLanguage::Swift => render_swift(&schema, name),
+        DtoLanguage::JsonSchema => render_json_schema(&schema, name),
     }
 }
 
@@ -98,16 +103,15 @@ fn build_schema(rule: &RuleFile) -> Result<SchemaNode, DtoError> {
             parse_path(&mapping.target).map_err(|_| DtoError::new("target path is invalid"))?;
         if tokens
             .iter()
-            .any(|token| matches!(token, PathToken::Index(_)))
+            .any(|token| !matches!(token, PathToken::Key(_)))
         {
             return Err(DtoError::new("target path must not include indexes"));
         }
 
         let mut keys = Vec::new();
         for token in tokens {
-            match token {
-                PathToken::Key(key) => keys.push(key),
-                PathToken::Index(_) => {}
+            if let PathToken::Key(key) = token {
+                keys.push(key);
             }
         }
 
@@ -128,8 +132,7 @@ fn build_schema(rule: &RuleFile) -> Result<SchemaNode, DtoError> {
             Some(Expr::Literal(JsonValue::Bool(true))) => false,
             _ => true,
         };
-        let optional = conditional
-            || !(mapping.required || mapping.value.is_some() || mapping.default.is_some());
+        let optional = conditional || mapping_may_be_absent(mapping, rule.version);
 
         insert_field(&mut root, &keys, field_type, optional)?;
     }
@@ -137,6 +140,72 @@ fn build_schema(rule: &RuleFile) -> Result<SchemaNode, DtoError> {
     Ok(root)
 }
 
+/// Whether a mapping can leave its target missing or null. Expressions are
+/// only treated as always present when that is provable from their shape.
+fn mapping_may_be_absent(mapping: &Mapping, version: u8) -> bool {
+    if mapping.required || mapping.value.is_some() || mapping.default.is_some() {
+        return false;
+    }
+    match &mapping.expr {
+        Some(expr) => !expr_always_present(expr, version),
+        None => true,
+    }
+}
+
+fn expr_always_present(expr: &Expr, version: u8) -> bool {
+    if version >= 2
+        && let Some(pipe_json) = expr_to_json_for_v2_pipe(expr)
+    {
+        return parse_v2_pipe_from_value(&pipe_json)
+            .map(|pipe| v2_pipe_always_present(&pipe, false))
+            .unwrap_or(false);
+    }
+    v1_expr_always_present(expr)
+}
+
+fn v1_expr_always_present(expr: &Expr) -> bool {
+    match expr {
+        Expr::Literal(value) => !value.is_null(),
+        Expr::Op(expr_op) if expr_op.op == "coalesce" => {
+            expr_op.args.iter().any(v1_expr_always_present)
+        }
+        _ => false,
+    }
+}
+
+fn v2_pipe_always_present(pipe: &V2Pipe, pipe_value_present: bool) -> bool {
+    let mut present = match &pipe.start {
+        V2Start::Literal(value) => !value.is_null(),
+        V2Start::PipeValue => pipe_value_present,
+        V2Start::V1Expr(expr) => v1_expr_always_present(expr),
+        V2Start::Ref(_) => false,
+    };
+    for step in &pipe.steps {
+        present = match step {
+            V2Step::Op(op_step) if matches!(op_step.op.as_str(), "coalesce" | "default") => {
+                present || op_step.args.iter().any(v2_expr_always_present)
+            }
+            V2Step::If(if_step) => {
+                v2_pipe_always_present(&if_step.then_branch, present)
+                    && if_step
+                        .else_branch
+                        .as_ref()
+                        .map(|else_branch| v2_pipe_always_present(else_branch, present))
+                        .unwrap_or(present)
+            }
+            _ => false,
+        };
+    }
+    present
+}
+
+fn v2_expr_always_present(expr: &V2Expr) -> bool {
+    match expr {
+        V2Expr::Pipe(pipe) => v2_pipe_always_present(pipe, false),
+        V2Expr::V1Fallback(expr) => v1_expr_always_present(expr),
+    }
+}
+
 fn insert_field(
     node: &mut SchemaNode,
     keys: &[String],
@@ -285,9 +354,11 @@ fn collect_types<'a>(
 fn field_identifier(lang: DtoLanguage, key: &str, used: &mut HashMap<String, usize>) -> String {
     let base = match lang {
         DtoLanguage::Rust | DtoLanguage::Python => snake_case(&words_from_key(key)),
-        DtoLanguage::TypeScript | DtoLanguage::Java | DtoLanguage::Kotlin | DtoLanguage::Swift => {
-            lower_camel(&words_from_key(key))
-        }
+        DtoLanguage::TypeScript
+        | DtoLanguage::Java
+        | DtoLanguage::Kotlin
+        | DtoLanguage::Swift
+        | DtoLanguage::JsonSchema => lower_camel(&words_from_key(key)),
         DtoLanguage::Go => pascal_case(&words_from_key(key)),
     };
 
@@ -403,6 +474,7 @@ fn is_reserved(lang: DtoLanguage, ident: &str) -> bool {
         DtoLanguage::Java => is_reserved_java(ident),
         DtoLanguage::Kotlin => is_reserved_kotlin(ident),
         DtoLanguage::Swift => is_reserved_swift(ident),
+        DtoLanguage::JsonSchema => false,
     }
 }
 
@@ -849,6 +921,7 @@ fn render_python(schema: &SchemaNode, name: &str) -> Result<String, DtoError> {
     let uses_rename = schema_has_rename(schema, DtoLanguage::Python);
 
     let mut out = String::new();
+    out.push_str("from __future__ import annotations\n\n");
     out.push_str("from dataclasses import dataclass");
     if uses_rename {
         out.push_str(", field");
@@ -1247,6 +1320,55 @@ fn swift_type_for_field(
     if optional { format!("{}?", base) } else { base }
 }
 
+fn render_json_schema(schema: &SchemaNode, name: &str) -> Result<String, DtoError> {
+    let mut root = match json_schema_object(schema) {
+        JsonValue::Object(map) => map,
+        _ => unreachable!("json_schema_object always returns an object"),
+    };
+    root.insert(
+        "$schema".to_string(),
+        JsonValue::String(JSON_SCHEMA_DRAFT.to_string()),
+    );
+    root.insert("title".to_string(), JsonValue::String(name.to_string()));
+    serde_json::to_string_pretty(&JsonValue::Object(root))
+        .map_err(|err| DtoError::new(format!("failed to render JSON Schema: {}", err)))
+}
+
+fn json_schema_object(node: &SchemaNode) -> JsonValue {
+    let mut properties = serde_json::Map::new();
+    let mut required = Vec::new();
+    for field in &node.fields {
+        let optional = match &field.field_type {
+            FieldType::Object(child) => !node_has_required(child),
+            _ => field.optional,
+        };
+        if !optional {
+            required.push(JsonValue::String(field.key.clone()));
+        }
+        properties.insert(field.key.clone(), json_schema_for_field(field));
+    }
+
+    let mut object = serde_json::Map::new();
+    object.insert("type".to_string(), JsonValue::String("object".to_string()));
+    object.insert("properties".to_string(), JsonValue::Object(properties));
+    if !required.is_empty() {
+        object.insert("required".to_string(), JsonValue::Array(required));
+    }
+    JsonValue::Object(object)
+}
+
+fn json_schema_for_field(field: &Field) -> JsonValue {
+    let type_name = match &field.field_type {
+        FieldType::Primitive(PrimitiveType::String) => "string",
+        FieldType::Primitive(PrimitiveType::Int) => "integer",
+        FieldType::Primitive(PrimitiveType::Float) => "number",
+        FieldType::Primitive(PrimitiveType::Bool) => "boolean",
+        FieldType::Object(child) => return json_schema_object(child),
+        FieldType::JsonValue => return JsonValue::Object(serde_json::Map::new()),
+    };
+    serde_json::json!({ "type": type_name })
+}
+
 fn schema_has_optional(node: &SchemaNode) -> bool {
     for field in &node.fields {
         match &field.field_type {
@@ -1281,4 +1403,6 @@ fn schema_has_rename(node: &SchemaNode, lang: DtoLanguage) -> bool {
     false
 }
 
+const JSON_SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
 const SWIFT_JSON_VALUE: &str = "enum JSONValue: Codable {\n    case string(String)\n    case number(Double)\n    case bool(Bool)\n    case object([String: JSONValue])\n    case array([JSONValue])\n    case null\n\n    init(from decoder: Decoder) throws {\n        let container = try decoder.singleValueContainer()\n        if container.decodeNil() {\n            self = .null\n        } else if let value = try? container.decode(Bool.self) {\n            self = .bool(value)\n        } else if let value = try? container.decode(Double.self) {\n            self = .number(value)\n        } else if let value = try? container.decode(String.self) {\n            self = .string(value)\n        } else if let value = try? container.decode([String: JSONValue].self) {\n            self = .object(value)\n        } else if let value = try? container.decode([JSONValue].self) {\n            self = .array(value)\n        } else {\n            throw DecodingError.typeMismatch(JSONValue.self, DecodingError.Context(codingPath: decoder.codingPath, debugDescription: \"Unsupported JSON value\"))\n        }\n    }\n\n    func encode(to encoder: Encoder) throws {\n        var container = encoder.singleValueContainer()\n        switch self {\n        case .string(let value):\n            try container.encode(value)\n        case .number(let value):\n            try container.encode(value)\n        case .bool(let value):\n            try container.encode(value)\n        case .object(let value):\n            try container.encode(value)\n        case .array(let value):\n            try container.encode(value)\n        case .null:\n            try container.encodeNil()\n        }\n    }\n}\n";
@@ -1,3 +1,7 @@
+use std::path::PathBuf;
+
+use crate::locator::YamlLocator;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ErrorCode {
     InvalidVersion,
@@ -6,7 +10,7 @@ pub enum ErrorCode {
     MissingCsvSection,
     MissingJsonSection,
     InvalidDelimiterLength,
-    MissingCsvColumns,
+    InvalidQuoteLength,
 
     MissingTarget,
     DuplicateTarget,
@@ -36,6 +40,13 @@ pub enum ErrorCode {
     StepsMappingExclusive,
     InvalidStep,
     InvalidFinalize,
+
+    // deprecation (warning unless validated in strict mode)
+    DeprecatedOp,
+
+    // include resolution errors
+    InvalidInclude,
+    IncludeCycle,
 }
 
 impl ErrorCode {
@@ -47,7 +58,7 @@ impl ErrorCode {
             ErrorCode::MissingCsvSection => "MissingCsvSection",
             ErrorCode::MissingJsonSection => "MissingJsonSection",
             ErrorCode::InvalidDelimiterLength => "InvalidDelimiterLength",
-            ErrorCode::MissingCsvColumns => "MissingCsvColumns",
+            ErrorCode::InvalidQuoteLength => "InvalidQuoteLength",
             ErrorCode::MissingTarget => "MissingTarget",
             ErrorCode::DuplicateTarget => "DuplicateTarget",
             ErrorCode::SourceValueExprExclusive => "SourceValueExprExclusive",
@@ -70,6 +81,9 @@ impl ErrorCode {
             ErrorCode::StepsMappingExclusive => "StepsMappingExclusive",
             ErrorCode::InvalidStep => "InvalidStep",
             ErrorCode::InvalidFinalize => "InvalidFinalize",
+            ErrorCode::DeprecatedOp => "DeprecatedOp",
+            ErrorCode::InvalidInclude => "InvalidInclude",
+            ErrorCode::IncludeCycle => "IncludeCycle",
         }
     }
 }
@@ -86,6 +100,7 @@ pub struct RuleError {
     pub message: String,
     pub location: Option<YamlLocation>,
     pub path: Option<String>,
+    pub file: Option<PathBuf>,
 }
 
 impl RuleError {
@@ -95,6 +110,7 @@ impl RuleError {
             message: message.into(),
             location: None,
             path: None,
+            file: None,
         }
     }
 
@@ -103,6 +119,11 @@ impl RuleError {
         self
     }
 
+    pub fn with_file(mut self, file: impl Into<PathBuf>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
     pub fn with_location(mut self, line: usize, column: usize) -> Self {
         self.location = Some(YamlLocation { line, column });
         self
@@ -121,6 +142,8 @@ pub enum TransformErrorKind {
     TypeCastFailed,
     ExprError,
     AssertionFailed,
+    TypeAssertionFailed,
+    Deprecated,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -150,6 +173,7 @@ pub struct TransformError {
     pub kind: TransformErrorKind,
     pub message: String,
     pub path: Option<String>,
+    pub location: Option<YamlLocation>,
 }
 
 impl TransformError {
@@ -158,6 +182,7 @@ impl TransformError {
             kind,
             message: message.into(),
             path: None,
+            location: None,
         }
     }
 
@@ -165,6 +190,45 @@ impl TransformError {
         self.path = Some(path.into());
         self
     }
+
+    pub fn with_location(mut self, line: usize, column: usize) -> Self {
+        self.location = Some(YamlLocation { line, column });
+        self
+    }
+
+    /// Attach the line and column of the error path in the rule's YAML `source`.
+    ///
+    /// Paths inside an expression resolve to the nearest enclosing step or key. The error
+    /// is returned unchanged when it has no path or the path is not found. This scans
+    /// `source` on every call; use [`TransformErrorLocator`] to locate many errors.
+    pub fn with_source_location(self, source: &str) -> Self {
+        TransformErrorLocator::new(source).locate(self)
+    }
+}
+
+/// Locates transform errors in a rule's YAML source, scanning the source once.
+#[derive(Debug)]
+pub struct TransformErrorLocator {
+    locator: YamlLocator,
+}
+
+impl TransformErrorLocator {
+    pub fn new(source: &str) -> Self {
+        Self {
+            locator: YamlLocator::from_str(source),
+        }
+    }
+
+    /// Attach the location of the error path, as [`TransformError::with_source_location`].
+    pub fn locate(&self, mut err: TransformError) -> TransformError {
+        if let Some(path) = &err.path {
+            let location = self.locator.nearest_location_for(path);
+            if location.is_some() {
+                err.location = location;
+            }
+        }
+        err
+    }
 }
 
 impl std::fmt::Display for TransformError {
@@ -189,6 +253,16 @@ impl From<TransformError> for TransformWarning {
     }
 }
 
+impl From<TransformWarning> for TransformError {
+    fn from(warning: TransformWarning) -> Self {
+        let mut err = TransformError::new(warning.kind, warning.message);
+        if let Some(path) = warning.path {
+            err = err.with_path(path);
+        }
+        err
+    }
+}
+
 impl From<csv::Error> for TransformError {
     fn from(err: csv::Error) -> Self {
         TransformError::new(
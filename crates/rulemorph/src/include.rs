@@ -0,0 +1,287 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::error::{ErrorCode, RuleError, TransformErrorLocator, ValidationResult};
+use crate::locator::YamlLocator;
+use crate::model::{Mapping, RuleFile};
+use crate::validator::{validate_rule_file, validate_rule_file_strict};
+
+/// Shared mappings pulled into a rule file through `include`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleFragment {
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    mappings: Vec<Mapping>,
+}
+
+/// A rule file with its includes merged in.
+///
+/// Keeps the source of every file involved so validation errors can be
+/// reported against the file and line a mapping was defined in.
+#[derive(Debug, Clone)]
+pub struct ResolvedRuleFile {
+    pub rule: RuleFile,
+    files: Vec<SourceFile>,
+    origins: Vec<MappingOrigin>,
+}
+
+#[derive(Debug, Clone)]
+struct SourceFile {
+    path: PathBuf,
+    source: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct MappingOrigin {
+    file: usize,
+    index: usize,
+}
+
+impl ResolvedRuleFile {
+    /// Path of the root rule file.
+    pub fn path(&self) -> &Path {
+        &self.files[0].path
+    }
+
+    /// YAML source of the root rule file.
+    pub fn source(&self) -> &str {
+        &self.files[0].source
+    }
+
+    /// Paths of the files pulled in through `include`, in load order.
+    pub fn included_files(&self) -> impl Iterator<Item = &Path> {
+        self.files[1..].iter().map(|file| file.path.as_path())
+    }
+
+    /// Locator for transform errors, when the rule has no includes.
+    ///
+    /// Runtime paths index the merged rule, so they only line up with the root file's
+    /// source when nothing was merged in.
+    pub fn error_locator(&self) -> Option<TransformErrorLocator> {
+        (self.files.len() == 1).then(|| TransformErrorLocator::new(self.source()))
+    }
+
+    /// File that defines the merged mapping at `index`.
+    pub fn mapping_file(&self, index: usize) -> Option<&Path> {
+        self.origins
+            .get(index)
+            .map(|origin| self.files[origin.file].path.as_path())
+    }
+
+    fn attribute(&self, result: ValidationResult) -> ValidationResult {
+        let errors = match result {
+            Ok(()) => return Ok(()),
+            Err(errors) => errors,
+        };
+        let locators: Vec<YamlLocator> = self
+            .files
+            .iter()
+            .map(|file| YamlLocator::from_str(&file.source))
+            .collect();
+        let errors = errors
+            .into_iter()
+            .map(|mut err| {
+                let mut file = 0;
+                if let Some(path) = err.path.take() {
+                    let path = match split_mapping_index(&path)
+                        .and_then(|(index, rest)| self.origins.get(index).map(|o| (o, rest)))
+                    {
+                        Some((origin, rest)) => {
+                            file = origin.file;
+                            format!("mappings[{}]{}", origin.index, rest)
+                        }
+                        None => path,
+                    };
+                    if let Some(location) = locators[file].location_for(&path) {
+                        err = err.with_location(location.line, location.column);
+                    }
+                    err = err.with_path(path);
+                }
+                err.with_file(self.files[file].path.clone())
+            })
+            .collect();
+        Err(errors)
+    }
+}
+
+/// Read a rule file and merge the mappings of every file it includes.
+///
+/// Include paths are resolved relative to the including file. Included
+/// mappings come first, and a mapping defined in the including file replaces
+/// an included mapping with the same target.
+pub fn load_rule_file_with_includes(path: &Path) -> Result<ResolvedRuleFile, RuleError> {
+    let source = read_source(path)?;
+    let rule = crate::parse_rule_file(&source).map_err(|err| {
+        RuleError::new(
+            ErrorCode::InvalidInclude,
+            format!("failed to parse rule: {}", err),
+        )
+        .with_file(path)
+    })?;
+    resolve_rule_includes(rule, source, path)
+}
+
+/// Merge the includes of an already parsed rule file read from `path`.
+pub fn resolve_rule_includes(
+    mut rule: RuleFile,
+    source: String,
+    path: &Path,
+) -> Result<ResolvedRuleFile, RuleError> {
+    let mut resolver = IncludeResolver {
+        files: vec![SourceFile {
+            path: path.to_path_buf(),
+            source,
+        }],
+        stack: vec![canonical_path(path)],
+    };
+    let mut merged = resolver.resolve_includes(0, &rule.include)?;
+    let local = std::mem::take(&mut rule.mappings)
+        .into_iter()
+        .enumerate()
+        .map(|(index, mapping)| (mapping, MappingOrigin { file: 0, index }))
+        .collect();
+    merge_mappings(&mut merged, local);
+
+    let (mappings, origins) = merged.into_iter().unzip();
+    rule.mappings = mappings;
+    rule.include.clear();
+    Ok(ResolvedRuleFile {
+        rule,
+        files: resolver.files,
+        origins,
+    })
+}
+
+/// Validate a rule with includes, reporting errors against the originating file.
+pub fn validate_rule_file_with_includes(resolved: &ResolvedRuleFile) -> ValidationResult {
+    resolved.attribute(validate_rule_file(&resolved.rule))
+}
+
+/// Like `validate_rule_file_with_includes`, but deprecated ops are reported as errors.
+pub fn validate_rule_file_with_includes_strict(resolved: &ResolvedRuleFile) -> ValidationResult {
+    resolved.attribute(validate_rule_file_strict(&resolved.rule))
+}
+
+struct IncludeResolver {
+    files: Vec<SourceFile>,
+    stack: Vec<PathBuf>,
+}
+
+impl IncludeResolver {
+    fn resolve_includes(
+        &mut self,
+        file: usize,
+        includes: &[String],
+    ) -> Result<Vec<(Mapping, MappingOrigin)>, RuleError> {
+        let mut merged = Vec::new();
+        for (index, include) in includes.iter().enumerate() {
+            let mappings = self.resolve_include(file, include, &format!("include[{}]", index))?;
+            merge_mappings(&mut merged, mappings);
+        }
+        Ok(merged)
+    }
+
+    fn resolve_include(
+        &mut self,
+        parent: usize,
+        include: &str,
+        include_path: &str,
+    ) -> Result<Vec<(Mapping, MappingOrigin)>, RuleError> {
+        let parent_path = self.files[parent].path.clone();
+        let locate = |err: RuleError, files: &[SourceFile]| {
+            let err = err.with_path(include_path).with_file(&parent_path);
+            match YamlLocator::from_str(&files[parent].source).location_for(include_path) {
+                Some(location) => err.with_location(location.line, location.column),
+                None => err,
+            }
+        };
+
+        let base_dir = parent_path.parent().unwrap_or_else(|| Path::new("."));
+        let path = resolve_include_path(base_dir, include);
+        let canonical = canonical_path(&path);
+        if self.stack.contains(&canonical) {
+            let mut chain: Vec<String> = self
+                .stack
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect();
+            chain.push(canonical.display().to_string());
+            let err = RuleError::new(
+                ErrorCode::IncludeCycle,
+                format!("include cycle: {}", chain.join(" -> ")),
+            );
+            return Err(locate(err, &self.files));
+        }
+
+        let source = read_source(&path).map_err(|err| locate(err, &self.files))?;
+        let fragment: RuleFragment = serde_yaml::from_str(&source).map_err(|err| {
+            let err = RuleError::new(
+                ErrorCode::InvalidInclude,
+                format!("failed to parse include {}: {}", path.display(), err),
+            );
+            locate(err, &self.files)
+        })?;
+
+        let file = self.files.len();
+        self.files.push(SourceFile { path, source });
+        self.stack.push(canonical);
+        let mut merged = self.resolve_includes(file, &fragment.include)?;
+        self.stack.pop();
+
+        let local = fragment
+            .mappings
+            .into_iter()
+            .enumerate()
+            .map(|(index, mapping)| (mapping, MappingOrigin { file, index }))
+            .collect();
+        merge_mappings(&mut merged, local);
+        Ok(merged)
+    }
+}
+
+/// Append `local` to `merged`, dropping merged mappings whose target `local` redefines.
+fn merge_mappings(
+    merged: &mut Vec<(Mapping, MappingOrigin)>,
+    local: Vec<(Mapping, MappingOrigin)>,
+) {
+    let targets: HashSet<&str> = local
+        .iter()
+        .map(|(mapping, _)| mapping.target.as_str())
+        .collect();
+    merged.retain(|(mapping, _)| !targets.contains(mapping.target.as_str()));
+    merged.extend(local);
+}
+
+fn read_source(path: &Path) -> Result<String, RuleError> {
+    std::fs::read_to_string(path).map_err(|err| {
+        RuleError::new(
+            ErrorCode::InvalidInclude,
+            format!("failed to read {}: {}", path.display(), err),
+        )
+        .with_file(path)
+    })
+}
+
+fn resolve_include_path(base_dir: &Path, include: &str) -> PathBuf {
+    let include_path = PathBuf::from(include);
+    if include_path.is_absolute() {
+        include_path
+    } else {
+        base_dir.join(include_path)
+    }
+}
+
+fn canonical_path(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn split_mapping_index(path: &str) -> Option<(usize, &str)> {
+    let rest = path.strip_prefix("mappings[")?;
+    let end = rest.find(']')?;
+    let index = rest[..end].parse().ok()?;
+    Some((index, &rest[end + 1..]))
+}
@@ -0,0 +1,147 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use flate2::read::MultiGzDecoder;
+use zip::ZipArchive;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Most bytes `read_input_file` inflates a compressed input to (1 GiB).
+pub const MAX_DECOMPRESSED_INPUT_BYTES: u64 = 1 << 30;
+
+/// Read an input file as UTF-8 text, decompressing it when needed.
+///
+/// Gzip input is detected by a `.gz` extension or the gzip magic bytes. Zip
+/// input (`.zip` extension or zip magic bytes) must contain a single file
+/// unless `entry` names the one to read; an archive with several files and no
+/// `entry` is an error listing the available names. Decompressed input larger
+/// than `MAX_DECOMPRESSED_INPUT_BYTES` is an error.
+pub fn read_input_file(path: &Path, entry: Option<&str>) -> io::Result<String> {
+    read_input_file_with_limit(path, entry, MAX_DECOMPRESSED_INPUT_BYTES)
+}
+
+/// Like `read_input_file`, but with a custom cap on the decompressed size.
+pub fn read_input_file_with_limit(
+    path: &Path,
+    entry: Option<&str>,
+    max_decompressed_bytes: u64,
+) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut magic = [0u8; 4];
+    let read = read_prefix(&mut file, &mut magic)?;
+    file.seek(SeekFrom::Start(0))?;
+    let magic = &magic[..read];
+
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+    let is_zip = extension.as_deref() == Some("zip") || magic.starts_with(ZIP_MAGIC);
+    let is_gzip = extension.as_deref() == Some("gz") || magic.starts_with(GZIP_MAGIC);
+
+    let bytes = if is_zip {
+        read_zip_entry(file, entry, max_decompressed_bytes)?
+    } else {
+        if let Some(entry) = entry {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("input entry {} requires a zip archive", entry),
+            ));
+        }
+        if is_gzip {
+            read_limited(
+                MultiGzDecoder::new(BufReader::new(file)),
+                max_decompressed_bytes,
+            )?
+        } else {
+            let mut bytes = Vec::new();
+            file.read_to_end(&mut bytes)?;
+            bytes
+        }
+    };
+
+    String::from_utf8(bytes).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "input is not valid UTF-8 after decompression",
+        )
+    })
+}
+
+fn read_prefix(file: &mut File, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Read a decompressing reader to the end, failing once it yields more than `limit` bytes.
+fn read_limited(reader: impl Read, limit: u64) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > limit {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("decompressed input exceeds {} bytes", limit),
+        ));
+    }
+    Ok(bytes)
+}
+
+fn read_zip_entry(
+    file: File,
+    entry: Option<&str>,
+    max_decompressed_bytes: u64,
+) -> io::Result<Vec<u8>> {
+    let mut archive = ZipArchive::new(BufReader::new(file)).map_err(zip_error)?;
+    let name = match entry {
+        Some(entry) => entry.to_string(),
+        None => {
+            let names: Vec<&str> = archive
+                .file_names()
+                .filter(|name| !name.ends_with('/'))
+                .collect();
+            match names.as_slice() {
+                [name] => name.to_string(),
+                [] => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "zip archive contains no files",
+                    ));
+                }
+                _ => {
+                    let mut names = names;
+                    names.sort_unstable();
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "zip archive contains {} files ({}); choose one with an input entry",
+                            names.len(),
+                            names.join(", ")
+                        ),
+                    ));
+                }
+            }
+        }
+    };
+    let zip_file = archive.by_name(&name).map_err(zip_error)?;
+    read_limited(zip_file, max_decompressed_bytes)
+}
+
+fn zip_error(err: zip::result::ZipError) -> io::Error {
+    match err {
+        zip::result::ZipError::Io(err) => err,
+        zip::result::ZipError::FileNotFound => {
+            io::Error::new(io::ErrorKind::NotFound, "entry not found in zip archive")
+        }
+        other => io::Error::new(io::ErrorKind::InvalidData, other.to_string()),
+    }
+}
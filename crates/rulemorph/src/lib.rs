@@ -1,10 +1,15 @@
 mod cache;
+mod columns;
 mod dto;
 mod error;
+mod include;
+mod input_file;
 mod locator;
 mod model;
 mod path;
+mod stats;
 mod transform;
+mod type_spec;
 pub mod v2_eval;
 pub mod v2_model;
 pub mod v2_parser;
@@ -14,33 +19,81 @@ mod validator;
 /// Library version from Cargo.toml
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+pub use cache::LruCache;
+pub use columns::{
+    ArrayColumns, ColumnTable, VALUE_COLUMN, coerce_record_to_columns, coerce_records_to_columns,
+    records_to_csv,
+};
 pub use dto::{DtoError, DtoLanguage, generate_dto};
 pub use error::{
-    ErrorCode, RuleError, TransformError, TransformErrorKind, TransformWarning, ValidationResult,
-    YamlLocation,
+    ErrorCode, RuleError, TransformError, TransformErrorKind, TransformErrorLocator,
+    TransformWarning, ValidationResult, YamlLocation,
+};
+pub use include::{
+    ResolvedRuleFile, load_rule_file_with_includes, resolve_rule_includes,
+    validate_rule_file_with_includes, validate_rule_file_with_includes_strict,
+};
+pub use input_file::{MAX_DECOMPRESSED_INPUT_BYTES, read_input_file, read_input_file_with_limit};
+pub use model::{
+    Expr, ExprChain, ExprOp, ExprRef, InputFormat, InputSpec, Mapping, OutputFormat, OutputSpec,
+    RuleFile,
 };
-pub use model::{Expr, ExprChain, ExprOp, ExprRef, InputFormat, InputSpec, Mapping, RuleFile};
 pub use path::{PathError, PathToken, get_path, parse_path};
+pub use stats::{RuleStats, rule_stats};
+#[cfg(feature = "rayon")]
+pub use transform::transform_parallel;
 pub use transform::{
-    TransformStream, TransformStreamItem, preflight_validate, preflight_validate_with_base_dir,
-    preflight_validate_with_warnings, preflight_validate_with_warnings_with_base_dir, transform,
-    transform_record, transform_record_with_base_dir, transform_record_with_warnings,
-    transform_record_with_warnings_with_base_dir, transform_stream, transform_stream_with_base_dir,
-    transform_with_base_dir, transform_with_warnings, transform_with_warnings_with_base_dir,
+    BatchLimits, CollectedRecords, DuplicateKeys, TransformBatch, TransformOptions,
+    TransformPreview, TransformStream, TransformStreamItem, preflight_validate,
+    preflight_validate_with_base_dir, preflight_validate_with_warnings,
+    preflight_validate_with_warnings_with_base_dir, transform, transform_collect_errors,
+    transform_collect_errors_with_base_dir, transform_preview, transform_record,
+    transform_record_with_base_dir, transform_record_with_warnings,
+    transform_record_with_warnings_with_base_dir, transform_stream, transform_stream_batched,
+    transform_stream_batched_with_base_dir, transform_stream_reader,
+    transform_stream_reader_with_base_dir, transform_stream_with_base_dir, transform_strict,
+    transform_strict_with_base_dir, transform_with_base_dir, transform_with_options,
+    transform_with_warnings, transform_with_warnings_with_base_dir,
+};
+pub use validator::{
+    validate_rule_file, validate_rule_file_strict, validate_rule_file_with_source,
+    validate_rule_file_with_source_strict,
 };
-pub use validator::{validate_rule_file, validate_rule_file_with_source};
 
 use std::sync::{Mutex, OnceLock};
 
-use cache::LruCache;
-
-const RULE_CACHE_CAPACITY: usize = 128;
+/// Default number of parsed rule files kept by [`parse_rule_file`].
+pub const RULE_CACHE_CAPACITY: usize = 128;
 
 fn rule_cache() -> &'static Mutex<LruCache<String, RuleFile>> {
     static RULE_CACHE: OnceLock<Mutex<LruCache<String, RuleFile>>> = OnceLock::new();
     RULE_CACHE.get_or_init(|| Mutex::new(LruCache::new(RULE_CACHE_CAPACITY)))
 }
 
+/// Set how many parsed rule files [`parse_rule_file`] keeps, evicting the least recently
+/// used ones that no longer fit. A capacity of 0 disables the cache.
+pub fn set_rule_cache_capacity(capacity: usize) {
+    let mut cache = rule_cache().lock().unwrap_or_else(|err| err.into_inner());
+    cache.set_capacity(capacity);
+}
+
+/// Drop every cached rule file, e.g. after reloading rules from disk.
+pub fn clear_rule_cache() {
+    let mut cache = rule_cache().lock().unwrap_or_else(|err| err.into_inner());
+    cache.clear();
+}
+
+/// Number of rule files currently cached by [`parse_rule_file`].
+pub fn rule_cache_len() -> usize {
+    let cache = rule_cache().lock().unwrap_or_else(|err| err.into_inner());
+    cache.len()
+}
+
+/// Parse a rule file from YAML.
+///
+/// `include` entries are kept as written; resolve them with
+/// [`resolve_rule_includes`] or load the file with [`load_rule_file_with_includes`].
+/// Transforming a rule with unresolved includes fails.
 pub fn parse_rule_file(yaml: &str) -> Result<RuleFile, serde_yaml::Error> {
     let key = yaml.to_string();
     if let Some(rule) = {
@@ -20,6 +20,20 @@ impl YamlLocator {
         self.locations.get(path).cloned()
     }
 
+    /// Location of `path`, or of its closest ancestor that has one.
+    ///
+    /// Runtime paths reach into flow sequences and op args (`expr[1].args[0]`), which
+    /// have no line of their own; the enclosing step or key does.
+    pub fn nearest_location_for(&self, path: &str) -> Option<YamlLocation> {
+        let mut path = path;
+        loop {
+            if let Some(location) = self.location_for(path) {
+                return Some(location);
+            }
+            path = &path[..path.rfind(['.', '['])?];
+        }
+    }
+
     fn build(&mut self, source: &str) {
         let mut scopes: Vec<Scope> = vec![Scope {
             indent: 0,
@@ -5,6 +5,8 @@ use serde_json::Value as JsonValue;
 #[serde(deny_unknown_fields)]
 pub struct RuleFile {
     pub version: u8,
+    #[serde(default)]
+    pub include: Vec<String>,
     pub input: InputSpec,
     #[serde(default)]
     pub output: Option<OutputSpec>,
@@ -22,6 +24,26 @@ pub struct RuleFile {
 #[serde(deny_unknown_fields)]
 pub struct OutputSpec {
     pub name: Option<String>,
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+impl RuleFile {
+    /// The output format declared in `output.format`, defaulting to JSON.
+    pub fn output_format(&self) -> OutputFormat {
+        self.output
+            .as_ref()
+            .map(|output| output.format)
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -47,6 +69,10 @@ fn default_delimiter() -> String {
     ",".to_string()
 }
 
+fn default_quote() -> String {
+    "\"".to_string()
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct CsvInput {
@@ -54,9 +80,30 @@ pub struct CsvInput {
     pub has_header: bool,
     #[serde(default = "default_delimiter")]
     pub delimiter: String,
+    #[serde(default = "default_quote")]
+    pub quote: String,
     pub columns: Option<Vec<Column>>,
 }
 
+impl CsvInput {
+    /// The delimiter as the single byte the CSV reader takes, if it is one ASCII character.
+    pub fn delimiter_byte(&self) -> Option<u8> {
+        single_ascii_byte(&self.delimiter)
+    }
+
+    /// The quote character as a single byte, if it is one ASCII character.
+    pub fn quote_byte(&self) -> Option<u8> {
+        single_ascii_byte(&self.quote)
+    }
+}
+
+fn single_ascii_byte(value: &str) -> Option<u8> {
+    match value.as_bytes() {
+        [byte] if byte.is_ascii() => Some(*byte),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct Column {
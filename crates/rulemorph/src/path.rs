@@ -1,9 +1,26 @@
+use std::borrow::Cow;
+
 use serde_json::Value as JsonValue;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum PathToken {
     Key(String),
     Index(usize),
+    /// `[*]`: every element of an array or every value of an object.
+    Wildcard,
+    /// `[start:end]`: array elements from `start` up to, not including, `end`.
+    /// Either bound may be omitted; bounds past the end are clamped.
+    Slice {
+        start: usize,
+        end: Option<usize>,
+    },
+}
+
+impl PathToken {
+    /// Wildcards and slices select several values rather than one.
+    pub fn is_multi(&self) -> bool {
+        matches!(self, PathToken::Wildcard | PathToken::Slice { .. })
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -12,6 +29,8 @@ pub enum PathError {
     InvalidSyntax,
     InvalidEscape,
     EmptyKey,
+    /// A wildcard or slice met a value it cannot select from.
+    NotACollection,
 }
 
 impl PathError {
@@ -21,6 +40,9 @@ impl PathError {
             PathError::InvalidSyntax => "path syntax is invalid",
             PathError::InvalidEscape => "path escape is invalid",
             PathError::EmptyKey => "path segment is empty",
+            PathError::NotACollection => {
+                "path wildcard or slice applied to a value that is not a collection"
+            }
         }
     }
 }
@@ -44,14 +66,19 @@ pub fn parse_path(path: &str) -> Result<Vec<PathToken>, PathError> {
             tokens.push(token);
             index = next;
         } else {
-            let start = index;
+            let mut key = String::new();
             while index < chars.len() && chars[index] != '.' && chars[index] != '[' {
+                if chars[index] == '\\'
+                    && let Some(&escaped) = chars.get(index + 1)
+                    && is_escapable(escaped)
+                {
+                    key.push(escaped);
+                    index += 1;
+                } else {
+                    key.push(chars[index]);
+                }
                 index += 1;
             }
-            if start == index {
-                return Err(PathError::EmptyKey);
-            }
-            let key: String = chars[start..index].iter().collect();
             if key.is_empty() {
                 return Err(PathError::EmptyKey);
             }
@@ -79,6 +106,24 @@ pub fn parse_path(path: &str) -> Result<Vec<PathToken>, PathError> {
     Ok(tokens)
 }
 
+/// Characters a bare key segment can escape with a backslash. A backslash before any
+/// other character, or at the end, is kept as a literal, so keys like `C:\dir` still parse.
+fn is_escapable(ch: char) -> bool {
+    matches!(ch, '\\' | '.' | '[' | ']')
+}
+
+/// Escape `key` so it parses back as a single bare key segment.
+pub(crate) fn escape_key(key: &str) -> String {
+    let mut escaped = String::with_capacity(key.len());
+    for ch in key.chars() {
+        if is_escapable(ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
 fn parse_bracket(chars: &[char], start: usize) -> Result<(PathToken, usize), PathError> {
     if chars.get(start) != Some(&'[') {
         return Err(PathError::InvalidSyntax);
@@ -90,32 +135,41 @@ fn parse_bracket(chars: &[char], start: usize) -> Result<(PathToken, usize), Pat
 
     match chars[index] {
         '"' | '\'' => parse_quoted(chars, index),
-        c if c.is_ascii_digit() => parse_index(chars, index),
+        '*' if chars.get(index + 1) == Some(&']') => Ok((PathToken::Wildcard, index + 2)),
+        c if c.is_ascii_digit() || c == ':' => parse_index(chars, index),
         _ => Err(PathError::InvalidSyntax),
     }
 }
 
 fn parse_index(chars: &[char], start: usize) -> Result<(PathToken, usize), PathError> {
-    let mut index = start;
-    let mut value: usize = 0;
-    let mut has_digit = false;
-
-    while index < chars.len() && chars[index].is_ascii_digit() {
-        has_digit = true;
-        value = value
-            .saturating_mul(10)
-            .saturating_add(chars[index].to_digit(10).unwrap_or(0) as usize);
-        index += 1;
-    }
+    let (value, mut index) = parse_number(chars, start);
+    let token = if chars.get(index) == Some(&':') {
+        let (end, next) = parse_number(chars, index + 1);
+        index = next;
+        PathToken::Slice {
+            start: value.unwrap_or(0),
+            end,
+        }
+    } else {
+        PathToken::Index(value.ok_or(PathError::InvalidSyntax)?)
+    };
 
-    if !has_digit {
-        return Err(PathError::InvalidSyntax);
-    }
     if chars.get(index) != Some(&']') {
         return Err(PathError::InvalidSyntax);
     }
     index += 1;
-    Ok((PathToken::Index(value), index))
+    Ok((token, index))
+}
+
+fn parse_number(chars: &[char], start: usize) -> (Option<usize>, usize) {
+    let mut index = start;
+    let mut value: Option<usize> = None;
+    while index < chars.len() && chars[index].is_ascii_digit() {
+        let digit = chars[index].to_digit(10).unwrap_or(0) as usize;
+        value = Some(value.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+        index += 1;
+    }
+    (value, index)
 }
 
 fn parse_quoted(chars: &[char], start: usize) -> Result<(PathToken, usize), PathError> {
@@ -165,19 +219,75 @@ fn parse_quoted(chars: &[char], start: usize) -> Result<(PathToken, usize), Path
     Ok((PathToken::Key(value), index))
 }
 
-pub fn get_path<'a>(value: &'a JsonValue, tokens: &[PathToken]) -> Option<&'a JsonValue> {
+/// Resolve `tokens` against `value`.
+///
+/// Without wildcards or slices this borrows the single value at the path. A wildcard or
+/// slice applies the rest of the path to each selected element and collects every match,
+/// in order, into one flat array; elements the rest of the path does not resolve in are
+/// skipped. Returns `Ok(None)` when the path is missing, and
+/// `PathError::NotACollection` when a wildcard or slice meets something other than an
+/// array (or, for a wildcard, an object).
+pub fn get_path<'a>(
+    value: &'a JsonValue,
+    tokens: &[PathToken],
+) -> Result<Option<Cow<'a, JsonValue>>, PathError> {
+    if !tokens.iter().any(PathToken::is_multi) {
+        return Ok(get_single(value, tokens).map(Cow::Borrowed));
+    }
+    let mut matches = Vec::new();
+    if !collect_path(value, tokens, &mut matches)? {
+        return Ok(None);
+    }
+    Ok(Some(Cow::Owned(JsonValue::Array(
+        matches.into_iter().cloned().collect(),
+    ))))
+}
+
+fn get_single<'a>(value: &'a JsonValue, tokens: &[PathToken]) -> Option<&'a JsonValue> {
     let mut current = value;
     for token in tokens {
-        match token {
-            PathToken::Key(key) => match current {
-                JsonValue::Object(map) => current = map.get(key)?,
-                _ => return None,
-            },
-            PathToken::Index(index) => match current {
-                JsonValue::Array(items) => current = items.get(*index)?,
-                _ => return None,
-            },
-        }
+        current = step(current, token)?;
     }
     Some(current)
 }
+
+fn step<'a>(value: &'a JsonValue, token: &PathToken) -> Option<&'a JsonValue> {
+    match (token, value) {
+        (PathToken::Key(key), JsonValue::Object(map)) => map.get(key),
+        (PathToken::Index(index), JsonValue::Array(items)) => items.get(*index),
+        _ => None,
+    }
+}
+
+/// Push every value `tokens` selects under `value`; false when the path is missing.
+fn collect_path<'a>(
+    value: &'a JsonValue,
+    tokens: &[PathToken],
+    matches: &mut Vec<&'a JsonValue>,
+) -> Result<bool, PathError> {
+    let Some((token, rest)) = tokens.split_first() else {
+        matches.push(value);
+        return Ok(true);
+    };
+    let selected: Vec<&JsonValue> = match (token, value) {
+        (PathToken::Wildcard, JsonValue::Array(items)) => items.iter().collect(),
+        (PathToken::Wildcard, JsonValue::Object(map)) => map.values().collect(),
+        (PathToken::Slice { start, end }, JsonValue::Array(items)) => {
+            let end = end.unwrap_or(items.len()).min(items.len());
+            items.get(*start..end).unwrap_or_default().iter().collect()
+        }
+        (PathToken::Wildcard | PathToken::Slice { .. }, _) => {
+            return Err(PathError::NotACollection);
+        }
+        (token, value) => {
+            return match step(value, token) {
+                Some(next) => collect_path(next, rest, matches),
+                None => Ok(false),
+            };
+        }
+    };
+    for item in selected {
+        collect_path(item, rest, matches)?;
+    }
+    Ok(true)
+}
@@ -0,0 +1,289 @@
+//! Static usage summary of a rule file: ops, referenced paths, targets, and pipe depth.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{Value as JsonValue, json};
+
+use crate::model::{Expr, Mapping, RuleFile};
+use crate::transform::{expr_to_json_for_v2_condition, expr_to_json_for_v2_pipe};
+use crate::v2_model::{V2ComparisonOp, V2Condition, V2Expr, V2Pipe, V2Ref, V2Start, V2Step};
+use crate::v2_parser::{parse_v2_condition, parse_v2_expr, parse_v2_pipe_from_value, parse_v2_ref};
+
+/// What a rule file uses, collected without running it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleStats {
+    /// Number of times each op appears.
+    pub ops: BTreeMap<String, usize>,
+    /// Paths read from the input record.
+    pub input_paths: BTreeSet<String>,
+    /// Paths read from the context.
+    pub context_paths: BTreeSet<String>,
+    /// Mapping targets, including those in steps.
+    pub output_targets: BTreeSet<String>,
+    /// Deepest nesting of expressions; a flat reference or pipe counts as 1.
+    pub max_pipe_depth: usize,
+}
+
+impl RuleStats {
+    pub fn to_json(&self) -> JsonValue {
+        json!({
+            "ops": self.ops,
+            "input_paths": self.input_paths,
+            "context_paths": self.context_paths,
+            "output_targets": self.output_targets,
+            "max_pipe_depth": self.max_pipe_depth,
+        })
+    }
+}
+
+/// Walk every expression in `rule` and summarize what it uses.
+pub fn rule_stats(rule: &RuleFile) -> RuleStats {
+    let mut walker = StatsWalker {
+        stats: RuleStats::default(),
+        version: rule.version,
+    };
+    if let Some(expr) = &rule.record_when {
+        walker.condition(expr);
+    }
+    walker.mappings(&rule.mappings);
+    for step in rule.steps.iter().flatten() {
+        if let Some(mappings) = &step.mappings {
+            walker.mappings(mappings);
+        }
+        if let Some(expr) = &step.record_when {
+            walker.condition(expr);
+        }
+        for assert in step.asserts.iter().flatten() {
+            walker.condition(&assert.when);
+        }
+        if let Some(branch) = &step.branch {
+            walker.condition(&branch.when);
+        }
+    }
+    if let Some(finalize) = &rule.finalize {
+        if let Some(filter) = &finalize.filter {
+            walker.condition(filter);
+        }
+        if let Some(wrap) = &finalize.wrap {
+            walker.wrap_value(wrap);
+        }
+    }
+    walker.stats
+}
+
+struct StatsWalker {
+    stats: RuleStats,
+    version: u8,
+}
+
+impl StatsWalker {
+    fn mappings(&mut self, mappings: &[Mapping]) {
+        for mapping in mappings {
+            self.stats.output_targets.insert(mapping.target.clone());
+            if let Some(source) = &mapping.source {
+                self.source(source);
+            }
+            if let Some(expr) = &mapping.expr {
+                self.expr(expr);
+            }
+            if let Some(expr) = &mapping.when {
+                self.condition(expr);
+            }
+        }
+    }
+
+    fn source(&mut self, source: &str) {
+        match source.split_once('.') {
+            Some((namespace, path)) => self.namespaced_ref(namespace, path),
+            None => {
+                self.stats.input_paths.insert(source.to_string());
+            }
+        }
+        self.depth(1);
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        if self.version >= 2
+            && let Some(value) = expr_to_json_for_v2_pipe(expr)
+            && let Ok(pipe) = parse_v2_pipe_from_value(&value)
+        {
+            self.v2_pipe(&pipe, 1);
+            return;
+        }
+        self.v1_expr(expr, 1);
+    }
+
+    fn condition(&mut self, expr: &Expr) {
+        if self.version >= 2
+            && let Some(value) = expr_to_json_for_v2_condition(expr)
+            && let Ok(condition) = parse_v2_condition(&value)
+        {
+            self.v2_condition(&condition, 1);
+            return;
+        }
+        self.v1_expr(expr, 1);
+    }
+
+    fn wrap_value(&mut self, value: &JsonValue) {
+        match value {
+            JsonValue::Object(map) => map.values().for_each(|value| self.wrap_value(value)),
+            other => {
+                if let Ok(expr) = parse_v2_expr(other) {
+                    self.v2_expr(&expr, 1);
+                }
+            }
+        }
+    }
+
+    fn depth(&mut self, depth: usize) {
+        self.stats.max_pipe_depth = self.stats.max_pipe_depth.max(depth);
+    }
+
+    fn op(&mut self, name: &str) {
+        *self.stats.ops.entry(name.to_string()).or_default() += 1;
+    }
+
+    fn namespaced_ref(&mut self, namespace: &str, path: &str) {
+        match namespace {
+            "input" => {
+                self.stats.input_paths.insert(path.to_string());
+            }
+            "context" => {
+                self.stats.context_paths.insert(path.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    fn ref_path(&mut self, ref_path: &str) {
+        if let Some(v2_ref) = parse_v2_ref(ref_path) {
+            self.v2_ref(&v2_ref);
+        } else if let Some((namespace, path)) = ref_path.split_once('.') {
+            self.namespaced_ref(namespace, path);
+        }
+    }
+
+    fn v1_expr(&mut self, expr: &Expr, depth: usize) {
+        self.depth(depth);
+        match expr {
+            Expr::Ref(expr_ref) => self.ref_path(&expr_ref.ref_path),
+            Expr::Op(op) => {
+                self.op(&op.op);
+                for arg in &op.args {
+                    self.v1_expr(arg, depth + 1);
+                }
+            }
+            Expr::Chain(chain) => {
+                for item in &chain.chain {
+                    self.v1_expr(item, depth + 1);
+                }
+            }
+            Expr::Literal(_) => {}
+        }
+    }
+
+    fn v2_expr(&mut self, expr: &V2Expr, depth: usize) {
+        match expr {
+            V2Expr::Pipe(pipe) => self.v2_pipe(pipe, depth),
+            V2Expr::V1Fallback(expr) => self.v1_expr(expr, depth),
+        }
+    }
+
+    fn v2_pipe(&mut self, pipe: &V2Pipe, depth: usize) {
+        self.depth(depth);
+        match &pipe.start {
+            V2Start::Ref(v2_ref) => self.v2_ref(v2_ref),
+            V2Start::V1Expr(expr) => self.v1_expr(expr, depth),
+            V2Start::PipeValue | V2Start::Literal(_) => {}
+        }
+        self.v2_steps(&pipe.steps, depth);
+    }
+
+    fn v2_steps(&mut self, steps: &[V2Step], depth: usize) {
+        for step in steps {
+            match step {
+                V2Step::Op(op_step) => {
+                    self.op(&op_step.op);
+                    for arg in &op_step.args {
+                        self.v2_expr(arg, depth + 1);
+                    }
+                }
+                V2Step::Let(let_step) => {
+                    for (_, expr) in &let_step.bindings {
+                        self.v2_expr(expr, depth + 1);
+                    }
+                }
+                V2Step::If(if_step) => {
+                    self.v2_condition(&if_step.cond, depth + 1);
+                    self.v2_pipe(&if_step.then_branch, depth + 1);
+                    if let Some(else_branch) = &if_step.else_branch {
+                        self.v2_pipe(else_branch, depth + 1);
+                    }
+                }
+                V2Step::Map(map_step) => {
+                    self.depth(depth + 1);
+                    self.v2_steps(&map_step.steps, depth + 1);
+                }
+                V2Step::Ref(v2_ref) => self.v2_ref(v2_ref),
+            }
+        }
+    }
+
+    fn v2_condition(&mut self, condition: &V2Condition, depth: usize) {
+        match condition {
+            V2Condition::All(conditions) | V2Condition::Any(conditions) => {
+                self.op(if matches!(condition, V2Condition::All(_)) {
+                    "all"
+                } else {
+                    "any"
+                });
+                for condition in conditions {
+                    self.v2_condition(condition, depth + 1);
+                }
+            }
+            V2Condition::Comparison(comparison) => {
+                self.op(comparison_op_name(comparison.op));
+                for arg in &comparison.args {
+                    self.v2_expr(arg, depth + 1);
+                }
+            }
+            V2Condition::Exists(v2_ref) => {
+                self.op("exists");
+                self.v2_ref(v2_ref);
+            }
+            V2Condition::Missing(v2_ref) => {
+                self.op("missing");
+                self.v2_ref(v2_ref);
+            }
+            V2Condition::Expr(expr) => self.v2_expr(expr, depth),
+        }
+    }
+
+    fn v2_ref(&mut self, v2_ref: &V2Ref) {
+        match v2_ref {
+            V2Ref::Input(path) => {
+                self.stats.input_paths.insert(path.clone());
+            }
+            V2Ref::Context(path) => {
+                self.stats.context_paths.insert(path.clone());
+            }
+            V2Ref::Out(_) | V2Ref::Item(_) | V2Ref::Acc(_) | V2Ref::Local(_) => {}
+        }
+    }
+}
+
+fn comparison_op_name(op: V2ComparisonOp) -> &'static str {
+    match op {
+        V2ComparisonOp::Eq => "eq",
+        V2ComparisonOp::EqI => "eqi",
+        V2ComparisonOp::Ne => "ne",
+        V2ComparisonOp::Gt => "gt",
+        V2ComparisonOp::Gte => "gte",
+        V2ComparisonOp::Lt => "lt",
+        V2ComparisonOp::Lte => "lte",
+        V2ComparisonOp::Match => "match",
+        V2ComparisonOp::In => "in",
+        V2ComparisonOp::NotIn => "not_in",
+        V2ComparisonOp::Between => "between",
+    }
+}
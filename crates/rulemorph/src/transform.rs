@@ -3,8 +3,10 @@ use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
 use csv::ReaderBuilder;
 use regex::Regex;
 use serde_json::{Map, Value as JsonValue};
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Read};
 use std::path::{Path, PathBuf};
 use std::sync::{Mutex, OnceLock};
 
@@ -13,7 +15,7 @@ use crate::error::{TransformError, TransformErrorKind, TransformWarning};
 use crate::model::{
     Expr, ExprChain, ExprOp, ExprRef, FinalizeSpec, InputFormat, Mapping, RuleFile, V2RuleStep,
 };
-use crate::path::{PathToken, get_path, parse_path};
+use crate::path::{PathToken, escape_key, get_path, parse_path};
 use crate::v2_eval::{
     EvalItem as V2EvalItem, EvalValue as V2EvalValue, V2EvalContext, eval_v2_condition,
     eval_v2_expr, eval_v2_pipe,
@@ -22,6 +24,7 @@ use crate::v2_parser::{
     is_literal_escape, is_pipe_value, is_v2_ref, parse_v2_condition, parse_v2_expr,
     parse_v2_pipe_from_value,
 };
+use crate::validator::deprecation_warnings;
 
 const REGEX_CACHE_CAPACITY: usize = 128;
 
@@ -30,7 +33,7 @@ fn regex_cache() -> &'static Mutex<LruCache<String, Regex>> {
     REGEX_CACHE.get_or_init(|| Mutex::new(LruCache::new(REGEX_CACHE_CAPACITY)))
 }
 
-fn cached_regex(pattern: &str, path: &str) -> Result<Regex, TransformError> {
+pub(crate) fn cached_regex(pattern: &str, path: &str) -> Result<Regex, TransformError> {
     let key = pattern.to_string();
     if let Some(regex) = {
         let mut cache = regex_cache().lock().unwrap_or_else(|err| err.into_inner());
@@ -58,6 +61,53 @@ pub fn transform(
     transform_with_warnings(rule, input, context).map(|(output, _)| output)
 }
 
+/// Like `transform`, but transforms records concurrently on the rayon thread pool.
+///
+/// Output order and errors match `transform`: when several records fail, the error is the
+/// one from the earliest record. `@out` only sees the record being built, so records never
+/// depend on each other. Input is parsed up front, so the whole input is held in memory.
+#[cfg(feature = "rayon")]
+pub fn transform_parallel(
+    rule: &RuleFile,
+    input: &str,
+    context: Option<&JsonValue>,
+) -> Result<JsonValue, TransformError> {
+    use rayon::prelude::*;
+
+    let mut records = Vec::new();
+    let mut input_error = None;
+    for record in input_records_iter(rule, input)? {
+        match record {
+            Ok(record) => records.push(record),
+            Err(err) => {
+                // Sequential transform would still fail on any earlier record first.
+                input_error = Some(err);
+                break;
+            }
+        }
+    }
+
+    let results: Vec<Result<Option<JsonValue>, TransformError>> = records
+        .par_iter()
+        .map(|record| apply_rule_to_record(rule, record, context, &mut Vec::new(), None))
+        .collect();
+    let mut output_records = Vec::with_capacity(results.len());
+    for result in results {
+        if let Some(output) = result? {
+            output_records.push(output);
+        }
+    }
+    if let Some(err) = input_error {
+        return Err(err);
+    }
+
+    let output = JsonValue::Array(output_records);
+    match &rule.finalize {
+        Some(finalize) => apply_finalize(finalize, output, context),
+        None => Ok(output),
+    }
+}
+
 pub fn transform_with_base_dir(
     rule: &RuleFile,
     input: &str,
@@ -95,6 +145,7 @@ pub struct TransformStream<'a> {
     context: Option<&'a JsonValue>,
     records: InputRecordsIter<'a>,
     base_dir: Option<&'a Path>,
+    pending_warnings: Vec<TransformWarning>,
     done: bool,
 }
 
@@ -106,13 +157,23 @@ impl<'a> TransformStream<'a> {
         base_dir: Option<&'a Path>,
     ) -> Result<Self, TransformError> {
         let records = input_records_iter(rule, input)?;
-        Ok(Self {
+        Ok(Self::from_records(rule, records, context, base_dir))
+    }
+
+    fn from_records(
+        rule: &'a RuleFile,
+        records: InputRecordsIter<'a>,
+        context: Option<&'a JsonValue>,
+        base_dir: Option<&'a Path>,
+    ) -> Self {
+        Self {
             rule,
             context,
             records,
             base_dir,
+            pending_warnings: deprecation_warnings(rule),
             done: false,
-        })
+        }
     }
 }
 
@@ -128,7 +189,13 @@ impl<'a> Iterator for TransformStream<'a> {
             let record = match self.records.next() {
                 None => {
                     self.done = true;
-                    return None;
+                    if self.pending_warnings.is_empty() {
+                        return None;
+                    }
+                    return Some(Ok(TransformStreamItem {
+                        output: None,
+                        warnings: std::mem::take(&mut self.pending_warnings),
+                    }));
                 }
                 Some(Ok(record)) => record,
                 Some(Err(err)) => {
@@ -137,7 +204,7 @@ impl<'a> Iterator for TransformStream<'a> {
                 }
             };
 
-            let mut warnings = Vec::new();
+            let mut warnings = std::mem::take(&mut self.pending_warnings);
             match apply_rule_to_record(
                 self.rule,
                 &record,
@@ -189,12 +256,154 @@ pub fn transform_stream_with_base_dir<'a>(
     TransformStream::new(rule, input, context, Some(base_dir))
 }
 
+/// Stream records read lazily from `reader` instead of a fully loaded string.
+///
+/// JSON input may be a single array, read one element at a time, or a
+/// sequence of top-level values such as NDJSON. With `input.json.records_path`
+/// the whole document is needed, so the reader is consumed up front.
+pub fn transform_stream_reader<'a, R>(
+    rule: &'a RuleFile,
+    reader: R,
+    context: Option<&'a JsonValue>,
+) -> Result<TransformStream<'a>, TransformError>
+where
+    R: BufRead + 'a,
+{
+    if rule.finalize.is_some() {
+        return Err(TransformError::new(
+            TransformErrorKind::InvalidInput,
+            "finalize is not supported in stream mode",
+        ));
+    }
+    let records = reader_records_iter(rule, reader)?;
+    Ok(TransformStream::from_records(rule, records, context, None))
+}
+
+pub fn transform_stream_reader_with_base_dir<'a, R>(
+    rule: &'a RuleFile,
+    reader: R,
+    context: Option<&'a JsonValue>,
+    base_dir: &'a Path,
+) -> Result<TransformStream<'a>, TransformError>
+where
+    R: BufRead + 'a,
+{
+    if rule.finalize.is_some() {
+        return Err(TransformError::new(
+            TransformErrorKind::InvalidInput,
+            "finalize is not supported in stream mode",
+        ));
+    }
+    let records = reader_records_iter(rule, reader)?;
+    Ok(TransformStream::from_records(
+        rule,
+        records,
+        context,
+        Some(base_dir),
+    ))
+}
+
+/// Size limits for `transform_stream_batched`. `None` leaves a dimension unbounded.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BatchLimits {
+    pub max_records: Option<usize>,
+    pub max_bytes: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+pub struct TransformBatch {
+    pub records: Vec<JsonValue>,
+    /// Serialized size of `records` as a compact JSON array.
+    pub bytes: usize,
+    pub warnings: Vec<TransformWarning>,
+}
+
+/// Stream records and hand them to `on_batch` in batches bounded by `limits`.
+///
+/// A single record larger than `max_bytes` is delivered in a batch of its own.
+pub fn transform_stream_batched<F>(
+    rule: &RuleFile,
+    input: &str,
+    context: Option<&JsonValue>,
+    limits: BatchLimits,
+    on_batch: F,
+) -> Result<(), TransformError>
+where
+    F: FnMut(TransformBatch) -> Result<(), TransformError>,
+{
+    let stream = transform_stream(rule, input, context)?;
+    emit_batches(stream, limits, on_batch)
+}
+
+pub fn transform_stream_batched_with_base_dir<F>(
+    rule: &RuleFile,
+    input: &str,
+    context: Option<&JsonValue>,
+    base_dir: &Path,
+    limits: BatchLimits,
+    on_batch: F,
+) -> Result<(), TransformError>
+where
+    F: FnMut(TransformBatch) -> Result<(), TransformError>,
+{
+    let stream = transform_stream_with_base_dir(rule, input, context, base_dir)?;
+    emit_batches(stream, limits, on_batch)
+}
+
+fn emit_batches<F>(
+    stream: TransformStream<'_>,
+    limits: BatchLimits,
+    mut on_batch: F,
+) -> Result<(), TransformError>
+where
+    F: FnMut(TransformBatch) -> Result<(), TransformError>,
+{
+    if limits.max_records == Some(0) {
+        return Err(TransformError::new(
+            TransformErrorKind::InvalidInput,
+            "batch max_records must be greater than 0",
+        ));
+    }
+
+    let mut batch = TransformBatch::default();
+    for item in stream {
+        let item = item?;
+        let Some(output) = item.output else {
+            batch.warnings.extend(item.warnings);
+            continue;
+        };
+        let record_bytes = serde_json::to_vec(&output)?.len();
+        let fits = limits
+            .max_records
+            .is_none_or(|max| batch.records.len() < max)
+            && limits
+                .max_bytes
+                .is_none_or(|max| batch.bytes + record_bytes < max);
+        if !batch.records.is_empty() && !fits {
+            on_batch(std::mem::take(&mut batch))?;
+        }
+        // "[" + "]" for the first record, "," separators afterwards.
+        batch.bytes += if batch.records.is_empty() {
+            record_bytes + 2
+        } else {
+            record_bytes + 1
+        };
+        batch.records.push(output);
+        batch.warnings.extend(item.warnings);
+    }
+    if !batch.records.is_empty() || !batch.warnings.is_empty() {
+        on_batch(batch)?;
+    }
+    Ok(())
+}
+
 pub fn transform_with_warnings(
     rule: &RuleFile,
     input: &str,
     context: Option<&JsonValue>,
 ) -> Result<(JsonValue, Vec<TransformWarning>), TransformError> {
-    transform_with_warnings_inner(rule, input, context, None)
+    transform_with_warnings_inner(rule, input, context, None, None)
+        .map(|preview| (preview.output, preview.warnings))
 }
 
 pub fn transform_with_warnings_with_base_dir(
@@ -203,7 +412,194 @@ pub fn transform_with_warnings_with_base_dir(
     context: Option<&JsonValue>,
     base_dir: &Path,
 ) -> Result<(JsonValue, Vec<TransformWarning>), TransformError> {
-    transform_with_warnings_inner(rule, input, context, Some(base_dir))
+    transform_with_warnings_inner(rule, input, context, Some(base_dir), None)
+        .map(|preview| (preview.output, preview.warnings))
+}
+
+/// Like `transform`, but fails with the first warning, such as a deprecated op, as an error.
+pub fn transform_strict(
+    rule: &RuleFile,
+    input: &str,
+    context: Option<&JsonValue>,
+) -> Result<JsonValue, TransformError> {
+    strict_output(transform_with_warnings(rule, input, context)?)
+}
+
+pub fn transform_strict_with_base_dir(
+    rule: &RuleFile,
+    input: &str,
+    context: Option<&JsonValue>,
+    base_dir: &Path,
+) -> Result<JsonValue, TransformError> {
+    strict_output(transform_with_warnings_with_base_dir(
+        rule, input, context, base_dir,
+    )?)
+}
+
+fn strict_output(
+    (output, warnings): (JsonValue, Vec<TransformWarning>),
+) -> Result<JsonValue, TransformError> {
+    match warnings.into_iter().next() {
+        Some(warning) => Err(warning.into()),
+        None => Ok(output),
+    }
+}
+
+/// Optional behavior for `transform_with_options`. The default matches `transform_with_warnings`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TransformOptions<'a> {
+    pub base_dir: Option<&'a Path>,
+    /// Recursively remove object keys whose value is null from the output.
+    pub drop_null_fields: bool,
+    /// Also remove null items from arrays (only with `drop_null_fields`).
+    pub drop_null_items: bool,
+    /// Return an object keyed by this dot path of each output record instead of an array.
+    pub key_by: Option<&'a str>,
+    /// How `key_by` handles two records with the same key.
+    pub duplicate_keys: DuplicateKeys,
+    /// Stop after this many output records. Without `finalize` the remaining input is not
+    /// transformed; with `finalize` the cap applies to the finalized array.
+    pub max_records: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Fail the transform.
+    #[default]
+    Error,
+    /// Keep the last record and emit a warning.
+    Warn,
+}
+
+/// Output of `transform_preview`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransformPreview {
+    pub output: JsonValue,
+    pub warnings: Vec<TransformWarning>,
+    /// True when `max_records` cut off at least one more output record.
+    pub has_more: bool,
+}
+
+pub fn transform_with_options(
+    rule: &RuleFile,
+    input: &str,
+    context: Option<&JsonValue>,
+    options: &TransformOptions<'_>,
+) -> Result<(JsonValue, Vec<TransformWarning>), TransformError> {
+    transform_preview(rule, input, context, options)
+        .map(|preview| (preview.output, preview.warnings))
+}
+
+/// Like `transform_with_options`, but also reports whether `max_records` truncated the output.
+pub fn transform_preview(
+    rule: &RuleFile,
+    input: &str,
+    context: Option<&JsonValue>,
+    options: &TransformOptions<'_>,
+) -> Result<TransformPreview, TransformError> {
+    let mut preview =
+        transform_with_warnings_inner(rule, input, context, options.base_dir, options.max_records)?;
+    if let Some(key_by) = options.key_by {
+        preview.output = key_output_records(
+            preview.output,
+            key_by,
+            options.duplicate_keys,
+            &mut preview.warnings,
+        )?;
+    }
+    if options.drop_null_fields {
+        drop_null_fields(&mut preview.output, options.drop_null_items);
+    }
+    Ok(preview)
+}
+
+fn key_output_records(
+    output: JsonValue,
+    key_by: &str,
+    duplicate_keys: DuplicateKeys,
+    warnings: &mut Vec<TransformWarning>,
+) -> Result<JsonValue, TransformError> {
+    let tokens = parse_path(key_by).map_err(|_| {
+        TransformError::new(TransformErrorKind::InvalidInput, "key_by path is invalid")
+            .with_path(key_by)
+    })?;
+    let records = match output {
+        JsonValue::Array(records) => records,
+        _ => {
+            return Err(TransformError::new(
+                TransformErrorKind::InvalidInput,
+                "key_by requires the output to be an array of records",
+            ));
+        }
+    };
+
+    let mut keyed = Map::new();
+    for (index, record) in records.into_iter().enumerate() {
+        let record_path = format!("records[{}].{}", index, key_by);
+        let key = match lookup_path(
+            &record,
+            &tokens,
+            TransformErrorKind::InvalidTarget,
+            record_path.as_str(),
+        )?
+        .as_deref()
+        {
+            Some(JsonValue::String(key)) => key.clone(),
+            Some(JsonValue::Number(key)) => key.to_string(),
+            Some(JsonValue::Bool(key)) => key.to_string(),
+            Some(_) => {
+                return Err(TransformError::new(
+                    TransformErrorKind::InvalidTarget,
+                    "key_by value must be a string, number, or bool",
+                )
+                .with_path(record_path));
+            }
+            None => {
+                return Err(TransformError::new(
+                    TransformErrorKind::MissingRequired,
+                    "key_by value is missing",
+                )
+                .with_path(record_path));
+            }
+        };
+        if keyed.contains_key(&key) {
+            let message = format!("duplicate key_by value: {}", key);
+            match duplicate_keys {
+                DuplicateKeys::Error => {
+                    return Err(
+                        TransformError::new(TransformErrorKind::InvalidTarget, message)
+                            .with_path(record_path),
+                    );
+                }
+                DuplicateKeys::Warn => warnings.push(
+                    TransformWarning::new(TransformErrorKind::InvalidTarget, message)
+                        .with_path(record_path),
+                ),
+            }
+        }
+        keyed.insert(key, record);
+    }
+    Ok(JsonValue::Object(keyed))
+}
+
+fn drop_null_fields(value: &mut JsonValue, drop_null_items: bool) {
+    match value {
+        JsonValue::Object(map) => {
+            map.retain(|_, value| !value.is_null());
+            for value in map.values_mut() {
+                drop_null_fields(value, drop_null_items);
+            }
+        }
+        JsonValue::Array(items) => {
+            if drop_null_items {
+                items.retain(|item| !item.is_null());
+            }
+            for item in items {
+                drop_null_fields(item, drop_null_items);
+            }
+        }
+        _ => {}
+    }
 }
 
 fn transform_with_warnings_inner(
@@ -211,10 +607,13 @@ fn transform_with_warnings_inner(
     input: &str,
     context: Option<&JsonValue>,
     base_dir: Option<&Path>,
-) -> Result<(JsonValue, Vec<TransformWarning>), TransformError> {
+    max_records: Option<usize>,
+) -> Result<TransformPreview, TransformError> {
     let mut warnings = Vec::new();
     let mut output_records = Vec::new();
+    let mut has_more = false;
     if rule.finalize.is_some() {
+        warnings.extend(deprecation_warnings(rule));
         let mut records = input_records_iter(rule, input)?;
         while let Some(record) = records.next() {
             let record = record?;
@@ -232,6 +631,16 @@ fn transform_with_warnings_inner(
             None => transform_stream(rule, input, context)?,
         };
         for item in stream {
+            if max_records.is_some_and(|max| output_records.len() >= max) {
+                // Only look for one more output record; errors past the cap also mean more input.
+                match item {
+                    Ok(item) if item.output.is_none() => continue,
+                    _ => {
+                        has_more = true;
+                        break;
+                    }
+                }
+            }
             let item = item?;
             warnings.extend(item.warnings);
             if let Some(output) = item.output {
@@ -243,9 +652,73 @@ fn transform_with_warnings_inner(
     let mut output = JsonValue::Array(output_records);
     if let Some(finalize) = &rule.finalize {
         output = apply_finalize(finalize, output, context)?;
+        if let (Some(max), JsonValue::Array(records)) = (max_records, &mut output) {
+            has_more = records.len() > max;
+            records.truncate(max);
+        }
     }
 
-    Ok((output, warnings))
+    Ok(TransformPreview {
+        output,
+        warnings,
+        has_more,
+    })
+}
+
+/// Output of `transform_collect_errors`: the successful outputs, and each failed record's
+/// input index with its error.
+pub type CollectedRecords = (Vec<JsonValue>, Vec<(usize, TransformError)>);
+
+/// Like `transform`, but keeps going when a record fails.
+///
+/// Returns the output of every record that succeeded, in input order, and each failed
+/// record's input index with its error. A record that cannot be read (such as a malformed
+/// CSV row) is reported at its index and ends the input. Errors that stop the whole input,
+/// such as unparsable JSON or a failing `finalize`, are still returned as `Err`. With
+/// `finalize`, it runs over the successful outputs.
+pub fn transform_collect_errors(
+    rule: &RuleFile,
+    input: &str,
+    context: Option<&JsonValue>,
+) -> Result<CollectedRecords, TransformError> {
+    transform_collect_errors_inner(rule, input, context, None)
+}
+
+pub fn transform_collect_errors_with_base_dir(
+    rule: &RuleFile,
+    input: &str,
+    context: Option<&JsonValue>,
+    base_dir: &Path,
+) -> Result<CollectedRecords, TransformError> {
+    transform_collect_errors_inner(rule, input, context, Some(base_dir))
+}
+
+fn transform_collect_errors_inner(
+    rule: &RuleFile,
+    input: &str,
+    context: Option<&JsonValue>,
+    base_dir: Option<&Path>,
+) -> Result<CollectedRecords, TransformError> {
+    let mut outputs = Vec::new();
+    let mut failures = Vec::new();
+    for (index, record) in input_records_iter(rule, input)?.enumerate() {
+        let result = record.and_then(|record| {
+            apply_rule_to_record(rule, &record, context, &mut Vec::new(), base_dir)
+        });
+        match result {
+            Ok(Some(output)) => outputs.push(output),
+            Ok(None) => {}
+            Err(err) => failures.push((index, err)),
+        }
+    }
+
+    if let Some(finalize) = &rule.finalize {
+        outputs = match apply_finalize(finalize, JsonValue::Array(outputs), context)? {
+            JsonValue::Array(records) => records,
+            other => vec![other],
+        };
+    }
+    Ok((outputs, failures))
 }
 
 pub fn transform_record(
@@ -332,6 +805,7 @@ fn preflight_validate_with_warnings_inner(
 ) -> Result<Vec<TransformWarning>, TransformError> {
     let mut warnings = Vec::new();
     if rule.finalize.is_some() {
+        warnings.extend(deprecation_warnings(rule));
         let mut output_records = Vec::new();
         let mut records = input_records_iter(rule, input)?;
         while let Some(record) = records.next() {
@@ -416,6 +890,13 @@ fn apply_rule_to_record(
     warnings: &mut Vec<TransformWarning>,
     base_dir: Option<&Path>,
 ) -> Result<Option<JsonValue>, TransformError> {
+    if !rule.include.is_empty() {
+        return Err(TransformError::new(
+            TransformErrorKind::InvalidInput,
+            "rule has unresolved include; load it with load_rule_file_with_includes",
+        )
+        .with_path("include"));
+    }
     if let Some(steps) = &rule.steps {
         return apply_steps(steps, record, context, warnings, rule.version, base_dir);
     }
@@ -582,6 +1063,19 @@ fn load_rule_from_path(
         )
         .with_path(path)
     })?;
+    let rule = if rule.include.is_empty() {
+        rule
+    } else {
+        crate::resolve_rule_includes(rule, yaml, &resolved)
+            .map_err(|err| {
+                TransformError::new(
+                    TransformErrorKind::InvalidInput,
+                    format!("failed to resolve includes: {}", err.message),
+                )
+                .with_path(path)
+            })?
+            .rule
+    };
     let resolved_base = resolved
         .parent()
         .unwrap_or_else(|| Path::new("."))
@@ -660,14 +1154,20 @@ fn apply_finalize(
 
         let mut items = Vec::with_capacity(records.len());
         for (index, item) in records.iter().enumerate() {
-            let key_value = get_path(item, &tokens).ok_or_else(|| {
+            let key_value = lookup_path(
+                item,
+                &tokens,
+                TransformErrorKind::InvalidRef,
+                "finalize.sort.by",
+            )?
+            .ok_or_else(|| {
                 TransformError::new(
                     TransformErrorKind::InvalidRef,
                     "finalize.sort.by path not found",
                 )
                 .with_path("finalize.sort.by")
             })?;
-            let key = sort_key_from_value(key_value, "finalize.sort.by")?;
+            let key = sort_key_from_value(&key_value, "finalize.sort.by")?;
             items.push(SortItem {
                 key,
                 index,
@@ -772,16 +1272,54 @@ fn input_records_iter<'a>(
     input: &'a str,
 ) -> Result<InputRecordsIter<'a>, TransformError> {
     match rule.input.format {
-        InputFormat::Csv => Ok(InputRecordsIter::Csv(CsvRecordIter::new(rule, input)?)),
+        InputFormat::Csv => Ok(InputRecordsIter::Csv(CsvRecordIter::new(
+            rule,
+            input.as_bytes(),
+        )?)),
         InputFormat::Json => Ok(InputRecordsIter::Json(JsonRecordIter::new(parse_json(
             rule, input,
         )?))),
     }
 }
 
+fn reader_records_iter<'a, R>(
+    rule: &RuleFile,
+    mut reader: R,
+) -> Result<InputRecordsIter<'a>, TransformError>
+where
+    R: BufRead + 'a,
+{
+    let has_records_path = rule
+        .input
+        .json
+        .as_ref()
+        .is_some_and(|json| json.records_path.is_some());
+    match rule.input.format {
+        InputFormat::Csv => Ok(InputRecordsIter::Reader(Box::new(CsvRecordIter::new(
+            rule, reader,
+        )?))),
+        InputFormat::Json if has_records_path => {
+            let mut input = String::new();
+            reader.read_to_string(&mut input).map_err(|err| {
+                TransformError::new(
+                    TransformErrorKind::InvalidInput,
+                    format!("failed to read input: {}", err),
+                )
+            })?;
+            Ok(InputRecordsIter::Json(JsonRecordIter::new(parse_json(
+                rule, &input,
+            )?)))
+        }
+        InputFormat::Json => Ok(InputRecordsIter::Reader(Box::new(
+            JsonReaderRecordIter::new(reader)?,
+        ))),
+    }
+}
+
 enum InputRecordsIter<'a> {
-    Csv(CsvRecordIter<'a>),
+    Csv(CsvRecordIter<&'a [u8]>),
     Json(JsonRecordIter),
+    Reader(Box<dyn Iterator<Item = Result<JsonValue, TransformError>> + 'a>),
 }
 
 impl<'a> Iterator for InputRecordsIter<'a> {
@@ -791,18 +1329,31 @@ impl<'a> Iterator for InputRecordsIter<'a> {
         match self {
             InputRecordsIter::Csv(iter) => iter.next(),
             InputRecordsIter::Json(iter) => iter.next(),
+            InputRecordsIter::Reader(iter) => iter.next(),
         }
     }
 }
 
-struct CsvRecordIter<'a> {
-    reader: csv::Reader<&'a [u8]>,
+struct CsvRecordIter<R: Read> {
+    reader: csv::Reader<R>,
     headers: Vec<String>,
+    header_source: CsvHeaderSource,
     done: bool,
 }
 
-impl<'a> CsvRecordIter<'a> {
-    fn new(rule: &RuleFile, input: &'a str) -> Result<Self, TransformError> {
+/// Where column names for CSV records come from.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CsvHeaderSource {
+    /// The first row of the input.
+    HeaderRow,
+    /// `csv.columns`; every record must have exactly that many fields.
+    Columns,
+    /// `col0`, `col1`, ... named from the width of the first record.
+    Positional,
+}
+
+impl<R: Read> CsvRecordIter<R> {
+    fn new(rule: &RuleFile, input: R) -> Result<Self, TransformError> {
         let csv_spec = rule.input.csv.as_ref().ok_or_else(|| {
             TransformError::new(
                 TransformErrorKind::InvalidInput,
@@ -810,47 +1361,51 @@ impl<'a> CsvRecordIter<'a> {
             )
         })?;
 
-        let delimiter_chars: Vec<char> = csv_spec.delimiter.chars().collect();
-        if delimiter_chars.len() != 1 {
-            return Err(TransformError::new(
+        let delimiter = csv_spec.delimiter_byte().ok_or_else(|| {
+            TransformError::new(
                 TransformErrorKind::InvalidInput,
-                "csv.delimiter must be a single character",
-            ));
-        }
-        let delimiter = delimiter_chars[0] as u8;
+                "csv.delimiter must be a single ASCII character",
+            )
+        })?;
+        let quote = csv_spec.quote_byte().ok_or_else(|| {
+            TransformError::new(
+                TransformErrorKind::InvalidInput,
+                "csv.quote must be a single ASCII character",
+            )
+        })?;
 
         let mut reader = ReaderBuilder::new()
             .delimiter(delimiter)
+            .quote(quote)
             .has_headers(csv_spec.has_header)
-            .from_reader(input.as_bytes());
+            .from_reader(input);
 
-        let headers: Vec<String> = if csv_spec.has_header {
+        let (headers, header_source) = if csv_spec.has_header {
             let header_record = reader.headers().map_err(|err| {
                 TransformError::new(
                     TransformErrorKind::InvalidInput,
                     format!("failed to read csv header: {}", err),
                 )
             })?;
-            header_record.iter().map(|s| s.to_string()).collect()
+            let headers = header_record.iter().map(|s| s.to_string()).collect();
+            (headers, CsvHeaderSource::HeaderRow)
+        } else if let Some(columns) = &csv_spec.columns {
+            let headers = columns.iter().map(|col| col.name.clone()).collect();
+            (headers, CsvHeaderSource::Columns)
         } else {
-            let columns = csv_spec.columns.as_ref().ok_or_else(|| {
-                TransformError::new(
-                    TransformErrorKind::InvalidInput,
-                    "csv.columns is required when has_header=false",
-                )
-            })?;
-            columns.iter().map(|col| col.name.clone()).collect()
+            (Vec::new(), CsvHeaderSource::Positional)
         };
 
         Ok(Self {
             reader,
             headers,
+            header_source,
             done: false,
         })
     }
 }
 
-impl<'a> Iterator for CsvRecordIter<'a> {
+impl<R: Read> Iterator for CsvRecordIter<R> {
     type Item = Result<JsonValue, TransformError>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -865,6 +1420,29 @@ impl<'a> Iterator for CsvRecordIter<'a> {
                     self.done = true;
                     return None;
                 }
+                match self.header_source {
+                    CsvHeaderSource::HeaderRow => {}
+                    CsvHeaderSource::Columns => {
+                        if record.len() != self.headers.len() {
+                            self.done = true;
+                            return Some(Err(TransformError::new(
+                                TransformErrorKind::InvalidInput,
+                                format!(
+                                    "csv record has {} fields but csv.columns lists {}",
+                                    record.len(),
+                                    self.headers.len()
+                                ),
+                            )));
+                        }
+                    }
+                    CsvHeaderSource::Positional => {
+                        if self.headers.is_empty() {
+                            self.headers = (0..record.len())
+                                .map(|index| format!("col{}", index))
+                                .collect();
+                        }
+                    }
+                }
                 let obj = record_to_object(&self.headers, &record);
                 Some(Ok(JsonValue::Object(obj)))
             }
@@ -899,6 +1477,197 @@ impl Iterator for JsonRecordIter {
     }
 }
 
+/// Reads JSON records from a reader one at a time: the elements of a
+/// top-level array, or each top-level value of a stream such as NDJSON.
+struct JsonReaderRecordIter<R: BufRead> {
+    state: JsonReaderState<R>,
+    index: usize,
+}
+
+enum JsonReaderState<R: BufRead> {
+    Array(R),
+    Values(serde_json::StreamDeserializer<'static, serde_json::de::IoRead<R>, JsonValue>),
+    Done,
+}
+
+impl<R: BufRead> JsonReaderRecordIter<R> {
+    fn new(mut reader: R) -> Result<Self, TransformError> {
+        let state = match skip_json_whitespace(&mut reader)? {
+            None => {
+                return Err(TransformError::new(
+                    TransformErrorKind::InvalidInput,
+                    "failed to parse JSON input: input is empty",
+                ));
+            }
+            Some(b'[') => {
+                reader.consume(1);
+                JsonReaderState::Array(reader)
+            }
+            Some(_) => JsonReaderState::Values(
+                serde_json::Deserializer::from_reader(reader).into_iter::<JsonValue>(),
+            ),
+        };
+        Ok(Self { state, index: 0 })
+    }
+
+    fn next_array_element(&mut self) -> Result<Option<JsonValue>, TransformError> {
+        let JsonReaderState::Array(reader) = &mut self.state else {
+            return Ok(None);
+        };
+        let mut next = skip_json_whitespace(reader)?;
+        if self.index > 0 {
+            match next {
+                Some(b']') => return finish_json_array(reader, self.index),
+                Some(b',') => {
+                    reader.consume(1);
+                    next = skip_json_whitespace(reader)?;
+                }
+                None => return Err(json_record_error(self.index, "unexpected end of input")),
+                _ => return Err(json_record_error(self.index, "expected `,` or `]`")),
+            }
+        } else if next == Some(b']') {
+            return finish_json_array(reader, self.index);
+        }
+        if next.is_none() {
+            return Err(json_record_error(self.index, "unexpected end of input"));
+        }
+        let bytes = read_json_array_element(reader, self.index)?;
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|err| json_record_error(self.index, &err.to_string()))
+    }
+}
+
+impl<R: BufRead> Iterator for JsonReaderRecordIter<R> {
+    type Item = Result<JsonValue, TransformError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = match &mut self.state {
+            JsonReaderState::Done => return None,
+            JsonReaderState::Array(_) => self.next_array_element(),
+            JsonReaderState::Values(values) => values
+                .next()
+                .transpose()
+                .map_err(|err| json_record_error(self.index, &err.to_string())),
+        };
+        match result {
+            Ok(Some(record)) => {
+                self.index += 1;
+                Some(Ok(record))
+            }
+            Ok(None) => {
+                self.state = JsonReaderState::Done;
+                None
+            }
+            Err(err) => {
+                self.state = JsonReaderState::Done;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+fn json_record_error(index: usize, message: &str) -> TransformError {
+    TransformError::new(
+        TransformErrorKind::InvalidInput,
+        format!(
+            "failed to parse JSON input at record {}: {}",
+            index, message
+        ),
+    )
+}
+
+fn json_read_error(err: std::io::Error) -> TransformError {
+    TransformError::new(
+        TransformErrorKind::InvalidInput,
+        format!("failed to read input: {}", err),
+    )
+}
+
+/// Consumes the closing `]` and rejects anything but whitespace after it.
+fn finish_json_array<R: BufRead>(
+    reader: &mut R,
+    index: usize,
+) -> Result<Option<JsonValue>, TransformError> {
+    reader.consume(1);
+    match skip_json_whitespace(reader)? {
+        None => Ok(None),
+        Some(_) => Err(json_record_error(index, "trailing data after array")),
+    }
+}
+
+/// Skips whitespace and returns the next byte without consuming it.
+fn skip_json_whitespace<R: BufRead>(reader: &mut R) -> Result<Option<u8>, TransformError> {
+    loop {
+        let buf = reader.fill_buf().map_err(json_read_error)?;
+        if buf.is_empty() {
+            return Ok(None);
+        }
+        match buf.iter().position(|byte| !byte.is_ascii_whitespace()) {
+            Some(pos) => {
+                let byte = buf[pos];
+                reader.consume(pos);
+                return Ok(Some(byte));
+            }
+            None => {
+                let len = buf.len();
+                reader.consume(len);
+            }
+        }
+    }
+}
+
+/// Collects the bytes of one array element, stopping before the `,` or `]`
+/// that ends it at nesting depth zero.
+fn read_json_array_element<R: BufRead>(
+    reader: &mut R,
+    index: usize,
+) -> Result<Vec<u8>, TransformError> {
+    let mut bytes = Vec::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    loop {
+        let buf = reader.fill_buf().map_err(json_read_error)?;
+        if buf.is_empty() {
+            if bytes.is_empty() {
+                return Err(json_record_error(index, "unexpected end of input"));
+            }
+            // The missing `]` is reported when the next element is read.
+            return Ok(bytes);
+        }
+        let mut end = None;
+        for (pos, &byte) in buf.iter().enumerate() {
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if byte == b'\\' {
+                    escaped = true;
+                } else if byte == b'"' {
+                    in_string = false;
+                }
+                continue;
+            }
+            match byte {
+                b'"' => in_string = true,
+                b'[' | b'{' => depth += 1,
+                b']' | b'}' if depth > 0 => depth -= 1,
+                b',' | b']' | b'}' if depth == 0 => {
+                    end = Some(pos);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let len = end.unwrap_or(buf.len());
+        bytes.extend_from_slice(&buf[..len]);
+        reader.consume(len);
+        if end.is_some() {
+            return Ok(bytes);
+        }
+    }
+}
+
 fn parse_json(rule: &RuleFile, input: &str) -> Result<Vec<JsonValue>, TransformError> {
     let value: JsonValue = serde_json::from_str(input).map_err(|err| {
         TransformError::new(
@@ -918,21 +1687,26 @@ fn parse_json(rule: &RuleFile, input: &str) -> Result<Vec<JsonValue>, TransformE
                 TransformError::new(TransformErrorKind::InvalidRecordsPath, err.message())
                     .with_path("input.json.records_path")
             })?;
-            let found = get_path(&value, &tokens).ok_or_else(|| {
+            lookup_path(
+                &value,
+                &tokens,
+                TransformErrorKind::InvalidRecordsPath,
+                "input.json.records_path",
+            )?
+            .ok_or_else(|| {
                 TransformError::new(
                     TransformErrorKind::InvalidRecordsPath,
                     "records_path does not exist",
                 )
                 .with_path("input.json.records_path")
-            })?;
-            found
+            })?
         }
-        None => &value,
+        None => Cow::Borrowed(&value),
     };
 
-    match records_value {
+    match records_value.as_ref() {
         JsonValue::Array(items) => Ok(items.clone()),
-        JsonValue::Object(_) => Ok(vec![records_value.clone()]),
+        JsonValue::Object(_) => Ok(vec![records_value.into_owned()]),
         _ => Err(TransformError::new(
             TransformErrorKind::InvalidInput,
             "records_path must point to an array or object",
@@ -1168,8 +1942,13 @@ fn resolve_source(
         }
     };
 
-    match target.and_then(|value| get_path(value, &tokens)) {
-        Some(value) => Ok(EvalValue::Value(value.clone())),
+    match target {
+        Some(value) => lookup_path_value(
+            value,
+            &tokens,
+            TransformErrorKind::InvalidRef,
+            format!("{}.source", mapping_path),
+        ),
         None => Ok(EvalValue::Missing),
     }
 }
@@ -1284,10 +2063,7 @@ fn eval_ref(
                     .with_path(base_path));
                 }
             };
-            return match get_path(root, rest) {
-                Some(value) => Ok(EvalValue::Value(value.clone())),
-                None => Ok(EvalValue::Missing),
-            };
+            return lookup_path_value(root, rest, TransformErrorKind::InvalidRef, base_path);
         }
         Namespace::Acc => {
             let acc = locals.and_then(|locals| locals.acc).ok_or_else(|| {
@@ -1307,10 +2083,7 @@ fn eval_ref(
                     .with_path(base_path));
                 }
             };
-            return match get_path(root, rest) {
-                Some(value) => Ok(EvalValue::Value(value.clone())),
-                None => Ok(EvalValue::Missing),
-            };
+            return lookup_path_value(root, rest, TransformErrorKind::InvalidRef, base_path);
         }
         Namespace::Pipe => {
             let pipe_value = locals.and_then(|locals| locals.pipe).ok_or_else(|| {
@@ -1334,10 +2107,7 @@ fn eval_ref(
                 EvalValue::Missing => return Ok(EvalValue::Missing),
                 EvalValue::Value(value) => value,
             };
-            return match get_path(value, rest) {
-                Some(value) => Ok(EvalValue::Value(value.clone())),
-                None => Ok(EvalValue::Missing),
-            };
+            return lookup_path_value(value, rest, TransformErrorKind::InvalidRef, base_path);
         }
         Namespace::Local => {
             let locals_map = locals.and_then(|locals| locals.locals).ok_or_else(|| {
@@ -1368,15 +2138,12 @@ fn eval_ref(
                 EvalValue::Missing => return Ok(EvalValue::Missing),
                 EvalValue::Value(value) => value,
             };
-            return match get_path(value, rest) {
-                Some(value) => Ok(EvalValue::Value(value.clone())),
-                None => Ok(EvalValue::Missing),
-            };
+            return lookup_path_value(value, rest, TransformErrorKind::InvalidRef, base_path);
         }
     };
 
-    match target.and_then(|value| get_path(value, &tokens)) {
-        Some(value) => Ok(EvalValue::Value(value.clone())),
+    match target {
+        Some(value) => lookup_path_value(value, &tokens, TransformErrorKind::InvalidRef, base_path),
         None => Ok(EvalValue::Missing),
     }
 }
@@ -2850,11 +3617,16 @@ fn eval_lookup(
 
     let mut results = Vec::new();
     for item in &collection_array {
-        let key_value = match get_path(item, &key_tokens) {
+        let key_value = match lookup_path(
+            item,
+            &key_tokens,
+            TransformErrorKind::ExprError,
+            format!("{}.args[1]", base_path),
+        )? {
             Some(value) => value,
             None => continue,
         };
-        let item_key = match value_to_string_optional(key_value) {
+        let item_key = match value_to_string_optional(&key_value) {
             Some(value) => value,
             None => continue,
         };
@@ -2863,15 +3635,20 @@ fn eval_lookup(
         }
 
         let selected = match output_tokens.as_ref() {
-            Some(tokens) => get_path(item, tokens),
-            None => Some(item),
+            Some(tokens) => lookup_path(
+                item,
+                tokens,
+                TransformErrorKind::ExprError,
+                format!("{}.args[3]", base_path),
+            )?,
+            None => Some(Cow::Borrowed(item)),
         };
 
         if let Some(value) = selected {
             if first_only {
-                return Ok(EvalValue::Value(value.clone()));
+                return Ok(EvalValue::Value(value.into_owned()));
             }
-            results.push(value.clone());
+            results.push(value.into_owned());
         }
     }
 
@@ -3193,7 +3970,7 @@ fn eval_array_flat_map(
     Ok(EvalValue::Value(JsonValue::Array(results)))
 }
 
-fn flatten_value(value: &JsonValue, depth: usize, out: &mut Vec<JsonValue>) {
+pub(crate) fn flatten_value(value: &JsonValue, depth: usize, out: &mut Vec<JsonValue>) {
     if depth == 0 {
         out.push(value.clone());
         return;
@@ -4367,7 +5144,7 @@ fn eval_json_merge(
     deep: bool,
     locals: Option<&EvalLocals<'_>>,
 ) -> Result<EvalValue, TransformError> {
-    let total_len = args_len(args, injected);
+    let mut total_len = args_len(args, injected);
     if total_len < 2 {
         return Err(TransformError::new(
             TransformErrorKind::ExprError,
@@ -4376,12 +5153,42 @@ fn eval_json_merge(
         .with_path(format!("{}.args", base_path)));
     }
 
+    // deep_merge takes an optional trailing array strategy: "replace" (default) or "concat".
+    // The last arg is evaluated once; when it is not a strategy, the merge reuses its value.
+    let mut concat_arrays = false;
+    let mut last_value = None;
+    if deep {
+        let last = total_len - 1;
+        match eval_expr_at_index(
+            last, args, injected, record, context, out, base_path, locals,
+        )? {
+            EvalValue::Value(JsonValue::String(strategy)) => {
+                concat_arrays = match strategy.as_str() {
+                    "replace" => false,
+                    "concat" => true,
+                    _ => {
+                        return Err(TransformError::new(
+                            TransformErrorKind::ExprError,
+                            "deep_merge array strategy must be \"replace\" or \"concat\"",
+                        )
+                        .with_path(format!("{}.args[{}]", base_path, last)));
+                    }
+                };
+                total_len = last;
+            }
+            value => last_value = Some(value),
+        }
+    }
+
     let mut result: Option<Map<String, JsonValue>> = None;
     for index in 0..total_len {
         let arg_path = format!("{}.args[{}]", base_path, index);
-        let value = eval_expr_at_index(
-            index, args, injected, record, context, out, base_path, locals,
-        )?;
+        let value = match last_value.take_if(|_| index + 1 == total_len) {
+            Some(value) => value,
+            None => eval_expr_at_index(
+                index, args, injected, record, context, out, base_path, locals,
+            )?,
+        };
         let value = match value {
             EvalValue::Missing => continue,
             EvalValue::Value(value) => value,
@@ -4405,7 +5212,7 @@ fn eval_json_merge(
         };
 
         match result {
-            Some(ref mut existing) => merge_object(existing, &obj, deep),
+            Some(ref mut existing) => merge_object(existing, &obj, deep, concat_arrays),
             None => result = Some(obj),
         }
     }
@@ -4467,10 +5274,12 @@ fn eval_json_get(
         .with_path(path_path));
     }
     let tokens = parse_path_tokens(&path, TransformErrorKind::ExprError, &path_path)?;
-    match get_path(&base_value, &tokens) {
-        Some(value) => Ok(EvalValue::Value(value.clone())),
-        None => Ok(EvalValue::Missing),
-    }
+    lookup_path_value(
+        &base_value,
+        &tokens,
+        TransformErrorKind::ExprError,
+        path_path,
+    )
 }
 
 fn eval_json_pick(
@@ -4526,8 +5335,13 @@ fn eval_json_pick(
 
     let mut output = JsonValue::Object(Map::new());
     for tokens in paths {
-        if let Some(value) = get_path(&base_value, &tokens) {
-            set_path_with_indexes(&mut output, &tokens, value.clone(), base_path)?;
+        if let Some(value) = lookup_path(
+            &base_value,
+            &tokens,
+            TransformErrorKind::ExprError,
+            base_path,
+        )? {
+            set_path_with_indexes(&mut output, &tokens, value.into_owned(), base_path)?;
         }
     }
 
@@ -4883,7 +5697,7 @@ fn eval_json_object_unflatten(
                 )?;
                 if tokens
                     .iter()
-                    .any(|token| matches!(token, PathToken::Index(_)))
+                    .any(|token| !matches!(token, PathToken::Key(_)))
                 {
                     return Err(TransformError::new(
                         TransformErrorKind::ExprError,
@@ -5016,6 +5830,13 @@ fn eval_json_paths_arg(
     let mut paths = Vec::new();
     for (item_path, path) in items {
         let tokens = parse_path_tokens(&path, TransformErrorKind::ExprError, &item_path)?;
+        if tokens.iter().any(PathToken::is_multi) {
+            return Err(TransformError::new(
+                TransformErrorKind::ExprError,
+                "wildcards and slices are not allowed in path",
+            )
+            .with_path(item_path));
+        }
         if !allow_terminal_index && matches!(tokens.last(), Some(PathToken::Index(_))) {
             return Err(TransformError::new(
                 TransformErrorKind::ExprError,
@@ -5060,14 +5881,22 @@ fn merge_object(
     target: &mut Map<String, JsonValue>,
     incoming: &Map<String, JsonValue>,
     deep: bool,
+    concat_arrays: bool,
 ) {
     for (key, value) in incoming {
         if deep {
-            if let (Some(JsonValue::Object(target_obj)), JsonValue::Object(incoming_obj)) =
-                (target.get_mut(key), value)
-            {
-                merge_object(target_obj, incoming_obj, true);
-                continue;
+            match (target.get_mut(key), value) {
+                (Some(JsonValue::Object(target_obj)), JsonValue::Object(incoming_obj)) => {
+                    merge_object(target_obj, incoming_obj, true, concat_arrays);
+                    continue;
+                }
+                (Some(JsonValue::Array(target_items)), JsonValue::Array(incoming_items))
+                    if concat_arrays =>
+                {
+                    target_items.extend(incoming_items.iter().cloned());
+                    continue;
+                }
+                _ => {}
             }
         }
         target.insert(key.clone(), value.clone());
@@ -5131,7 +5960,7 @@ fn format_path_tokens(tokens: &[PathToken]) -> String {
                     if !path.is_empty() {
                         path.push('.');
                     }
-                    path.push_str(key);
+                    path.push_str(&escape_key(key));
                 }
             }
             PathToken::Index(index) => {
@@ -5139,13 +5968,22 @@ fn format_path_tokens(tokens: &[PathToken]) -> String {
                 path.push_str(&index.to_string());
                 path.push(']');
             }
+            PathToken::Wildcard => path.push_str("[*]"),
+            PathToken::Slice { start, end } => {
+                path.push_str(&format!("[{}:", start));
+                if let Some(end) = end {
+                    path.push_str(&end.to_string());
+                }
+                path.push(']');
+            }
         }
     }
     path
 }
 
 fn needs_bracket_quote(key: &str) -> bool {
-    key.contains('.')
+    // Bracket quotes cannot hold `[` or `]`; those keys use backslash escapes instead.
+    key.contains('.') && !key.contains(['[', ']'])
 }
 
 fn set_path_object_only(
@@ -5165,7 +6003,7 @@ fn set_path_object_only(
     for (index, token) in tokens.iter().enumerate() {
         let key = match token {
             PathToken::Key(key) => key,
-            PathToken::Index(_) => {
+            _ => {
                 return Err(TransformError::new(
                     TransformErrorKind::ExprError,
                     "array indexes are not allowed in path",
@@ -5303,6 +6141,13 @@ fn set_path_with_indexes(
                     }
                 }
             }
+            PathToken::Wildcard | PathToken::Slice { .. } => {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "wildcards and slices are not allowed in path",
+                )
+                .with_path(format!("{}.args[1]", base_path)));
+            }
         }
     }
 
@@ -5334,6 +6179,8 @@ fn remove_path(root: &mut JsonValue, tokens: &[PathToken]) {
                 }
             }
         }
+        // Rejected when the omit paths are parsed.
+        PathToken::Wildcard | PathToken::Slice { .. } => {}
     }
 }
 
@@ -5671,6 +6518,38 @@ fn apply_timezone(
         .ok_or_else(|| expr_type_error("date is invalid", path))
 }
 
+/// Parse a date string, interpreting values without an offset in `default_zone`.
+pub(crate) fn parse_datetime_in_zone(
+    value: &str,
+    default_zone: &chrono_tz::Tz,
+) -> Option<DateTime<FixedOffset>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt);
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc2822(value) {
+        return Some(dt);
+    }
+    for format in DEFAULT_DATE_FORMATS_WITH_TZ {
+        if let Ok(dt) = DateTime::parse_from_str(value, format) {
+            return Some(dt);
+        }
+    }
+    for format in DEFAULT_DATE_FORMATS {
+        let naive = match NaiveDateTime::parse_from_str(value, format) {
+            Ok(naive) => naive,
+            Err(_) => match NaiveDate::parse_from_str(value, format) {
+                Ok(date) => date.and_hms_opt(0, 0, 0)?,
+                Err(_) => continue,
+            },
+        };
+        return default_zone
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|dt| dt.fixed_offset());
+    }
+    None
+}
+
 fn looks_like_timezone(value: &str) -> bool {
     if value.eq_ignore_ascii_case("utc") || value == "Z" {
         return true;
@@ -5816,7 +6695,11 @@ fn value_to_number(value: &JsonValue, path: &str, message: &str) -> Result<f64,
     }
 }
 
-fn value_to_i64(value: &JsonValue, path: &str, message: &str) -> Result<i64, TransformError> {
+pub(crate) fn value_to_i64(
+    value: &JsonValue,
+    path: &str,
+    message: &str,
+) -> Result<i64, TransformError> {
     match value {
         JsonValue::Number(n) => {
             if let Some(i) = n.as_i64() {
@@ -5843,7 +6726,7 @@ fn value_to_i64(value: &JsonValue, path: &str, message: &str) -> Result<i64, Tra
     }
 }
 
-fn json_number_from_f64(value: f64, path: &str) -> Result<JsonValue, TransformError> {
+pub(crate) fn json_number_from_f64(value: f64, path: &str) -> Result<JsonValue, TransformError> {
     if !value.is_finite() {
         return Err(expr_type_error("number result is not finite", path));
     }
@@ -6072,6 +6955,29 @@ fn parse_path_tokens(
         .map_err(|err| TransformError::new(kind, err.message()).with_path(error_path.into()))
 }
 
+fn lookup_path<'a>(
+    value: &'a JsonValue,
+    tokens: &[PathToken],
+    kind: TransformErrorKind,
+    error_path: impl Into<String>,
+) -> Result<Option<Cow<'a, JsonValue>>, TransformError> {
+    get_path(value, tokens)
+        .map_err(|err| TransformError::new(kind, err.message()).with_path(error_path.into()))
+}
+
+/// Like `lookup_path`, but as an `EvalValue` that is missing when the path is.
+fn lookup_path_value(
+    value: &JsonValue,
+    tokens: &[PathToken],
+    kind: TransformErrorKind,
+    error_path: impl Into<String>,
+) -> Result<EvalValue, TransformError> {
+    Ok(match lookup_path(value, tokens, kind, error_path)? {
+        Some(value) => EvalValue::Value(value.into_owned()),
+        None => EvalValue::Missing,
+    })
+}
+
 fn set_path(
     root: &mut JsonValue,
     path: &str,
@@ -6096,7 +7002,7 @@ fn set_path(
         let is_last = index == tokens.len() - 1;
         let key = match token {
             PathToken::Key(key) => key,
-            PathToken::Index(_) => {
+            _ => {
                 return Err(TransformError::new(
                     TransformErrorKind::InvalidTarget,
                     "target path must not include indexes",
@@ -6150,7 +7056,7 @@ fn literal_string(expr: &Expr) -> Option<&str> {
 /// - Ref where ref_path starts with @ -> single element array
 /// - Chain where first element starts with @ -> convert to array
 /// Returns None if it looks like v1 expression and should be handled by v1 eval.
-fn expr_to_json_for_v2_pipe(expr: &Expr) -> Option<JsonValue> {
+pub(crate) fn expr_to_json_for_v2_pipe(expr: &Expr) -> Option<JsonValue> {
     match expr {
         Expr::Literal(JsonValue::Array(arr)) => {
             // Direct array - v2 pipe
@@ -6192,7 +7098,7 @@ fn expr_to_json_for_v2_pipe(expr: &Expr) -> Option<JsonValue> {
 
 /// Convert an Expr to JSON value for v2 condition parsing.
 /// Accepts literal values and v2-looking refs/chains while avoiding v1-only forms.
-fn expr_to_json_for_v2_condition(expr: &Expr) -> Option<JsonValue> {
+pub(crate) fn expr_to_json_for_v2_condition(expr: &Expr) -> Option<JsonValue> {
     match expr {
         Expr::Literal(value) => Some(value.clone()),
         Expr::Ref(ref_expr)
@@ -6505,6 +7411,35 @@ mappings:
         assert_eq!(result, serde_json::json!([{"dept_name": "Marketing"}]));
     }
 
+    #[test]
+    fn test_v2_lookup_first_coerce_transform() {
+        let yaml = r#"
+version: 2
+input:
+  format: csv
+  csv:
+    has_header: true
+mappings:
+  - target: dept_name
+    expr:
+      - "@context.departments"
+      - lookup_first:
+        - id
+        - "@input.dept_id"
+        - name
+        - { coerce: true }
+"#;
+        let rule = parse_rule_file(yaml).unwrap();
+        let context = serde_json::json!({
+            "departments": [
+                {"id": 1, "name": "Engineering"},
+                {"id": 2.0, "name": "Marketing"}
+            ]
+        });
+        let result = transform(&rule, "dept_id\n2\n", Some(&context)).unwrap();
+        assert_eq!(result, serde_json::json!([{"dept_name": "Marketing"}]));
+    }
+
     #[test]
     fn test_v2_lookup_first_with_pipe_value_transform() {
         let yaml = r#"
@@ -0,0 +1,324 @@
+//! Lightweight type specs used by the `assert_type` op.
+//!
+//! Grammar:
+//!
+//! ```text
+//! spec  := base "?"?
+//! base  := "string" | "number" | "integer" | "bool" | "null" | "any"
+//!        | "array" ("<" spec ">")?
+//!        | "object" ("{" field ("," field)* "}")?
+//! field := name "?"? (":" spec)?
+//! ```
+//!
+//! A trailing `?` also accepts `null`; a `?` after a field name makes the field optional.
+//! Fields without a spec only need to be present.
+
+use serde_json::Value as JsonValue;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TypeSpec {
+    String,
+    Number,
+    Integer,
+    Bool,
+    Null,
+    Any,
+    Array(Option<Box<TypeSpec>>),
+    Object(Vec<FieldSpec>),
+    Nullable(Box<TypeSpec>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FieldSpec {
+    name: String,
+    optional: bool,
+    spec: TypeSpec,
+}
+
+/// Where and why a value failed to match a spec.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TypeMismatch {
+    pub(crate) location: String,
+    pub(crate) reason: String,
+}
+
+impl TypeSpec {
+    pub(crate) fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = SpecParser { text, pos: 0 };
+        let spec = parser.parse_spec()?;
+        parser.skip_ws();
+        if parser.pos < text.len() {
+            return Err(format!(
+                "unexpected '{}' at offset {} in type spec",
+                &text[parser.pos..],
+                parser.pos
+            ));
+        }
+        Ok(spec)
+    }
+
+    pub(crate) fn is_nullable(&self) -> bool {
+        matches!(self, TypeSpec::Nullable(_) | TypeSpec::Null | TypeSpec::Any)
+    }
+
+    pub(crate) fn check(&self, value: &JsonValue) -> Result<(), TypeMismatch> {
+        self.check_at(value, "$")
+    }
+
+    fn check_at(&self, value: &JsonValue, location: &str) -> Result<(), TypeMismatch> {
+        let matches = match self {
+            TypeSpec::Any => true,
+            TypeSpec::Nullable(inner) => {
+                return if value.is_null() {
+                    Ok(())
+                } else {
+                    inner.check_at(value, location)
+                };
+            }
+            TypeSpec::String => value.is_string(),
+            TypeSpec::Number => value.is_number(),
+            TypeSpec::Integer => value.is_i64() || value.is_u64(),
+            TypeSpec::Bool => value.is_boolean(),
+            TypeSpec::Null => value.is_null(),
+            TypeSpec::Array(items) => {
+                let Some(array) = value.as_array() else {
+                    return Err(self.mismatch(value, location));
+                };
+                if let Some(items) = items {
+                    for (index, item) in array.iter().enumerate() {
+                        items.check_at(item, &format!("{}[{}]", location, index))?;
+                    }
+                }
+                true
+            }
+            TypeSpec::Object(fields) => {
+                let Some(object) = value.as_object() else {
+                    return Err(self.mismatch(value, location));
+                };
+                for field in fields {
+                    let field_location = format!("{}.{}", location, field.name);
+                    match object.get(&field.name) {
+                        Some(field_value) => field.spec.check_at(field_value, &field_location)?,
+                        None if field.optional => {}
+                        None => {
+                            return Err(TypeMismatch {
+                                location: field_location,
+                                reason: "required field is missing".to_string(),
+                            });
+                        }
+                    }
+                }
+                true
+            }
+        };
+        if matches {
+            Ok(())
+        } else {
+            Err(self.mismatch(value, location))
+        }
+    }
+
+    fn mismatch(&self, value: &JsonValue, location: &str) -> TypeMismatch {
+        TypeMismatch {
+            location: location.to_string(),
+            reason: format!("expected {}, got {}", self.name(), json_type_name(value)),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            TypeSpec::String => "string",
+            TypeSpec::Number => "number",
+            TypeSpec::Integer => "integer",
+            TypeSpec::Bool => "bool",
+            TypeSpec::Null => "null",
+            TypeSpec::Any => "any",
+            TypeSpec::Array(_) => "array",
+            TypeSpec::Object(_) => "object",
+            TypeSpec::Nullable(inner) => inner.name(),
+        }
+    }
+}
+
+fn json_type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "bool",
+        JsonValue::Number(_) => "number",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
+
+struct SpecParser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl SpecParser<'_> {
+    fn skip_ws(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, ch: char) -> bool {
+        self.skip_ws();
+        if self.text[self.pos..].starts_with(ch) {
+            self.pos += ch.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, ch: char) -> Result<(), String> {
+        if self.eat(ch) {
+            Ok(())
+        } else {
+            Err(format!(
+                "expected '{}' at offset {} in type spec",
+                ch, self.pos
+            ))
+        }
+    }
+
+    fn ident(&mut self) -> Result<&str, String> {
+        self.skip_ws();
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '-'))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(format!(
+                "expected a name at offset {} in type spec",
+                self.pos
+            ));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn parse_spec(&mut self) -> Result<TypeSpec, String> {
+        let start = self.pos;
+        let spec = match self.ident()? {
+            "string" => TypeSpec::String,
+            "number" => TypeSpec::Number,
+            "integer" => TypeSpec::Integer,
+            "bool" => TypeSpec::Bool,
+            "null" => TypeSpec::Null,
+            "any" => TypeSpec::Any,
+            "array" => {
+                if self.eat('<') {
+                    let items = self.parse_spec()?;
+                    self.expect('>')?;
+                    TypeSpec::Array(Some(Box::new(items)))
+                } else {
+                    TypeSpec::Array(None)
+                }
+            }
+            "object" => {
+                let mut fields = Vec::new();
+                if self.eat('{') {
+                    loop {
+                        fields.push(self.parse_field()?);
+                        if !self.eat(',') {
+                            break;
+                        }
+                    }
+                    self.expect('}')?;
+                }
+                TypeSpec::Object(fields)
+            }
+            other => {
+                return Err(format!(
+                    "unknown type '{}' at offset {} in type spec",
+                    other, start
+                ));
+            }
+        };
+        if self.eat('?') {
+            Ok(TypeSpec::Nullable(Box::new(spec)))
+        } else {
+            Ok(spec)
+        }
+    }
+
+    fn parse_field(&mut self) -> Result<FieldSpec, String> {
+        let name = self.ident()?.to_string();
+        let optional = self.eat('?');
+        let spec = if self.eat(':') {
+            self.parse_spec()?
+        } else {
+            TypeSpec::Any
+        };
+        Ok(FieldSpec {
+            name,
+            optional,
+            spec,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_nested_specs() {
+        let spec = TypeSpec::parse("array<object{id: integer, name?: string?, tags}>").unwrap();
+        assert_eq!(
+            spec,
+            TypeSpec::Array(Some(Box::new(TypeSpec::Object(vec![
+                FieldSpec {
+                    name: "id".to_string(),
+                    optional: false,
+                    spec: TypeSpec::Integer,
+                },
+                FieldSpec {
+                    name: "name".to_string(),
+                    optional: true,
+                    spec: TypeSpec::Nullable(Box::new(TypeSpec::String)),
+                },
+                FieldSpec {
+                    name: "tags".to_string(),
+                    optional: false,
+                    spec: TypeSpec::Any,
+                },
+            ]))))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_specs() {
+        assert!(TypeSpec::parse("").is_err());
+        assert!(TypeSpec::parse("strng").is_err());
+        assert!(TypeSpec::parse("array<string").is_err());
+        assert!(TypeSpec::parse("object{id,}").is_err());
+        assert!(TypeSpec::parse("string extra").is_err());
+    }
+
+    #[test]
+    fn reports_first_mismatch_location() {
+        let spec = TypeSpec::parse("array<object{id: integer}>").unwrap();
+        assert!(
+            spec.check(&json!([{ "id": 1 }, { "id": 2, "x": true }]))
+                .is_ok()
+        );
+
+        let err = spec
+            .check(&json!([{ "id": 1 }, { "id": "2" }]))
+            .unwrap_err();
+        assert_eq!(err.location, "$[1].id");
+        assert_eq!(err.reason, "expected integer, got string");
+
+        let err = spec.check(&json!([{ "name": "a" }])).unwrap_err();
+        assert_eq!(err.location, "$[0].id");
+        assert_eq!(err.reason, "required field is missing");
+
+        let err = spec.check(&json!({ "id": 1 })).unwrap_err();
+        assert_eq!(err.location, "$");
+        assert_eq!(err.reason, "expected array, got object");
+    }
+}
@@ -3,20 +3,26 @@
 //! This module provides the evaluation context and functions for v2 expressions,
 //! including pipe value tracking, let bindings, and item/acc scopes.
 
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
 use serde_json::Value as JsonValue;
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use crate::error::{TransformError, TransformErrorKind};
 use crate::model::{Expr, ExprOp, ExprRef};
 use crate::path::{get_path, parse_path};
 use crate::transform::{
-    EvalItem as V1EvalItem, EvalLocals as V1EvalLocals, EvalValue as V1EvalValue,
-    eval_op as eval_v1_op,
+    EvalItem as V1EvalItem, EvalLocals as V1EvalLocals, EvalValue as V1EvalValue, cached_regex,
+    eval_op as eval_v1_op, flatten_value, json_number_from_f64, parse_datetime_in_zone,
+    value_to_i64,
 };
+use crate::type_spec::TypeSpec;
 use crate::v2_model::{
     V2Comparison, V2ComparisonOp, V2Condition, V2Expr, V2IfStep, V2LetStep, V2MapStep, V2OpStep,
     V2Pipe, V2Ref, V2Start, V2Step,
 };
+use crate::v2_validator::{get_op_arg_range, split_op_options};
 
 // =============================================================================
 // EvalValue - Same as v1 transform
@@ -61,12 +67,15 @@ pub struct EvalItem<'a> {
 }
 
 /// v2 evaluation context - tracks pipe value, let bindings, and iteration scopes
+///
+/// The pipe value and bindings are shared, so cloning a context is cheap; adding a
+/// binding to a shared map copies its entries but not the bound values.
 #[derive(Clone)]
 pub struct V2EvalContext<'a> {
     /// Current pipe value ($)
-    pipe_value: Option<EvalValue>,
+    pipe_value: Option<Arc<EvalValue>>,
     /// Let-bound variables (local scope)
-    let_bindings: HashMap<String, EvalValue>,
+    let_bindings: Arc<HashMap<String, Arc<EvalValue>>>,
     /// Item scope for map/filter operations (@item)
     item: Option<EvalItem<'a>>,
     /// Accumulator scope for reduce/fold operations (@acc)
@@ -78,7 +87,7 @@ impl<'a> V2EvalContext<'a> {
     pub fn new() -> Self {
         Self {
             pipe_value: None,
-            let_bindings: HashMap::new(),
+            let_bindings: Arc::default(),
             item: None,
             acc: None,
         }
@@ -86,20 +95,21 @@ impl<'a> V2EvalContext<'a> {
 
     /// Create a new context with a pipe value
     pub fn with_pipe_value(mut self, value: EvalValue) -> Self {
-        self.pipe_value = Some(value);
+        self.pipe_value = Some(Arc::new(value));
         self
     }
 
     /// Create a new context with a let binding added
     pub fn with_let_binding(mut self, name: String, value: EvalValue) -> Self {
-        self.let_bindings.insert(name, value);
+        Arc::make_mut(&mut self.let_bindings).insert(name, Arc::new(value));
         self
     }
 
     /// Create a new context with multiple let bindings added
     pub fn with_let_bindings(mut self, bindings: Vec<(String, EvalValue)>) -> Self {
+        let let_bindings = Arc::make_mut(&mut self.let_bindings);
         for (name, value) in bindings {
-            self.let_bindings.insert(name, value);
+            let_bindings.insert(name, Arc::new(value));
         }
         self
     }
@@ -118,12 +128,18 @@ impl<'a> V2EvalContext<'a> {
 
     /// Get the current pipe value
     pub fn get_pipe_value(&self) -> Option<&EvalValue> {
-        self.pipe_value.as_ref()
+        self.pipe_value.as_deref()
+    }
+
+    /// Take the pipe value out, cloning it only if another context still shares it
+    fn into_pipe_value(self) -> Option<EvalValue> {
+        self.pipe_value
+            .map(|value| Arc::try_unwrap(value).unwrap_or_else(|value| (*value).clone()))
     }
 
     /// Resolve a local variable name
     pub fn resolve_local(&self, name: &str) -> Option<&EvalValue> {
-        self.let_bindings.get(name)
+        self.let_bindings.get(name).map(Arc::as_ref)
     }
 
     /// Get the current item (if in map/filter scope)
@@ -292,8 +308,13 @@ fn get_path_str<'a>(
         .with_path(error_path)
     })?;
     match get_path(value, &tokens) {
-        Some(v) => Ok(EvalValue::Value(v.clone())),
-        None => Ok(EvalValue::Missing),
+        Ok(Some(v)) => Ok(EvalValue::Value(v.into_owned())),
+        Ok(None) => Ok(EvalValue::Missing),
+        Err(err) => Err(TransformError::new(
+            TransformErrorKind::ExprError,
+            format!("{}: {}", err.message(), path_str),
+        )
+        .with_path(error_path)),
     }
 }
 
@@ -833,11 +854,11 @@ pub fn eval_v2_pipe<'a>(
     for (i, step) in pipe.steps.iter().enumerate() {
         let step_path = format!("{}[{}]", path, i + 1);
         // Update context with current pipe value for each step
-        current_ctx = current_ctx.clone().with_pipe_value(current.clone());
+        current_ctx = current_ctx.with_pipe_value(current.clone());
 
         match step {
             V2Step::Op(op_step) => {
-                current = eval_v2_op_step(
+                current = eval_v2_op_step_in_ctx(
                     op_step,
                     current,
                     record,
@@ -923,7 +944,7 @@ pub fn eval_v2_if_step<'a>(
     ctx: &V2EvalContext<'a>,
 ) -> Result<EvalValue, TransformError> {
     // Create context with current pipe value for condition evaluation
-    let cond_ctx = ctx.clone().with_pipe_value(pipe_value.clone());
+    let cond_ctx = ctx.clone().with_pipe_value(pipe_value);
 
     // Evaluate condition
     let cond_path = format!("{}.cond", path);
@@ -947,7 +968,7 @@ pub fn eval_v2_if_step<'a>(
         eval_v2_pipe(else_branch, record, context, out, &else_path, &cond_ctx)
     } else {
         // No else branch, return pipe value unchanged
-        Ok(pipe_value)
+        Ok(cond_ctx.into_pipe_value().unwrap_or(EvalValue::Missing))
     }
 }
 
@@ -981,26 +1002,22 @@ pub fn eval_v2_map_step<'a>(
     for (index, item_value) in arr.iter().enumerate() {
         let item_path = format!("{}[{}]", path, index);
 
-        // Create context with item scope
-        let item_ctx = ctx
-            .clone()
-            .with_pipe_value(EvalValue::Value(item_value.clone()))
-            .with_item(EvalItem {
-                value: item_value,
-                index,
-            });
+        // Create context with item scope; each step sets the pipe value below
+        let mut step_ctx = ctx.clone().with_item(EvalItem {
+            value: item_value,
+            index,
+        });
 
         // Apply all steps to this item
         let mut current = EvalValue::Value(item_value.clone());
-        let mut step_ctx = item_ctx.clone(); // Declare outside loop to preserve let bindings
 
         for (step_idx, step) in map_step.steps.iter().enumerate() {
             let step_path = format!("{}.step[{}]", item_path, step_idx);
-            step_ctx = step_ctx.clone().with_pipe_value(current.clone());
+            step_ctx = step_ctx.with_pipe_value(current.clone());
 
             match step {
                 V2Step::Op(op_step) => {
-                    current = eval_v2_op_step(
+                    current = eval_v2_op_step_in_ctx(
                         op_step, current, record, context, out, &step_path, &step_ctx,
                     )?;
                 }
@@ -1015,8 +1032,7 @@ pub fn eval_v2_map_step<'a>(
                         &step_path,
                         &step_ctx,
                     )?;
-                    // Let doesn't change pipe value
-                    current = step_ctx.get_pipe_value().cloned().unwrap_or(current);
+                    // Let doesn't change pipe value, so `current` stays as it is
                 }
                 V2Step::If(if_step) => {
                     current = eval_v2_if_step(
@@ -1075,6 +1091,14 @@ pub fn eval_v2_condition<'a>(
         V2Condition::Comparison(comparison) => {
             eval_v2_comparison(comparison, record, context, out, path, ctx)
         }
+        V2Condition::Exists(v2_ref) => {
+            let value = eval_v2_ref(v2_ref, record, context, out, path, ctx)?;
+            Ok(!value.is_missing())
+        }
+        V2Condition::Missing(v2_ref) => {
+            let value = eval_v2_ref(v2_ref, record, context, out, path, ctx)?;
+            Ok(value.is_missing())
+        }
         V2Condition::Expr(expr) => {
             let expr_path = format!("{}.expr", path);
             let value = eval_v2_expr(expr, record, context, out, &expr_path, ctx)?;
@@ -1100,11 +1124,13 @@ fn eval_v2_comparison<'a>(
     path: &str,
     ctx: &V2EvalContext<'a>,
 ) -> Result<bool, TransformError> {
-    if comparison.args.len() != 2 {
+    let expected = comparison.op.arg_count();
+    if comparison.args.len() != expected {
         return Err(TransformError::new(
             TransformErrorKind::ExprError,
             format!(
-                "comparison requires exactly 2 arguments, got {}",
+                "comparison requires exactly {} arguments, got {}",
+                expected,
                 comparison.args.len()
             ),
         )
@@ -1119,6 +1145,13 @@ fn eval_v2_comparison<'a>(
 
     match comparison.op {
         V2ComparisonOp::Eq => Ok(compare_values_eq(&left, &right)),
+        V2ComparisonOp::EqI => match (&left, &right) {
+            (
+                EvalValue::Value(JsonValue::String(left)),
+                EvalValue::Value(JsonValue::String(right)),
+            ) => Ok(left.to_lowercase() == right.to_lowercase()),
+            _ => Ok(compare_values_eq(&left, &right)),
+        },
         V2ComparisonOp::Ne => Ok(!compare_values_eq(&left, &right)),
         V2ComparisonOp::Gt => {
             compare_values_ord(&left, &right, path).map(|ord| ord == std::cmp::Ordering::Greater)
@@ -1133,6 +1166,34 @@ fn eval_v2_comparison<'a>(
             compare_values_ord(&left, &right, path).map(|ord| ord != std::cmp::Ordering::Greater)
         }
         V2ComparisonOp::Match => compare_values_match(&left, &right, path),
+        V2ComparisonOp::In | V2ComparisonOp::NotIn => {
+            let EvalValue::Value(JsonValue::Array(items)) = &right else {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "in/not_in requires an array on the right",
+                )
+                .with_path(right_path));
+            };
+            let found = items
+                .iter()
+                .any(|item| compare_values_eq(&left, &EvalValue::Value(item.clone())));
+            Ok(found == (comparison.op == V2ComparisonOp::In))
+        }
+        V2ComparisonOp::Between => {
+            let high_path = format!("{}.args[2]", path);
+            let high = eval_v2_expr(&comparison.args[2], record, context, out, &high_path, ctx)?;
+            if compare_values_ord(&right, &high, path)? == std::cmp::Ordering::Greater {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "between requires low <= high",
+                )
+                .with_path(path));
+            }
+            Ok(
+                compare_values_ord(&left, &right, path)? != std::cmp::Ordering::Less
+                    && compare_values_ord(&left, &high, path)? != std::cmp::Ordering::Greater,
+            )
+        }
     }
 }
 
@@ -1146,6 +1207,73 @@ fn compare_values_eq(left: &EvalValue, right: &EvalValue) -> bool {
     }
 }
 
+/// Structural equality used by lookups: numbers compare by value (`1` equals
+/// `1.0`) at any depth, and with `coerce` a string equals the number it parses to.
+fn json_values_eq_normalized(left: &JsonValue, right: &JsonValue, coerce: bool) -> bool {
+    match (left, right) {
+        (JsonValue::Number(l), JsonValue::Number(r)) => {
+            if l.is_f64() || r.is_f64() {
+                l.as_f64() == r.as_f64()
+            } else {
+                l == r
+            }
+        }
+        (JsonValue::String(s), JsonValue::Number(n))
+        | (JsonValue::Number(n), JsonValue::String(s))
+            if coerce =>
+        {
+            s.parse::<f64>().ok() == n.as_f64()
+        }
+        (JsonValue::Array(l), JsonValue::Array(r)) => {
+            l.len() == r.len()
+                && l.iter()
+                    .zip(r)
+                    .all(|(l, r)| json_values_eq_normalized(l, r, coerce))
+        }
+        (JsonValue::Object(l), JsonValue::Object(r)) => {
+            l.len() == r.len()
+                && l.iter().all(|(key, l)| {
+                    r.get(key)
+                        .is_some_and(|r| json_values_eq_normalized(l, r, coerce))
+                })
+        }
+        _ => left == right,
+    }
+}
+
+/// Splits lookup's trailing `{ coerce: bool }` options object off its args and checks
+/// the positional args that remain against the op's arg range.
+fn split_lookup_options<'a>(
+    op: &str,
+    args: &'a [V2Expr],
+    path: &str,
+) -> Result<(&'a [V2Expr], bool), TransformError> {
+    let (positional, options) = split_op_options(op, args);
+    let (_, max) = get_op_arg_range(op);
+    if max.is_some_and(|max| positional.len() > max) {
+        return Err(TransformError::new(
+            TransformErrorKind::ExprError,
+            format!(
+                "{} accepts at most {} positional arguments plus an options object",
+                op,
+                max.unwrap_or_default()
+            ),
+        )
+        .with_path(path));
+    }
+    let coerce = match options.and_then(|options| options.get("coerce")) {
+        Some(coerce) => coerce.as_bool().ok_or_else(|| {
+            TransformError::new(
+                TransformErrorKind::ExprError,
+                format!("{} coerce must be a boolean", op),
+            )
+            .with_path(format!("{}.args[{}].coerce", path, positional.len()))
+        })?,
+        None => false,
+    };
+    Ok((positional, coerce))
+}
+
 /// Compare two values for ordering
 fn compare_values_ord(
     left: &EvalValue,
@@ -1274,6 +1402,237 @@ fn eval_value_as_string(value: &EvalValue, path: &str) -> Result<String, Transfo
     }
 }
 
+/// Decode a `application/x-www-form-urlencoded` component (`+` and `%XX` escapes).
+fn percent_decode_query(text: &str, path: &str) -> Result<String, TransformError> {
+    let invalid = || {
+        TransformError::new(
+            TransformErrorKind::ExprError,
+            format!("invalid percent-encoding in query string: {}", text),
+        )
+        .with_path(path)
+    };
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let hex = bytes.get(index + 1..index + 3).ok_or_else(invalid)?;
+                let hex = std::str::from_utf8(hex).map_err(|_| invalid())?;
+                decoded.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+                index += 2;
+            }
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8(decoded).map_err(|_| invalid())
+}
+
+/// Split text into words at spaces, `_`, `-`, and camel humps (`fooBar`, `HTTPServer`).
+fn split_case_words(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for (index, &c) in chars.iter().enumerate() {
+        if c.is_whitespace() || c == '_' || c == '-' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[index - 1];
+            let next_is_lower = chars.get(index + 1).is_some_and(|next| next.is_lowercase());
+            if !prev.is_uppercase() || next_is_lower {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize_word(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+fn convert_case(op: &str, text: &str) -> String {
+    let words = split_case_words(text);
+    match op {
+        "snake_case" => words
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("_"),
+        "kebab_case" => words
+            .iter()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("-"),
+        "camel_case" => words
+            .iter()
+            .enumerate()
+            .map(|(index, word)| {
+                if index == 0 {
+                    word.to_lowercase()
+                } else {
+                    capitalize_word(word)
+                }
+            })
+            .collect(),
+        _ => words
+            .iter()
+            .map(|word| capitalize_word(word))
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+const MAX_FILENAME_BYTES: usize = 255;
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Turn untrusted text into a single portable path component.
+///
+/// Separators, control and Windows-invalid characters become `_`, leading dots and
+/// surrounding spaces are dropped, reserved device names get a `_` prefix, and the result is
+/// cut to 255 bytes keeping a short extension.
+fn sanitize_filename(text: &str) -> String {
+    let replaced: String = text
+        .chars()
+        .map(|ch| {
+            if ch.is_control() || matches!(ch, '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*')
+            {
+                '_'
+            } else {
+                ch
+            }
+        })
+        .collect();
+    let trimmed = replaced
+        .trim_start_matches(|ch: char| ch == '.' || ch.is_whitespace())
+        .trim_end_matches(|ch: char| ch == '.' || ch.is_whitespace());
+    let mut name = if trimmed.is_empty() {
+        "_".to_string()
+    } else {
+        trimmed.to_string()
+    };
+
+    let stem = name.split('.').next().unwrap_or_default();
+    if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| stem.trim_end().eq_ignore_ascii_case(reserved))
+    {
+        name.insert(0, '_');
+    }
+
+    if name.len() > MAX_FILENAME_BYTES {
+        let extension = name
+            .rfind('.')
+            .map(|dot| &name[dot..])
+            .filter(|extension| extension.len() <= 16)
+            .unwrap_or_default()
+            .to_string();
+        let mut end = MAX_FILENAME_BYTES - extension.len();
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        let stem = name[..end].trim_end_matches(|ch: char| ch == '.' || ch.is_whitespace());
+        name = format!("{}{}", stem, extension);
+    }
+    name
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        out.push(DIGITS[(byte >> 4) as usize] as char);
+        out.push(DIGITS[(byte & 0x0f) as usize] as char);
+    }
+    out
+}
+
+fn hex_decode(text: &str, path: &str) -> Result<Vec<u8>, TransformError> {
+    let invalid = |message: &str| {
+        TransformError::new(TransformErrorKind::ExprError, message.to_string()).with_path(path)
+    };
+    if !text.len().is_multiple_of(2) {
+        return Err(invalid(
+            "hex_decode input must have an even number of digits",
+        ));
+    }
+    text.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let high = (pair[0] as char).to_digit(16);
+            let low = (pair[1] as char).to_digit(16);
+            match (high, low) {
+                (Some(high), Some(low)) => Ok((high * 16 + low) as u8),
+                _ => Err(invalid("hex_decode input contains a non-hex digit")),
+            }
+        })
+        .collect()
+}
+
+fn decoded_utf8(bytes: Vec<u8>, op: &str, path: &str) -> Result<EvalValue, TransformError> {
+    String::from_utf8(bytes)
+        .map(|text| EvalValue::Value(JsonValue::String(text)))
+        .map_err(|_| {
+            TransformError::new(
+                TransformErrorKind::ExprError,
+                format!("{} produced bytes that are not valid UTF-8", op),
+            )
+            .with_path(path)
+        })
+}
+
+/// Parse `text` with a chrono format; formats without an offset are read as UTC and
+/// date-only formats as midnight.
+fn parse_date_with_format(
+    text: &str,
+    format: &str,
+) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+    if let Ok(dt) = DateTime::parse_from_str(text, format) {
+        return Some(dt);
+    }
+    let naive = NaiveDateTime::parse_from_str(text, format)
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(text, format)
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })?;
+    Some(naive.and_utc().fixed_offset())
+}
+
+fn format_date_with_format(
+    dt: &chrono::DateTime<chrono::FixedOffset>,
+    format: &str,
+) -> Option<String> {
+    use std::fmt::Write as _;
+
+    let mut text = String::new();
+    write!(text, "{}", dt.format(format)).ok()?;
+    Some(text)
+}
+
 /// Helper to convert EvalValue to number
 fn eval_value_as_number(value: &EvalValue, path: &str) -> Result<f64, TransformError> {
     match value {
@@ -1428,14 +1787,26 @@ fn eval_v2_key_expr_string<'a>(
     path: &str,
     ctx: &V2EvalContext<'a>,
 ) -> Result<String, TransformError> {
+    eval_v2_optional_key_expr_string(expr, record, context, out, path, ctx)?.ok_or_else(|| {
+        TransformError::new(
+            TransformErrorKind::ExprError,
+            "expr arg must not be missing",
+        )
+        .with_path(path)
+    })
+}
+
+/// Like [`eval_v2_key_expr_string`], but a missing key is `None` instead of an error.
+fn eval_v2_optional_key_expr_string<'a>(
+    expr: &V2Expr,
+    record: &'a JsonValue,
+    context: Option<&'a JsonValue>,
+    out: &'a JsonValue,
+    path: &str,
+    ctx: &V2EvalContext<'a>,
+) -> Result<Option<String>, TransformError> {
     let value = match eval_v2_expr(expr, record, context, out, path, ctx)? {
-        EvalValue::Missing => {
-            return Err(TransformError::new(
-                TransformErrorKind::ExprError,
-                "expr arg must not be missing",
-            )
-            .with_path(path));
-        }
+        EvalValue::Missing => return Ok(None),
         EvalValue::Value(value) => value,
     };
     if value.is_null() {
@@ -1445,7 +1816,7 @@ fn eval_v2_key_expr_string<'a>(
         )
         .with_path(path));
     }
-    value_to_string(&value, path)
+    value_to_string(&value, path).map(Some)
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -1553,6 +1924,24 @@ fn eval_v2_array_from_eval_value(
         }
     }
 }
+
+/// Truthiness used by `assert`: missing and `null` are false, anything else must be a bool.
+fn v2_pipe_value_truthy(value: &EvalValue, path: &str) -> Result<bool, TransformError> {
+    match value {
+        EvalValue::Missing => Ok(false),
+        EvalValue::Value(JsonValue::Null) => Ok(false),
+        EvalValue::Value(value) => value_as_bool(value, path),
+    }
+}
+
+/// Rows of the elements at each index, truncated to the shortest array.
+fn zip_rows(arrays: &[Vec<JsonValue>]) -> Vec<JsonValue> {
+    let min_len = arrays.iter().map(|items| items.len()).min().unwrap_or(0);
+    (0..min_len)
+        .map(|index| JsonValue::Array(arrays.iter().map(|items| items[index].clone()).collect()))
+        .collect()
+}
+
 fn v2_eval_to_v1_eval(value: &EvalValue) -> V1EvalValue {
     match value {
         EvalValue::Missing => V1EvalValue::Missing,
@@ -1674,6 +2063,87 @@ fn value_to_string(value: &JsonValue, path: &str) -> Result<String, TransformErr
     }
 }
 
+/// Render a `format` template. `{{` and `}}` are literal braces; `{$}` is the
+/// pipe value and `{@ref}` any v2 reference. Missing or null values render as
+/// an empty string unless `fail_on_missing` is set.
+#[allow(clippy::too_many_arguments)]
+fn render_format_template<'a>(
+    template: &str,
+    fail_on_missing: bool,
+    pipe_value: &EvalValue,
+    record: &'a JsonValue,
+    context: Option<&'a JsonValue>,
+    out: &'a JsonValue,
+    path: &str,
+    ctx: &V2EvalContext<'a>,
+) -> Result<String, TransformError> {
+    let template_error = |message: String| {
+        TransformError::new(TransformErrorKind::ExprError, message)
+            .with_path(format!("{}.args[0]", path))
+    };
+
+    let mut rendered = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                rendered.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                rendered.push('}');
+            }
+            '}' => {
+                return Err(template_error(
+                    "format template has an unmatched '}' (use '}}' for a literal brace)"
+                        .to_string(),
+                ));
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => {
+                            return Err(template_error(
+                                "format template has an unclosed '{'".to_string(),
+                            ));
+                        }
+                    }
+                }
+                let placeholder = placeholder.trim();
+                let value = if placeholder == "$" {
+                    pipe_value.clone()
+                } else {
+                    let v2_ref = crate::v2_parser::parse_v2_ref(placeholder).ok_or_else(|| {
+                        template_error(format!(
+                            "format placeholder '{{{}}}' is not a reference",
+                            placeholder
+                        ))
+                    })?;
+                    eval_v2_ref(&v2_ref, record, context, out, path, ctx)?
+                };
+                match value {
+                    EvalValue::Missing | EvalValue::Value(JsonValue::Null) => {
+                        if fail_on_missing {
+                            return Err(TransformError::new(
+                                TransformErrorKind::ExprError,
+                                format!("format placeholder '{{{}}}' is missing", placeholder),
+                            )
+                            .with_path(path));
+                        }
+                    }
+                    EvalValue::Value(value) => rendered.push_str(&value_to_string(&value, path)?),
+                }
+            }
+            _ => rendered.push(c),
+        }
+    }
+    Ok(rendered)
+}
+
 fn cast_to_int(value: &JsonValue, path: &str) -> Result<JsonValue, TransformError> {
     match value {
         JsonValue::Number(n) => {
@@ -1761,6 +2231,235 @@ fn eval_type_cast(op: &str, value: &EvalValue, path: &str) -> Result<EvalValue,
     }
 }
 
+fn json_patch_error(message: impl Into<String>, path: &str) -> TransformError {
+    TransformError::new(TransformErrorKind::ExprError, message).with_path(path)
+}
+
+/// Parse an RFC 6901 JSON Pointer into unescaped reference tokens
+fn parse_json_pointer(pointer: &str, path: &str) -> Result<Vec<String>, TransformError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(json_patch_error(
+            format!("json pointer must start with '/': {}", pointer),
+            path,
+        ));
+    };
+    rest.split('/')
+        .map(|token| {
+            let mut unescaped = String::with_capacity(token.len());
+            let mut chars = token.chars();
+            while let Some(ch) = chars.next() {
+                if ch != '~' {
+                    unescaped.push(ch);
+                    continue;
+                }
+                match chars.next() {
+                    Some('0') => unescaped.push('~'),
+                    Some('1') => unescaped.push('/'),
+                    _ => {
+                        return Err(json_patch_error(
+                            format!("invalid escape in json pointer: {}", pointer),
+                            path,
+                        ));
+                    }
+                }
+            }
+            Ok(unescaped)
+        })
+        .collect()
+}
+
+fn json_pointer_array_index(token: &str, len: usize, path: &str) -> Result<usize, TransformError> {
+    let valid = !token.is_empty()
+        && token.chars().all(|ch| ch.is_ascii_digit())
+        && (token == "0" || !token.starts_with('0'));
+    match token.parse::<usize>() {
+        Ok(index) if valid && index < len => Ok(index),
+        _ => Err(json_patch_error(
+            format!("array index out of range: {}", token),
+            path,
+        )),
+    }
+}
+
+fn json_pointer_get<'v>(
+    doc: &'v JsonValue,
+    tokens: &[String],
+    path: &str,
+) -> Result<&'v JsonValue, TransformError> {
+    let mut current = doc;
+    for token in tokens {
+        current = match current {
+            JsonValue::Object(map) => map
+                .get(token)
+                .ok_or_else(|| json_patch_error(format!("path not found: {}", token), path))?,
+            JsonValue::Array(items) => &items[json_pointer_array_index(token, items.len(), path)?],
+            _ => {
+                return Err(json_patch_error(format!("path not found: {}", token), path));
+            }
+        };
+    }
+    Ok(current)
+}
+
+fn json_pointer_parent_mut<'v>(
+    doc: &'v mut JsonValue,
+    tokens: &[String],
+    path: &str,
+) -> Result<&'v mut JsonValue, TransformError> {
+    let mut current = doc;
+    for token in tokens {
+        current = match current {
+            JsonValue::Object(map) => map
+                .get_mut(token)
+                .ok_or_else(|| json_patch_error(format!("path not found: {}", token), path))?,
+            JsonValue::Array(items) => {
+                let index = json_pointer_array_index(token, items.len(), path)?;
+                &mut items[index]
+            }
+            _ => {
+                return Err(json_patch_error(format!("path not found: {}", token), path));
+            }
+        };
+    }
+    Ok(current)
+}
+
+fn json_patch_add(
+    doc: &mut JsonValue,
+    tokens: &[String],
+    value: JsonValue,
+    path: &str,
+) -> Result<(), TransformError> {
+    let Some((last, parent_tokens)) = tokens.split_last() else {
+        *doc = value;
+        return Ok(());
+    };
+    match json_pointer_parent_mut(doc, parent_tokens, path)? {
+        JsonValue::Object(map) => {
+            map.insert(last.clone(), value);
+            Ok(())
+        }
+        JsonValue::Array(items) => {
+            if last == "-" {
+                items.push(value);
+            } else {
+                let index = json_pointer_array_index(last, items.len() + 1, path)?;
+                items.insert(index, value);
+            }
+            Ok(())
+        }
+        _ => Err(json_patch_error(
+            "add target parent must be an object or array",
+            path,
+        )),
+    }
+}
+
+fn json_patch_remove(
+    doc: &mut JsonValue,
+    tokens: &[String],
+    path: &str,
+) -> Result<JsonValue, TransformError> {
+    let Some((last, parent_tokens)) = tokens.split_last() else {
+        return Err(json_patch_error("cannot remove the document root", path));
+    };
+    match json_pointer_parent_mut(doc, parent_tokens, path)? {
+        JsonValue::Object(map) => map
+            .remove(last)
+            .ok_or_else(|| json_patch_error(format!("path not found: {}", last), path)),
+        JsonValue::Array(items) => {
+            let index = json_pointer_array_index(last, items.len(), path)?;
+            Ok(items.remove(index))
+        }
+        _ => Err(json_patch_error(format!("path not found: {}", last), path)),
+    }
+}
+
+fn json_patch_op_string<'p>(
+    op: &'p serde_json::Map<String, JsonValue>,
+    key: &str,
+    path: &str,
+) -> Result<&'p str, TransformError> {
+    op.get(key)
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| json_patch_error(format!("patch operation requires string {}", key), path))
+}
+
+fn json_patch_op_value(
+    op: &serde_json::Map<String, JsonValue>,
+    path: &str,
+) -> Result<JsonValue, TransformError> {
+    op.get("value")
+        .cloned()
+        .ok_or_else(|| json_patch_error("patch operation requires value", path))
+}
+
+/// Apply an RFC 6902 JSON Patch document to a value
+fn apply_json_patch(
+    mut doc: JsonValue,
+    patch: &JsonValue,
+    path: &str,
+) -> Result<JsonValue, TransformError> {
+    let ops = patch
+        .as_array()
+        .ok_or_else(|| json_patch_error("json patch must be an array", path))?;
+    for (index, op) in ops.iter().enumerate() {
+        let op_path = format!("{}[{}]", path, index);
+        let op = op
+            .as_object()
+            .ok_or_else(|| json_patch_error("patch operation must be an object", &op_path))?;
+        let name = json_patch_op_string(op, "op", &op_path)?;
+        let target = parse_json_pointer(json_patch_op_string(op, "path", &op_path)?, &op_path)?;
+        match name {
+            "add" => {
+                let value = json_patch_op_value(op, &op_path)?;
+                json_patch_add(&mut doc, &target, value, &op_path)?;
+            }
+            "remove" => {
+                json_patch_remove(&mut doc, &target, &op_path)?;
+            }
+            "replace" => {
+                let value = json_patch_op_value(op, &op_path)?;
+                *json_pointer_parent_mut(&mut doc, &target, &op_path)? = value;
+            }
+            "move" => {
+                let from =
+                    parse_json_pointer(json_patch_op_string(op, "from", &op_path)?, &op_path)?;
+                if target.len() > from.len() && target.starts_with(&from) {
+                    return Err(json_patch_error(
+                        "cannot move a value into one of its children",
+                        &op_path,
+                    ));
+                }
+                let value = json_patch_remove(&mut doc, &from, &op_path)?;
+                json_patch_add(&mut doc, &target, value, &op_path)?;
+            }
+            "copy" => {
+                let from =
+                    parse_json_pointer(json_patch_op_string(op, "from", &op_path)?, &op_path)?;
+                let value = json_pointer_get(&doc, &from, &op_path)?.clone();
+                json_patch_add(&mut doc, &target, value, &op_path)?;
+            }
+            "test" => {
+                let expected = json_patch_op_value(op, &op_path)?;
+                if *json_pointer_get(&doc, &target, &op_path)? != expected {
+                    return Err(json_patch_error("json patch test failed", &op_path));
+                }
+            }
+            other => {
+                return Err(json_patch_error(
+                    format!("unknown patch operation: {}", other),
+                    &op_path,
+                ));
+            }
+        }
+    }
+    Ok(doc)
+}
+
 /// Evaluate a v2 op step with a pipe value as implicit first argument
 pub fn eval_v2_op_step<'a>(
     op_step: &V2OpStep,
@@ -1773,12 +2472,24 @@ pub fn eval_v2_op_step<'a>(
 ) -> Result<EvalValue, TransformError> {
     // Create a new context with the current pipe value
     let step_ctx = ctx.clone().with_pipe_value(pipe_value.clone());
+    eval_v2_op_step_in_ctx(op_step, pipe_value, record, context, out, path, &step_ctx)
+}
 
+/// `eval_v2_op_step` for callers whose context already holds `pipe_value`.
+fn eval_v2_op_step_in_ctx<'a>(
+    op_step: &V2OpStep,
+    pipe_value: EvalValue,
+    record: &'a JsonValue,
+    context: Option<&'a JsonValue>,
+    out: &'a JsonValue,
+    path: &str,
+    step_ctx: &V2EvalContext<'a>,
+) -> Result<EvalValue, TransformError> {
     // Handle "@..." as a reference (from shorthand string in step position)
     if op_step.op.starts_with('@') {
         use crate::v2_parser::parse_v2_ref;
         if let Some(v2_ref) = parse_v2_ref(&op_step.op) {
-            return eval_v2_ref(&v2_ref, record, context, out, path, &step_ctx);
+            return eval_v2_ref(&v2_ref, record, context, out, path, step_ctx);
         }
         return Err(TransformError::new(
             TransformErrorKind::ExprError,
@@ -1810,19 +2521,396 @@ pub fn eval_v2_op_step<'a>(
             let s = eval_value_as_string(&pipe_value, path)?;
             Ok(EvalValue::Value(JsonValue::String(s.to_uppercase())))
         }
-        "to_string" => match &pipe_value {
-            EvalValue::Missing => Ok(EvalValue::Missing),
-            EvalValue::Value(v) => {
-                let s = match v {
-                    JsonValue::String(s) => s.clone(),
-                    JsonValue::Number(n) => n.to_string(),
-                    JsonValue::Bool(b) => b.to_string(),
-                    JsonValue::Null => "null".to_string(),
-                    JsonValue::Array(_) | JsonValue::Object(_) => v.to_string(),
-                };
-                Ok(EvalValue::Value(JsonValue::String(s)))
+        "paragraphs" => {
+            let text = match &pipe_value {
+                EvalValue::Missing => return Ok(EvalValue::Missing),
+                EvalValue::Value(JsonValue::String(text)) => text,
+                EvalValue::Value(other) => {
+                    return Err(TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("paragraphs expects a string, got {:?}", other),
+                    )
+                    .with_path(path));
+                }
+            };
+            let mut paragraphs = Vec::new();
+            let mut current: Vec<&str> = Vec::new();
+            for line in text.lines() {
+                if line.trim().is_empty() {
+                    if !current.is_empty() {
+                        paragraphs.push(JsonValue::String(current.join("\n").trim().to_string()));
+                        current.clear();
+                    }
+                } else {
+                    current.push(line);
+                }
             }
-        },
+            if !current.is_empty() {
+                paragraphs.push(JsonValue::String(current.join("\n").trim().to_string()));
+            }
+            Ok(EvalValue::Value(JsonValue::Array(paragraphs)))
+        }
+        "replace_regex" => {
+            if op_step.args.len() != 2 {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "replace_regex requires a pattern and a replacement",
+                )
+                .with_path(path));
+            }
+            let text = match &pipe_value {
+                EvalValue::Missing => return Ok(EvalValue::Missing),
+                EvalValue::Value(JsonValue::String(text)) => text,
+                EvalValue::Value(other) => {
+                    return Err(TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("replace_regex expects a string, got {:?}", other),
+                    )
+                    .with_path(path));
+                }
+            };
+            let mut strings = Vec::with_capacity(2);
+            for (index, arg) in op_step.args.iter().enumerate() {
+                let arg_path = format!("{}.args[{}]", path, index);
+                match eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)? {
+                    EvalValue::Missing => return Ok(EvalValue::Missing),
+                    EvalValue::Value(JsonValue::String(value)) => strings.push(value),
+                    EvalValue::Value(_) => {
+                        return Err(TransformError::new(
+                            TransformErrorKind::ExprError,
+                            "replace_regex arguments must be strings",
+                        )
+                        .with_path(arg_path));
+                    }
+                }
+            }
+            let regex = cached_regex(&strings[0], &format!("{}.args[0]", path))?;
+            let replaced = regex.replace_all(text, strings[1].as_str());
+            Ok(EvalValue::Value(JsonValue::String(replaced.into_owned())))
+        }
+        "contains" | "starts_with" | "ends_with" => {
+            let op_name = op_step.op.as_str();
+            if op_step.args.len() != 1 {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    format!("{} requires exactly one argument", op_name),
+                )
+                .with_path(path));
+            }
+            if matches!(pipe_value, EvalValue::Missing) {
+                return Ok(EvalValue::Missing);
+            }
+            let arg_path = format!("{}.args[0]", path);
+            let needle = eval_v2_expr(&op_step.args[0], record, context, out, &arg_path, step_ctx)?;
+            if let (EvalValue::Value(JsonValue::Array(items)), "contains") = (&pipe_value, op_name)
+            {
+                let found = items
+                    .iter()
+                    .any(|item| compare_values_eq(&EvalValue::Value(item.clone()), &needle));
+                return Ok(EvalValue::Value(JsonValue::Bool(found)));
+            }
+            let EvalValue::Value(JsonValue::String(text)) = &pipe_value else {
+                let expected = if op_name == "contains" {
+                    "a string or array"
+                } else {
+                    "a string"
+                };
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    format!("{} expects {}, got {:?}", op_name, expected, pipe_value),
+                )
+                .with_path(path));
+            };
+            let needle = match needle {
+                EvalValue::Missing => return Ok(EvalValue::Missing),
+                EvalValue::Value(JsonValue::String(needle)) => needle,
+                EvalValue::Value(_) => {
+                    return Err(TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("{} argument must be a string", op_name),
+                    )
+                    .with_path(arg_path));
+                }
+            };
+            let result = match op_name {
+                "contains" => text.contains(needle.as_str()),
+                "starts_with" => text.starts_with(needle.as_str()),
+                _ => text.ends_with(needle.as_str()),
+            };
+            Ok(EvalValue::Value(JsonValue::Bool(result)))
+        }
+        "join" => {
+            if op_step.args.len() != 1 {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "join requires exactly one argument",
+                )
+                .with_path(path));
+            }
+            if matches!(pipe_value, EvalValue::Missing) {
+                return Ok(EvalValue::Missing);
+            }
+            let arg_path = format!("{}.args[0]", path);
+            let separator =
+                match eval_v2_expr(&op_step.args[0], record, context, out, &arg_path, step_ctx)? {
+                    EvalValue::Missing => return Ok(EvalValue::Missing),
+                    EvalValue::Value(JsonValue::String(separator)) => separator,
+                    EvalValue::Value(_) => {
+                        return Err(TransformError::new(
+                            TransformErrorKind::ExprError,
+                            "join separator must be a string",
+                        )
+                        .with_path(arg_path));
+                    }
+                };
+            let items = eval_v2_array_from_eval_value(pipe_value.clone(), path)?;
+            let parts = items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| value_to_string(item, &format!("{}[{}]", path, index)))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(EvalValue::Value(JsonValue::String(parts.join(&separator))))
+        }
+        "substring" => {
+            if !(1..=2).contains(&op_step.args.len()) {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "substring requires one or two arguments",
+                )
+                .with_path(path));
+            }
+            let text = match &pipe_value {
+                EvalValue::Missing => return Ok(EvalValue::Missing),
+                EvalValue::Value(JsonValue::String(text)) => text,
+                EvalValue::Value(other) => {
+                    return Err(TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("substring expects a string, got {:?}", other),
+                    )
+                    .with_path(path));
+                }
+            };
+            let mut bounds = Vec::with_capacity(op_step.args.len());
+            for (index, (arg, message)) in op_step
+                .args
+                .iter()
+                .zip(["start must be an integer", "length must be an integer"])
+                .enumerate()
+            {
+                let arg_path = format!("{}.args[{}]", path, index);
+                match eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)? {
+                    EvalValue::Missing => return Ok(EvalValue::Missing),
+                    EvalValue::Value(JsonValue::Null) => {
+                        return Err(TransformError::new(
+                            TransformErrorKind::ExprError,
+                            "expr arg must not be null",
+                        )
+                        .with_path(arg_path));
+                    }
+                    EvalValue::Value(value) => {
+                        bounds.push(value_to_i64(&value, &arg_path, message)?)
+                    }
+                }
+            }
+            // Indices count chars, so slices never split a UTF-8 sequence.
+            let len = text.chars().count() as i64;
+            let start = if bounds[0] < 0 {
+                len + bounds[0]
+            } else {
+                bounds[0]
+            }
+            .clamp(0, len);
+            let end = match bounds.get(1) {
+                Some(length) => start.saturating_add((*length).max(0)).min(len),
+                None => len,
+            };
+            let slice: String = text
+                .chars()
+                .skip(start as usize)
+                .take((end - start) as usize)
+                .collect();
+            Ok(EvalValue::Value(JsonValue::String(slice)))
+        }
+        "format" => {
+            if op_step.args.is_empty() || op_step.args.len() > 2 {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "format requires a template and an optional missing mode",
+                )
+                .with_path(path));
+            }
+            let template_path = format!("{}.args[0]", path);
+            let template = eval_v2_expr_or_null(
+                &op_step.args[0],
+                record,
+                context,
+                out,
+                &template_path,
+                step_ctx,
+            )?;
+            let JsonValue::String(template) = template else {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "format template must be a string",
+                )
+                .with_path(&template_path));
+            };
+            let fail_on_missing = match op_step.args.get(1) {
+                None => false,
+                Some(arg) => {
+                    let mode_path = format!("{}.args[1]", path);
+                    match eval_v2_expr_or_null(arg, record, context, out, &mode_path, step_ctx)? {
+                        JsonValue::String(mode) if mode == "empty" => false,
+                        JsonValue::String(mode) if mode == "error" => true,
+                        _ => {
+                            return Err(TransformError::new(
+                                TransformErrorKind::ExprError,
+                                "format missing mode must be \"empty\" or \"error\"",
+                            )
+                            .with_path(&mode_path));
+                        }
+                    }
+                }
+            };
+            let rendered = render_format_template(
+                &template,
+                fail_on_missing,
+                &pipe_value,
+                record,
+                context,
+                out,
+                path,
+                step_ctx,
+            )?;
+            Ok(EvalValue::Value(JsonValue::String(rendered)))
+        }
+        "snake_case" | "camel_case" | "kebab_case" | "title_case" => match &pipe_value {
+            EvalValue::Missing => Ok(EvalValue::Missing),
+            EvalValue::Value(JsonValue::String(text)) => Ok(EvalValue::Value(JsonValue::String(
+                convert_case(op_step.op.as_str(), text),
+            ))),
+            EvalValue::Value(other) => Err(TransformError::new(
+                TransformErrorKind::ExprError,
+                format!("{} expects a string, got {:?}", op_step.op, other),
+            )
+            .with_path(path)),
+        },
+        "sanitize_filename" => match &pipe_value {
+            EvalValue::Missing => Ok(EvalValue::Missing),
+            EvalValue::Value(JsonValue::String(text)) => {
+                Ok(EvalValue::Value(JsonValue::String(sanitize_filename(text))))
+            }
+            EvalValue::Value(other) => Err(TransformError::new(
+                TransformErrorKind::ExprError,
+                format!("sanitize_filename expects a string, got {:?}", other),
+            )
+            .with_path(path)),
+        },
+        "base64_encode" | "base64_decode" | "hex_encode" | "hex_decode" => {
+            let op_name = op_step.op.as_str();
+            let text = match &pipe_value {
+                EvalValue::Missing => return Ok(EvalValue::Missing),
+                EvalValue::Value(JsonValue::String(text)) => text,
+                EvalValue::Value(other) => {
+                    return Err(TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("{} expects a string, got {:?}", op_name, other),
+                    )
+                    .with_path(path));
+                }
+            };
+            match op_name {
+                "base64_encode" => Ok(EvalValue::Value(JsonValue::String(BASE64.encode(text)))),
+                "hex_encode" => Ok(EvalValue::Value(JsonValue::String(hex_encode(
+                    text.as_bytes(),
+                )))),
+                "base64_decode" => {
+                    let bytes = BASE64.decode(text).map_err(|err| {
+                        TransformError::new(
+                            TransformErrorKind::ExprError,
+                            format!("base64_decode got invalid input: {}", err),
+                        )
+                        .with_path(path)
+                    })?;
+                    decoded_utf8(bytes, op_name, path)
+                }
+                _ => decoded_utf8(hex_decode(text, path)?, op_name, path),
+            }
+        }
+        "to_json" => match &pipe_value {
+            EvalValue::Missing => Ok(EvalValue::Missing),
+            EvalValue::Value(value) => {
+                let text = serde_json::to_string(value).map_err(|err| {
+                    TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("to_json failed: {}", err),
+                    )
+                    .with_path(path)
+                })?;
+                Ok(EvalValue::Value(JsonValue::String(text)))
+            }
+        },
+        "from_json" => match &pipe_value {
+            EvalValue::Missing => Ok(EvalValue::Missing),
+            EvalValue::Value(JsonValue::String(text)) => serde_json::from_str(text)
+                .map(EvalValue::Value)
+                .map_err(|err| {
+                    TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("from_json got invalid JSON: {}", err),
+                    )
+                    .with_path(path)
+                }),
+            EvalValue::Value(other) => Err(TransformError::new(
+                TransformErrorKind::ExprError,
+                format!("from_json expects a string, got {:?}", other),
+            )
+            .with_path(path)),
+        },
+        "parse_query_string" => {
+            let query = match &pipe_value {
+                EvalValue::Missing => return Ok(EvalValue::Missing),
+                EvalValue::Value(JsonValue::String(query)) => query,
+                EvalValue::Value(other) => {
+                    return Err(TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("parse_query_string expects a string, got {:?}", other),
+                    )
+                    .with_path(path));
+                }
+            };
+            let query = query.strip_prefix('?').unwrap_or(query);
+            let mut params = serde_json::Map::new();
+            for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                let key = percent_decode_query(key, path)?;
+                let value = JsonValue::String(percent_decode_query(value, path)?);
+                match params.get_mut(&key) {
+                    Some(JsonValue::Array(values)) => values.push(value),
+                    Some(existing) => {
+                        let first = existing.take();
+                        *existing = JsonValue::Array(vec![first, value]);
+                    }
+                    None => {
+                        params.insert(key, value);
+                    }
+                }
+            }
+            Ok(EvalValue::Value(JsonValue::Object(params)))
+        }
+        "to_string" => match &pipe_value {
+            EvalValue::Missing => Ok(EvalValue::Missing),
+            EvalValue::Value(v) => {
+                let s = match v {
+                    JsonValue::String(s) => s.clone(),
+                    JsonValue::Number(n) => n.to_string(),
+                    JsonValue::Bool(b) => b.to_string(),
+                    JsonValue::Null => "null".to_string(),
+                    JsonValue::Array(_) | JsonValue::Object(_) => v.to_string(),
+                };
+                Ok(EvalValue::Value(JsonValue::String(s)))
+            }
+        },
         "concat" => {
             // Pipe value is first, then args
             let mut parts = Vec::new();
@@ -1832,7 +2920,7 @@ pub fn eval_v2_op_step<'a>(
             parts.push(eval_value_as_string(&pipe_value, path)?);
             for (i, arg) in op_step.args.iter().enumerate() {
                 let arg_path = format!("{}.args[{}]", path, i);
-                let arg_value = eval_v2_expr(arg, record, context, out, &arg_path, &step_ctx)?;
+                let arg_value = eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)?;
                 if matches!(arg_value, EvalValue::Missing) {
                     return Ok(EvalValue::Missing);
                 }
@@ -1852,7 +2940,7 @@ pub fn eval_v2_op_step<'a>(
             let mut result = eval_value_as_number(&pipe_value, path)?;
             for (i, arg) in op_step.args.iter().enumerate() {
                 let arg_path = format!("{}.args[{}]", path, i);
-                let arg_value = eval_v2_expr(arg, record, context, out, &arg_path, &step_ctx)?;
+                let arg_value = eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)?;
                 if matches!(arg_value, EvalValue::Missing) {
                     return Ok(EvalValue::Missing);
                 }
@@ -1874,7 +2962,7 @@ pub fn eval_v2_op_step<'a>(
             let mut result = eval_value_as_number(&pipe_value, path)?;
             for (i, arg) in op_step.args.iter().enumerate() {
                 let arg_path = format!("{}.args[{}]", path, i);
-                let arg_value = eval_v2_expr(arg, record, context, out, &arg_path, &step_ctx)?;
+                let arg_value = eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)?;
                 if matches!(arg_value, EvalValue::Missing) {
                     return Ok(EvalValue::Missing);
                 }
@@ -1889,7 +2977,7 @@ pub fn eval_v2_op_step<'a>(
             let mut result = eval_value_as_number(&pipe_value, path)?;
             for (i, arg) in op_step.args.iter().enumerate() {
                 let arg_path = format!("{}.args[{}]", path, i);
-                let arg_value = eval_v2_expr(arg, record, context, out, &arg_path, &step_ctx)?;
+                let arg_value = eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)?;
                 if matches!(arg_value, EvalValue::Missing) {
                     return Ok(EvalValue::Missing);
                 }
@@ -1911,7 +2999,7 @@ pub fn eval_v2_op_step<'a>(
             let mut result = eval_value_as_number(&pipe_value, path)?;
             for (i, arg) in op_step.args.iter().enumerate() {
                 let arg_path = format!("{}.args[{}]", path, i);
-                let arg_value = eval_v2_expr(arg, record, context, out, &arg_path, &step_ctx)?;
+                let arg_value = eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)?;
                 if matches!(arg_value, EvalValue::Missing) {
                     return Ok(EvalValue::Missing);
                 }
@@ -1927,6 +3015,100 @@ pub fn eval_v2_op_step<'a>(
             }
             Ok(EvalValue::Value(serde_json::json!(result)))
         }
+        "max_of" | "min_of" => {
+            let is_max = op_step.op == "max_of";
+            let mut best: Option<(f64, JsonValue)> = None;
+            let mut consider = |value: EvalValue, value_path: &str| -> Result<(), TransformError> {
+                if matches!(
+                    value,
+                    EvalValue::Missing | EvalValue::Value(JsonValue::Null)
+                ) {
+                    return Ok(());
+                }
+                let number = eval_value_as_number(&value, value_path)?;
+                let replace = match &best {
+                    Some((current, _)) if is_max => number > *current,
+                    Some((current, _)) => number < *current,
+                    None => true,
+                };
+                if replace {
+                    let json = match value {
+                        EvalValue::Value(JsonValue::Number(n)) => JsonValue::Number(n),
+                        _ => serde_json::json!(number),
+                    };
+                    best = Some((number, json));
+                }
+                Ok(())
+            };
+            consider(pipe_value, path)?;
+            for (i, arg) in op_step.args.iter().enumerate() {
+                let arg_path = format!("{}.args[{}]", path, i);
+                let arg_value = eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)?;
+                consider(arg_value, &arg_path)?;
+            }
+            match best {
+                Some((_, value)) => Ok(EvalValue::Value(value)),
+                None => Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    format!("{} requires at least one numeric operand", op_step.op),
+                )
+                .with_path(path)),
+            }
+        }
+        "abs" | "ceil" | "floor" | "sqrt" | "pow" | "mod" => {
+            let op_name = op_step.op.as_str();
+            let expected_args = usize::from(matches!(op_name, "pow" | "mod"));
+            if op_step.args.len() != expected_args {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    format!("{} requires exactly {} argument(s)", op_name, expected_args),
+                )
+                .with_path(path));
+            }
+            if matches!(pipe_value, EvalValue::Missing) {
+                return Ok(EvalValue::Missing);
+            }
+            let value = eval_value_as_number(&pipe_value, path)?;
+            let operand = match op_step.args.first() {
+                Some(arg) => {
+                    let arg_path = format!("{}.args[0]", path);
+                    let arg_value = eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)?;
+                    if matches!(arg_value, EvalValue::Missing) {
+                        return Ok(EvalValue::Missing);
+                    }
+                    Some((eval_value_as_number(&arg_value, &arg_path)?, arg_path))
+                }
+                None => None,
+            };
+            let math_error = |message: &str, at: &str| {
+                TransformError::new(TransformErrorKind::ExprError, message.to_string())
+                    .with_path(at)
+            };
+            let result = match (op_name, operand) {
+                ("abs", _) => value.abs(),
+                ("ceil", _) => value.ceil(),
+                ("floor", _) => value.floor(),
+                ("sqrt", _) if value < 0.0 => {
+                    return Err(math_error("sqrt of a negative number", path));
+                }
+                ("sqrt", _) => value.sqrt(),
+                ("mod", Some((divisor, arg_path))) => {
+                    if divisor == 0.0 {
+                        return Err(math_error("modulo by zero", &arg_path));
+                    }
+                    value % divisor
+                }
+                (_, Some((exponent, _))) => value.powf(exponent),
+                (_, None) => unreachable!("pow and mod always have an operand"),
+            };
+            if !result.is_finite() {
+                return Err(math_error(
+                    &format!("{} result is not a finite number", op_name),
+                    path,
+                ));
+            }
+            Ok(EvalValue::Value(serde_json::json!(result)))
+        }
         "map" => {
             if op_step.args.len() != 1 {
                 return Err(TransformError::new(
@@ -2056,6 +3238,41 @@ pub fn eval_v2_op_step<'a>(
             }
             Ok(EvalValue::Value(JsonValue::Object(results)))
         }
+        "count_by" => {
+            if op_step.args.len() != 1 {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "count_by requires exactly one argument",
+                )
+                .with_path(path));
+            }
+            if matches!(pipe_value, EvalValue::Missing) {
+                return Ok(EvalValue::Missing);
+            }
+            let array = eval_v2_array_from_eval_value(pipe_value.clone(), path)?;
+            let arg_path = format!("{}.args[0]", path);
+            let mut counts: serde_json::Map<String, JsonValue> = serde_json::Map::new();
+            for (index, item) in array.iter().enumerate() {
+                let item_ctx = step_ctx
+                    .clone()
+                    .with_pipe_value(EvalValue::Value(item.clone()))
+                    .with_item(EvalItem { value: item, index });
+                let Some(key) = eval_v2_optional_key_expr_string(
+                    &op_step.args[0],
+                    record,
+                    context,
+                    out,
+                    &arg_path,
+                    &item_ctx,
+                )?
+                else {
+                    continue;
+                };
+                let count = counts.entry(key).or_insert_with(|| JsonValue::from(0u64));
+                *count = JsonValue::from(count.as_u64().unwrap_or(0) + 1);
+            }
+            Ok(EvalValue::Value(JsonValue::Object(counts)))
+        }
         "key_by" => {
             if op_step.args.len() != 1 {
                 return Err(TransformError::new(
@@ -2150,15 +3367,121 @@ pub fn eval_v2_op_step<'a>(
             }
             Ok(EvalValue::Value(JsonValue::Array(results)))
         }
-        "sort_by" => {
-            if !(1..=2).contains(&op_step.args.len()) {
+        "count_distinct" => {
+            if op_step.args.len() > 1 {
                 return Err(TransformError::new(
                     TransformErrorKind::ExprError,
-                    "sort_by requires one or two arguments",
+                    "count_distinct accepts at most one argument",
                 )
                 .with_path(path));
             }
-            let array = eval_v2_array_from_eval_value(pipe_value.clone(), path)?;
+            if matches!(pipe_value, EvalValue::Missing) {
+                return Ok(EvalValue::Missing);
+            }
+            let array = eval_v2_array_from_eval_value(pipe_value.clone(), path)?;
+            let arg_path = format!("{}.args[0]", path);
+            let mut seen = HashSet::new();
+            for (index, item) in array.iter().enumerate() {
+                let key = match op_step.args.first() {
+                    Some(key_expr) => {
+                        let item_ctx = step_ctx
+                            .clone()
+                            .with_pipe_value(EvalValue::Value(item.clone()))
+                            .with_item(EvalItem { value: item, index });
+                        eval_v2_key_expr_string(
+                            key_expr, record, context, out, &arg_path, &item_ctx,
+                        )?
+                    }
+                    None => {
+                        let item_path = format!("{}[{}]", path, index);
+                        if item.is_null() {
+                            return Err(TransformError::new(
+                                TransformErrorKind::ExprError,
+                                "count_distinct item must not be null",
+                            )
+                            .with_path(item_path));
+                        }
+                        value_to_string(item, &item_path)?
+                    }
+                };
+                seen.insert(key);
+            }
+            Ok(EvalValue::Value(JsonValue::from(seen.len())))
+        }
+        "sum_by" | "avg_by" | "min_by" | "max_by" => {
+            let op_name = op_step.op.as_str();
+            if op_step.args.len() != 1 {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    format!("{} requires exactly one argument", op_name),
+                )
+                .with_path(path));
+            }
+            if matches!(pipe_value, EvalValue::Missing) {
+                return Ok(EvalValue::Missing);
+            }
+            let array = eval_v2_array_from_eval_value(pipe_value.clone(), path)?;
+            let arg_path = format!("{}.args[0]", path);
+            let mut keyed: Vec<(f64, &JsonValue)> = Vec::new();
+            for (index, item) in array.iter().enumerate() {
+                let item_ctx = step_ctx
+                    .clone()
+                    .with_pipe_value(EvalValue::Value(item.clone()))
+                    .with_item(EvalItem { value: item, index });
+                let key = match eval_v2_expr(
+                    &op_step.args[0],
+                    record,
+                    context,
+                    out,
+                    &arg_path,
+                    &item_ctx,
+                )? {
+                    EvalValue::Missing => continue,
+                    EvalValue::Value(key) => key,
+                };
+                let key = value_to_number(
+                    &key,
+                    &format!("{}[{}]", path, index),
+                    &format!("{} key must be a number", op_name),
+                )?;
+                keyed.push((key, item));
+            }
+            let keys = keyed.iter().map(|(key, _)| *key);
+            match op_name {
+                "sum_by" => Ok(EvalValue::Value(json_number_from_f64(keys.sum(), path)?)),
+                "avg_by" if keyed.is_empty() => Ok(EvalValue::Missing),
+                "avg_by" => {
+                    let avg = keys.sum::<f64>() / keyed.len() as f64;
+                    Ok(EvalValue::Value(json_number_from_f64(avg, path)?))
+                }
+                _ => {
+                    // Ties keep the earliest element.
+                    let mut best: Option<&(f64, &JsonValue)> = None;
+                    for entry in &keyed {
+                        let better = match best {
+                            None => true,
+                            Some(current) if op_name == "min_by" => entry.0 < current.0,
+                            Some(current) => entry.0 > current.0,
+                        };
+                        if better {
+                            best = Some(entry);
+                        }
+                    }
+                    Ok(best.map_or(EvalValue::Missing, |(_, item)| {
+                        EvalValue::Value((*item).clone())
+                    }))
+                }
+            }
+        }
+        "sort_by" => {
+            if !(1..=2).contains(&op_step.args.len()) {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "sort_by requires one or two arguments",
+                )
+                .with_path(path));
+            }
+            let array = eval_v2_array_from_eval_value(pipe_value.clone(), path)?;
             if array.is_empty() {
                 return Ok(EvalValue::Value(JsonValue::Array(Vec::new())));
             }
@@ -2171,7 +3494,7 @@ pub fn eval_v2_op_step<'a>(
                     context,
                     out,
                     &order_path,
-                    &step_ctx,
+                    step_ctx,
                 )?;
                 let order = match order_value {
                     EvalValue::Missing => return Ok(EvalValue::Missing),
@@ -2342,17 +3665,11 @@ pub fn eval_v2_op_step<'a>(
             }
             let array = eval_v2_array_from_eval_value(pipe_value.clone(), path)?;
             let init_path = format!("{}.args[0]", path);
-            let initial = match eval_v2_expr(
-                &op_step.args[0],
-                record,
-                context,
-                out,
-                &init_path,
-                &step_ctx,
-            )? {
-                EvalValue::Missing => return Ok(EvalValue::Missing),
-                EvalValue::Value(value) => value,
-            };
+            let initial =
+                match eval_v2_expr(&op_step.args[0], record, context, out, &init_path, step_ctx)? {
+                    EvalValue::Missing => return Ok(EvalValue::Missing),
+                    EvalValue::Value(value) => value,
+                };
             let expr_path = format!("{}.args[1]", path);
             let mut acc = initial;
             for (index, item) in array.iter().enumerate() {
@@ -2373,6 +3690,23 @@ pub fn eval_v2_op_step<'a>(
             }
             Ok(EvalValue::Value(acc))
         }
+        "zip" => {
+            if op_step.args.is_empty() {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "zip requires at least one argument",
+                )
+                .with_path(path));
+            }
+            let mut arrays = Vec::with_capacity(op_step.args.len() + 1);
+            arrays.push(eval_v2_array_from_eval_value(pipe_value.clone(), path)?);
+            for (index, arg) in op_step.args.iter().enumerate() {
+                let arg_path = format!("{}.args[{}]", path, index);
+                let value = eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)?;
+                arrays.push(eval_v2_array_from_eval_value(value, &arg_path)?);
+            }
+            Ok(EvalValue::Value(JsonValue::Array(zip_rows(&arrays))))
+        }
         "zip_with" => {
             if op_step.args.len() < 2 {
                 return Err(TransformError::new(
@@ -2385,21 +3719,16 @@ pub fn eval_v2_op_step<'a>(
             arrays.push(eval_v2_array_from_eval_value(pipe_value.clone(), path)?);
             for (index, arg) in op_step.args.iter().enumerate().take(op_step.args.len() - 1) {
                 let arg_path = format!("{}.args[{}]", path, index);
-                let value = eval_v2_expr(arg, record, context, out, &arg_path, &step_ctx)?;
+                let value = eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)?;
                 arrays.push(eval_v2_array_from_eval_value(value, &arg_path)?);
             }
 
-            let min_len = arrays.iter().map(|items| items.len()).min().unwrap_or(0);
             let expr_index = op_step.args.len() - 1;
             let expr_path = format!("{}.args[{}]", path, expr_index);
             let expr = &op_step.args[expr_index];
-            let mut results = Vec::with_capacity(min_len);
-            for row_index in 0..min_len {
-                let mut row = Vec::with_capacity(arrays.len());
-                for array in &arrays {
-                    row.push(array[row_index].clone());
-                }
-                let row_value = JsonValue::Array(row);
+            let rows = zip_rows(&arrays);
+            let mut results = Vec::with_capacity(rows.len());
+            for (row_index, row_value) in rows.into_iter().enumerate() {
                 let item_ctx = step_ctx
                     .clone()
                     .with_pipe_value(EvalValue::Value(row_value.clone()))
@@ -2413,6 +3742,309 @@ pub fn eval_v2_op_step<'a>(
             }
             Ok(EvalValue::Value(JsonValue::Array(results)))
         }
+        "length" => match &pipe_value {
+            EvalValue::Missing => Ok(EvalValue::Missing),
+            EvalValue::Value(JsonValue::String(text)) => {
+                Ok(EvalValue::Value(JsonValue::from(text.chars().count())))
+            }
+            EvalValue::Value(JsonValue::Array(items)) => {
+                Ok(EvalValue::Value(JsonValue::from(items.len())))
+            }
+            EvalValue::Value(JsonValue::Object(map)) => {
+                Ok(EvalValue::Value(JsonValue::from(map.len())))
+            }
+            EvalValue::Value(other) => Err(TransformError::new(
+                TransformErrorKind::ExprError,
+                format!("length requires string, array, or object, got {:?}", other),
+            )
+            .with_path(path)),
+        },
+        "is_empty" => match &pipe_value {
+            EvalValue::Missing | EvalValue::Value(JsonValue::Null) => {
+                Ok(EvalValue::Value(JsonValue::Bool(true)))
+            }
+            EvalValue::Value(JsonValue::String(text)) => {
+                Ok(EvalValue::Value(JsonValue::Bool(text.is_empty())))
+            }
+            EvalValue::Value(JsonValue::Array(items)) => {
+                Ok(EvalValue::Value(JsonValue::Bool(items.is_empty())))
+            }
+            EvalValue::Value(JsonValue::Object(map)) => {
+                Ok(EvalValue::Value(JsonValue::Bool(map.is_empty())))
+            }
+            EvalValue::Value(other) => Err(TransformError::new(
+                TransformErrorKind::ExprError,
+                format!(
+                    "is_empty requires string, array, or object, got {:?}",
+                    other
+                ),
+            )
+            .with_path(path)),
+        },
+        "is_null" => Ok(EvalValue::Value(JsonValue::Bool(matches!(
+            pipe_value,
+            EvalValue::Missing | EvalValue::Value(JsonValue::Null)
+        )))),
+        "count" => {
+            if matches!(pipe_value, EvalValue::Missing) {
+                return Ok(EvalValue::Missing);
+            }
+            let items = eval_v2_array_from_eval_value(pipe_value, path)?;
+            Ok(EvalValue::Value(JsonValue::Number(items.len().into())))
+        }
+        "sum" | "avg" | "min" | "max" => {
+            if matches!(pipe_value, EvalValue::Missing) {
+                return Ok(EvalValue::Missing);
+            }
+            let op_name = op_step.op.as_str();
+            let items = eval_v2_array_from_eval_value(pipe_value, path)?;
+            let numbers = items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| {
+                    value_to_number(
+                        item,
+                        &format!("{}[{}]", path, index),
+                        &format!("{} item must be a number", op_name),
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let result = match op_name {
+                "sum" => Some(numbers.iter().sum()),
+                "avg" if numbers.is_empty() => None,
+                "avg" => Some(numbers.iter().sum::<f64>() / numbers.len() as f64),
+                "min" => numbers.iter().copied().reduce(f64::min),
+                _ => numbers.iter().copied().reduce(f64::max),
+            };
+            match result {
+                Some(value) => Ok(EvalValue::Value(json_number_from_f64(value, path)?)),
+                None => Ok(EvalValue::Missing),
+            }
+        }
+        "reverse" | "unique" | "chunk" | "window" => {
+            let op_name = op_step.op.as_str();
+            let expected_args = usize::from(matches!(op_name, "chunk" | "window"));
+            if op_step.args.len() != expected_args {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    format!("{} requires exactly {} argument(s)", op_name, expected_args),
+                )
+                .with_path(path));
+            }
+            let items = match &pipe_value {
+                EvalValue::Missing => return Ok(EvalValue::Missing),
+                EvalValue::Value(JsonValue::Array(items)) => items,
+                EvalValue::Value(other) => {
+                    return Err(TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("{} expects an array, got {:?}", op_name, other),
+                    )
+                    .with_path(path));
+                }
+            };
+            let result = match op_name {
+                "reverse" => items.iter().rev().cloned().collect(),
+                "unique" => {
+                    // Object keys serialize in sorted order, so equal values share a key.
+                    let mut seen = HashSet::new();
+                    items
+                        .iter()
+                        .filter(|item| seen.insert(item.to_string()))
+                        .cloned()
+                        .collect()
+                }
+                _ => {
+                    let arg_path = format!("{}.args[0]", path);
+                    let size = match eval_v2_expr(
+                        &op_step.args[0],
+                        record,
+                        context,
+                        out,
+                        &arg_path,
+                        step_ctx,
+                    )? {
+                        EvalValue::Missing => return Ok(EvalValue::Missing),
+                        EvalValue::Value(value) => value_to_i64(
+                            &value,
+                            &arg_path,
+                            &format!("{} size must be an integer", op_name),
+                        )?,
+                    };
+                    if size <= 0 {
+                        return Err(TransformError::new(
+                            TransformErrorKind::ExprError,
+                            format!("{} size must be a positive integer", op_name),
+                        )
+                        .with_path(arg_path));
+                    }
+                    let size = usize::try_from(size).unwrap_or(usize::MAX);
+                    if op_name == "chunk" {
+                        items
+                            .chunks(size)
+                            .map(|chunk| JsonValue::Array(chunk.to_vec()))
+                            .collect()
+                    } else {
+                        // `windows` yields nothing when size exceeds the length.
+                        items
+                            .windows(size)
+                            .map(|window| JsonValue::Array(window.to_vec()))
+                            .collect()
+                    }
+                }
+            };
+            Ok(EvalValue::Value(JsonValue::Array(result)))
+        }
+        "flatten" => {
+            if op_step.args.len() > 1 {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "flatten accepts at most one argument",
+                )
+                .with_path(path));
+            }
+            let items = match &pipe_value {
+                EvalValue::Missing => return Ok(EvalValue::Missing),
+                EvalValue::Value(JsonValue::Array(items)) => items,
+                EvalValue::Value(other) => {
+                    return Err(TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("flatten expects an array, got {:?}", other),
+                    )
+                    .with_path(path));
+                }
+            };
+            let depth = match op_step.args.first() {
+                None => 1,
+                Some(arg) => {
+                    let arg_path = format!("{}.args[0]", path);
+                    let depth_value =
+                        match eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)? {
+                            EvalValue::Missing => return Ok(EvalValue::Missing),
+                            EvalValue::Value(value) => value,
+                        };
+                    match value_to_i64(
+                        &depth_value,
+                        &arg_path,
+                        "depth must be a non-negative integer or -1",
+                    )? {
+                        -1 => usize::MAX,
+                        depth if depth >= 0 => usize::try_from(depth).unwrap_or(usize::MAX),
+                        _ => {
+                            return Err(TransformError::new(
+                                TransformErrorKind::ExprError,
+                                "depth must be a non-negative integer or -1",
+                            )
+                            .with_path(arg_path));
+                        }
+                    }
+                }
+            };
+            let mut flattened = Vec::with_capacity(items.len());
+            for item in items {
+                flatten_value(item, depth, &mut flattened);
+            }
+            Ok(EvalValue::Value(JsonValue::Array(flattened)))
+        }
+        "take" | "drop" => {
+            let op_name = op_step.op.as_str();
+            if op_step.args.len() != 1 {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    format!("{} requires exactly one argument", op_name),
+                )
+                .with_path(path));
+            }
+            let items = match &pipe_value {
+                EvalValue::Missing => return Ok(EvalValue::Missing),
+                EvalValue::Value(JsonValue::Array(items)) => items,
+                EvalValue::Value(other) => {
+                    return Err(TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("{} expects an array, got {:?}", op_name, other),
+                    )
+                    .with_path(path));
+                }
+            };
+            let arg_path = format!("{}.args[0]", path);
+            let count_value =
+                eval_v2_expr(&op_step.args[0], record, context, out, &arg_path, step_ctx)?;
+            if matches!(count_value, EvalValue::Missing) {
+                return Ok(EvalValue::Missing);
+            }
+            let count = eval_value_as_number(&count_value, &arg_path)?;
+            if count.fract() != 0.0 || !count.is_finite() {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    format!("{} count must be an integer", op_name),
+                )
+                .with_path(arg_path));
+            }
+            // A negative count works from the tail: take -2 keeps the last two elements,
+            // drop -2 removes them.
+            let head = count.abs().min(items.len() as f64) as usize;
+            let split_at = if count >= 0.0 {
+                head
+            } else {
+                items.len() - head
+            };
+            let (front, back) = items.split_at(split_at);
+            let kept = match (op_name, count >= 0.0) {
+                ("take", true) | ("drop", false) => front,
+                _ => back,
+            };
+            Ok(EvalValue::Value(JsonValue::Array(kept.to_vec())))
+        }
+        "slice" => {
+            if !(1..=2).contains(&op_step.args.len()) {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "slice requires one or two arguments",
+                )
+                .with_path(path));
+            }
+            let items = match &pipe_value {
+                EvalValue::Missing => return Ok(EvalValue::Missing),
+                EvalValue::Value(JsonValue::Array(items)) => items,
+                EvalValue::Value(other) => {
+                    return Err(TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("slice expects an array, got {:?}", other),
+                    )
+                    .with_path(path));
+                }
+            };
+            let len = items.len() as i64;
+            let mut bounds = Vec::with_capacity(2);
+            for (index, (arg, message)) in op_step
+                .args
+                .iter()
+                .zip(["start must be an integer", "end must be an integer"])
+                .enumerate()
+            {
+                let arg_path = format!("{}.args[{}]", path, index);
+                let bound = match eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)? {
+                    EvalValue::Missing => return Ok(EvalValue::Missing),
+                    EvalValue::Value(JsonValue::Null) => {
+                        return Err(TransformError::new(
+                            TransformErrorKind::ExprError,
+                            "expr arg must not be null",
+                        )
+                        .with_path(arg_path));
+                    }
+                    EvalValue::Value(value) => value_to_i64(&value, &arg_path, message)?,
+                };
+                let bound = if bound < 0 { len + bound } else { bound };
+                bounds.push(bound.clamp(0, len) as usize);
+            }
+            let start = bounds[0];
+            let end = bounds.get(1).copied().unwrap_or(items.len());
+            let slice = if end > start {
+                items[start..end].to_vec()
+            } else {
+                Vec::new()
+            };
+            Ok(EvalValue::Value(JsonValue::Array(slice)))
+        }
         "first" => match &pipe_value {
             EvalValue::Missing => Ok(EvalValue::Missing),
             EvalValue::Value(JsonValue::Array(arr)) => {
@@ -2443,6 +4075,23 @@ pub fn eval_v2_op_step<'a>(
             )
             .with_path(path)),
         },
+        "only" => match &pipe_value {
+            EvalValue::Missing => Ok(EvalValue::Missing),
+            EvalValue::Value(JsonValue::Array(arr)) => match arr.as_slice() {
+                [] => Ok(EvalValue::Missing),
+                [value] => Ok(EvalValue::Value(value.clone())),
+                _ => Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    format!("only requires at most one element, got {}", arr.len()),
+                )
+                .with_path(path)),
+            },
+            EvalValue::Value(other) => Err(TransformError::new(
+                TransformErrorKind::ExprError,
+                format!("only requires array, got {:?}", other),
+            )
+            .with_path(path)),
+        },
 
         // Coalesce
         "coalesce" => {
@@ -2455,7 +4104,7 @@ pub fn eval_v2_op_step<'a>(
             // Otherwise, try args in order
             for (i, arg) in op_step.args.iter().enumerate() {
                 let arg_path = format!("{}.args[{}]", path, i);
-                let arg_value = eval_v2_expr(arg, record, context, out, &arg_path, &step_ctx)?;
+                let arg_value = eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)?;
                 if let EvalValue::Value(v) = &arg_value {
                     if !v.is_null() {
                         return Ok(arg_value);
@@ -2464,6 +4113,36 @@ pub fn eval_v2_op_step<'a>(
             }
             Ok(EvalValue::Missing)
         }
+        "fallback" => {
+            // Unlike coalesce, only missing values fall through; an explicit null is kept.
+            if !matches!(pipe_value, EvalValue::Missing) {
+                return Ok(pipe_value);
+            }
+            for (i, arg) in op_step.args.iter().enumerate() {
+                let arg_path = format!("{}.args[{}]", path, i);
+                let arg_value = eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)?;
+                if !matches!(arg_value, EvalValue::Missing) {
+                    return Ok(arg_value);
+                }
+            }
+            Ok(EvalValue::Missing)
+        }
+        "default" => {
+            if op_step.args.len() != 1 {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "default requires exactly one argument",
+                )
+                .with_path(path));
+            }
+            match &pipe_value {
+                EvalValue::Missing | EvalValue::Value(JsonValue::Null) => {
+                    let arg_path = format!("{}.args[0]", path);
+                    eval_v2_expr(&op_step.args[0], record, context, out, &arg_path, step_ctx)
+                }
+                EvalValue::Value(_) => Ok(pipe_value),
+            }
+        }
         "and" | "or" => {
             let is_and = op_step.op == "and";
             let total_len = op_step.args.len() + 1;
@@ -2492,7 +4171,7 @@ pub fn eval_v2_op_step<'a>(
 
             for (index, arg) in op_step.args.iter().enumerate() {
                 let arg_path = format!("{}.args[{}]", path, index);
-                let value = eval_v2_expr(arg, record, context, out, &arg_path, &step_ctx)?;
+                let value = eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)?;
                 match value {
                     EvalValue::Missing => {
                         saw_missing = true;
@@ -2553,7 +4232,7 @@ pub fn eval_v2_op_step<'a>(
                 context,
                 out,
                 &right_path,
-                &step_ctx,
+                step_ctx,
             )?;
             let left_path = path.to_string();
             let op = match op_step.op.as_str() {
@@ -2590,7 +4269,7 @@ pub fn eval_v2_op_step<'a>(
             let mut path_values = Vec::new();
             for (index, arg) in op_step.args.iter().enumerate() {
                 let arg_path = format!("{}.args[{}]", path, index);
-                let value = match eval_v2_expr(arg, record, context, out, &arg_path, &step_ctx)? {
+                let value = match eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)? {
                     EvalValue::Missing => return Ok(EvalValue::Missing),
                     EvalValue::Value(value) => value,
                 };
@@ -2642,31 +4321,288 @@ pub fn eval_v2_op_step<'a>(
                 context,
                 out,
                 path,
-                &step_ctx,
+                step_ctx,
             )
         }
 
-        // Lookup operations - v2 keyword format: lookup_first: {from: ..., match: [...], get: ...}
-        // For v2, lookup args are parsed from V2OpStep with special handling
-        // Explicit from:
-        // args[0] = from (array to search in)
-        // args[1] = match key (field name in array items to match)
-        // args[2] = match value (value to match against)
-        // args[3] = get (optional - field to extract from matched item)
-        // Implicit from (pipe value):
-        // args[0] = match key
-        // args[1] = match value
-        // args[2] = get (optional)
-        "lookup_first" => {
-            if op_step.args.len() < 2 {
+        "values_at" => {
+            if op_step.args.is_empty() {
                 return Err(TransformError::new(
                     TransformErrorKind::ExprError,
-                    "lookup_first requires at least 2 arguments: match_key, match_value",
+                    "values_at requires at least one argument",
                 )
-                .with_path(path));
+                .with_path(format!("{}.args", path)));
+            }
+            let source = match &pipe_value {
+                EvalValue::Missing => return Ok(EvalValue::Missing),
+                EvalValue::Value(value) => value,
+            };
+
+            let mut skip_missing = false;
+            let mut paths = Vec::new();
+            let last_index = op_step.args.len() - 1;
+            for (index, arg) in op_step.args.iter().enumerate() {
+                let arg_path = format!("{}.args[{}]", path, index);
+                let value = match eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)? {
+                    EvalValue::Missing => return Ok(EvalValue::Missing),
+                    EvalValue::Value(value) => value,
+                };
+                match value {
+                    // A trailing boolean toggles skipping missing fields
+                    JsonValue::Bool(flag) if index == last_index && index > 0 => {
+                        skip_missing = flag;
+                    }
+                    JsonValue::String(path_value) => paths.push((path_value, arg_path)),
+                    JsonValue::Array(items) => {
+                        for (item_index, item) in items.iter().enumerate() {
+                            let item_path = format!("{}[{}]", arg_path, item_index);
+                            let path_value = item.as_str().ok_or_else(|| {
+                                TransformError::new(
+                                    TransformErrorKind::ExprError,
+                                    "paths must be a string or array of strings",
+                                )
+                                .with_path(&item_path)
+                            })?;
+                            paths.push((path_value.to_string(), item_path));
+                        }
+                    }
+                    _ => {
+                        return Err(TransformError::new(
+                            TransformErrorKind::ExprError,
+                            "paths must be a string or array of strings",
+                        )
+                        .with_path(arg_path));
+                    }
+                }
+            }
+
+            let mut values = Vec::with_capacity(paths.len());
+            for (path_value, arg_path) in &paths {
+                match get_path_str(source, path_value, arg_path)? {
+                    EvalValue::Value(value) => values.push(value),
+                    EvalValue::Missing if skip_missing => {}
+                    EvalValue::Missing => values.push(JsonValue::Null),
+                }
+            }
+            Ok(EvalValue::Value(JsonValue::Array(values)))
+        }
+
+        // Date operations
+        "to_timezone" => {
+            if op_step.args.is_empty() || op_step.args.len() > 2 {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "to_timezone requires one or two arguments",
+                )
+                .with_path(path));
+            }
+            let mut zones = Vec::with_capacity(op_step.args.len());
+            for (index, arg) in op_step.args.iter().enumerate() {
+                let arg_path = format!("{}.args[{}]", path, index);
+                let value = eval_v2_expr(arg, record, context, out, &arg_path, step_ctx)?;
+                let name = eval_value_as_string(&value, &arg_path)?;
+                let zone = name.parse::<chrono_tz::Tz>().map_err(|_| {
+                    TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("invalid timezone: {}", name),
+                    )
+                    .with_path(&arg_path)
+                })?;
+                zones.push(zone);
+            }
+            let target = zones[0];
+            let default_zone = zones.get(1).copied().unwrap_or(chrono_tz::UTC);
+            let Some(value) = pipe_value.as_value().and_then(|value| value.as_str()) else {
+                return Ok(EvalValue::Missing);
+            };
+            match parse_datetime_in_zone(value, &default_zone) {
+                Some(dt) => Ok(EvalValue::Value(JsonValue::String(
+                    dt.with_timezone(&target)
+                        .to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+                ))),
+                None => Ok(EvalValue::Missing),
+            }
+        }
+
+        "parse_date" | "format_date" => {
+            let op_name = op_step.op.as_str();
+            if op_step.args.len() != 1 {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    format!("{} requires exactly one argument", op_name),
+                )
+                .with_path(path));
+            }
+            let text = match &pipe_value {
+                EvalValue::Missing => return Ok(EvalValue::Missing),
+                EvalValue::Value(JsonValue::String(text)) => text,
+                EvalValue::Value(other) => {
+                    return Err(TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("{} expects a string, got {:?}", op_name, other),
+                    )
+                    .with_path(path));
+                }
+            };
+            let arg_path = format!("{}.args[0]", path);
+            let format_value =
+                eval_v2_expr(&op_step.args[0], record, context, out, &arg_path, step_ctx)?;
+            let format = eval_value_as_string(&format_value, &arg_path)?;
+            let result = if op_name == "parse_date" {
+                let dt = parse_date_with_format(text, &format).ok_or_else(|| {
+                    TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!(
+                            "parse_date could not parse {:?} with format {:?}",
+                            text, format
+                        ),
+                    )
+                    .with_path(path)
+                })?;
+                dt.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+            } else {
+                let dt = chrono::DateTime::parse_from_rfc3339(text).map_err(|_| {
+                    TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("format_date expects an RFC 3339 date, got {:?}", text),
+                    )
+                    .with_path(path)
+                })?;
+                format_date_with_format(&dt, &format).ok_or_else(|| {
+                    TransformError::new(
+                        TransformErrorKind::ExprError,
+                        format!("invalid date format: {}", format),
+                    )
+                    .with_path(&arg_path)
+                })?
+            };
+            Ok(EvalValue::Value(JsonValue::String(result)))
+        }
+
+        // Reads the wall clock on every call, so output that uses it is not reproducible.
+        "now" => Ok(EvalValue::Value(JsonValue::String(
+            chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+        ))),
+
+        "apply_json_patch" => {
+            if op_step.args.len() != 1 {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "apply_json_patch requires exactly one argument",
+                )
+                .with_path(path));
+            }
+            let doc = match &pipe_value {
+                EvalValue::Missing => return Ok(EvalValue::Missing),
+                EvalValue::Value(value) => value.clone(),
+            };
+            let arg_path = format!("{}.args[0]", path);
+            let patch =
+                match eval_v2_expr(&op_step.args[0], record, context, out, &arg_path, step_ctx)? {
+                    EvalValue::Missing => return Ok(EvalValue::Missing),
+                    EvalValue::Value(value) => value,
+                };
+            Ok(EvalValue::Value(apply_json_patch(doc, &patch, &arg_path)?))
+        }
+
+        "assert_type" => {
+            if op_step.args.len() != 1 {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "assert_type requires exactly one argument",
+                )
+                .with_path(path));
+            }
+            let arg_path = format!("{}.args[0]", path);
+            let spec_value =
+                eval_v2_expr(&op_step.args[0], record, context, out, &arg_path, step_ctx)?;
+            let spec_text = eval_value_as_string(&spec_value, &arg_path)?;
+            let spec = TypeSpec::parse(&spec_text).map_err(|message| {
+                TransformError::new(TransformErrorKind::ExprError, message).with_path(&arg_path)
+            })?;
+            let mismatch = match &pipe_value {
+                EvalValue::Missing if spec.is_nullable() => return Ok(EvalValue::Missing),
+                EvalValue::Missing => Some("$: value is missing".to_string()),
+                EvalValue::Value(value) => spec
+                    .check(value)
+                    .err()
+                    .map(|mismatch| format!("{}: {}", mismatch.location, mismatch.reason)),
+            };
+            match mismatch {
+                Some(detail) => Err(TransformError::new(
+                    TransformErrorKind::TypeAssertionFailed,
+                    format!("assert_type {} failed at {}", spec_text, detail),
+                )
+                .with_path(path)),
+                None => Ok(pipe_value),
+            }
+        }
+
+        "assert" => {
+            // `assert: [message]` checks the pipe value; `assert: [condition, message]`
+            // checks the condition. Either way the pipe value passes through.
+            let (ok, message_arg) = match op_step.args.as_slice() {
+                [] => (v2_pipe_value_truthy(&pipe_value, path)?, None),
+                [message] => (v2_pipe_value_truthy(&pipe_value, path)?, Some((0, message))),
+                [condition, message] => {
+                    let condition_path = format!("{}.args[0]", path);
+                    let ok = eval_v2_predicate_expr(
+                        condition,
+                        record,
+                        context,
+                        out,
+                        &condition_path,
+                        step_ctx,
+                    )?;
+                    (ok, Some((1, message)))
+                }
+                _ => {
+                    return Err(TransformError::new(
+                        TransformErrorKind::ExprError,
+                        "assert takes at most two arguments",
+                    )
+                    .with_path(path));
+                }
+            };
+            if ok {
+                return Ok(pipe_value);
+            }
+            let message = match message_arg {
+                Some((index, message)) => {
+                    let arg_path = format!("{}.args[{}]", path, index);
+                    let value = eval_v2_expr(message, record, context, out, &arg_path, step_ctx)?;
+                    format!(
+                        "assert failed: {}",
+                        eval_value_as_string(&value, &arg_path)?
+                    )
+                }
+                None => "assert failed".to_string(),
+            };
+            Err(TransformError::new(TransformErrorKind::AssertionFailed, message).with_path(path))
+        }
+
+        // Lookup operations - v2 keyword format: lookup_first: {from: ..., match: [...], get: ...}
+        // For v2, lookup args are parsed from V2OpStep with special handling
+        // Explicit from:
+        // args[0] = from (array to search in)
+        // args[1] = match key (field name in array items to match)
+        // args[2] = match value (value to match against)
+        // args[3] = get (optional - field to extract from matched item)
+        // Implicit from (pipe value):
+        // args[0] = match key
+        // args[1] = match value
+        // args[2] = get (optional)
+        // A trailing `{ coerce: true }` literal lets strings match numbers ("2" == 2).
+        "lookup_first" => {
+            let (args, coerce) = split_lookup_options(&op_step.op, &op_step.args, path)?;
+            if args.len() < 2 {
+                return Err(TransformError::new(
+                    TransformErrorKind::ExprError,
+                    "lookup_first requires at least 2 arguments: match_key, match_value",
+                )
+                .with_path(path));
             }
 
-            let args = &op_step.args;
             let from_path = format!("{}.from", path);
             let match_key_path = format!("{}.match_key", path);
             let get_path = format!("{}.get", path);
@@ -2680,7 +4616,7 @@ pub fn eval_v2_op_step<'a>(
                         context,
                         out,
                         &format!("{}.args[0]", path),
-                        &step_ctx,
+                        step_ctx,
                     )?;
                     let match_value = eval_v2_expr(
                         &args[1],
@@ -2688,7 +4624,7 @@ pub fn eval_v2_op_step<'a>(
                         context,
                         out,
                         &format!("{}.args[1]", path),
-                        &step_ctx,
+                        step_ctx,
                     )?;
                     (pipe_value.clone(), match_key_value, match_value, None)
                 }
@@ -2700,7 +4636,7 @@ pub fn eval_v2_op_step<'a>(
                             context,
                             out,
                             &format!("{}.args[0]", path),
-                            &step_ctx,
+                            step_ctx,
                         )?;
                         let use_explicit_from =
                             matches!(first_value, EvalValue::Value(JsonValue::Array(_)));
@@ -2713,7 +4649,7 @@ pub fn eval_v2_op_step<'a>(
                             context,
                             out,
                             &format!("{}.args[1]", path),
-                            &step_ctx,
+                            step_ctx,
                         )?;
                         let match_value = eval_v2_expr(
                             &args[2],
@@ -2721,7 +4657,7 @@ pub fn eval_v2_op_step<'a>(
                             context,
                             out,
                             &format!("{}.args[2]", path),
-                            &step_ctx,
+                            step_ctx,
                         )?;
                         (first_value, match_key_value, match_value, None)
                     } else {
@@ -2731,7 +4667,7 @@ pub fn eval_v2_op_step<'a>(
                             context,
                             out,
                             &format!("{}.args[0]", path),
-                            &step_ctx,
+                            step_ctx,
                         )?;
                         let use_explicit_from = matches!(
                             first_value,
@@ -2744,7 +4680,7 @@ pub fn eval_v2_op_step<'a>(
                                 context,
                                 out,
                                 &format!("{}.args[1]", path),
-                                &step_ctx,
+                                step_ctx,
                             )?;
                             let match_value = eval_v2_expr(
                                 &args[2],
@@ -2752,7 +4688,7 @@ pub fn eval_v2_op_step<'a>(
                                 context,
                                 out,
                                 &format!("{}.args[2]", path),
-                                &step_ctx,
+                                step_ctx,
                             )?;
                             (first_value, match_key_value, match_value, None)
                         } else {
@@ -2762,7 +4698,7 @@ pub fn eval_v2_op_step<'a>(
                                 context,
                                 out,
                                 &format!("{}.args[1]", path),
-                                &step_ctx,
+                                step_ctx,
                             )?;
                             let get_value = eval_v2_expr(
                                 &args[2],
@@ -2770,7 +4706,7 @@ pub fn eval_v2_op_step<'a>(
                                 context,
                                 out,
                                 &format!("{}.args[2]", path),
-                                &step_ctx,
+                                step_ctx,
                             )?;
                             let get_field = Some(eval_value_as_string(&get_value, &get_path)?);
                             (pipe_value.clone(), first_value, match_value, get_field)
@@ -2784,7 +4720,7 @@ pub fn eval_v2_op_step<'a>(
                         context,
                         out,
                         &format!("{}.args[0]", path),
-                        &step_ctx,
+                        step_ctx,
                     )?;
                     let match_key_value = eval_v2_expr(
                         &args[1],
@@ -2792,7 +4728,7 @@ pub fn eval_v2_op_step<'a>(
                         context,
                         out,
                         &format!("{}.args[1]", path),
-                        &step_ctx,
+                        step_ctx,
                     )?;
                     let match_value = eval_v2_expr(
                         &args[2],
@@ -2800,7 +4736,7 @@ pub fn eval_v2_op_step<'a>(
                         context,
                         out,
                         &format!("{}.args[2]", path),
-                        &step_ctx,
+                        step_ctx,
                     )?;
                     let get_value = eval_v2_expr(
                         &args[3],
@@ -2808,7 +4744,7 @@ pub fn eval_v2_op_step<'a>(
                         context,
                         out,
                         &format!("{}.args[3]", path),
-                        &step_ctx,
+                        step_ctx,
                     )?;
                     let get_field = Some(eval_value_as_string(&get_value, &get_path)?);
                     (from_value, match_key_value, match_value, get_field)
@@ -2830,28 +4766,26 @@ pub fn eval_v2_op_step<'a>(
 
             // Get match key as string
             let match_key = eval_value_as_string(&match_key_value, &match_key_path)?;
-            if matches!(match_value, EvalValue::Missing) {
+            let EvalValue::Value(match_value) = match_value else {
                 return Ok(EvalValue::Missing);
-            }
+            };
 
             // Search for first matching item
             for item in arr {
-                if let JsonValue::Object(obj) = item {
-                    if let Some(field_val) = obj.get(&match_key) {
-                        let item_val = EvalValue::Value(field_val.clone());
-                        if compare_values_eq(&item_val, &match_value) {
-                            // Found a match
-                            if let Some(ref get_key) = get_field {
-                                // Return specific field from matched item
-                                return match obj.get(get_key) {
-                                    Some(v) => Ok(EvalValue::Value(v.clone())),
-                                    None => Ok(EvalValue::Missing),
-                                };
-                            } else {
-                                // Return entire matched item
-                                return Ok(EvalValue::Value(item.clone()));
-                            }
-                        }
+                if let JsonValue::Object(obj) = item
+                    && let Some(field_val) = obj.get(&match_key)
+                    && json_values_eq_normalized(field_val, &match_value, coerce)
+                {
+                    // Found a match
+                    if let Some(ref get_key) = get_field {
+                        // Return specific field from matched item
+                        return match obj.get(get_key) {
+                            Some(v) => Ok(EvalValue::Value(v.clone())),
+                            None => Ok(EvalValue::Missing),
+                        };
+                    } else {
+                        // Return entire matched item
+                        return Ok(EvalValue::Value(item.clone()));
                     }
                 }
             }
@@ -2860,7 +4794,8 @@ pub fn eval_v2_op_step<'a>(
         }
 
         "lookup" => {
-            if op_step.args.len() < 2 {
+            let (args, coerce) = split_lookup_options(&op_step.op, &op_step.args, path)?;
+            if args.len() < 2 {
                 return Err(TransformError::new(
                     TransformErrorKind::ExprError,
                     "lookup requires at least 2 arguments: match_key, match_value",
@@ -2868,7 +4803,6 @@ pub fn eval_v2_op_step<'a>(
                 .with_path(path));
             }
 
-            let args = &op_step.args;
             let from_path = format!("{}.from", path);
             let match_key_path = format!("{}.match_key", path);
             let get_path = format!("{}.get", path);
@@ -2882,7 +4816,7 @@ pub fn eval_v2_op_step<'a>(
                         context,
                         out,
                         &format!("{}.args[0]", path),
-                        &step_ctx,
+                        step_ctx,
                     )?;
                     let match_value = eval_v2_expr(
                         &args[1],
@@ -2890,7 +4824,7 @@ pub fn eval_v2_op_step<'a>(
                         context,
                         out,
                         &format!("{}.args[1]", path),
-                        &step_ctx,
+                        step_ctx,
                     )?;
                     (pipe_value.clone(), match_key_value, match_value, None)
                 }
@@ -2902,7 +4836,7 @@ pub fn eval_v2_op_step<'a>(
                             context,
                             out,
                             &format!("{}.args[0]", path),
-                            &step_ctx,
+                            step_ctx,
                         )?;
                         let use_explicit_from =
                             matches!(first_value, EvalValue::Value(JsonValue::Array(_)));
@@ -2915,7 +4849,7 @@ pub fn eval_v2_op_step<'a>(
                             context,
                             out,
                             &format!("{}.args[1]", path),
-                            &step_ctx,
+                            step_ctx,
                         )?;
                         let match_value = eval_v2_expr(
                             &args[2],
@@ -2923,7 +4857,7 @@ pub fn eval_v2_op_step<'a>(
                             context,
                             out,
                             &format!("{}.args[2]", path),
-                            &step_ctx,
+                            step_ctx,
                         )?;
                         (first_value, match_key_value, match_value, None)
                     } else {
@@ -2933,7 +4867,7 @@ pub fn eval_v2_op_step<'a>(
                             context,
                             out,
                             &format!("{}.args[0]", path),
-                            &step_ctx,
+                            step_ctx,
                         )?;
                         let use_explicit_from = matches!(
                             first_value,
@@ -2946,7 +4880,7 @@ pub fn eval_v2_op_step<'a>(
                                 context,
                                 out,
                                 &format!("{}.args[1]", path),
-                                &step_ctx,
+                                step_ctx,
                             )?;
                             let match_value = eval_v2_expr(
                                 &args[2],
@@ -2954,7 +4888,7 @@ pub fn eval_v2_op_step<'a>(
                                 context,
                                 out,
                                 &format!("{}.args[2]", path),
-                                &step_ctx,
+                                step_ctx,
                             )?;
                             (first_value, match_key_value, match_value, None)
                         } else {
@@ -2964,7 +4898,7 @@ pub fn eval_v2_op_step<'a>(
                                 context,
                                 out,
                                 &format!("{}.args[1]", path),
-                                &step_ctx,
+                                step_ctx,
                             )?;
                             let get_value = eval_v2_expr(
                                 &args[2],
@@ -2972,7 +4906,7 @@ pub fn eval_v2_op_step<'a>(
                                 context,
                                 out,
                                 &format!("{}.args[2]", path),
-                                &step_ctx,
+                                step_ctx,
                             )?;
                             let get_field = Some(eval_value_as_string(&get_value, &get_path)?);
                             (pipe_value.clone(), first_value, match_value, get_field)
@@ -2986,7 +4920,7 @@ pub fn eval_v2_op_step<'a>(
                         context,
                         out,
                         &format!("{}.args[0]", path),
-                        &step_ctx,
+                        step_ctx,
                     )?;
                     let match_key_value = eval_v2_expr(
                         &args[1],
@@ -2994,7 +4928,7 @@ pub fn eval_v2_op_step<'a>(
                         context,
                         out,
                         &format!("{}.args[1]", path),
-                        &step_ctx,
+                        step_ctx,
                     )?;
                     let match_value = eval_v2_expr(
                         &args[2],
@@ -3002,7 +4936,7 @@ pub fn eval_v2_op_step<'a>(
                         context,
                         out,
                         &format!("{}.args[2]", path),
-                        &step_ctx,
+                        step_ctx,
                     )?;
                     let get_value = eval_v2_expr(
                         &args[3],
@@ -3010,7 +4944,7 @@ pub fn eval_v2_op_step<'a>(
                         context,
                         out,
                         &format!("{}.args[3]", path),
-                        &step_ctx,
+                        step_ctx,
                     )?;
                     let get_field = Some(eval_value_as_string(&get_value, &get_path)?);
                     (from_value, match_key_value, match_value, get_field)
@@ -3032,28 +4966,26 @@ pub fn eval_v2_op_step<'a>(
 
             // Get match key as string
             let match_key = eval_value_as_string(&match_key_value, &match_key_path)?;
-            if matches!(match_value, EvalValue::Missing) {
+            let EvalValue::Value(match_value) = match_value else {
                 return Ok(EvalValue::Missing);
-            }
+            };
 
             // Search for ALL matching items
             let mut results = Vec::new();
             for item in arr {
-                if let JsonValue::Object(obj) = item {
-                    if let Some(field_val) = obj.get(&match_key) {
-                        let item_val = EvalValue::Value(field_val.clone());
-                        if compare_values_eq(&item_val, &match_value) {
-                            // Found a match
-                            if let Some(ref get_key) = get_field {
-                                // Add specific field from matched item
-                                if let Some(v) = obj.get(get_key) {
-                                    results.push(v.clone());
-                                }
-                            } else {
-                                // Add entire matched item
-                                results.push(item.clone());
-                            }
+                if let JsonValue::Object(obj) = item
+                    && let Some(field_val) = obj.get(&match_key)
+                    && json_values_eq_normalized(field_val, &match_value, coerce)
+                {
+                    // Found a match
+                    if let Some(ref get_key) = get_field {
+                        // Add specific field from matched item
+                        if let Some(v) = obj.get(get_key) {
+                            results.push(v.clone());
                         }
+                    } else {
+                        // Add entire matched item
+                        results.push(item.clone());
                     }
                 }
             }
@@ -3062,9 +4994,7 @@ pub fn eval_v2_op_step<'a>(
         }
 
         // Default case - fall back to v1 op evaluation
-        _ => {
-            eval_v2_op_with_v1_fallback(op_step, pipe_value, record, context, out, path, &step_ctx)
-        }
+        _ => eval_v2_op_with_v1_fallback(op_step, pipe_value, record, context, out, path, step_ctx),
     }
 }
 
@@ -3359,420 +5289,2325 @@ mod v2_op_step_eval_tests {
     }
 
     #[test]
-    fn test_eval_op_first_last() {
-        let first = V2OpStep {
-            op: "first".to_string(),
+    fn test_eval_op_length() {
+        let ctx = V2EvalContext::new();
+        let op = V2OpStep {
+            op: "length".to_string(),
             args: vec![],
         };
-        let last = V2OpStep {
-            op: "last".to_string(),
-            args: vec![],
+        let eval = |value: EvalValue| {
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
         };
-        let ctx = V2EvalContext::new();
 
-        let first_result = eval_v2_op_step(
-            &first,
-            EvalValue::Value(json!([1, 2])),
-            &json!({}),
-            None,
-            &json!({}),
-            "test",
-            &ctx,
+        assert_eq!(
+            eval(EvalValue::Value(json!("café"))).unwrap(),
+            EvalValue::Value(json!(4))
         );
-        assert!(matches!(first_result, Ok(EvalValue::Value(v)) if v == json!(1)));
-
-        let last_result = eval_v2_op_step(
-            &last,
-            EvalValue::Value(json!([1, 2])),
-            &json!({}),
-            None,
-            &json!({}),
-            "test",
-            &ctx,
+        assert_eq!(
+            eval(EvalValue::Value(json!(""))).unwrap(),
+            EvalValue::Value(json!(0))
         );
-        assert!(matches!(last_result, Ok(EvalValue::Value(v)) if v == json!(2)));
+        assert_eq!(
+            eval(EvalValue::Value(json!([1, [2, 3], null]))).unwrap(),
+            EvalValue::Value(json!(3))
+        );
+        assert_eq!(
+            eval(EvalValue::Value(json!({ "a": 1, "b": { "c": 2 } }))).unwrap(),
+            EvalValue::Value(json!(2))
+        );
+        assert_eq!(eval(EvalValue::Missing).unwrap(), EvalValue::Missing);
+
+        for value in [json!(42), json!(null), json!(true)] {
+            let err = eval(EvalValue::Value(value)).unwrap_err();
+            assert_eq!(err.kind, TransformErrorKind::ExprError);
+            assert_eq!(err.path.as_deref(), Some("test"));
+        }
     }
 
     #[test]
-    fn test_eval_op_type_casts() {
-        let op_int = V2OpStep {
-            op: "int".to_string(),
-            args: vec![],
-        };
-        let op_float = V2OpStep {
-            op: "float".to_string(),
-            args: vec![],
+    fn test_eval_op_is_empty_and_is_null() {
+        let ctx = V2EvalContext::new();
+        let eval = |op: &str, value: EvalValue| {
+            let op = V2OpStep {
+                op: op.to_string(),
+                args: vec![],
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
         };
-        let op_bool = V2OpStep {
-            op: "bool".to_string(),
-            args: vec![],
+
+        let cases = [
+            (EvalValue::Value(json!("")), true, false),
+            (EvalValue::Value(json!("a")), false, false),
+            (EvalValue::Value(json!([])), true, false),
+            (EvalValue::Value(json!([null])), false, false),
+            (EvalValue::Value(json!({})), true, false),
+            (EvalValue::Value(json!({ "a": 1 })), false, false),
+            (EvalValue::Value(json!(null)), true, true),
+            (EvalValue::Missing, true, true),
+        ];
+        for (value, empty, nullish) in cases {
+            assert_eq!(
+                eval("is_empty", value.clone()).unwrap(),
+                EvalValue::Value(json!(empty))
+            );
+            assert_eq!(
+                eval("is_null", value).unwrap(),
+                EvalValue::Value(json!(nullish))
+            );
+        }
+
+        for value in [json!(0), json!(false)] {
+            let err = eval("is_empty", EvalValue::Value(value.clone())).unwrap_err();
+            assert_eq!(err.kind, TransformErrorKind::ExprError);
+            assert_eq!(err.path.as_deref(), Some("test"));
+            assert_eq!(
+                eval("is_null", EvalValue::Value(value)).unwrap(),
+                EvalValue::Value(json!(false))
+            );
+        }
+    }
+
+    #[test]
+    fn test_eval_op_assert() {
+        let record = json!({ "total": 5 });
+        let ctx = V2EvalContext::new();
+        let eval = |value: EvalValue, args: Vec<V2Expr>| {
+            let op = V2OpStep {
+                op: "assert".to_string(),
+                args,
+            };
+            eval_v2_op_step(&op, value, &record, None, &json!({}), "test", &ctx)
         };
-        let op_string = V2OpStep {
-            op: "string".to_string(),
-            args: vec![],
+        let positive = || {
+            V2Expr::Pipe(V2Pipe {
+                start: V2Start::Ref(V2Ref::Input("total".to_string())),
+                steps: vec![V2Step::Op(V2OpStep {
+                    op: "gt".to_string(),
+                    args: vec![lit(json!(0))],
+                })],
+            })
         };
-        let ctx = V2EvalContext::new();
 
-        let int_result = eval_v2_op_step(
-            &op_int,
-            EvalValue::Value(json!("42")),
-            &json!({}),
-            None,
-            &json!({}),
-            "test",
-            &ctx,
+        assert_eq!(
+            eval(EvalValue::Value(json!(true)), vec![]).unwrap(),
+            EvalValue::Value(json!(true))
+        );
+        assert_eq!(
+            eval(
+                EvalValue::Value(json!({ "id": 1 })),
+                vec![positive(), lit(json!("total must be positive"))]
+            )
+            .unwrap(),
+            EvalValue::Value(json!({ "id": 1 }))
         );
-        assert!(matches!(int_result, Ok(EvalValue::Value(v)) if v == json!(42)));
 
-        let float_result = eval_v2_op_step(
-            &op_float,
-            EvalValue::Value(json!("3.14")),
+        let err = eval(
+            EvalValue::Value(json!(false)),
+            vec![lit(json!("order is not open"))],
+        )
+        .unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::AssertionFailed);
+        assert_eq!(err.message, "assert failed: order is not open");
+        assert_eq!(err.path.as_deref(), Some("test"));
+
+        let err = eval(EvalValue::Missing, vec![]).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::AssertionFailed);
+        assert_eq!(err.message, "assert failed");
+
+        let record = json!({ "total": 0 });
+        let op = V2OpStep {
+            op: "assert".to_string(),
+            args: vec![positive(), lit(json!("total must be positive"))],
+        };
+        let err = eval_v2_op_step(
+            &op,
+            EvalValue::Value(json!({ "id": 1 })),
+            &record,
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::AssertionFailed);
+        assert!(err.message.contains("total must be positive"));
+
+        let err = eval(EvalValue::Value(json!("yes")), vec![]).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+    }
+
+    #[test]
+    fn test_eval_op_zip() {
+        let ctx = V2EvalContext::new();
+        let eval = |value: JsonValue, args: Vec<JsonValue>| {
+            let op = V2OpStep {
+                op: "zip".to_string(),
+                args: args.into_iter().map(lit).collect(),
+            };
+            eval_v2_op_step(
+                &op,
+                EvalValue::Value(value),
+                &json!({}),
+                None,
+                &json!({}),
+                "test",
+                &ctx,
+            )
+        };
+
+        assert_eq!(
+            eval(json!([1, 2, 3]), vec![json!(["a", "b", "c"])]).unwrap(),
+            EvalValue::Value(json!([[1, "a"], [2, "b"], [3, "c"]]))
+        );
+        assert_eq!(
+            eval(
+                json!([1, 2, 3]),
+                vec![json!(["a", "b"]), json!([true, false, true])]
+            )
+            .unwrap(),
+            EvalValue::Value(json!([[1, "a", true], [2, "b", false]]))
+        );
+        assert_eq!(
+            eval(json!([1, 2]), vec![json!([])]).unwrap(),
+            EvalValue::Value(json!([]))
+        );
+
+        let err = eval(json!([1, 2]), vec![json!(["a"]), json!("b")]).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test.args[1]"));
+        let err = eval(json!([1, 2]), vec![]).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test"));
+    }
+
+    #[test]
+    fn test_eval_op_format() {
+        let record = json!({ "name": "Ada", "balance": 12.5, "tags": ["a"] });
+        let item_value = json!({ "total": 3 });
+        let ctx = V2EvalContext::new().with_item(EvalItem {
+            value: &item_value,
+            index: 0,
+        });
+        let eval = |value: EvalValue, args: Vec<JsonValue>| {
+            let op = V2OpStep {
+                op: "format".to_string(),
+                args: args.into_iter().map(lit).collect(),
+            };
+            eval_v2_op_step(&op, value, &record, None, &json!({}), "test", &ctx)
+        };
+        let text = |value: &str| EvalValue::Value(json!(value));
+
+        assert_eq!(
+            eval(
+                EvalValue::Missing,
+                vec![json!(
+                    "Hello {@input.name}, you owe {@input.balance} for {@item.total}"
+                )]
+            )
+            .unwrap(),
+            text("Hello Ada, you owe 12.5 for 3")
+        );
+        assert_eq!(
+            eval(text("x"), vec![json!("{{ {$} }} {{@input.name}}")]).unwrap(),
+            text("{ x } {@input.name}")
+        );
+        assert_eq!(
+            eval(EvalValue::Missing, vec![json!("[{@input.nickname}][{$}]")]).unwrap(),
+            text("[][]")
+        );
+        assert_eq!(
+            eval(
+                EvalValue::Missing,
+                vec![json!("[{@input.nickname}]"), json!("empty")]
+            )
+            .unwrap(),
+            text("[]")
+        );
+
+        let err = eval(
+            EvalValue::Missing,
+            vec![json!("Hi {@input.nickname}"), json!("error")],
+        )
+        .unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(
+            err.message,
+            "format placeholder '{@input.nickname}' is missing"
+        );
+
+        for template in ["{name}", "{@input.name", "a } b"] {
+            let err = eval(EvalValue::Missing, vec![json!(template)]).unwrap_err();
+            assert_eq!(err.path.as_deref(), Some("test.args[0]"), "{}", template);
+        }
+        assert!(eval(EvalValue::Missing, vec![json!("{@input.tags}")]).is_err());
+        assert!(eval(EvalValue::Missing, vec![json!("{$}"), json!("skip")]).is_err());
+    }
+
+    #[test]
+    fn test_eval_op_case_conversion() {
+        let ctx = V2EvalContext::new();
+        let eval = |op: &str, value: EvalValue| {
+            let op = V2OpStep {
+                op: op.to_string(),
+                args: vec![],
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
+        let text = |value: &str| EvalValue::Value(json!(value));
+
+        for input in ["helloWorld", "hello world", "hello-world", "Hello_World"] {
+            assert_eq!(
+                eval("snake_case", text(input)).unwrap(),
+                text("hello_world")
+            );
+            assert_eq!(eval("camel_case", text(input)).unwrap(), text("helloWorld"));
+            assert_eq!(
+                eval("kebab_case", text(input)).unwrap(),
+                text("hello-world")
+            );
+            assert_eq!(
+                eval("title_case", text(input)).unwrap(),
+                text("Hello World")
+            );
+        }
+
+        assert_eq!(
+            eval("snake_case", text("parseHTTPResponse2xx")).unwrap(),
+            text("parse_http_response2xx")
+        );
+        assert_eq!(
+            eval("camel_case", text("  user__ID  ")).unwrap(),
+            text("userId")
+        );
+        assert_eq!(eval("title_case", text("")).unwrap(), text(""));
+        assert_eq!(
+            eval("kebab_case", EvalValue::Missing).unwrap(),
+            EvalValue::Missing
+        );
+        let err = eval("snake_case", EvalValue::Value(json!(1))).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test"));
+    }
+
+    #[test]
+    fn test_eval_op_deep_merge() {
+        let ctx = V2EvalContext::new();
+        let eval = |value: JsonValue, args: Vec<JsonValue>| {
+            let op = V2OpStep {
+                op: "deep_merge".to_string(),
+                args: args.into_iter().map(lit).collect(),
+            };
+            eval_v2_op_step(
+                &op,
+                EvalValue::Value(value),
+                &json!({}),
+                None,
+                &json!({}),
+                "test",
+                &ctx,
+            )
+        };
+        let base = json!({
+            "db": { "host": "localhost", "pool": { "min": 1, "max": 5 } },
+            "tags": ["a"],
+            "debug": false
+        });
+        let patch = json!({
+            "db": { "pool": { "max": 20 }, "user": "app" },
+            "tags": ["b"],
+            "debug": true
+        });
+
+        assert_eq!(
+            eval(base.clone(), vec![patch.clone()]).unwrap(),
+            EvalValue::Value(json!({
+                "db": { "host": "localhost", "pool": { "min": 1, "max": 20 }, "user": "app" },
+                "tags": ["b"],
+                "debug": true
+            }))
+        );
+        assert_eq!(
+            eval(base.clone(), vec![patch.clone(), json!("replace")]).unwrap(),
+            eval(base.clone(), vec![patch.clone()]).unwrap()
+        );
+        assert_eq!(
+            eval(base.clone(), vec![patch.clone(), json!("concat")]).unwrap(),
+            EvalValue::Value(json!({
+                "db": { "host": "localhost", "pool": { "min": 1, "max": 20 }, "user": "app" },
+                "tags": ["a", "b"],
+                "debug": true
+            }))
+        );
+
+        let err = eval(base.clone(), vec![patch.clone(), json!("append")]).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test.args[2]"));
+        let err = eval(json!([1]), vec![patch]).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert!(eval(base, vec![json!(1)]).is_err());
+    }
+
+    #[test]
+    fn test_eval_op_math() {
+        let ctx = V2EvalContext::new();
+        let eval = |op: &str, value: JsonValue, args: Vec<JsonValue>| {
+            let op = V2OpStep {
+                op: op.to_string(),
+                args: args.into_iter().map(lit).collect(),
+            };
+            eval_v2_op_step(
+                &op,
+                EvalValue::Value(value),
+                &json!({}),
+                None,
+                &json!({}),
+                "test",
+                &ctx,
+            )
+        };
+        let number = |value: f64| EvalValue::Value(json!(value));
+
+        assert_eq!(eval("abs", json!(-3.5), vec![]).unwrap(), number(3.5));
+        assert_eq!(eval("abs", json!("-2"), vec![]).unwrap(), number(2.0));
+        assert_eq!(eval("ceil", json!(-1.5), vec![]).unwrap(), number(-1.0));
+        assert_eq!(eval("ceil", json!(1.2), vec![]).unwrap(), number(2.0));
+        assert_eq!(eval("floor", json!(-1.5), vec![]).unwrap(), number(-2.0));
+        assert_eq!(eval("floor", json!(1.8), vec![]).unwrap(), number(1.0));
+        assert_eq!(eval("sqrt", json!(16), vec![]).unwrap(), number(4.0));
+        assert_eq!(
+            eval("pow", json!(2), vec![json!(10)]).unwrap(),
+            number(1024.0)
+        );
+        assert_eq!(
+            eval("pow", json!(-2), vec![json!(3)]).unwrap(),
+            number(-8.0)
+        );
+        assert_eq!(
+            eval("pow", json!(4), vec![json!(-0.5)]).unwrap(),
+            number(0.5)
+        );
+        assert_eq!(eval("mod", json!(17), vec![json!(5)]).unwrap(), number(2.0));
+        assert_eq!(
+            eval("mod", json!(-17), vec![json!(5)]).unwrap(),
+            number(-2.0)
+        );
+
+        let missing = V2OpStep {
+            op: "sqrt".to_string(),
+            args: vec![],
+        };
+        assert_eq!(
+            eval_v2_op_step(
+                &missing,
+                EvalValue::Missing,
+                &json!({}),
+                None,
+                &json!({}),
+                "test",
+                &ctx
+            )
+            .unwrap(),
+            EvalValue::Missing
+        );
+
+        let err = eval("mod", json!(10), vec![json!(0)]).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
+        let err = eval("sqrt", json!(-4), vec![]).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test"));
+        let err = eval("pow", json!(0), vec![json!(-1)]).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test"));
+        let err = eval("pow", json!(-8), vec![json!(0.5)]).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test"));
+        let err = eval("abs", json!("abc"), vec![]).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+    }
+
+    #[test]
+    fn test_eval_op_array_aggregates() {
+        let ctx = V2EvalContext::new();
+        let eval = |op: &str, value: EvalValue| {
+            let op = V2OpStep {
+                op: op.to_string(),
+                args: vec![],
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
+        let numbers = || EvalValue::Value(json!([3, "1.5", 2]));
+
+        assert_eq!(
+            eval("sum", numbers()).unwrap(),
+            EvalValue::Value(json!(6.5))
+        );
+        assert_eq!(
+            eval("sum", EvalValue::Value(json!([1, 2, 3]))).unwrap(),
+            EvalValue::Value(json!(6))
+        );
+        assert_eq!(
+            eval("avg", EvalValue::Value(json!([1, 2]))).unwrap(),
+            EvalValue::Value(json!(1.5))
+        );
+        assert_eq!(
+            eval("min", numbers()).unwrap(),
+            EvalValue::Value(json!(1.5))
+        );
+        assert_eq!(eval("max", numbers()).unwrap(), EvalValue::Value(json!(3)));
+        assert_eq!(
+            eval("count", numbers()).unwrap(),
+            EvalValue::Value(json!(3))
+        );
+
+        let empty = || EvalValue::Value(json!([]));
+        assert_eq!(eval("sum", empty()).unwrap(), EvalValue::Value(json!(0)));
+        assert_eq!(eval("avg", empty()).unwrap(), EvalValue::Missing);
+        assert_eq!(eval("min", empty()).unwrap(), EvalValue::Missing);
+        assert_eq!(eval("max", empty()).unwrap(), EvalValue::Missing);
+        assert_eq!(eval("count", empty()).unwrap(), EvalValue::Value(json!(0)));
+        assert_eq!(eval("sum", EvalValue::Missing).unwrap(), EvalValue::Missing);
+        assert_eq!(
+            eval("count", EvalValue::Missing).unwrap(),
+            EvalValue::Missing
+        );
+
+        let mixed = || EvalValue::Value(json!([1, "two", { "n": 3 }, null]));
+        assert_eq!(eval("count", mixed()).unwrap(), EvalValue::Value(json!(4)));
+        for op in ["sum", "avg", "min", "max"] {
+            let err = eval(op, mixed()).unwrap_err();
+            assert_eq!(err.kind, TransformErrorKind::ExprError);
+            assert_eq!(err.path.as_deref(), Some("test[1]"));
+        }
+        let err = eval("sum", EvalValue::Value(json!({ "n": 1 }))).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test"));
+    }
+
+    #[test]
+    fn test_eval_op_keyed_aggregates() {
+        let ctx = V2EvalContext::new();
+        let eval = |op: &str, value: JsonValue| {
+            let op = V2OpStep {
+                op: op.to_string(),
+                args: vec![V2Expr::Pipe(V2Pipe {
+                    start: V2Start::Ref(V2Ref::Item("price".to_string())),
+                    steps: vec![],
+                })],
+            };
+            eval_v2_op_step(
+                &op,
+                EvalValue::Value(value),
+                &json!({}),
+                None,
+                &json!({}),
+                "test",
+                &ctx,
+            )
+        };
+        let items = || {
+            json!([
+                { "sku": "a", "price": 5 },
+                { "sku": "b", "price": "12.5" },
+                { "sku": "c" },
+                { "sku": "d", "price": 12.5 },
+                { "sku": "e", "price": 2 }
+            ])
+        };
+
+        assert_eq!(
+            eval("sum_by", items()).unwrap(),
+            EvalValue::Value(json!(32))
+        );
+        assert_eq!(eval("avg_by", items()).unwrap(), EvalValue::Value(json!(8)));
+        assert_eq!(
+            eval("max_by", items()).unwrap(),
+            EvalValue::Value(json!({ "sku": "b", "price": "12.5" }))
+        );
+        assert_eq!(
+            eval("min_by", items()).unwrap(),
+            EvalValue::Value(json!({ "sku": "e", "price": 2 }))
+        );
+
+        let no_keys = || json!([{ "sku": "a" }]);
+        assert_eq!(
+            eval("sum_by", no_keys()).unwrap(),
+            EvalValue::Value(json!(0))
+        );
+        assert_eq!(eval("avg_by", no_keys()).unwrap(), EvalValue::Missing);
+        assert_eq!(eval("max_by", json!([])).unwrap(), EvalValue::Missing);
+
+        let err = eval("sum_by", json!([{ "price": 1 }, { "price": "n/a" }])).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test[1]"));
+    }
+
+    #[test]
+    fn test_eval_op_reverse_unique_chunk() {
+        let ctx = V2EvalContext::new();
+        let eval = |op: &str, value: EvalValue, args: Vec<JsonValue>| {
+            let op = V2OpStep {
+                op: op.to_string(),
+                args: args.into_iter().map(lit).collect(),
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
+        let array = |value: JsonValue| EvalValue::Value(value);
+
+        assert_eq!(
+            eval("reverse", array(json!([1, "b", [3]])), vec![]).unwrap(),
+            array(json!([[3], "b", 1]))
+        );
+        assert_eq!(
+            eval("reverse", array(json!([])), vec![]).unwrap(),
+            array(json!([]))
+        );
+
+        let duplicates = array(json!([
+            "b",
+            1,
+            "a",
+            "b",
+            "1",
+            1,
+            { "x": 1, "y": 2 },
+            { "y": 2, "x": 1 },
+            [1, 2],
+            [2, 1],
+            null,
+            null
+        ]));
+        assert_eq!(
+            eval("unique", duplicates, vec![]).unwrap(),
+            array(json!(["b", 1, "a", "1", { "x": 1, "y": 2 }, [1, 2], [2, 1], null]))
+        );
+
+        let items = || array(json!([1, 2, 3, 4, 5]));
+        assert_eq!(
+            eval("chunk", items(), vec![json!(2)]).unwrap(),
+            array(json!([[1, 2], [3, 4], [5]]))
+        );
+        assert_eq!(
+            eval("chunk", items(), vec![json!(5)]).unwrap(),
+            array(json!([[1, 2, 3, 4, 5]]))
+        );
+        assert_eq!(
+            eval("chunk", items(), vec![json!(10)]).unwrap(),
+            array(json!([[1, 2, 3, 4, 5]]))
+        );
+        assert_eq!(
+            eval("chunk", array(json!([])), vec![json!(3)]).unwrap(),
+            array(json!([]))
+        );
+
+        for op in ["reverse", "unique"] {
+            assert_eq!(
+                eval(op, EvalValue::Missing, vec![]).unwrap(),
+                EvalValue::Missing
+            );
+            let err = eval(op, array(json!("abc")), vec![]).unwrap_err();
+            assert_eq!(err.kind, TransformErrorKind::ExprError);
+            assert_eq!(err.path.as_deref(), Some("test"));
+        }
+        let err = eval("chunk", items(), vec![json!(0)]).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
+        let err = eval("chunk", items(), vec![json!(-1)]).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
+    }
+
+    #[test]
+    fn test_eval_op_window() {
+        let ctx = V2EvalContext::new();
+        let eval = |value: EvalValue, size: JsonValue| {
+            let op = V2OpStep {
+                op: "window".to_string(),
+                args: vec![lit(size)],
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
+        let array = |value: JsonValue| EvalValue::Value(value);
+
+        assert_eq!(
+            eval(array(json!([1, 2, 3, 4])), json!(2)).unwrap(),
+            array(json!([[1, 2], [2, 3], [3, 4]]))
+        );
+        assert_eq!(
+            eval(array(json!([1, 2, 3, 4])), json!(4)).unwrap(),
+            array(json!([[1, 2, 3, 4]]))
+        );
+        assert_eq!(
+            eval(array(json!([1, 2, 3])), json!(5)).unwrap(),
+            array(json!([]))
+        );
+        assert_eq!(
+            eval(EvalValue::Missing, json!(2)).unwrap(),
+            EvalValue::Missing
+        );
+
+        let err = eval(array(json!([1, 2])), json!(0)).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.message, "window size must be a positive integer");
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
+        let err = eval(array(json!({ "a": 1 })), json!(2)).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test"));
+    }
+
+    #[test]
+    fn test_eval_op_flatten() {
+        let ctx = V2EvalContext::new();
+        let eval = |value: EvalValue, args: Vec<JsonValue>| {
+            let op = V2OpStep {
+                op: "flatten".to_string(),
+                args: args.into_iter().map(lit).collect(),
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
+        let nested = || EvalValue::Value(json!([1, [2, [3, [4, [5]]]], "x", { "a": [6] }]));
+        let array = |value: JsonValue| EvalValue::Value(value);
+
+        assert_eq!(
+            eval(nested(), vec![]).unwrap(),
+            array(json!([1, 2, [3, [4, [5]]], "x", { "a": [6] }]))
+        );
+        assert_eq!(
+            eval(nested(), vec![json!(2)]).unwrap(),
+            array(json!([1, 2, 3, [4, [5]], "x", { "a": [6] }]))
+        );
+        assert_eq!(
+            eval(nested(), vec![json!(-1)]).unwrap(),
+            array(json!([1, 2, 3, 4, 5, "x", { "a": [6] }]))
+        );
+        assert_eq!(
+            eval(nested(), vec![json!(100)]).unwrap(),
+            array(json!([1, 2, 3, 4, 5, "x", { "a": [6] }]))
+        );
+        assert_eq!(eval(nested(), vec![json!(0)]).unwrap(), nested());
+        assert_eq!(
+            eval(EvalValue::Value(json!([[], [null], []])), vec![]).unwrap(),
+            array(json!([null]))
+        );
+        assert_eq!(
+            eval(EvalValue::Missing, vec![]).unwrap(),
+            EvalValue::Missing
+        );
+
+        let err = eval(EvalValue::Value(json!("abc")), vec![]).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test"));
+        let err = eval(nested(), vec![json!(-2)]).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
+    }
+
+    #[test]
+    fn test_eval_op_take_drop() {
+        let ctx = V2EvalContext::new();
+        let eval = |op: &str, value: EvalValue, count: JsonValue| {
+            let op = V2OpStep {
+                op: op.to_string(),
+                args: vec![lit(count)],
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
+        let items = || EvalValue::Value(json!([1, 2, 3, 4]));
+        let array = |value: JsonValue| EvalValue::Value(value);
+
+        assert_eq!(
+            eval("take", items(), json!(2)).unwrap(),
+            array(json!([1, 2]))
+        );
+        assert_eq!(
+            eval("drop", items(), json!(2)).unwrap(),
+            array(json!([3, 4]))
+        );
+        assert_eq!(eval("take", items(), json!(0)).unwrap(), array(json!([])));
+        assert_eq!(
+            eval("drop", items(), json!(0)).unwrap(),
+            array(json!([1, 2, 3, 4]))
+        );
+        assert_eq!(
+            eval("take", items(), json!(10)).unwrap(),
+            array(json!([1, 2, 3, 4]))
+        );
+        assert_eq!(eval("drop", items(), json!(10)).unwrap(), array(json!([])));
+        assert_eq!(
+            eval("take", items(), json!("3")).unwrap(),
+            array(json!([1, 2, 3]))
+        );
+        assert_eq!(eval("take", items(), json!(-1)).unwrap(), array(json!([4])));
+        assert_eq!(
+            eval("drop", items(), json!(-1)).unwrap(),
+            array(json!([1, 2, 3]))
+        );
+        assert_eq!(
+            eval("take", EvalValue::Missing, json!(1)).unwrap(),
+            EvalValue::Missing
+        );
+
+        let err = eval("take", items(), json!(1.5)).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
+        let err = eval("drop", items(), json!("two")).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
+        let err = eval("drop", EvalValue::Value(json!({ "a": 1 })), json!(1)).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test"));
+    }
+
+    #[test]
+    fn test_eval_op_slice() {
+        let ctx = V2EvalContext::new();
+        let eval = |value: EvalValue, args: Vec<JsonValue>| {
+            let op = V2OpStep {
+                op: "slice".to_string(),
+                args: args.into_iter().map(lit).collect(),
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
+        let items = || EvalValue::Value(json!(["a", "b", "c", "d", "e"]));
+        let array = |value: JsonValue| EvalValue::Value(value);
+
+        assert_eq!(
+            eval(items(), vec![json!(1), json!(3)]).unwrap(),
+            array(json!(["b", "c"]))
+        );
+        assert_eq!(
+            eval(items(), vec![json!(2)]).unwrap(),
+            array(json!(["c", "d", "e"]))
+        );
+        assert_eq!(
+            eval(items(), vec![json!(-2)]).unwrap(),
+            array(json!(["d", "e"]))
+        );
+        assert_eq!(
+            eval(items(), vec![json!(1), json!(-1)]).unwrap(),
+            array(json!(["b", "c", "d"]))
+        );
+        assert_eq!(
+            eval(items(), vec![json!(-10), json!(10)]).unwrap(),
+            array(json!(["a", "b", "c", "d", "e"]))
+        );
+        assert_eq!(
+            eval(items(), vec![json!(3), json!(1)]).unwrap(),
+            array(json!([]))
+        );
+        assert_eq!(
+            eval(items(), vec![json!(2), json!(2)]).unwrap(),
+            array(json!([]))
+        );
+        assert_eq!(eval(items(), vec![json!(9)]).unwrap(), array(json!([])));
+        assert_eq!(
+            eval(EvalValue::Missing, vec![json!(0)]).unwrap(),
+            EvalValue::Missing
+        );
+
+        let err = eval(EvalValue::Value(json!("abc")), vec![json!(0)]).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test"));
+        let err = eval(items(), vec![json!(0), json!("x")]).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test.args[1]"));
+    }
+
+    #[test]
+    fn test_eval_op_first_last() {
+        let first = V2OpStep {
+            op: "first".to_string(),
+            args: vec![],
+        };
+        let last = V2OpStep {
+            op: "last".to_string(),
+            args: vec![],
+        };
+        let ctx = V2EvalContext::new();
+
+        let first_result = eval_v2_op_step(
+            &first,
+            EvalValue::Value(json!([1, 2])),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(matches!(first_result, Ok(EvalValue::Value(v)) if v == json!(1)));
+
+        let last_result = eval_v2_op_step(
+            &last,
+            EvalValue::Value(json!([1, 2])),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(matches!(last_result, Ok(EvalValue::Value(v)) if v == json!(2)));
+    }
+
+    #[test]
+    fn test_eval_op_only() {
+        let op = V2OpStep {
+            op: "only".to_string(),
+            args: vec![],
+        };
+        let ctx = V2EvalContext::new();
+        let eval = |value: EvalValue| {
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
+
+        assert_eq!(
+            eval(EvalValue::Value(json!([]))).unwrap(),
+            EvalValue::Missing
+        );
+        assert_eq!(
+            eval(EvalValue::Value(json!([{"id": 1}]))).unwrap(),
+            EvalValue::Value(json!({"id": 1}))
+        );
+        assert_eq!(eval(EvalValue::Missing).unwrap(), EvalValue::Missing);
+
+        let err = eval(EvalValue::Value(json!([1, 2]))).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test"));
+        assert!(err.message.contains("got 2"), "{}", err.message);
+        let err = eval(EvalValue::Value(json!("abc"))).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+    }
+
+    #[test]
+    fn test_eval_op_type_casts() {
+        let op_int = V2OpStep {
+            op: "int".to_string(),
+            args: vec![],
+        };
+        let op_float = V2OpStep {
+            op: "float".to_string(),
+            args: vec![],
+        };
+        let op_bool = V2OpStep {
+            op: "bool".to_string(),
+            args: vec![],
+        };
+        let op_string = V2OpStep {
+            op: "string".to_string(),
+            args: vec![],
+        };
+        let ctx = V2EvalContext::new();
+
+        let int_result = eval_v2_op_step(
+            &op_int,
+            EvalValue::Value(json!("42")),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(matches!(int_result, Ok(EvalValue::Value(v)) if v == json!(42)));
+
+        let float_result = eval_v2_op_step(
+            &op_float,
+            EvalValue::Value(json!("3.14")),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        if let Ok(EvalValue::Value(v)) = float_result {
+            let value = v.as_f64().unwrap();
+            assert!((value - 3.14).abs() < 1e-9);
+        } else {
+            panic!("expected float cast");
+        }
+
+        let bool_result = eval_v2_op_step(
+            &op_bool,
+            EvalValue::Value(json!("true")),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(matches!(bool_result, Ok(EvalValue::Value(v)) if v == json!(true)));
+
+        let string_result = eval_v2_op_step(
+            &op_string,
+            EvalValue::Value(json!(12)),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(matches!(string_result, Ok(EvalValue::Value(v)) if v == json!("12")));
+    }
+
+    #[test]
+    fn test_eval_op_and_or_short_circuit() {
+        let or_op = V2OpStep {
+            op: "or".to_string(),
+            args: vec![V2Expr::Pipe(V2Pipe {
+                start: V2Start::Literal(json!(1)),
+                steps: vec![V2Step::Op(V2OpStep {
+                    op: "divide".to_string(),
+                    args: vec![V2Expr::Pipe(V2Pipe {
+                        start: V2Start::Literal(json!(0)),
+                        steps: vec![],
+                    })],
+                })],
+            })],
+        };
+        let and_op = V2OpStep {
+            op: "and".to_string(),
+            args: vec![V2Expr::Pipe(V2Pipe {
+                start: V2Start::Literal(json!(1)),
+                steps: vec![V2Step::Op(V2OpStep {
+                    op: "divide".to_string(),
+                    args: vec![V2Expr::Pipe(V2Pipe {
+                        start: V2Start::Literal(json!(0)),
+                        steps: vec![],
+                    })],
+                })],
+            })],
+        };
+        let ctx = V2EvalContext::new();
+
+        let or_result = eval_v2_op_step(
+            &or_op,
+            EvalValue::Value(json!(true)),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(matches!(or_result, Ok(EvalValue::Value(v)) if v == json!(true)));
+
+        let and_result = eval_v2_op_step(
+            &and_op,
+            EvalValue::Value(json!(false)),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(matches!(and_result, Ok(EvalValue::Value(v)) if v == json!(false)));
+    }
+
+    #[test]
+    fn test_eval_op_add() {
+        let op = V2OpStep {
+            op: "add".to_string(),
+            args: vec![V2Expr::Pipe(V2Pipe {
+                start: V2Start::Literal(json!(10)),
+                steps: vec![],
+            })],
+        };
+        let ctx = V2EvalContext::new();
+        let result = eval_v2_op_step(
+            &op,
+            EvalValue::Value(json!(5)),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!(15.0)));
+    }
+
+    #[test]
+    fn test_eval_op_subtract() {
+        let op = V2OpStep {
+            op: "subtract".to_string(),
+            args: vec![V2Expr::Pipe(V2Pipe {
+                start: V2Start::Literal(json!(3)),
+                steps: vec![],
+            })],
+        };
+        let ctx = V2EvalContext::new();
+        let result = eval_v2_op_step(
+            &op,
+            EvalValue::Value(json!(10)),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!(7.0)));
+    }
+
+    #[test]
+    fn test_eval_op_comparison_aliases() {
+        let ctx = V2EvalContext::new();
+        let cases = [
+            ("eq", json!(1), json!("1"), true),
+            ("ne", json!(1), json!(2), true),
+            ("lt", json!(5), json!(10), true),
+            ("lte", json!(10), json!(10), true),
+            ("gt", json!(10), json!(5), true),
+            ("gte", json!(10), json!(10), true),
+            ("match", json!("apple"), json!("^a.*"), true),
+        ];
+
+        for (op, left, right, expected) in cases {
+            let op_step = V2OpStep {
+                op: op.to_string(),
+                args: vec![lit(right)],
+            };
+            let result = eval_v2_op_step(
+                &op_step,
+                EvalValue::Value(left),
+                &json!({}),
+                None,
+                &json!({}),
+                "test",
+                &ctx,
+            );
+            assert!(
+                matches!(result, Ok(EvalValue::Value(v)) if v == json!(expected)),
+                "op {}",
+                op
+            );
+        }
+    }
+
+    #[test]
+    fn test_eval_op_pick_multiple_paths() {
+        let op = V2OpStep {
+            op: "pick".to_string(),
+            args: vec![lit(json!("name")), lit(json!("price"))],
+        };
+        let ctx = V2EvalContext::new();
+        let result = eval_v2_op_step(
+            &op,
+            EvalValue::Value(json!({"name": "apple", "price": 100, "category": "fruit"})),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(matches!(
+            result,
+            Ok(EvalValue::Value(v)) if v == json!({"name": "apple", "price": 100})
+        ));
+    }
+
+    #[test]
+    fn test_eval_op_omit_multiple_paths() {
+        let op = V2OpStep {
+            op: "omit".to_string(),
+            args: vec![lit(json!("category")), lit(json!("price"))],
+        };
+        let ctx = V2EvalContext::new();
+        let result = eval_v2_op_step(
+            &op,
+            EvalValue::Value(json!({"name": "apple", "price": 100, "category": "fruit"})),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(matches!(
+            result,
+            Ok(EvalValue::Value(v)) if v == json!({"name": "apple"})
+        ));
+    }
+
+    #[test]
+    fn test_eval_op_pick_paths_array_arg() {
+        let op = V2OpStep {
+            op: "pick".to_string(),
+            args: vec![lit(json!(["name", "price"]))],
+        };
+        let ctx = V2EvalContext::new();
+        let result = eval_v2_op_step(
+            &op,
+            EvalValue::Value(json!({"name": "apple", "price": 100, "category": "fruit"})),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(matches!(
+            result,
+            Ok(EvalValue::Value(v)) if v == json!({"name": "apple", "price": 100})
+        ));
+    }
+
+    #[test]
+    fn test_eval_op_multiply() {
+        let op = V2OpStep {
+            op: "multiply".to_string(),
+            args: vec![V2Expr::Pipe(V2Pipe {
+                start: V2Start::Literal(json!(0.9)),
+                steps: vec![],
+            })],
+        };
+        let ctx = V2EvalContext::new();
+        let result = eval_v2_op_step(
+            &op,
+            EvalValue::Value(json!(100)),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!(90.0)));
+    }
+
+    #[test]
+    fn test_eval_op_divide() {
+        let op = V2OpStep {
+            op: "divide".to_string(),
+            args: vec![V2Expr::Pipe(V2Pipe {
+                start: V2Start::Literal(json!(2)),
+                steps: vec![],
+            })],
+        };
+        let ctx = V2EvalContext::new();
+        let result = eval_v2_op_step(
+            &op,
+            EvalValue::Value(json!(10)),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!(5.0)));
+    }
+
+    #[test]
+    fn test_eval_op_divide_by_zero() {
+        let op = V2OpStep {
+            op: "divide".to_string(),
+            args: vec![V2Expr::Pipe(V2Pipe {
+                start: V2Start::Literal(json!(0)),
+                steps: vec![],
+            })],
+        };
+        let ctx = V2EvalContext::new();
+        let result = eval_v2_op_step(
+            &op,
+            EvalValue::Value(json!(10)),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(result.is_err());
+    }
+
+    fn eval_extreme(op: &str, pipe_value: EvalValue, args: Vec<V2Expr>) -> EvalValue {
+        let op = V2OpStep {
+            op: op.to_string(),
+            args,
+        };
+        let ctx = V2EvalContext::new();
+        eval_v2_op_step(&op, pipe_value, &json!({}), None, &json!({}), "test", &ctx).unwrap()
+    }
+
+    #[test]
+    fn test_eval_op_max_of_and_min_of() {
+        let args = || vec![lit(json!(7)), lit(json!("2.5")), lit(json!(0))];
+        let max = eval_extreme("max_of", EvalValue::Value(json!(3)), args());
+        assert_eq!(max, EvalValue::Value(json!(7)));
+        let min = eval_extreme("min_of", EvalValue::Value(json!(3)), args());
+        assert_eq!(min, EvalValue::Value(json!(0)));
+    }
+
+    #[test]
+    fn test_eval_op_max_of_skips_missing_and_null() {
+        let max = eval_extreme(
+            "max_of",
+            EvalValue::Missing,
+            vec![lit(json!(null)), lit(json!(-4))],
+        );
+        assert_eq!(max, EvalValue::Value(json!(-4)));
+    }
+
+    #[test]
+    fn test_eval_op_max_of_requires_numeric_operand() {
+        let op = V2OpStep {
+            op: "max_of".to_string(),
+            args: vec![lit(json!(null))],
+        };
+        let ctx = V2EvalContext::new();
+        let result = eval_v2_op_step(
+            &op,
+            EvalValue::Missing,
             &json!({}),
             None,
             &json!({}),
             "test",
             &ctx,
         );
-        if let Ok(EvalValue::Value(v)) = float_result {
-            let value = v.as_f64().unwrap();
-            assert!((value - 3.14).abs() < 1e-9);
-        } else {
-            panic!("expected float cast");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_eval_op_replace_regex() {
+        let ctx = V2EvalContext::new();
+        let eval = |value: EvalValue, pattern: &str, replacement: &str| {
+            let op = V2OpStep {
+                op: "replace_regex".to_string(),
+                args: vec![lit(json!(pattern)), lit(json!(replacement))],
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
+
+        let result = eval(
+            EvalValue::Value(json!("2024-01-15, 2025-12-31")),
+            r"(\d{4})-(\d{2})-(\d{2})",
+            "$3/$2/$1",
+        )
+        .unwrap();
+        assert_eq!(result, EvalValue::Value(json!("15/01/2024, 31/12/2025")));
+        let result = eval(
+            EvalValue::Value(json!("John Smith")),
+            r"(?P<first>\w+) (?P<last>\w+)",
+            "${last}, ${first}",
+        )
+        .unwrap();
+        assert_eq!(result, EvalValue::Value(json!("Smith, John")));
+        let result = eval(EvalValue::Value(json!("no digits")), r"\d+", "#").unwrap();
+        assert_eq!(result, EvalValue::Value(json!("no digits")));
+        assert_eq!(
+            eval(EvalValue::Missing, r"\d+", "#").unwrap(),
+            EvalValue::Missing
+        );
+
+        let err = eval(EvalValue::Value(json!("abc")), "(unclosed", "x").unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
+        let err = eval(EvalValue::Value(json!(12)), r"\d", "x").unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test"));
+    }
+
+    #[test]
+    fn test_eval_op_parse_format_date() {
+        let ctx = V2EvalContext::new();
+        let eval = |op: &str, value: EvalValue, format: &str| {
+            let op = V2OpStep {
+                op: op.to_string(),
+                args: vec![lit(json!(format))],
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
+        let text = |value: &str| EvalValue::Value(json!(value));
+
+        assert_eq!(
+            eval("parse_date", text("15/01/2024 09:30"), "%d/%m/%Y %H:%M").unwrap(),
+            text("2024-01-15T09:30:00Z")
+        );
+        assert_eq!(
+            eval("parse_date", text("2024-01-15"), "%Y-%m-%d").unwrap(),
+            text("2024-01-15T00:00:00Z")
+        );
+        assert_eq!(
+            eval(
+                "parse_date",
+                text("2024-01-15 09:30 +0900"),
+                "%Y-%m-%d %H:%M %z"
+            )
+            .unwrap(),
+            text("2024-01-15T09:30:00+09:00")
+        );
+        assert_eq!(
+            eval(
+                "format_date",
+                text("2024-01-15T09:30:00+09:00"),
+                "%Y/%m/%d %H:%M"
+            )
+            .unwrap(),
+            text("2024/01/15 09:30")
+        );
+
+        let parsed = eval(
+            "parse_date",
+            text("03.02.2025 17:45:10"),
+            "%d.%m.%Y %H:%M:%S",
+        )
+        .unwrap();
+        assert_eq!(
+            eval("format_date", parsed, "%d.%m.%Y %H:%M:%S").unwrap(),
+            text("03.02.2025 17:45:10")
+        );
+        assert_eq!(
+            eval("parse_date", EvalValue::Missing, "%Y").unwrap(),
+            EvalValue::Missing
+        );
+
+        let err = eval("parse_date", text("yesterday"), "%Y-%m-%d").unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test"));
+        let err = eval("format_date", text("2024-01-15"), "%Y").unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test"));
+        let err = eval("format_date", text("2024-01-15T00:00:00Z"), "%Q").unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
+    }
+
+    #[test]
+    fn test_eval_op_now() {
+        let ctx = V2EvalContext::new();
+        let op = V2OpStep {
+            op: "now".to_string(),
+            args: vec![],
+        };
+        let before = chrono::Utc::now();
+        let result = eval_v2_op_step(
+            &op,
+            EvalValue::Missing,
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        )
+        .unwrap();
+        let after = chrono::Utc::now();
+
+        let EvalValue::Value(JsonValue::String(stamp)) = result else {
+            panic!("now should produce a string");
+        };
+        let stamp = chrono::DateTime::parse_from_rfc3339(&stamp).unwrap();
+        assert!(stamp >= before && stamp <= after);
+    }
+
+    #[test]
+    fn test_eval_op_base64_hex() {
+        let ctx = V2EvalContext::new();
+        let eval = |op: &str, value: JsonValue| {
+            let op = V2OpStep {
+                op: op.to_string(),
+                args: vec![],
+            };
+            eval_v2_op_step(
+                &op,
+                EvalValue::Value(value),
+                &json!({}),
+                None,
+                &json!({}),
+                "test",
+                &ctx,
+            )
+        };
+
+        assert_eq!(
+            eval("base64_encode", json!("héllo?")).unwrap(),
+            EvalValue::Value(json!("aMOpbGxvPw=="))
+        );
+        assert_eq!(
+            eval("base64_decode", json!("aMOpbGxvPw==")).unwrap(),
+            EvalValue::Value(json!("héllo?"))
+        );
+        assert_eq!(
+            eval("hex_encode", json!("Hé")).unwrap(),
+            EvalValue::Value(json!("48c3a9"))
+        );
+        assert_eq!(
+            eval("hex_decode", json!("48C3A9")).unwrap(),
+            EvalValue::Value(json!("Hé"))
+        );
+        assert_eq!(
+            eval("base64_encode", json!("")).unwrap(),
+            EvalValue::Value(json!(""))
+        );
+        let missing = V2OpStep {
+            op: "hex_decode".to_string(),
+            args: vec![],
+        };
+        assert_eq!(
+            eval_v2_op_step(
+                &missing,
+                EvalValue::Missing,
+                &json!({}),
+                None,
+                &json!({}),
+                "test",
+                &ctx
+            )
+            .unwrap(),
+            EvalValue::Missing
+        );
+
+        for (op, input) in [
+            ("base64_decode", json!("not base64!")),
+            ("base64_decode", json!("/w==")),
+            ("hex_decode", json!("abc")),
+            ("hex_decode", json!("zz")),
+            ("hex_decode", json!("ff")),
+            ("hex_encode", json!(12)),
+        ] {
+            let err = eval(op, input).unwrap_err();
+            assert_eq!(err.kind, TransformErrorKind::ExprError);
+            assert_eq!(err.path.as_deref(), Some("test"));
         }
+    }
+
+    #[test]
+    fn test_eval_op_to_json_from_json() {
+        let ctx = V2EvalContext::new();
+        let eval = |op: &str, value: EvalValue| {
+            let op = V2OpStep {
+                op: op.to_string(),
+                args: vec![],
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
+
+        let value = json!({ "id": 1, "tags": ["a", "b"], "meta": { "ok": true, "note": null } });
+        let EvalValue::Value(JsonValue::String(text)) =
+            eval("to_json", EvalValue::Value(value.clone())).unwrap()
+        else {
+            panic!("to_json should produce a string");
+        };
+        assert!(!text.contains(' '));
+        assert_eq!(
+            eval("from_json", EvalValue::Value(json!(text))).unwrap(),
+            EvalValue::Value(value)
+        );
+        assert_eq!(
+            eval("to_json", EvalValue::Value(json!("hi"))).unwrap(),
+            EvalValue::Value(json!("\"hi\""))
+        );
+        assert_eq!(
+            eval("to_json", EvalValue::Value(json!(null))).unwrap(),
+            EvalValue::Value(json!("null"))
+        );
+        assert_eq!(
+            eval("from_json", EvalValue::Value(json!("[1, 2.5]"))).unwrap(),
+            EvalValue::Value(json!([1, 2.5]))
+        );
+        assert_eq!(
+            eval("to_json", EvalValue::Missing).unwrap(),
+            EvalValue::Missing
+        );
+        assert_eq!(
+            eval("from_json", EvalValue::Missing).unwrap(),
+            EvalValue::Missing
+        );
+
+        let err = eval("from_json", EvalValue::Value(json!("{\"a\":"))).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test"));
+        let err = eval("from_json", EvalValue::Value(json!({ "a": 1 }))).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+    }
+
+    #[test]
+    fn test_eval_op_default() {
+        let ctx = V2EvalContext::new();
+        let op = V2OpStep {
+            op: "default".to_string(),
+            args: vec![lit(json!("n/a"))],
+        };
+        let eval = |value: EvalValue| {
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx).unwrap()
+        };
+
+        assert_eq!(eval(EvalValue::Missing), EvalValue::Value(json!("n/a")));
+        assert_eq!(
+            eval(EvalValue::Value(json!(null))),
+            EvalValue::Value(json!("n/a"))
+        );
+        assert_eq!(
+            eval(EvalValue::Value(json!("set"))),
+            EvalValue::Value(json!("set"))
+        );
+        assert_eq!(
+            eval(EvalValue::Value(json!(false))),
+            EvalValue::Value(json!(false))
+        );
+        assert_eq!(eval(EvalValue::Value(json!(0))), EvalValue::Value(json!(0)));
+        assert_eq!(
+            eval(EvalValue::Value(json!(""))),
+            EvalValue::Value(json!(""))
+        );
+    }
+
+    #[test]
+    fn test_eval_op_string_predicates() {
+        let ctx = V2EvalContext::new();
+        let eval = |op: &str, value: EvalValue, arg: JsonValue| {
+            let op = V2OpStep {
+                op: op.to_string(),
+                args: vec![lit(arg)],
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
+        let text = || EvalValue::Value(json!("order-2024-JP"));
+        let yes = EvalValue::Value(json!(true));
+        let no = EvalValue::Value(json!(false));
+
+        assert_eq!(eval("contains", text(), json!("2024")).unwrap(), yes);
+        assert_eq!(eval("contains", text(), json!("jp")).unwrap(), no);
+        assert_eq!(eval("starts_with", text(), json!("order-")).unwrap(), yes);
+        assert_eq!(eval("starts_with", text(), json!("JP")).unwrap(), no);
+        assert_eq!(eval("ends_with", text(), json!("-JP")).unwrap(), yes);
+        assert_eq!(eval("ends_with", text(), json!("")).unwrap(), yes);
+        assert_eq!(
+            eval("starts_with", EvalValue::Missing, json!("x")).unwrap(),
+            EvalValue::Missing
+        );
+
+        let err = eval("ends_with", EvalValue::Value(json!(["a"])), json!("a")).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test"));
+        let err = eval("starts_with", text(), json!(1)).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
+    }
+
+    #[test]
+    fn test_eval_op_contains_array() {
+        let ctx = V2EvalContext::new();
+        let eval = |value: JsonValue, arg: JsonValue| {
+            let op = V2OpStep {
+                op: "contains".to_string(),
+                args: vec![lit(arg)],
+            };
+            eval_v2_op_step(
+                &op,
+                EvalValue::Value(value),
+                &json!({}),
+                None,
+                &json!({}),
+                "test",
+                &ctx,
+            )
+            .unwrap()
+        };
+        let yes = EvalValue::Value(json!(true));
+        let no = EvalValue::Value(json!(false));
+
+        assert_eq!(eval(json!(["a", "b"]), json!("b")), yes);
+        assert_eq!(eval(json!(["a", "b"]), json!("c")), no);
+        assert_eq!(eval(json!([1, 2, 3]), json!(2)), yes);
+        assert_eq!(eval(json!([1, 2, 3]), json!("2")), no);
+        assert_eq!(eval(json!([{ "id": 1 }, null]), json!({ "id": 1 })), yes);
+        assert_eq!(eval(json!([null]), json!(null)), yes);
+        assert_eq!(eval(json!([]), json!("a")), no);
+    }
+
+    #[test]
+    fn test_eval_op_join() {
+        let ctx = V2EvalContext::new();
+        let eval = |value: EvalValue, separator: JsonValue| {
+            let op = V2OpStep {
+                op: "join".to_string(),
+                args: vec![lit(separator)],
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
+
+        let result = eval(EvalValue::Value(json!(["a", 1, 2.5, true])), json!(",")).unwrap();
+        assert_eq!(result, EvalValue::Value(json!("a,1,2.5,true")));
+        let result = eval(EvalValue::Value(json!(["x", "y"])), json!("")).unwrap();
+        assert_eq!(result, EvalValue::Value(json!("xy")));
+        let result = eval(EvalValue::Value(json!([])), json!(", ")).unwrap();
+        assert_eq!(result, EvalValue::Value(json!("")));
+        assert_eq!(
+            eval(EvalValue::Missing, json!(",")).unwrap(),
+            EvalValue::Missing
+        );
+
+        let err = eval(EvalValue::Value(json!(["a", { "b": 1 }])), json!(",")).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test[1]"));
+        let err = eval(EvalValue::Value(json!([["a"]])), json!(",")).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test[0]"));
+        let err = eval(EvalValue::Value(json!("a,b")), json!(",")).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test"));
+        let err = eval(EvalValue::Value(json!(["a"])), json!(1)).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
+    }
+
+    #[test]
+    fn test_eval_op_substring() {
+        let ctx = V2EvalContext::new();
+        let eval = |value: EvalValue, args: Vec<JsonValue>| {
+            let op = V2OpStep {
+                op: "substring".to_string(),
+                args: args.into_iter().map(lit).collect(),
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
+        let text =
+            |value: &str, args: Vec<JsonValue>| eval(EvalValue::Value(json!(value)), args).unwrap();
+
+        assert_eq!(
+            text("ABC20240115XYZ", vec![json!(3), json!(8)]),
+            EvalValue::Value(json!("20240115"))
+        );
+        assert_eq!(
+            text("hello", vec![json!(1)]),
+            EvalValue::Value(json!("ello"))
+        );
+        assert_eq!(
+            text("hello", vec![json!(-3)]),
+            EvalValue::Value(json!("llo"))
+        );
+        assert_eq!(
+            text("hello", vec![json!(-3), json!(2)]),
+            EvalValue::Value(json!("ll"))
+        );
+
+        // Multibyte characters are counted as single positions.
+        assert_eq!(
+            text("日本語テキスト", vec![json!(2), json!(3)]),
+            EvalValue::Value(json!("語テキ"))
+        );
+        assert_eq!(
+            text("añb😀c", vec![json!(1), json!(3)]),
+            EvalValue::Value(json!("ñb😀"))
+        );
+
+        // Out-of-range bounds are clamped.
+        assert_eq!(text("hello", vec![json!(10)]), EvalValue::Value(json!("")));
+        assert_eq!(
+            text("hello", vec![json!(-10), json!(2)]),
+            EvalValue::Value(json!("he"))
+        );
+        assert_eq!(
+            text("hello", vec![json!(2), json!(100)]),
+            EvalValue::Value(json!("llo"))
+        );
+        assert_eq!(
+            text("hello", vec![json!(1), json!(-1)]),
+            EvalValue::Value(json!(""))
+        );
+
+        assert_eq!(
+            eval(EvalValue::Missing, vec![json!(0)]).unwrap(),
+            EvalValue::Missing
+        );
+        assert!(eval(EvalValue::Value(json!(12345)), vec![json!(0)]).is_err());
+        let err = eval(EvalValue::Value(json!("hello")), vec![json!("x")]).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
+        let err = eval(EvalValue::Value(json!("hello")), vec![json!(0), json!(1.5)]).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test.args[1]"));
+    }
+
+    #[test]
+    fn test_eval_op_sanitize_filename() {
+        let op = V2OpStep {
+            op: "sanitize_filename".to_string(),
+            args: vec![],
+        };
+        let ctx = V2EvalContext::new();
+        let eval = |value: JsonValue| {
+            eval_v2_op_step(
+                &op,
+                EvalValue::Value(value),
+                &json!({}),
+                None,
+                &json!({}),
+                "test",
+                &ctx,
+            )
+        };
+        let sanitized = |text: &str| match eval(json!(text)).unwrap() {
+            EvalValue::Value(JsonValue::String(name)) => name,
+            other => panic!("unexpected {:?}", other),
+        };
+
+        assert_eq!(sanitized("report 2024.pdf"), "report 2024.pdf");
+        assert_eq!(sanitized("../../etc/passwd"), "_.._etc_passwd");
+        assert_eq!(sanitized("..\\..\\boot.ini"), "_.._boot.ini");
+        assert_eq!(sanitized("/etc/shadow"), "_etc_shadow");
+        assert_eq!(sanitized(".."), "_");
+        assert_eq!(sanitized(""), "_");
+        assert_eq!(sanitized("a\u{0}b\nc:d*e?.txt"), "a_b_c_d_e_.txt");
+        assert_eq!(sanitized(" invoice.pdf. "), "invoice.pdf");
+        assert_eq!(sanitized(".htaccess"), "htaccess");
+
+        assert_eq!(sanitized("CON"), "_CON");
+        assert_eq!(sanitized("nul.txt"), "_nul.txt");
+        assert_eq!(sanitized("Com1.tar.gz"), "_Com1.tar.gz");
+        assert_eq!(sanitized("lpt9 .log"), "_lpt9 .log");
+        assert_eq!(sanitized("console.txt"), "console.txt");
+
+        let long = format!("{}.csv", "é".repeat(200));
+        let name = sanitized(&long);
+        assert!(name.len() <= 255);
+        assert!(name.ends_with("é.csv"));
+
+        assert!(matches!(
+            eval_v2_op_step(
+                &op,
+                EvalValue::Missing,
+                &json!({}),
+                None,
+                &json!({}),
+                "test",
+                &ctx
+            ),
+            Ok(EvalValue::Missing)
+        ));
+        assert!(eval(json!(1)).is_err());
+    }
+
+    #[test]
+    fn test_eval_op_parse_query_string() {
+        let op = V2OpStep {
+            op: "parse_query_string".to_string(),
+            args: vec![],
+        };
+        let ctx = V2EvalContext::new();
+        let eval = |value: EvalValue| {
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
 
-        let bool_result = eval_v2_op_step(
-            &op_bool,
-            EvalValue::Value(json!("true")),
-            &json!({}),
-            None,
-            &json!({}),
-            "test",
-            &ctx,
+        let result = eval(EvalValue::Value(json!("?a=1&b=2&b=3&b=4&flag&&"))).unwrap();
+        assert_eq!(
+            result,
+            EvalValue::Value(json!({ "a": "1", "b": ["2", "3", "4"], "flag": "" }))
         );
-        assert!(matches!(bool_result, Ok(EvalValue::Value(v)) if v == json!(true)));
 
-        let string_result = eval_v2_op_step(
-            &op_string,
-            EvalValue::Value(json!(12)),
-            &json!({}),
-            None,
-            &json!({}),
-            "test",
-            &ctx,
+        let result = eval(EvalValue::Value(json!(
+            "name=John+Doe&city=S%C3%A3o%20Paulo&q%5Bx%5D=a%26b%3Dc"
+        )))
+        .unwrap();
+        assert_eq!(
+            result,
+            EvalValue::Value(json!({
+                "name": "John Doe",
+                "city": "São Paulo",
+                "q[x]": "a&b=c"
+            }))
         );
-        assert!(matches!(string_result, Ok(EvalValue::Value(v)) if v == json!("12")));
+
+        assert!(matches!(eval(EvalValue::Missing), Ok(EvalValue::Missing)));
+        assert!(eval(EvalValue::Value(json!("a=%zz"))).is_err());
+        assert!(eval(EvalValue::Value(json!("a=%E"))).is_err());
+        assert!(eval(EvalValue::Value(json!("a=%FF"))).is_err());
+        assert!(eval(EvalValue::Value(json!(12))).is_err());
     }
 
     #[test]
-    fn test_eval_op_and_or_short_circuit() {
-        let or_op = V2OpStep {
-            op: "or".to_string(),
-            args: vec![V2Expr::Pipe(V2Pipe {
-                start: V2Start::Literal(json!(1)),
-                steps: vec![V2Step::Op(V2OpStep {
-                    op: "divide".to_string(),
-                    args: vec![V2Expr::Pipe(V2Pipe {
-                        start: V2Start::Literal(json!(0)),
-                        steps: vec![],
-                    })],
-                })],
-            })],
+    fn test_eval_op_assert_type() {
+        let ctx = V2EvalContext::new();
+        let eval = |spec: &str, value: EvalValue| {
+            let op = V2OpStep {
+                op: "assert_type".to_string(),
+                args: vec![lit(json!(spec))],
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
         };
-        let and_op = V2OpStep {
-            op: "and".to_string(),
-            args: vec![V2Expr::Pipe(V2Pipe {
-                start: V2Start::Literal(json!(1)),
-                steps: vec![V2Step::Op(V2OpStep {
-                    op: "divide".to_string(),
-                    args: vec![V2Expr::Pipe(V2Pipe {
-                        start: V2Start::Literal(json!(0)),
-                        steps: vec![],
-                    })],
-                })],
-            })],
+
+        let items = json!([{ "id": 1, "name": "a" }, { "id": 2, "name": null }]);
+        let result = eval(
+            "array<object{id: integer, name: string?}>",
+            EvalValue::Value(items.clone()),
+        )
+        .unwrap();
+        assert_eq!(result, EvalValue::Value(items));
+
+        let err = eval(
+            "array<object{id}>",
+            EvalValue::Value(json!([{ "id": 1 }, { "key": 2 }])),
+        )
+        .unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::TypeAssertionFailed);
+        assert_eq!(
+            err.message,
+            "assert_type array<object{id}> failed at $[1].id: required field is missing"
+        );
+
+        let err = eval("object", EvalValue::Value(json!("text"))).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::TypeAssertionFailed);
+        assert!(err.message.ends_with("$: expected object, got string"));
+
+        assert!(matches!(
+            eval("string?", EvalValue::Missing),
+            Ok(EvalValue::Missing)
+        ));
+        let err = eval("string", EvalValue::Missing).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::TypeAssertionFailed);
+
+        let err = eval("strng", EvalValue::Value(json!("x"))).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
+    }
+
+    #[test]
+    fn test_eval_op_paragraphs() {
+        let op = V2OpStep {
+            op: "paragraphs".to_string(),
+            args: vec![],
         };
         let ctx = V2EvalContext::new();
-
-        let or_result = eval_v2_op_step(
-            &or_op,
-            EvalValue::Value(json!(true)),
+        let text = "  First line\r\nstill first.\n\n\n \t\nSecond.\n\n";
+        let result = eval_v2_op_step(
+            &op,
+            EvalValue::Value(json!(text)),
             &json!({}),
             None,
             &json!({}),
             "test",
             &ctx,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            EvalValue::Value(json!(["First line\nstill first.", "Second."]))
         );
-        assert!(matches!(or_result, Ok(EvalValue::Value(v)) if v == json!(true)));
 
-        let and_result = eval_v2_op_step(
-            &and_op,
-            EvalValue::Value(json!(false)),
+        let missing = eval_v2_op_step(
+            &op,
+            EvalValue::Missing,
             &json!({}),
             None,
             &json!({}),
             "test",
             &ctx,
         );
-        assert!(matches!(and_result, Ok(EvalValue::Value(v)) if v == json!(false)));
-    }
+        assert!(matches!(missing, Ok(EvalValue::Missing)));
 
-    #[test]
-    fn test_eval_op_add() {
-        let op = V2OpStep {
-            op: "add".to_string(),
-            args: vec![V2Expr::Pipe(V2Pipe {
-                start: V2Start::Literal(json!(10)),
-                steps: vec![],
-            })],
-        };
-        let ctx = V2EvalContext::new();
-        let result = eval_v2_op_step(
+        let non_string = eval_v2_op_step(
             &op,
-            EvalValue::Value(json!(5)),
+            EvalValue::Value(json!(42)),
             &json!({}),
             None,
             &json!({}),
             "test",
             &ctx,
         );
-        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!(15.0)));
+        assert!(non_string.is_err());
     }
 
     #[test]
-    fn test_eval_op_subtract() {
+    fn test_eval_op_fallback_order() {
         let op = V2OpStep {
-            op: "subtract".to_string(),
-            args: vec![V2Expr::Pipe(V2Pipe {
-                start: V2Start::Literal(json!(3)),
-                steps: vec![],
-            })],
+            op: "fallback".to_string(),
+            args: vec![
+                V2Expr::Pipe(V2Pipe {
+                    start: V2Start::Ref(V2Ref::Input("query.limit".to_string())),
+                    steps: vec![],
+                }),
+                V2Expr::Pipe(V2Pipe {
+                    start: V2Start::Ref(V2Ref::Context("limit".to_string())),
+                    steps: vec![],
+                }),
+                lit(json!(20)),
+            ],
         };
         let ctx = V2EvalContext::new();
-        let result = eval_v2_op_step(
-            &op,
-            EvalValue::Value(json!(10)),
-            &json!({}),
-            None,
-            &json!({}),
-            "test",
-            &ctx,
+        let eval = |pipe: EvalValue, record: JsonValue, context: Option<&JsonValue>| {
+            eval_v2_op_step(&op, pipe, &record, context, &json!({}), "test", &ctx).unwrap()
+        };
+        let config = json!({ "limit": 50 });
+
+        let request = json!({ "query": { "limit": 5 } });
+        assert_eq!(
+            eval(EvalValue::Missing, request, Some(&config)),
+            EvalValue::Value(json!(5))
+        );
+        assert_eq!(
+            eval(EvalValue::Missing, json!({}), Some(&config)),
+            EvalValue::Value(json!(50))
+        );
+        assert_eq!(
+            eval(EvalValue::Missing, json!({}), None),
+            EvalValue::Value(json!(20))
+        );
+
+        // An explicit null is a present value
+        let request = json!({ "query": { "limit": null } });
+        assert_eq!(
+            eval(EvalValue::Missing, request, Some(&config)),
+            EvalValue::Value(json!(null))
+        );
+        // A present pipe value wins over the chain
+        assert_eq!(
+            eval(EvalValue::Value(json!(1)), json!({}), Some(&config)),
+            EvalValue::Value(json!(1))
         );
-        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!(7.0)));
     }
 
     #[test]
-    fn test_eval_op_comparison_aliases() {
+    fn test_eval_op_count_distinct() {
         let ctx = V2EvalContext::new();
-        let cases = [
-            ("eq", json!(1), json!("1"), true),
-            ("ne", json!(1), json!(2), true),
-            ("lt", json!(5), json!(10), true),
-            ("lte", json!(10), json!(10), true),
-            ("gt", json!(10), json!(5), true),
-            ("gte", json!(10), json!(10), true),
-            ("match", json!("apple"), json!("^a.*"), true),
-        ];
-
-        for (op, left, right, expected) in cases {
-            let op_step = V2OpStep {
-                op: op.to_string(),
-                args: vec![lit(right)],
+        let eval = |args: Vec<V2Expr>, value: EvalValue| {
+            let op = V2OpStep {
+                op: "count_distinct".to_string(),
+                args,
             };
-            let result = eval_v2_op_step(
-                &op_step,
-                EvalValue::Value(left),
-                &json!({}),
-                None,
-                &json!({}),
-                "test",
-                &ctx,
-            );
-            assert!(
-                matches!(result, Ok(EvalValue::Value(v)) if v == json!(expected)),
-                "op {}",
-                op
-            );
-        }
-    }
-
-    #[test]
-    fn test_eval_op_pick_multiple_paths() {
-        let op = V2OpStep {
-            op: "pick".to_string(),
-            args: vec![lit(json!("name")), lit(json!("price"))],
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
         };
-        let ctx = V2EvalContext::new();
-        let result = eval_v2_op_step(
-            &op,
-            EvalValue::Value(json!({"name": "apple", "price": 100, "category": "fruit"})),
-            &json!({}),
-            None,
-            &json!({}),
-            "test",
-            &ctx,
-        );
+
+        let result = eval(
+            vec![],
+            EvalValue::Value(json!(["a", "b", "a", 1, "1", true])),
+        )
+        .unwrap();
+        assert_eq!(result, EvalValue::Value(json!(4)));
+
+        let by_country = vec![V2Expr::Pipe(V2Pipe {
+            start: V2Start::Ref(V2Ref::Item("country".to_string())),
+            steps: vec![],
+        })];
+        let users = json!([
+            { "id": 1, "country": "JP" },
+            { "id": 2, "country": "US" },
+            { "id": 3, "country": "JP" }
+        ]);
+        let result = eval(by_country.clone(), EvalValue::Value(users)).unwrap();
+        assert_eq!(result, EvalValue::Value(json!(2)));
+
+        let result = eval(vec![], EvalValue::Value(json!([]))).unwrap();
+        assert_eq!(result, EvalValue::Value(json!(0)));
         assert!(matches!(
-            result,
-            Ok(EvalValue::Value(v)) if v == json!({"name": "apple", "price": 100})
+            eval(vec![], EvalValue::Missing),
+            Ok(EvalValue::Missing)
         ));
+
+        let err = eval(vec![], EvalValue::Value(json!("abc"))).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test"));
+        let err = eval(vec![], EvalValue::Value(json!(["a", null]))).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test[1]"));
+        let err = eval(by_country, EvalValue::Value(json!([{ "id": 1 }]))).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
     }
 
     #[test]
-    fn test_eval_op_omit_multiple_paths() {
-        let op = V2OpStep {
-            op: "omit".to_string(),
-            args: vec![lit(json!("category")), lit(json!("price"))],
-        };
+    fn test_eval_op_count_by() {
         let ctx = V2EvalContext::new();
-        let result = eval_v2_op_step(
-            &op,
-            EvalValue::Value(json!({"name": "apple", "price": 100, "category": "fruit"})),
-            &json!({}),
-            None,
-            &json!({}),
-            "test",
-            &ctx,
-        );
-        assert!(matches!(
+        let eval = |value: EvalValue| {
+            let op = V2OpStep {
+                op: "count_by".to_string(),
+                args: vec![V2Expr::Pipe(V2Pipe {
+                    start: V2Start::Ref(V2Ref::Item("country".to_string())),
+                    steps: vec![],
+                })],
+            };
+            eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+        };
+
+        let users = json!([
+            { "id": 1, "country": "JP" },
+            { "id": 2, "country": "US" },
+            { "id": 3, "country": "JP" },
+            { "id": 4 },
+            { "id": 5, "country": 7 }
+        ]);
+        let result = eval(EvalValue::Value(users)).unwrap();
+        assert_eq!(
             result,
-            Ok(EvalValue::Value(v)) if v == json!({"name": "apple"})
-        ));
+            EvalValue::Value(json!({ "JP": 2, "US": 1, "7": 1 }))
+        );
+        let EvalValue::Value(JsonValue::Object(counts)) = result else {
+            panic!("count_by should return an object");
+        };
+        assert!(counts.values().all(JsonValue::is_u64));
+
+        assert_eq!(
+            eval(EvalValue::Value(json!([]))).unwrap(),
+            EvalValue::Value(json!({}))
+        );
+        assert!(matches!(eval(EvalValue::Missing), Ok(EvalValue::Missing)));
+
+        let err = eval(EvalValue::Value(json!("abc"))).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test"));
+        let err = eval(EvalValue::Value(json!([{ "country": null }]))).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
     }
 
     #[test]
-    fn test_eval_op_pick_paths_array_arg() {
+    fn test_eval_op_fallback_short_circuits() {
+        // The second argument would fail to evaluate if it were reached.
         let op = V2OpStep {
-            op: "pick".to_string(),
-            args: vec![lit(json!(["name", "price"]))],
+            op: "fallback".to_string(),
+            args: vec![
+                lit(json!("first")),
+                V2Expr::Pipe(V2Pipe {
+                    start: V2Start::Literal(json!("x")),
+                    steps: vec![V2Step::Op(V2OpStep {
+                        op: "no_such_op".to_string(),
+                        args: vec![],
+                    })],
+                }),
+            ],
         };
         let ctx = V2EvalContext::new();
         let result = eval_v2_op_step(
             &op,
-            EvalValue::Value(json!({"name": "apple", "price": 100, "category": "fruit"})),
+            EvalValue::Missing,
             &json!({}),
             None,
             &json!({}),
             "test",
             &ctx,
-        );
-        assert!(matches!(
-            result,
-            Ok(EvalValue::Value(v)) if v == json!({"name": "apple", "price": 100})
-        ));
+        )
+        .unwrap();
+        assert_eq!(result, EvalValue::Value(json!("first")));
     }
 
     #[test]
-    fn test_eval_op_multiply() {
+    fn test_eval_op_coalesce() {
         let op = V2OpStep {
-            op: "multiply".to_string(),
+            op: "coalesce".to_string(),
             args: vec![V2Expr::Pipe(V2Pipe {
-                start: V2Start::Literal(json!(0.9)),
+                start: V2Start::Literal(json!("default")),
                 steps: vec![],
             })],
         };
         let ctx = V2EvalContext::new();
+
+        // When pipe value is present, use it
         let result = eval_v2_op_step(
             &op,
-            EvalValue::Value(json!(100)),
+            EvalValue::Value(json!("value")),
             &json!({}),
             None,
             &json!({}),
             "test",
             &ctx,
         );
-        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!(90.0)));
-    }
+        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!("value")));
 
-    #[test]
-    fn test_eval_op_divide() {
-        let op = V2OpStep {
-            op: "divide".to_string(),
-            args: vec![V2Expr::Pipe(V2Pipe {
-                start: V2Start::Literal(json!(2)),
-                steps: vec![],
-            })],
-        };
-        let ctx = V2EvalContext::new();
+        // When pipe value is null, use first non-null arg
         let result = eval_v2_op_step(
             &op,
-            EvalValue::Value(json!(10)),
+            EvalValue::Value(json!(null)),
             &json!({}),
             None,
             &json!({}),
             "test",
             &ctx,
         );
-        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!(5.0)));
+        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!("default")));
+
+        // When pipe value is missing, use first non-null arg
+        let result = eval_v2_op_step(
+            &op,
+            EvalValue::Missing,
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        );
+        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!("default")));
     }
 
     #[test]
-    fn test_eval_op_divide_by_zero() {
+    fn test_eval_op_values_at() {
         let op = V2OpStep {
-            op: "divide".to_string(),
-            args: vec![V2Expr::Pipe(V2Pipe {
-                start: V2Start::Literal(json!(0)),
-                steps: vec![],
-            })],
+            op: "values_at".to_string(),
+            args: vec![lit(json!("first")), lit(json!(["name.last", "missing"]))],
         };
         let ctx = V2EvalContext::new();
         let result = eval_v2_op_step(
             &op,
-            EvalValue::Value(json!(10)),
+            EvalValue::Value(json!({"first": "Ada", "name": {"last": "Lovelace"}})),
             &json!({}),
             None,
             &json!({}),
             "test",
             &ctx,
         );
-        assert!(result.is_err());
+        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!(["Ada", "Lovelace", null])));
     }
 
     #[test]
-    fn test_eval_op_coalesce() {
+    fn test_eval_op_values_at_skip_missing() {
         let op = V2OpStep {
-            op: "coalesce".to_string(),
-            args: vec![V2Expr::Pipe(V2Pipe {
-                start: V2Start::Literal(json!("default")),
-                steps: vec![],
-            })],
+            op: "values_at".to_string(),
+            args: vec![lit(json!("a")), lit(json!("b")), lit(json!(true))],
         };
         let ctx = V2EvalContext::new();
-
-        // When pipe value is present, use it
         let result = eval_v2_op_step(
             &op,
-            EvalValue::Value(json!("value")),
+            EvalValue::Value(json!({"b": 2})),
             &json!({}),
             None,
             &json!({}),
             "test",
             &ctx,
         );
-        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!("value")));
+        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!([2])));
 
-        // When pipe value is null, use first non-null arg
         let result = eval_v2_op_step(
             &op,
-            EvalValue::Value(json!(null)),
+            EvalValue::Missing,
             &json!({}),
             None,
             &json!({}),
             "test",
             &ctx,
         );
-        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!("default")));
+        assert!(matches!(result, Ok(EvalValue::Missing)));
+    }
 
-        // When pipe value is missing, use first non-null arg
-        let result = eval_v2_op_step(
+    fn eval_to_timezone(
+        value: EvalValue,
+        args: Vec<JsonValue>,
+    ) -> Result<EvalValue, TransformError> {
+        let op = V2OpStep {
+            op: "to_timezone".to_string(),
+            args: args.into_iter().map(lit).collect(),
+        };
+        let ctx = V2EvalContext::new();
+        eval_v2_op_step(&op, value, &json!({}), None, &json!({}), "test", &ctx)
+    }
+
+    #[test]
+    fn test_eval_op_to_timezone() {
+        let result = eval_to_timezone(
+            EvalValue::Value(json!("2024-01-15T09:30:00+09:00")),
+            vec![json!("UTC")],
+        );
+        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!("2024-01-15T00:30:00Z")));
+
+        let result = eval_to_timezone(
+            EvalValue::Value(json!("2024-07-01T12:00:00Z")),
+            vec![json!("America/New_York")],
+        );
+        assert!(
+            matches!(result, Ok(EvalValue::Value(v)) if v == json!("2024-07-01T08:00:00-04:00"))
+        );
+    }
+
+    #[test]
+    fn test_eval_op_to_timezone_naive_input_zone() {
+        // Naive timestamps default to UTC
+        let result = eval_to_timezone(
+            EvalValue::Value(json!("2024-01-15 09:30:00")),
+            vec![json!("Asia/Tokyo")],
+        );
+        assert!(
+            matches!(result, Ok(EvalValue::Value(v)) if v == json!("2024-01-15T18:30:00+09:00"))
+        );
+
+        let result = eval_to_timezone(
+            EvalValue::Value(json!("2024-01-15 09:30:00")),
+            vec![json!("UTC"), json!("Asia/Tokyo")],
+        );
+        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!("2024-01-15T00:30:00Z")));
+    }
+
+    #[test]
+    fn test_eval_op_to_timezone_invalid() {
+        let result = eval_to_timezone(
+            EvalValue::Value(json!("2024-01-15T09:30:00Z")),
+            vec![json!("Mars/Olympus")],
+        );
+        let err = result.unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test.args[0]"));
+
+        let result = eval_to_timezone(EvalValue::Value(json!("not a date")), vec![json!("UTC")]);
+        assert!(matches!(result, Ok(EvalValue::Missing)));
+    }
+
+    fn eval_json_patch(doc: JsonValue, patch: JsonValue) -> Result<EvalValue, TransformError> {
+        let op = V2OpStep {
+            op: "apply_json_patch".to_string(),
+            args: vec![lit(patch)],
+        };
+        let ctx = V2EvalContext::new();
+        eval_v2_op_step(
             &op,
-            EvalValue::Missing,
+            EvalValue::Value(doc),
             &json!({}),
             None,
             &json!({}),
             "test",
             &ctx,
+        )
+    }
+
+    #[test]
+    fn test_eval_op_apply_json_patch_add() {
+        let result = eval_json_patch(
+            json!({"a": 1, "tags": ["x"]}),
+            json!([
+                {"op": "add", "path": "/b", "value": 2},
+                {"op": "add", "path": "/tags/0", "value": "w"},
+                {"op": "add", "path": "/tags/-", "value": "y"}
+            ]),
         );
-        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!("default")));
+        assert!(
+            matches!(result, Ok(EvalValue::Value(v)) if v == json!({"a": 1, "b": 2, "tags": ["w", "x", "y"]}))
+        );
+    }
+
+    #[test]
+    fn test_eval_op_apply_json_patch_remove_and_replace() {
+        let result = eval_json_patch(
+            json!({"a": 1, "b": {"c": 2}, "items": [1, 2, 3]}),
+            json!([
+                {"op": "remove", "path": "/a"},
+                {"op": "replace", "path": "/b/c", "value": 3},
+                {"op": "remove", "path": "/items/1"}
+            ]),
+        );
+        assert!(
+            matches!(result, Ok(EvalValue::Value(v)) if v == json!({"b": {"c": 3}, "items": [1, 3]}))
+        );
+
+        let result = eval_json_patch(
+            json!({"a": 1}),
+            json!([{"op": "replace", "path": "/missing", "value": 3}]),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_eval_op_apply_json_patch_move_and_copy() {
+        let result = eval_json_patch(
+            json!({"a": {"b": 1}, "c": "x"}),
+            json!([
+                {"op": "move", "from": "/a/b", "path": "/d"},
+                {"op": "copy", "from": "/c", "path": "/a/e"}
+            ]),
+        );
+        assert!(
+            matches!(result, Ok(EvalValue::Value(v)) if v == json!({"a": {"e": "x"}, "c": "x", "d": 1}))
+        );
+
+        let result = eval_json_patch(
+            json!({"a": {"b": 1}}),
+            json!([{"op": "move", "from": "/a", "path": "/a/b/c"}]),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_eval_op_apply_json_patch_test_op() {
+        let result = eval_json_patch(
+            json!({"a/b": 1, "m~n": 2}),
+            json!([
+                {"op": "test", "path": "/a~1b", "value": 1},
+                {"op": "test", "path": "/m~0n", "value": 2}
+            ]),
+        );
+        assert!(matches!(result, Ok(EvalValue::Value(v)) if v == json!({"a/b": 1, "m~n": 2})));
+
+        let result = eval_json_patch(
+            json!({"a": 1}),
+            json!([{"op": "test", "path": "/a", "value": 2}]),
+        );
+        let err = result.unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test.args[0][0]"));
+    }
+
+    #[test]
+    fn test_eval_op_apply_json_patch_invalid_pointer() {
+        let result = eval_json_patch(
+            json!({"a": 1}),
+            json!([{"op": "add", "path": "a", "value": 2}]),
+        );
+        assert!(result.is_err());
+
+        let result = eval_json_patch(
+            json!({"items": [1]}),
+            json!([{"op": "add", "path": "/items/5", "value": 2}]),
+        );
+        assert!(result.is_err());
     }
 
     #[test]
@@ -4295,6 +8130,116 @@ mod v2_if_step_eval_tests {
         assert!(matches!(result, Ok(true)));
     }
 
+    #[test]
+    fn test_eval_condition_in_not_in() {
+        let record = json!({ "status": "pending", "allowed": ["active", "pending"], "code": 2 });
+        let out = json!({});
+        let ctx = V2EvalContext::new();
+        let eval = |value: JsonValue| {
+            let cond = crate::v2_parser::parse_v2_condition(&value).unwrap();
+            eval_v2_condition(&cond, &record, None, &out, "test", &ctx)
+        };
+
+        assert!(eval(json!({ "in": ["@input.status", ["active", "pending"]] })).unwrap());
+        assert!(!eval(json!({ "in": ["@input.status", ["active", "closed"]] })).unwrap());
+        assert!(eval(json!({ "in": ["@input.status", "@input.allowed"] })).unwrap());
+        assert!(!eval(json!({ "not_in": ["@input.status", "@input.allowed"] })).unwrap());
+        assert!(eval(json!({ "not_in": ["@input.status", ["closed"]] })).unwrap());
+        assert!(!eval(json!({ "in": ["@input.code", ["2", 3]] })).unwrap());
+        assert!(eval(json!({ "in": ["@input.missing", [null]] })).unwrap());
+        assert!(!eval(json!({ "in": ["@input.status", []] })).unwrap());
+    }
+
+    #[test]
+    fn test_eval_condition_exists_and_missing() {
+        let record = json!({ "present": 0, "null_field": null });
+        let out = json!({});
+        let ctx = V2EvalContext::new();
+        let eval = |cond: JsonValue| {
+            let cond = crate::v2_parser::parse_v2_condition(&cond).unwrap();
+            eval_v2_condition(&cond, &record, None, &out, "test", &ctx).unwrap()
+        };
+
+        assert!(eval(json!({ "exists": "@input.present" })));
+        assert!(!eval(json!({ "missing": "@input.present" })));
+
+        assert!(eval(json!({ "exists": "@input.null_field" })));
+        assert!(!eval(json!({ "missing": "@input.null_field" })));
+
+        assert!(!eval(json!({ "exists": "@input.absent" })));
+        assert!(eval(json!({ "missing": "@input.absent" })));
+        assert!(eval(json!({ "missing": "@context.absent" })));
+    }
+
+    #[test]
+    fn test_eval_condition_eqi() {
+        let record = json!({ "status": "AcTiVe", "count": 1 });
+        let out = json!({});
+        let ctx = V2EvalContext::new();
+        let eval = |cond: JsonValue| {
+            let cond = crate::v2_parser::parse_v2_condition(&cond).unwrap();
+            eval_v2_condition(&cond, &record, None, &out, "test", &ctx).unwrap()
+        };
+
+        assert!(eval(json!({ "eqi": ["@input.status", "active"] })));
+        assert!(eval(json!({ "eqi": ["@input.status", "ACTIVE"] })));
+        assert!(eval(json!({ "eqi": ["ÉCOLE", "école"] })));
+        assert!(!eval(json!({ "eqi": ["@input.status", "inactive"] })));
+
+        // Non-string operands fall back to strict equality
+        assert!(eval(json!({ "eqi": ["@input.count", 1] })));
+        assert!(!eval(json!({ "eqi": ["@input.count", "1"] })));
+        assert!(!eval(json!({ "eqi": ["@input.absent", "active"] })));
+    }
+
+    #[test]
+    fn test_eval_condition_between() {
+        let record = json!({});
+        let out = json!({});
+        let ctx = V2EvalContext::new();
+        let eval = |value: JsonValue, low: JsonValue, high: JsonValue| {
+            let cond =
+                crate::v2_parser::parse_v2_condition(&json!({ "between": [value, low, high] }))
+                    .unwrap();
+            eval_v2_condition(&cond, &record, None, &out, "test", &ctx)
+        };
+
+        assert!(eval(json!(100), json!(100), json!(1000)).unwrap());
+        assert!(eval(json!(1000), json!(100), json!(1000)).unwrap());
+        assert!(eval(json!("550.5"), json!(100), json!(1000)).unwrap());
+        assert!(!eval(json!(99.99), json!(100), json!(1000)).unwrap());
+        assert!(!eval(json!(1001), json!(100), json!(1000)).unwrap());
+        assert!(eval(json!(5), json!(5), json!(5)).unwrap());
+        assert!(eval(json!("m"), json!("a"), json!("z")).unwrap());
+
+        let err = eval(json!(500), json!(1000), json!(100)).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+        assert_eq!(err.path.as_deref(), Some("test"));
+    }
+
+    #[test]
+    fn test_eval_condition_between_requires_three_args() {
+        let cond = crate::v2_parser::parse_v2_condition(&json!({ "between": [1, 2] })).unwrap();
+        let ctx = V2EvalContext::new();
+        let err = eval_v2_condition(&cond, &json!({}), None, &json!({}), "test", &ctx).unwrap_err();
+        assert_eq!(err.kind, TransformErrorKind::ExprError);
+    }
+
+    #[test]
+    fn test_eval_condition_in_requires_array() {
+        let record = json!({ "status": "active" });
+        let out = json!({});
+        let ctx = V2EvalContext::new();
+        for right in [json!("active"), json!("@input.missing")] {
+            let cond =
+                crate::v2_parser::parse_v2_condition(&json!({ "in": ["@input.status", right] }))
+                    .unwrap();
+            let err = eval_v2_condition(&cond, &record, None, &out, "test", &ctx).unwrap_err();
+            assert_eq!(err.kind, TransformErrorKind::ExprError);
+            assert_eq!(err.path.as_deref(), Some("test.args[1]"));
+        }
+    }
+
     #[test]
     fn test_eval_condition_all_true() {
         let cond = V2Condition::All(vec![
@@ -5466,6 +9411,190 @@ mod v2_lookup_eval_tests {
         ])
     }
 
+    fn eval_lookup_literals(op: &str, from: JsonValue, args: Vec<JsonValue>) -> EvalValue {
+        let op = V2OpStep {
+            op: op.to_string(),
+            args: args
+                .into_iter()
+                .map(|arg| {
+                    V2Expr::Pipe(V2Pipe {
+                        start: V2Start::Literal(arg),
+                        steps: vec![],
+                    })
+                })
+                .collect(),
+        };
+        let ctx = V2EvalContext::new();
+        eval_v2_op_step(
+            &op,
+            EvalValue::Value(from),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        )
+        .expect("lookup")
+    }
+
+    #[test]
+    fn test_json_values_eq_normalized() {
+        assert!(json!(1) != json!(1.0));
+        assert!(json_values_eq_normalized(&json!(1), &json!(1.0), false));
+        assert!(json_values_eq_normalized(&json!(-3), &json!(-3.0), false));
+        assert!(!json_values_eq_normalized(&json!(1), &json!(1.5), false));
+        assert!(!json_values_eq_normalized(&json!("2"), &json!(2), false));
+        assert!(json_values_eq_normalized(&json!("2"), &json!(2), true));
+        assert!(json_values_eq_normalized(&json!(2.0), &json!("2"), true));
+        assert!(!json_values_eq_normalized(&json!("two"), &json!(2), true));
+        assert!(json_values_eq_normalized(
+            &json!({"id": 1, "tags": [{"n": 2.0}], "name": "a"}),
+            &json!({"name": "a", "tags": [{"n": 2}], "id": 1.0}),
+            false
+        ));
+        assert!(!json_values_eq_normalized(
+            &json!({"id": 1, "extra": null}),
+            &json!({"id": 1}),
+            false
+        ));
+        assert!(!json_values_eq_normalized(
+            &json!([1, 2]),
+            &json!([2, 1]),
+            false
+        ));
+    }
+
+    #[test]
+    fn test_lookup_matches_numbers_by_value() {
+        let result = eval_lookup_literals(
+            "lookup_first",
+            make_departments(),
+            vec![json!("id"), json!(2.0), json!("name")],
+        );
+        assert_eq!(result, EvalValue::Value(json!("Sales")));
+
+        let result = eval_lookup_literals(
+            "lookup",
+            make_departments(),
+            vec![json!("id"), json!("2"), json!("name")],
+        );
+        assert_eq!(result, EvalValue::Value(json!([])));
+    }
+
+    #[test]
+    fn test_lookup_options_follow_the_positional_args() {
+        // With only two args the object is the match value, not options
+        let from = json!([{"key": {"coerce": true}, "name": "literal"}]);
+        let result = eval_lookup_literals(
+            "lookup_first",
+            from,
+            vec![json!("key"), json!({"coerce": true})],
+        );
+        assert_eq!(
+            result,
+            EvalValue::Value(json!({"key": {"coerce": true}, "name": "literal"}))
+        );
+
+        let op = V2OpStep {
+            op: "lookup".to_string(),
+            args: [
+                json!([]),
+                json!("id"),
+                json!(2),
+                json!("name"),
+                json!("extra"),
+                json!({"coerce": true}),
+            ]
+            .into_iter()
+            .map(|arg| {
+                V2Expr::Pipe(V2Pipe {
+                    start: V2Start::Literal(arg),
+                    steps: vec![],
+                })
+            })
+            .collect(),
+        };
+        let err = eval_v2_op_step(
+            &op,
+            EvalValue::Missing,
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &V2EvalContext::new(),
+        )
+        .unwrap_err();
+        assert!(
+            err.message.contains("at most 4 positional"),
+            "{}",
+            err.message
+        );
+    }
+
+    #[test]
+    fn test_lookup_coerce_matches_strings_to_numbers() {
+        let result = eval_lookup_literals(
+            "lookup_first",
+            make_departments(),
+            vec![
+                json!("id"),
+                json!("2"),
+                json!("name"),
+                json!({"coerce": true}),
+            ],
+        );
+        assert_eq!(result, EvalValue::Value(json!("Sales")));
+
+        let result = eval_lookup_literals(
+            "lookup",
+            make_departments(),
+            vec![
+                json!("id"),
+                json!("3"),
+                json!("name"),
+                json!({"coerce": true}),
+            ],
+        );
+        assert_eq!(result, EvalValue::Value(json!(["HR"])));
+
+        let result = eval_lookup_literals(
+            "lookup_first",
+            make_departments(),
+            vec![json!("id"), json!("2"), json!({"coerce": false})],
+        );
+        assert_eq!(result, EvalValue::Missing);
+
+        let op = V2OpStep {
+            op: "lookup_first".to_string(),
+            args: vec![
+                V2Expr::Pipe(V2Pipe {
+                    start: V2Start::Literal(json!("id")),
+                    steps: vec![],
+                }),
+                V2Expr::Pipe(V2Pipe {
+                    start: V2Start::Literal(json!("2")),
+                    steps: vec![],
+                }),
+                V2Expr::Pipe(V2Pipe {
+                    start: V2Start::Literal(json!({"coerce": "yes"})),
+                    steps: vec![],
+                }),
+            ],
+        };
+        let ctx = V2EvalContext::new();
+        let err = eval_v2_op_step(
+            &op,
+            EvalValue::Value(make_departments()),
+            &json!({}),
+            None,
+            &json!({}),
+            "test",
+            &ctx,
+        )
+        .unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("test.args[2].coerce"));
+    }
+
     #[test]
     fn test_lookup_first_basic() {
         // lookup_first: {from: @context.departments, match: [id, 2], get: name}
@@ -90,6 +90,10 @@ pub enum V2Condition {
     All(Vec<V2Condition>),
     Any(Vec<V2Condition>),
     Comparison(V2Comparison),
+    /// True when the reference resolves to a value, including `null`
+    Exists(V2Ref),
+    /// True when the reference does not resolve
+    Missing(V2Ref),
     Expr(V2Expr),
 }
 
@@ -104,12 +108,27 @@ pub struct V2Comparison {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum V2ComparisonOp {
     Eq,
+    /// Case-insensitive `Eq` for strings
+    EqI,
     Ne,
     Gt,
     Gte,
     Lt,
     Lte,
     Match,
+    In,
+    NotIn,
+    Between,
+}
+
+impl V2ComparisonOp {
+    /// Number of arguments the comparison takes; `between` takes value, low, and high.
+    pub fn arg_count(self) -> usize {
+        match self {
+            V2ComparisonOp::Between => 3,
+            _ => 2,
+        }
+    }
 }
 
 // =============================================================================
@@ -497,7 +497,16 @@ pub fn parse_v2_condition(value: &JsonValue) -> Result<V2Condition, V2ParseError
             if let Some(any_arr) = obj.get("any") {
                 return parse_condition_array(any_arr, |conds| V2Condition::Any(conds));
             }
-            // Check for comparison operators: eq, ne, gt, gte, lt, lte, match
+            // Check for presence predicates: exists, missing
+            if let Some(ref_val) = obj.get("exists") {
+                return Ok(V2Condition::Exists(parse_presence_ref("exists", ref_val)?));
+            }
+            if let Some(ref_val) = obj.get("missing") {
+                return Ok(V2Condition::Missing(parse_presence_ref(
+                    "missing", ref_val,
+                )?));
+            }
+            // Check for comparison operators: eq, eqi, ne, gt, gte, lt, lte, match, in, not_in, between
             if let Some(comp) = parse_comparison_from_object(obj)? {
                 return Ok(V2Condition::Comparison(comp));
             }
@@ -518,6 +527,13 @@ pub fn parse_v2_condition(value: &JsonValue) -> Result<V2Condition, V2ParseError
     }
 }
 
+fn parse_presence_ref(key: &str, value: &JsonValue) -> Result<V2Ref, V2ParseError> {
+    value
+        .as_str()
+        .and_then(parse_v2_ref)
+        .ok_or_else(|| V2ParseError::InvalidCondition(format!("{} must be a reference", key)))
+}
+
 fn parse_condition_array<F>(value: &JsonValue, constructor: F) -> Result<V2Condition, V2ParseError>
 where
     F: FnOnce(Vec<V2Condition>) -> V2Condition,
@@ -539,17 +555,34 @@ fn parse_comparison_from_object(
 ) -> Result<Option<V2Comparison>, V2ParseError> {
     let ops = [
         ("eq", V2ComparisonOp::Eq),
+        ("eqi", V2ComparisonOp::EqI),
         ("ne", V2ComparisonOp::Ne),
         ("gt", V2ComparisonOp::Gt),
         ("gte", V2ComparisonOp::Gte),
         ("lt", V2ComparisonOp::Lt),
         ("lte", V2ComparisonOp::Lte),
         ("match", V2ComparisonOp::Match),
+        ("in", V2ComparisonOp::In),
+        ("not_in", V2ComparisonOp::NotIn),
+        ("between", V2ComparisonOp::Between),
     ];
 
     for (key, op) in ops.iter() {
         if let Some(args_val) = obj.get(*key) {
-            let args = parse_v2_expr_args(args_val)?;
+            let args = match (op, args_val) {
+                (V2ComparisonOp::In | V2ComparisonOp::NotIn, JsonValue::Array(items))
+                    if items.len() == 2 && is_literal_list(&items[1]) =>
+                {
+                    vec![
+                        parse_v2_expr(&items[0])?,
+                        V2Expr::Pipe(V2Pipe {
+                            start: V2Start::Literal(items[1].clone()),
+                            steps: vec![],
+                        }),
+                    ]
+                }
+                _ => parse_v2_expr_args(args_val)?,
+            };
             return Ok(Some(V2Comparison { op: *op, args }));
         }
     }
@@ -557,6 +590,19 @@ fn parse_comparison_from_object(
     Ok(None)
 }
 
+/// An array of plain scalars (no references or `$`) on the right of `in`/`not_in` is the
+/// list itself rather than a pipe, so `in: ["@input.status", ["active", "pending"]]` works.
+fn is_literal_list(value: &JsonValue) -> bool {
+    let JsonValue::Array(items) = value else {
+        return false;
+    };
+    items.iter().all(|item| match item {
+        JsonValue::String(s) => !is_pipe_value(s) && !is_v2_ref(s),
+        JsonValue::Array(_) | JsonValue::Object(_) => false,
+        _ => true,
+    })
+}
+
 // =============================================================================
 // Parse Errors
 // =============================================================================
@@ -971,6 +1017,82 @@ mod v2_condition_parser_tests {
         }
     }
 
+    #[test]
+    fn test_parse_condition_eqi() {
+        let value = json!({ "eqi": ["@input.status", "Active"] });
+        let cond = parse_v2_condition(&value).unwrap();
+
+        let V2Condition::Comparison(comp) = cond else {
+            panic!("Expected Comparison condition");
+        };
+        assert_eq!(comp.op, V2ComparisonOp::EqI);
+        assert_eq!(comp.args.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_condition_between() {
+        let value = json!({ "between": ["@input.amount", 100, 1000] });
+        let cond = parse_v2_condition(&value).unwrap();
+
+        let V2Condition::Comparison(comp) = cond else {
+            panic!("Expected Comparison condition");
+        };
+        assert_eq!(comp.op, V2ComparisonOp::Between);
+        assert_eq!(comp.args.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_condition_exists_and_missing() {
+        let cond = parse_v2_condition(&json!({ "exists": "@input.foo" })).unwrap();
+        assert_eq!(cond, V2Condition::Exists(V2Ref::Input("foo".to_string())));
+
+        let cond = parse_v2_condition(&json!({ "missing": "@context.bar" })).unwrap();
+        assert_eq!(
+            cond,
+            V2Condition::Missing(V2Ref::Context("bar".to_string()))
+        );
+
+        assert!(parse_v2_condition(&json!({ "exists": "foo" })).is_err());
+        assert!(parse_v2_condition(&json!({ "missing": ["@input.foo"] })).is_err());
+    }
+
+    #[test]
+    fn test_parse_condition_in_literal_list() {
+        let value = json!({ "in": ["@input.status", ["active", "pending", 3, null]] });
+        let cond = parse_v2_condition(&value).unwrap();
+
+        let V2Condition::Comparison(comp) = cond else {
+            panic!("Expected Comparison condition");
+        };
+        assert_eq!(comp.op, V2ComparisonOp::In);
+        assert_eq!(
+            comp.args[1],
+            V2Expr::Pipe(V2Pipe {
+                start: V2Start::Literal(json!(["active", "pending", 3, null])),
+                steps: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_condition_not_in_pipe_operand() {
+        let value = json!({ "not_in": ["@input.status", ["@context.blocked", "lowercase"]] });
+        let cond = parse_v2_condition(&value).unwrap();
+
+        let V2Condition::Comparison(comp) = cond else {
+            panic!("Expected Comparison condition");
+        };
+        assert_eq!(comp.op, V2ComparisonOp::NotIn);
+        let V2Expr::Pipe(pipe) = &comp.args[1] else {
+            panic!("Expected pipe operand");
+        };
+        assert_eq!(
+            pipe.start,
+            V2Start::Ref(V2Ref::Context("blocked".to_string()))
+        );
+        assert_eq!(pipe.steps.len(), 1);
+    }
+
     #[test]
     fn test_parse_nested_conditions() {
         let value = json!({
@@ -3,6 +3,7 @@
 //! This module provides compile-time validation for v2 expressions,
 //! catching errors that previously only occurred at runtime.
 
+use std::cell::Cell;
 use std::collections::{HashMap, HashSet};
 
 use serde_json::Value as JsonValue;
@@ -10,6 +11,7 @@ use serde_json::Value as JsonValue;
 use crate::error::{ErrorCode, RuleError};
 use crate::locator::YamlLocator;
 use crate::path::{PathToken, parse_path};
+use crate::type_spec::TypeSpec;
 use crate::v2_model::{
     V2Comparison, V2Condition, V2Expr, V2IfStep, V2LetStep, V2MapStep, V2OpStep, V2Pipe, V2Ref,
     V2Start, V2Step,
@@ -157,6 +159,8 @@ pub struct V2ValidationCtx<'a> {
     locator: Option<&'a YamlLocator>,
     /// Accumulated errors
     errors: Vec<RuleError>,
+    /// Deprecated op usages (reported as warnings unless escalated)
+    deprecations: Vec<RuleError>,
     /// Previously computed output targets (for @out forward reference check)
     produced_targets: HashSet<Vec<PathToken>>,
     /// Whether @out forward references are allowed
@@ -171,6 +175,7 @@ impl<'a> V2ValidationCtx<'a> {
         Self {
             locator,
             errors: Vec::new(),
+            deprecations: Vec::new(),
             produced_targets: HashSet::new(),
             allow_any_out_ref: false,
             context_referenced: false,
@@ -186,6 +191,7 @@ impl<'a> V2ValidationCtx<'a> {
         Self {
             locator,
             errors: Vec::new(),
+            deprecations: Vec::new(),
             produced_targets,
             allow_any_out_ref,
             context_referenced: false,
@@ -203,6 +209,15 @@ impl<'a> V2ValidationCtx<'a> {
         self.errors.push(err);
     }
 
+    /// Push a deprecated op usage with path
+    pub fn push_deprecation(&mut self, message: impl Into<String>, path: &str) {
+        let mut err = RuleError::new(ErrorCode::DeprecatedOp, message).with_path(path);
+        if let Some(location) = self.locator.and_then(|locator| locator.location_for(path)) {
+            err = err.with_location(location.line, location.column);
+        }
+        self.deprecations.push(err);
+    }
+
     /// Add a produced target
     pub fn add_produced_target(&mut self, tokens: Vec<PathToken>) {
         self.produced_targets.insert(tokens);
@@ -231,6 +246,11 @@ impl<'a> V2ValidationCtx<'a> {
     pub fn errors(&self) -> &[RuleError] {
         &self.errors
     }
+
+    /// Get collected deprecated op usages
+    pub fn deprecations(&self) -> &[RuleError] {
+        &self.deprecations
+    }
 }
 
 // =============================================================================
@@ -291,16 +311,29 @@ fn infer_step_result_type(step: &V2Step, _input_type: &V2Type) -> V2Type {
 fn infer_op_result_type(op: &str) -> V2Type {
     match op {
         // String operations
-        "trim" | "lowercase" | "uppercase" | "concat" | "to_string" => V2Type::String,
+        "trim" | "lowercase" | "uppercase" | "concat" | "to_string" | "to_timezone"
+        | "parse_date" | "format_date" | "now" | "sanitize_filename" | "substring" | "join"
+        | "format" | "snake_case" | "camel_case" | "kebab_case" | "title_case"
+        | "replace_regex" | "to_json" | "base64_encode" | "base64_decode" | "hex_encode"
+        | "hex_decode" => V2Type::String,
 
         // Numeric operations
-        "+" | "-" | "*" | "/" | "add" | "subtract" | "multiply" | "divide" => V2Type::Number,
+        "+" | "-" | "*" | "/" | "add" | "subtract" | "multiply" | "divide" | "max_of"
+        | "min_of" | "abs" | "ceil" | "floor" | "sqrt" | "pow" | "mod" | "count_distinct"
+        | "count" | "len" | "length" | "sum" | "avg" | "min" | "max" | "sum_by" | "avg_by" => {
+            V2Type::Number
+        }
+
+        // Predicates
+        "contains" | "starts_with" | "ends_with" | "is_empty" | "is_null" => V2Type::Bool,
 
         // Lookup returns arrays of matches
-        "lookup" => V2Type::Array(Box::new(V2Type::Unknown)),
+        "lookup" | "values_at" => V2Type::Array(Box::new(V2Type::Unknown)),
+        "paragraphs" => V2Type::Array(Box::new(V2Type::String)),
+        "parse_query_string" | "count_by" => V2Type::Object,
 
         // Coalesce and lookup_first return unknown (could be any type)
-        "coalesce" | "lookup_first" => V2Type::Unknown,
+        "coalesce" | "fallback" | "default" | "lookup_first" => V2Type::Unknown,
 
         // Default to unknown
         _ => V2Type::Unknown,
@@ -405,7 +438,7 @@ fn validate_out_not_forward(path: &str, base_path: &str, ctx: &mut V2ValidationC
         .iter()
         .filter_map(|t| match t {
             PathToken::Key(k) => Some(PathToken::Key(k.clone())),
-            PathToken::Index(_) => None,
+            _ => None,
         })
         .collect();
 
@@ -515,10 +548,65 @@ fn validate_v2_op_step(
             format!("unknown operation: {}", op_step.op),
             base_path,
         );
+    } else if let Some(replacement) = deprecated_op_replacement(&op_step.op) {
+        ctx.push_deprecation(
+            format!(
+                "operation {} is deprecated; use {} instead",
+                op_step.op, replacement
+            ),
+            base_path,
+        );
     }
 
-    // Validate argument count
-    validate_op_args_count(&op_step.op, op_step.args.len(), base_path, ctx);
+    // Validate argument count; a lookup options object is not a positional argument
+    let (positional, options) = split_op_options(&op_step.op, &op_step.args);
+    validate_op_args_count(&op_step.op, positional.len(), base_path, ctx);
+    if let Some(options) = options
+        && let Some(coerce) = options.get("coerce")
+        && !coerce.is_boolean()
+    {
+        ctx.push_error(
+            ErrorCode::InvalidArgs,
+            format!("{} coerce must be a boolean", op_step.op),
+            &format!("{}.args[{}].coerce", base_path, positional.len()),
+        );
+    }
+
+    // Literal type specs can be checked up front
+    if op_step.op == "assert_type"
+        && let Some(V2Expr::Pipe(V2Pipe {
+            start: V2Start::Literal(JsonValue::String(spec)),
+            steps,
+        })) = op_step.args.first()
+        && steps.is_empty()
+        && let Err(message) = TypeSpec::parse(spec)
+    {
+        ctx.push_error(
+            ErrorCode::InvalidArgs,
+            format!("invalid assert_type spec: {}", message),
+            &format!("{}.args[0]", base_path),
+        );
+    }
+
+    // A literal is always present, so fallback arguments after it never run
+    if op_step.op == "fallback"
+        && let Some(index) = op_step.args.iter().position(|arg| {
+            matches!(
+                arg,
+                V2Expr::Pipe(V2Pipe {
+                    start: V2Start::Literal(_),
+                    steps,
+                }) if steps.is_empty()
+            )
+        })
+        && index + 1 < op_step.args.len()
+    {
+        ctx.push_error(
+            ErrorCode::InvalidArgs,
+            "fallback arguments after a literal are unreachable",
+            &format!("{}.args[{}]", base_path, index + 1),
+        );
+    }
 
     // Validate each argument expression
     for (i, arg) in op_step.args.iter().enumerate() {
@@ -617,6 +705,12 @@ pub fn validate_v2_condition(
         V2Condition::Comparison(comp) => {
             validate_v2_comparison(comp, base_path, scope, ctx);
         }
+        V2Condition::Exists(v2_ref) => {
+            validate_v2_ref(v2_ref, &format!("{}.exists", base_path), scope, ctx);
+        }
+        V2Condition::Missing(v2_ref) => {
+            validate_v2_ref(v2_ref, &format!("{}.missing", base_path), scope, ctx);
+        }
         V2Condition::Expr(expr) => {
             validate_v2_expr(expr, base_path, scope, ctx);
             // Type check: must be bool or unknown
@@ -639,12 +733,14 @@ fn validate_v2_comparison(
     scope: &V2Scope,
     ctx: &mut V2ValidationCtx<'_>,
 ) {
-    // Comparisons need exactly 2 arguments
-    if comp.args.len() != 2 {
+    // Comparisons need exactly 2 arguments; between takes value, low, and high
+    let expected = comp.op.arg_count();
+    if comp.args.len() != expected {
         ctx.push_error(
             ErrorCode::InvalidArgs,
             format!(
-                "comparison requires exactly 2 arguments, got {}",
+                "comparison requires exactly {} arguments, got {}",
+                expected,
                 comp.args.len()
             ),
             base_path,
@@ -673,11 +769,32 @@ pub(crate) fn is_valid_op(op: &str) -> bool {
             | "lowercase"
             | "uppercase"
             | "replace"
+            | "replace_regex"
             | "split"
+            | "join"
+            | "starts_with"
+            | "ends_with"
+            | "substring"
+            | "paragraphs"
+            | "parse_query_string"
+            | "to_json"
+            | "from_json"
+            | "base64_encode"
+            | "base64_decode"
+            | "hex_encode"
+            | "hex_decode"
+            | "sanitize_filename"
+            | "snake_case"
+            | "camel_case"
+            | "kebab_case"
+            | "title_case"
+            | "format"
             | "pad_start"
             | "pad_end"
             // Null handling
             | "coalesce"
+            | "fallback"
+            | "default"
             // Lookup
             | "lookup"
             | "lookup_first"
@@ -690,11 +807,23 @@ pub(crate) fn is_valid_op(op: &str) -> bool {
             | "add"
             | "subtract"
             | "divide"
+            | "max_of"
+            | "min_of"
             | "round"
+            | "abs"
+            | "ceil"
+            | "floor"
+            | "sqrt"
+            | "pow"
+            | "mod"
             | "to_base"
             // Date
             | "date_format"
             | "to_unixtime"
+            | "to_timezone"
+            | "parse_date"
+            | "format_date"
+            | "now"
             // Logical
             | "and"
             | "or"
@@ -724,9 +853,17 @@ pub(crate) fn is_valid_op(op: &str) -> bool {
             | "values"
             | "entries"
             | "len"
+            | "length"
             | "from_entries"
             | "object_flatten"
             | "object_unflatten"
+            | "apply_json_patch"
+            | "values_at"
+            // Type checks
+            | "assert"
+            | "assert_type"
+            | "is_empty"
+            | "is_null"
             // Array
             | "map"
             | "filter"
@@ -736,14 +873,18 @@ pub(crate) fn is_valid_op(op: &str) -> bool {
             | "drop"
             | "slice"
             | "chunk"
+            | "window"
             | "zip"
             | "zip_with"
             | "unzip"
             | "group_by"
+            | "count_by"
             | "key_by"
             | "partition"
             | "unique"
+            | "reverse"
             | "distinct_by"
+            | "count_distinct"
             | "sort_by"
             | "find"
             | "find_index"
@@ -753,10 +894,16 @@ pub(crate) fn is_valid_op(op: &str) -> bool {
             | "avg"
             | "min"
             | "max"
+            | "count"
+            | "sum_by"
+            | "avg_by"
+            | "min_by"
+            | "max_by"
             | "reduce"
             | "fold"
             | "first"
             | "last"
+            | "only"
             // Type casts
             | "string"
             | "int"
@@ -765,11 +912,54 @@ pub(crate) fn is_valid_op(op: &str) -> bool {
     )
 }
 
+/// Deprecated operations and their recommended replacements
+const DEPRECATED_OPS: &[(&str, &str)] = &[];
+
+type DeprecatedOps = &'static [(&'static str, &'static str)];
+
+thread_local! {
+    static DEPRECATED_OPS_OVERRIDE: Cell<Option<DeprecatedOps>> = const { Cell::new(None) };
+}
+
+fn deprecated_ops() -> DeprecatedOps {
+    DEPRECATED_OPS_OVERRIDE
+        .with(Cell::get)
+        .unwrap_or(DEPRECATED_OPS)
+}
+
+/// Whether any operation is deprecated, so callers can skip looking for usages
+pub fn any_deprecated_ops() -> bool {
+    !deprecated_ops().is_empty()
+}
+
+/// Get the recommended replacement for a deprecated operation
+pub fn deprecated_op_replacement(op: &str) -> Option<&'static str> {
+    deprecated_ops()
+        .iter()
+        .find(|(name, _)| *name == op)
+        .map(|(_, replacement)| *replacement)
+}
+
+/// Run `f` with `ops` as the deprecated op registry on the current thread, so the
+/// deprecation path can be tested while no op is deprecated.
+#[doc(hidden)]
+pub fn with_deprecated_ops<R>(ops: DeprecatedOps, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<DeprecatedOps>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            DEPRECATED_OPS_OVERRIDE.with(|cell| cell.set(self.0));
+        }
+    }
+    let _restore = Restore(DEPRECATED_OPS_OVERRIDE.with(|cell| cell.replace(Some(ops))));
+    f()
+}
+
 /// Get the appropriate scope for an operation argument
 fn get_arg_scope_for_op(op: &str, arg_index: usize, parent_scope: &V2Scope) -> V2Scope {
     match op {
-        "map" | "filter" | "flat_map" | "group_by" | "key_by" | "partition" | "distinct_by"
-        | "sort_by" | "find" | "find_index"
+        "map" | "filter" | "flat_map" | "group_by" | "count_by" | "key_by" | "partition"
+        | "distinct_by" | "count_distinct" | "sort_by" | "find" | "find_index" | "sum_by"
+        | "avg_by" | "min_by" | "max_by"
             if arg_index == 0 =>
         {
             V2Scope::with_parent(parent_scope).with_item()
@@ -807,42 +997,90 @@ fn validate_op_args_count(op: &str, count: usize, base_path: &str, ctx: &mut V2V
     }
 }
 
+/// Split a trailing options object, such as lookup's `{ coerce: true }`, off an op's args.
+///
+/// Only ops that take options have one, and only once the args before it already
+/// fill the op's minimum positional count; anything shorter is all positional.
+pub(crate) fn split_op_options<'a>(
+    op: &str,
+    args: &'a [V2Expr],
+) -> (&'a [V2Expr], Option<&'a serde_json::Map<String, JsonValue>>) {
+    let option_keys: &[&str] = match op {
+        "lookup" | "lookup_first" => &["coerce"],
+        _ => return (args, None),
+    };
+    let (min, _) = get_op_arg_range(op);
+    if let Some((
+        V2Expr::Pipe(V2Pipe {
+            start: V2Start::Literal(JsonValue::Object(options)),
+            steps,
+        }),
+        positional,
+    )) = args.split_last()
+        && steps.is_empty()
+        && positional.len() >= min
+        && !options.is_empty()
+        && options
+            .keys()
+            .all(|key| option_keys.contains(&key.as_str()))
+    {
+        return (positional, Some(options));
+    }
+    (args, None)
+}
+
 /// Get the valid argument count range for an operation
-/// Returns (min, max) where max is None for unlimited
-fn get_op_arg_range(op: &str) -> (usize, Option<usize>) {
+/// Returns (min, max) where max is None for unlimited. Options objects split off by
+/// `split_op_options` are not counted.
+pub(crate) fn get_op_arg_range(op: &str) -> (usize, Option<usize>) {
     match op {
         // No arguments
-        "trim" | "lowercase" | "uppercase" | "to_string" | "keys" | "values" | "entries"
-        | "unique" | "unzip" | "first" | "last" | "len" | "sum" | "avg" | "min" | "max" | "not"
-        | "string" | "int" | "float" | "bool" => (0, Some(0)),
+        "trim" | "lowercase" | "uppercase" | "paragraphs" | "parse_query_string" | "to_json"
+        | "from_json" | "base64_encode" | "base64_decode" | "hex_encode" | "hex_decode"
+        | "sanitize_filename" | "snake_case" | "camel_case" | "kebab_case" | "title_case"
+        | "to_string" | "keys" | "values" | "entries" | "unique" | "reverse" | "unzip"
+        | "first" | "last" | "only" | "len" | "length" | "abs" | "ceil" | "floor" | "sqrt"
+        | "sum" | "avg" | "min" | "max" | "count" | "not" | "string" | "int" | "float" | "bool"
+        | "is_empty" | "is_null" => (0, Some(0)),
 
         // Optional one argument
-        "round" | "flatten" => (0, Some(1)),
+        "round" | "flatten" | "count_distinct" => (0, Some(1)),
+
+        // Up to two arguments
+        "assert" => (0, Some(2)),
 
         // Exactly 1 argument
-        "take" | "drop" | "get" | "object_flatten" | "object_unflatten" | "chunk" | "map"
-        | "filter" | "flat_map" | "group_by" | "key_by" | "distinct_by" | "find" | "find_index"
-        | "index_of" | "contains" | "partition" | "split" | "reduce" | "to_base" => (1, Some(1)),
+        "take" | "drop" | "get" | "object_flatten" | "object_unflatten" | "chunk" | "window"
+        | "map" | "filter" | "flat_map" | "group_by" | "count_by" | "key_by" | "distinct_by"
+        | "find" | "find_index" | "sum_by" | "avg_by" | "min_by" | "max_by" | "index_of"
+        | "contains" | "starts_with" | "ends_with" | "partition" | "split" | "join" | "reduce"
+        | "to_base" | "apply_json_patch" | "assert_type" => (1, Some(1)),
 
         // One or two arguments
         "sort_by" => (1, Some(2)),
 
         // One or two arguments
-        "pad_start" | "pad_end" | "slice" => (1, Some(2)),
+        "pad_start" | "pad_end" | "slice" | "substring" | "format" => (1, Some(2)),
 
         // Exactly 2 arguments
         "fold" => (2, Some(2)),
 
         // Two or three arguments
         "replace" => (2, Some(3)),
+        "replace_regex" => (2, Some(2)),
+        "default" | "pow" | "mod" => (1, Some(1)),
 
         // Date/Time
         "date_format" => (1, Some(3)),
         "to_unixtime" => (0, Some(2)),
+        "to_timezone" => (1, Some(2)),
+        "parse_date" | "format_date" => (1, Some(1)),
+        "now" => (0, Some(0)),
 
         // Variable arguments (at least 1)
-        "concat" | "coalesce" | "merge" | "deep_merge" | "and" | "or" | "pick" | "omit"
-        | "from_entries" | "add" | "subtract" | "multiply" | "divide" | "zip" => (1, None),
+        "concat" | "coalesce" | "fallback" | "merge" | "deep_merge" | "and" | "or" | "pick"
+        | "omit" | "from_entries" | "add" | "subtract" | "multiply" | "divide" | "max_of"
+        | "min_of" | "zip" | "values_at" => (1, None),
 
         // Variable arguments (at least 2)
         "zip_with" => (2, None),
@@ -948,6 +1186,13 @@ fn collect_out_refs_from_condition(cond: &V2Condition, refs: &mut HashSet<String
                 collect_out_refs_recursive(arg, refs);
             }
         }
+        V2Condition::Exists(v2_ref) | V2Condition::Missing(v2_ref) => {
+            if let V2Ref::Out(path) = v2_ref
+                && !path.is_empty()
+            {
+                refs.insert(path.clone());
+            }
+        }
         V2Condition::Expr(expr) => {
             collect_out_refs_recursive(expr, refs);
         }
@@ -1100,22 +1345,47 @@ mod tests {
         assert!(is_valid_op("trim"));
         assert!(is_valid_op("concat"));
         assert!(is_valid_op("coalesce"));
+        assert!(is_valid_op("fallback"));
+        assert!(is_valid_op("default"));
         assert!(is_valid_op("lookup_first"));
         assert!(is_valid_op("add"));
         assert!(is_valid_op("subtract"));
         assert!(is_valid_op("multiply"));
         assert!(is_valid_op("divide"));
+        assert!(is_valid_op("max_of"));
+        assert!(is_valid_op("min_of"));
         assert!(is_valid_op("+"));
         assert!(is_valid_op("replace"));
+        assert!(is_valid_op("replace_regex"));
         assert!(is_valid_op("split"));
+        assert!(is_valid_op("substring"));
+        assert!(is_valid_op("join"));
+        assert!(is_valid_op("starts_with"));
+        assert!(is_valid_op("ends_with"));
+        assert!(is_valid_op("paragraphs"));
+        assert!(is_valid_op("parse_query_string"));
+        assert!(is_valid_op("to_json"));
+        assert!(is_valid_op("from_json"));
+        assert!(is_valid_op("base64_decode"));
+        assert!(is_valid_op("hex_encode"));
+        assert!(is_valid_op("sanitize_filename"));
         assert!(is_valid_op("pad_start"));
         assert!(is_valid_op("merge"));
         assert!(is_valid_op("map"));
         assert!(is_valid_op("filter"));
         assert!(is_valid_op("round"));
+        assert!(is_valid_op("mod"));
+        assert!(is_valid_op("sqrt"));
+        assert!(is_valid_op("sum"));
+        assert!(is_valid_op("reverse"));
+        assert!(is_valid_op("count"));
+        assert!(is_valid_op("length"));
+        assert!(is_valid_op("max_by"));
         assert!(is_valid_op("to_base"));
         assert!(is_valid_op("date_format"));
         assert!(is_valid_op("to_unixtime"));
+        assert!(is_valid_op("parse_date"));
+        assert!(is_valid_op("now"));
         assert!(is_valid_op("string"));
         assert!(is_valid_op("gt"));
         assert!(is_valid_op("gte"));
@@ -1124,20 +1394,54 @@ mod tests {
         assert!(is_valid_op("eq"));
         assert!(is_valid_op("ne"));
         assert!(is_valid_op("match"));
+        assert!(is_valid_op("apply_json_patch"));
+        assert!(is_valid_op("assert"));
+        assert!(is_valid_op("assert_type"));
+        assert!(is_valid_op("count_distinct"));
+        assert!(is_valid_op("only"));
         assert!(!is_valid_op("nonexistent_op"));
     }
 
     #[test]
     fn test_op_arg_range() {
         assert_eq!(get_op_arg_range("trim"), (0, Some(0)));
+        assert_eq!(get_op_arg_range("paragraphs"), (0, Some(0)));
+        assert_eq!(get_op_arg_range("parse_query_string"), (0, Some(0)));
+        assert_eq!(get_op_arg_range("from_json"), (0, Some(0)));
+        assert_eq!(get_op_arg_range("hex_decode"), (0, Some(0)));
+        assert_eq!(get_op_arg_range("sanitize_filename"), (0, Some(0)));
+        assert_eq!(get_op_arg_range("format"), (1, Some(2)));
+        assert_eq!(get_op_arg_range("title_case"), (0, Some(0)));
         assert_eq!(get_op_arg_range("multiply"), (1, None));
         assert_eq!(get_op_arg_range("subtract"), (1, None));
         assert_eq!(get_op_arg_range("divide"), (1, None));
         assert_eq!(get_op_arg_range("concat"), (1, None));
         assert_eq!(get_op_arg_range("lookup_first"), (2, Some(4)));
         assert_eq!(get_op_arg_range("split"), (1, Some(1)));
+        assert_eq!(get_op_arg_range("join"), (1, Some(1)));
+        assert_eq!(get_op_arg_range("contains"), (1, Some(1)));
+        assert_eq!(get_op_arg_range("ends_with"), (1, Some(1)));
+        assert_eq!(get_op_arg_range("replace_regex"), (2, Some(2)));
+        assert_eq!(get_op_arg_range("default"), (1, Some(1)));
         assert_eq!(get_op_arg_range("pad_start"), (1, Some(2)));
+        assert_eq!(get_op_arg_range("substring"), (1, Some(2)));
+        assert_eq!(get_op_arg_range("slice"), (1, Some(2)));
+        assert_eq!(get_op_arg_range("take"), (1, Some(1)));
+        assert_eq!(get_op_arg_range("flatten"), (0, Some(1)));
+        assert_eq!(get_op_arg_range("reverse"), (0, Some(0)));
+        assert_eq!(get_op_arg_range("chunk"), (1, Some(1)));
+        assert_eq!(get_op_arg_range("window"), (1, Some(1)));
+        assert_eq!(get_op_arg_range("count_by"), (1, Some(1)));
+        assert_eq!(get_op_arg_range("drop"), (1, Some(1)));
         assert_eq!(get_op_arg_range("round"), (0, Some(1)));
+        assert_eq!(get_op_arg_range("floor"), (0, Some(0)));
+        assert_eq!(get_op_arg_range("pow"), (1, Some(1)));
+        assert_eq!(get_op_arg_range("count_distinct"), (0, Some(1)));
+        assert_eq!(get_op_arg_range("avg"), (0, Some(0)));
+        assert_eq!(get_op_arg_range("count"), (0, Some(0)));
+        assert_eq!(get_op_arg_range("length"), (0, Some(0)));
+        assert_eq!(get_op_arg_range("is_empty"), (0, Some(0)));
+        assert_eq!(get_op_arg_range("sum_by"), (1, Some(1)));
         assert_eq!(get_op_arg_range("zip"), (1, None));
         assert_eq!(get_op_arg_range("gt"), (1, Some(1)));
         assert_eq!(get_op_arg_range("gte"), (1, Some(1)));
@@ -1150,6 +1454,15 @@ mod tests {
         assert_eq!(get_op_arg_range("reduce"), (1, Some(1)));
         assert_eq!(get_op_arg_range("fold"), (2, Some(2)));
         assert_eq!(get_op_arg_range("to_unixtime"), (0, Some(2)));
+        assert_eq!(get_op_arg_range("apply_json_patch"), (1, Some(1)));
+        assert_eq!(get_op_arg_range("assert"), (0, Some(2)));
+        assert_eq!(get_op_arg_range("assert_type"), (1, Some(1)));
+        assert_eq!(get_op_arg_range("to_timezone"), (1, Some(2)));
+        assert_eq!(get_op_arg_range("format_date"), (1, Some(1)));
+        assert_eq!(get_op_arg_range("now"), (0, Some(0)));
+        assert_eq!(get_op_arg_range("values_at"), (1, None));
+        assert_eq!(get_op_arg_range("max_of"), (1, None));
+        assert_eq!(get_op_arg_range("min_of"), (1, None));
     }
 
     #[test]
@@ -1212,6 +1525,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_assert_type_rejects_invalid_literal_spec() {
+        let expr = V2Expr::Pipe(V2Pipe {
+            start: V2Start::Ref(V2Ref::Input("items".to_string())),
+            steps: vec![V2Step::Op(V2OpStep {
+                op: "assert_type".to_string(),
+                args: vec![V2Expr::Pipe(V2Pipe {
+                    start: V2Start::Literal(json!("array<objct>")),
+                    steps: vec![],
+                })],
+            })],
+        });
+        let scope = V2Scope::new();
+        let mut ctx = V2ValidationCtx::new(None);
+
+        validate_v2_expr(&expr, "test", &scope, &mut ctx);
+
+        let errors = ctx.errors();
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].code, ErrorCode::InvalidArgs);
+        assert_eq!(errors[0].path.as_deref(), Some("test[1].args[0]"));
+    }
+
+    #[test]
+    fn test_validate_fallback_rejects_args_after_literal() {
+        let arg = |start: V2Start| {
+            V2Expr::Pipe(V2Pipe {
+                start,
+                steps: vec![],
+            })
+        };
+        let expr = V2Expr::Pipe(V2Pipe {
+            start: V2Start::PipeValue,
+            steps: vec![V2Step::Op(V2OpStep {
+                op: "fallback".to_string(),
+                args: vec![
+                    arg(V2Start::Ref(V2Ref::Input("query.limit".to_string()))),
+                    arg(V2Start::Literal(json!(20))),
+                    arg(V2Start::Ref(V2Ref::Context("limit".to_string()))),
+                ],
+            })],
+        });
+        let scope = V2Scope::new();
+        let mut ctx = V2ValidationCtx::new(None);
+
+        validate_v2_expr(&expr, "test", &scope, &mut ctx);
+
+        let errors = ctx.errors();
+        assert_eq!(errors.len(), 1, "got: {:?}", errors);
+        assert_eq!(errors[0].code, ErrorCode::InvalidArgs);
+        assert_eq!(errors[0].path.as_deref(), Some("test[1].args[2]"));
+    }
+
     #[test]
     fn test_validate_v2_expr_rejects_unimplemented_op() {
         let expr = V2Expr::Pipe(V2Pipe {
@@ -1242,6 +1608,96 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_deprecated_op_replacement() {
+        assert_eq!(deprecated_op_replacement("~="), None);
+        assert_eq!(deprecated_op_replacement("match"), None);
+        assert!(!any_deprecated_ops());
+        with_deprecated_ops(&[("old_op", "new_op")], || {
+            assert!(any_deprecated_ops());
+            assert_eq!(deprecated_op_replacement("old_op"), Some("new_op"));
+            assert_eq!(deprecated_op_replacement("concat"), None);
+        });
+        assert_eq!(deprecated_op_replacement("old_op"), None);
+    }
+
+    #[test]
+    fn test_lookup_options_are_not_counted_as_args() {
+        let literal = |value: JsonValue| {
+            V2Expr::Pipe(V2Pipe {
+                start: V2Start::Literal(value),
+                steps: vec![],
+            })
+        };
+        let validate = |args: Vec<V2Expr>| {
+            let expr = V2Expr::Pipe(V2Pipe {
+                start: V2Start::Literal(json!([])),
+                steps: vec![V2Step::Op(V2OpStep {
+                    op: "lookup".to_string(),
+                    args,
+                })],
+            });
+            let mut ctx = V2ValidationCtx::new(None);
+            validate_v2_expr(&expr, "test", &V2Scope::new(), &mut ctx);
+            ctx.errors().to_vec()
+        };
+
+        let positional = || {
+            vec![
+                literal(json!([])),
+                literal(json!("id")),
+                literal(json!(2)),
+                literal(json!("name")),
+            ]
+        };
+        let mut args = positional();
+        args.push(literal(json!({"coerce": true})));
+        assert!(validate(args).is_empty());
+
+        let mut args = positional();
+        args.push(literal(json!("extra")));
+        let errors = validate(args);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].code, ErrorCode::InvalidArgs);
+
+        let errors = validate(vec![
+            literal(json!("id")),
+            literal(json!(2)),
+            literal(json!({"coerce": "yes"})),
+        ]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path.as_deref(), Some("test[1].args[2].coerce"));
+    }
+
+    #[test]
+    fn test_validate_v2_expr_reports_deprecated_op() {
+        let expr = V2Expr::Pipe(V2Pipe {
+            start: V2Start::Literal(json!("hello")),
+            steps: vec![V2Step::Op(V2OpStep {
+                op: "~=".to_string(),
+                args: vec![V2Expr::Pipe(V2Pipe {
+                    start: V2Start::Literal(json!("^h")),
+                    steps: vec![],
+                })],
+            })],
+        });
+        let scope = V2Scope::new();
+        let mut ctx = V2ValidationCtx::new(None);
+        validate_v2_expr(&expr, "test", &scope, &mut ctx);
+        assert!(!ctx.has_errors());
+        assert!(ctx.deprecations().is_empty());
+
+        let mut ctx = V2ValidationCtx::new(None);
+        with_deprecated_ops(&[("~=", "match")], || {
+            validate_v2_expr(&expr, "test", &scope, &mut ctx)
+        });
+
+        assert!(!ctx.has_errors());
+        assert_eq!(ctx.deprecations().len(), 1);
+        assert_eq!(ctx.deprecations()[0].code, ErrorCode::DeprecatedOp);
+        assert_eq!(ctx.deprecations()[0].path.as_deref(), Some("test[1]"));
+    }
+
     // Reference validation tests
     #[test]
     fn test_validate_item_ref_outside_map() {
@@ -1,29 +1,69 @@
 use std::collections::HashSet;
 
-use crate::error::{ErrorCode, RuleError, ValidationResult};
+use crate::error::{ErrorCode, RuleError, TransformErrorKind, TransformWarning, ValidationResult};
 use crate::locator::YamlLocator;
 use crate::model::{Expr, ExprChain, ExprOp, ExprRef, InputFormat, Mapping, RuleFile};
 use crate::path::{PathToken, parse_path};
+use crate::stats::rule_stats;
 use crate::v2_parser::{is_literal_escape, is_v2_expr, parse_v2_condition, parse_v2_expr};
 use crate::v2_validator::{
-    V2Scope, V2ValidationCtx, collect_out_references, validate_no_cyclic_dependencies,
-    validate_v2_condition, validate_v2_expr,
+    V2Scope, V2ValidationCtx, any_deprecated_ops, collect_out_references,
+    deprecated_op_replacement, validate_no_cyclic_dependencies, validate_v2_condition,
+    validate_v2_expr,
 };
 use serde_json::Value as JsonValue;
 
 pub fn validate_rule_file(rule: &RuleFile) -> ValidationResult {
-    validate_rule_file_with_locator(rule, None)
+    validate_rule_file_with_locator(rule, None).finish(false)
 }
 
 pub fn validate_rule_file_with_source(rule: &RuleFile, source: &str) -> ValidationResult {
     let locator = YamlLocator::from_str(source);
-    validate_rule_file_with_locator(rule, Some(&locator))
+    validate_rule_file_with_locator(rule, Some(&locator)).finish(false)
+}
+
+/// Like `validate_rule_file`, but deprecated ops are reported as errors.
+pub fn validate_rule_file_strict(rule: &RuleFile) -> ValidationResult {
+    validate_rule_file_with_locator(rule, None).finish(true)
+}
+
+/// Like `validate_rule_file_with_source`, but deprecated ops are reported as errors.
+pub fn validate_rule_file_with_source_strict(rule: &RuleFile, source: &str) -> ValidationResult {
+    let locator = YamlLocator::from_str(source);
+    validate_rule_file_with_locator(rule, Some(&locator)).finish(true)
+}
+
+/// Collect deprecated op usages in a rule as transform warnings.
+pub(crate) fn deprecation_warnings(rule: &RuleFile) -> Vec<TransformWarning> {
+    // Nothing to find while no op is deprecated. Otherwise scan the op names first; only a
+    // rule that uses a deprecated op pays for a full validation pass to locate each usage.
+    if !any_deprecated_ops() {
+        return Vec::new();
+    }
+    let uses_deprecated_op = rule_stats(rule)
+        .ops
+        .keys()
+        .any(|op| deprecated_op_replacement(op).is_some());
+    if !uses_deprecated_op {
+        return Vec::new();
+    }
+    validate_rule_file_with_locator(rule, None)
+        .deprecations
+        .into_iter()
+        .map(|err| {
+            let mut warning = TransformWarning::new(TransformErrorKind::Deprecated, err.message);
+            if let Some(path) = err.path {
+                warning = warning.with_path(path);
+            }
+            warning
+        })
+        .collect()
 }
 
-fn validate_rule_file_with_locator(
+fn validate_rule_file_with_locator<'a>(
     rule: &RuleFile,
-    locator: Option<&YamlLocator>,
-) -> ValidationResult {
+    locator: Option<&'a YamlLocator>,
+) -> ValidationCtx<'a> {
     let mut ctx = ValidationCtx::new(locator);
 
     validate_version(rule, &mut ctx);
@@ -33,7 +73,7 @@ fn validate_rule_file_with_locator(
     validate_mappings(rule, &mut ctx);
     validate_finalize(rule, &mut ctx);
 
-    ctx.finish()
+    ctx
 }
 
 fn validate_steps(rule: &RuleFile, ctx: &mut ValidationCtx<'_>) {
@@ -257,6 +297,7 @@ fn validate_finalize(rule: &RuleFile, ctx: &mut ValidationCtx<'_>) {
         for err in v2_ctx.errors() {
             ctx.errors.push(err.clone());
         }
+        ctx.deprecations.extend_from_slice(v2_ctx.deprecations());
     }
 }
 
@@ -323,7 +364,7 @@ fn validate_mappings_list(
         };
         if target_tokens
             .iter()
-            .any(|token| matches!(token, PathToken::Index(_)))
+            .any(|token| !matches!(token, PathToken::Key(_)))
         {
             ctx.push(
                 ErrorCode::InvalidPath,
@@ -447,18 +488,18 @@ fn validate_input(rule: &RuleFile, ctx: &mut ValidationCtx<'_>) {
     }
 
     if let Some(csv) = &rule.input.csv {
-        if csv.delimiter.chars().count() != 1 {
+        if csv.delimiter_byte().is_none() {
             ctx.push(
                 ErrorCode::InvalidDelimiterLength,
-                "csv.delimiter must be a single character",
+                "csv.delimiter must be a single ASCII character",
                 "input.csv.delimiter",
             );
         }
-        if !csv.has_header && csv.columns.is_none() {
+        if csv.quote_byte().is_none() {
             ctx.push(
-                ErrorCode::MissingCsvColumns,
-                "csv.columns is required when has_header=false",
-                "input.csv.columns",
+                ErrorCode::InvalidQuoteLength,
+                "csv.quote must be a single ASCII character",
+                "input.csv.quote",
             );
         }
     }
@@ -591,6 +632,7 @@ fn validate_v2_mapping_expr(
     for err in v2_ctx.errors() {
         ctx.errors.push(err.clone());
     }
+    ctx.deprecations.extend_from_slice(v2_ctx.deprecations());
 }
 
 fn validate_v2_condition_expr(
@@ -637,6 +679,7 @@ fn validate_v2_condition_expr_with_scope(
     for err in v2_ctx.errors() {
         ctx.errors.push(err.clone());
     }
+    ctx.deprecations.extend_from_slice(v2_ctx.deprecations());
 }
 
 fn count_value_fields(mapping: &Mapping) -> usize {
@@ -1289,7 +1332,7 @@ fn out_ref_resolves(tokens: &[PathToken], produced_targets: &HashSet<Vec<PathTok
         .iter()
         .filter_map(|token| match token {
             PathToken::Key(key) => Some(PathToken::Key(key.clone())),
-            PathToken::Index(_) => None,
+            _ => None,
         })
         .collect();
     if key_tokens.is_empty() {
@@ -1885,6 +1928,7 @@ fn literal_string(expr: &Expr) -> Option<&str> {
 struct ValidationCtx<'a> {
     locator: Option<&'a YamlLocator>,
     errors: Vec<RuleError>,
+    deprecations: Vec<RuleError>,
     allow_any_out_ref: bool,
 }
 
@@ -1893,6 +1937,7 @@ impl<'a> ValidationCtx<'a> {
         Self {
             locator,
             errors: Vec::new(),
+            deprecations: Vec::new(),
             allow_any_out_ref: false,
         }
     }
@@ -1908,7 +1953,10 @@ impl<'a> ValidationCtx<'a> {
         self.errors.push(err);
     }
 
-    fn finish(self) -> ValidationResult {
+    fn finish(mut self, strict: bool) -> ValidationResult {
+        if strict {
+            self.errors.append(&mut self.deprecations);
+        }
         if self.errors.is_empty() {
             Ok(())
         } else {
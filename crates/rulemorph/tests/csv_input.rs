@@ -0,0 +1,138 @@
+use rulemorph::{TransformErrorKind, parse_rule_file, transform, validate_rule_file};
+use serde_json::json;
+
+fn csv_rule(csv: &str) -> String {
+    format!(
+        r#"
+version: 2
+input:
+  format: csv
+  csv:
+{csv}
+mappings:
+  - target: "id"
+    source: "id"
+  - target: "name"
+    source: "name"
+"#
+    )
+}
+
+#[test]
+fn semicolon_delimited_input() {
+    let rule = parse_rule_file(&csv_rule(r#"    delimiter: ";""#)).expect("parse rule");
+    validate_rule_file(&rule).expect("valid rule");
+    let output = transform(&rule, "id;name\n1;Alice\n2;\"Bob; Jr.\"\n", None).expect("transform");
+    assert_eq!(
+        output,
+        json!([
+            { "id": "1", "name": "Alice" },
+            { "id": "2", "name": "Bob; Jr." }
+        ])
+    );
+}
+
+#[test]
+fn tab_delimited_input_with_custom_quote() {
+    let rule = parse_rule_file(&csv_rule(
+        r#"    delimiter: "\t"
+    quote: "'""#,
+    ))
+    .expect("parse rule");
+    validate_rule_file(&rule).expect("valid rule");
+    let output =
+        transform(&rule, "id\tname\n1\t'Alice\tSmith'\n2\t\"Bob\"\n", None).expect("transform");
+    assert_eq!(
+        output,
+        json!([
+            { "id": "1", "name": "Alice\tSmith" },
+            { "id": "2", "name": "\"Bob\"" }
+        ])
+    );
+}
+
+#[test]
+fn multi_char_delimiter_and_quote_are_rejected() {
+    let rule = parse_rule_file(&csv_rule(
+        r#"    delimiter: "::"
+    quote: "§""#,
+    ))
+    .expect("parse rule");
+    let errors = validate_rule_file(&rule).unwrap_err();
+    let codes: Vec<(&str, Option<&str>)> = errors
+        .iter()
+        .map(|err| (err.code.as_str(), err.path.as_deref()))
+        .collect();
+    assert_eq!(
+        codes,
+        vec![
+            ("InvalidDelimiterLength", Some("input.csv.delimiter")),
+            ("InvalidQuoteLength", Some("input.csv.quote")),
+        ]
+    );
+}
+
+#[test]
+fn headerless_input_uses_positional_column_names() {
+    let rule = parse_rule_file(
+        r#"
+version: 2
+input:
+  format: csv
+  csv:
+    has_header: false
+mappings:
+  - target: "id"
+    expr: "@input.col0"
+  - target: "name"
+    expr: "@input.col1"
+"#,
+    )
+    .expect("parse rule");
+    validate_rule_file(&rule).expect("valid rule");
+    let output = transform(&rule, "1,Alice\n2,Bob\n", None).expect("transform");
+    assert_eq!(
+        output,
+        json!([
+            { "id": "1", "name": "Alice" },
+            { "id": "2", "name": "Bob" }
+        ])
+    );
+}
+
+#[test]
+fn headerless_input_uses_explicit_columns() {
+    let rule = parse_rule_file(&csv_rule(
+        r#"    has_header: false
+    columns:
+      - { name: "id" }
+      - { name: "name" }"#,
+    ))
+    .expect("parse rule");
+    validate_rule_file(&rule).expect("valid rule");
+    let output = transform(&rule, "1,Alice\n2,Bob\n", None).expect("transform");
+    assert_eq!(
+        output,
+        json!([
+            { "id": "1", "name": "Alice" },
+            { "id": "2", "name": "Bob" }
+        ])
+    );
+}
+
+#[test]
+fn headerless_input_rejects_rows_wider_than_columns() {
+    let rule = parse_rule_file(&csv_rule(
+        r#"    has_header: false
+    columns:
+      - { name: "id" }
+      - { name: "name" }"#,
+    ))
+    .expect("parse rule");
+    let err = transform(&rule, "1,Alice,extra\n", None).unwrap_err();
+    assert_eq!(err.kind, TransformErrorKind::InvalidInput);
+    assert_eq!(
+        err.message,
+        "csv record has 3 fields but csv.columns lists 2"
+    );
+}
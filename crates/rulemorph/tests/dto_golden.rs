@@ -70,7 +70,54 @@ fn dto01_swift() {
     assert_golden_in_fixture(DtoLanguage::Swift, "dto01_basic", "expected_swift.swift");
 }
 
+#[test]
+fn dto01_json_schema() {
+    assert_golden_in_fixture(
+        DtoLanguage::JsonSchema,
+        "dto01_basic",
+        "expected_json_schema.json",
+    );
+}
+
 #[test]
 fn dto02_steps_rust() {
     assert_golden_in_fixture(DtoLanguage::Rust, "dto02_steps", "expected_rust.rs");
 }
+
+#[test]
+fn dto02_steps_json_schema() {
+    assert_golden_in_fixture(
+        DtoLanguage::JsonSchema,
+        "dto02_steps",
+        "expected_json_schema.json",
+    );
+}
+
+#[test]
+fn dto02_steps_go() {
+    assert_golden_in_fixture(DtoLanguage::Go, "dto02_steps", "expected_go.go");
+}
+
+#[test]
+fn dto02_steps_python() {
+    assert_golden_in_fixture(DtoLanguage::Python, "dto02_steps", "expected_python.py");
+}
+
+#[test]
+fn dto03_optional_typescript() {
+    assert_golden_in_fixture(
+        DtoLanguage::TypeScript,
+        "dto03_optional",
+        "expected_typescript.ts",
+    );
+}
+
+#[test]
+fn dto03_optional_go() {
+    assert_golden_in_fixture(DtoLanguage::Go, "dto03_optional", "expected_go.go");
+}
+
+#[test]
+fn dto03_optional_python() {
+    assert_golden_in_fixture(DtoLanguage::Python, "dto03_optional", "expected_python.py");
+}
@@ -0,0 +1,6 @@
+package dto
+
+type Record struct {
+    Name string `json:"name"`
+    Score int64 `json:"score"`
+}
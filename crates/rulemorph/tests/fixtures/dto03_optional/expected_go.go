@@ -0,0 +1,10 @@
+package dto
+
+type Record struct {
+    Id string `json:"id"`
+    Discount *float64 `json:"discount,omitempty"`
+    Tier string `json:"tier"`
+    Nickname *string `json:"nickname,omitempty"`
+    Label string `json:"label"`
+    Vip *bool `json:"vip,omitempty"`
+}
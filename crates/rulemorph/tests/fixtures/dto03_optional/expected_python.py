@@ -0,0 +1,13 @@
+from __future__ import annotations
+
+from dataclasses import dataclass
+from typing import Optional
+
+@dataclass
+class Record:
+    id: str
+    tier: str
+    label: str
+    discount: Optional[float] = None
+    nickname: Optional[str] = None
+    vip: Optional[bool] = None
@@ -0,0 +1,8 @@
+export interface Record {
+  id: string;
+  discount?: number;
+  tier: string;
+  nickname?: string;
+  label: string;
+  vip?: boolean;
+}
@@ -0,0 +1,102 @@
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use rulemorph::{read_input_file, read_input_file_with_limit};
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+const INPUT: &str = "id,name\n1,Alice\n";
+
+fn temp_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rulemorph-input-file-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+fn write_zip(name: &str, entries: &[(&str, &str)]) -> PathBuf {
+    let path = temp_path(name);
+    let mut writer = ZipWriter::new(fs::File::create(&path).unwrap());
+    writer
+        .add_directory("data/", SimpleFileOptions::default())
+        .unwrap();
+    for (entry, contents) in entries {
+        writer
+            .start_file(*entry, SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(contents.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap();
+    path
+}
+
+#[test]
+fn reads_plain_file_unchanged() {
+    let path = temp_path("plain.csv");
+    fs::write(&path, INPUT).unwrap();
+    assert_eq!(read_input_file(&path, None).unwrap(), INPUT);
+}
+
+#[test]
+fn decompresses_gzip_by_extension_and_magic() {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(INPUT.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let gz_path = temp_path("input.csv.gz");
+    fs::write(&gz_path, &compressed).unwrap();
+    assert_eq!(read_input_file(&gz_path, None).unwrap(), INPUT);
+
+    let sniffed_path = temp_path("input-no-extension");
+    fs::write(&sniffed_path, &compressed).unwrap();
+    assert_eq!(read_input_file(&sniffed_path, None).unwrap(), INPUT);
+}
+
+#[test]
+fn reads_single_entry_zip() {
+    let path = write_zip("single.zip", &[("data/input.csv", INPUT)]);
+    assert_eq!(read_input_file(&path, None).unwrap(), INPUT);
+}
+
+#[test]
+fn multi_entry_zip_requires_entry_name() {
+    let path = write_zip("multi.zip", &[("b.csv", "b\n"), ("a.csv", INPUT)]);
+
+    let err = read_input_file(&path, None).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("(a.csv, b.csv)"), "{}", err);
+
+    assert_eq!(read_input_file(&path, Some("a.csv")).unwrap(), INPUT);
+    let err = read_input_file(&path, Some("c.csv")).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+}
+
+#[test]
+fn entry_name_requires_zip_input() {
+    let path = temp_path("entry.csv");
+    fs::write(&path, INPUT).unwrap();
+    let err = read_input_file(&path, Some("a.csv")).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn decompressed_size_is_capped() {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(INPUT.as_bytes()).unwrap();
+    let gz_path = temp_path("capped.csv.gz");
+    fs::write(&gz_path, encoder.finish().unwrap()).unwrap();
+    let zip_path = write_zip("capped.zip", &[("input.csv", INPUT)]);
+
+    for path in [&gz_path, &zip_path] {
+        let limit = INPUT.len() as u64;
+        assert_eq!(
+            read_input_file_with_limit(path, None, limit).unwrap(),
+            INPUT
+        );
+        let err = read_input_file_with_limit(path, None, limit - 1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains("exceeds"), "{}", err);
+    }
+}
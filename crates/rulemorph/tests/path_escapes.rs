@@ -0,0 +1,91 @@
+use rulemorph::{PathError, PathToken, get_path, parse_path, parse_rule_file, transform};
+use serde_json::json;
+
+fn keys(path: &str) -> Vec<PathToken> {
+    parse_path(path).expect("parse path")
+}
+
+fn key(name: &str) -> PathToken {
+    PathToken::Key(name.to_string())
+}
+
+#[test]
+fn backslash_escapes_keep_dots_and_brackets_in_one_key() {
+    assert_eq!(keys(r"user\.name"), vec![key("user.name")]);
+    assert_eq!(
+        keys(r"meta.user\.name.first"),
+        vec![key("meta"), key("user.name"), key("first")]
+    );
+    assert_eq!(keys(r"tags\[0\]"), vec![key("tags[0]")]);
+    assert_eq!(keys(r"a\\b"), vec![key(r"a\b")]);
+    assert_eq!(
+        keys(r"items\.all[1]"),
+        vec![key("items.all"), PathToken::Index(1)]
+    );
+}
+
+#[test]
+fn bracket_quotes_keep_dots_in_one_key() {
+    assert_eq!(keys(r#"["user.name"]"#), vec![key("user.name")]);
+    assert_eq!(
+        keys(r#"meta['user.name'].first"#),
+        vec![key("meta"), key("user.name"), key("first")]
+    );
+}
+
+#[test]
+fn escaped_keys_resolve_verbatim() {
+    let value = json!({
+        "user.name": "flat",
+        "user": { "name": "nested" },
+        "tags[0]": "literal"
+    });
+    let get = |path: &str| {
+        get_path(&value, &keys(path))
+            .expect("resolve path")
+            .map(|value| value.into_owned())
+    };
+    assert_eq!(get(r"user\.name"), Some(json!("flat")));
+    assert_eq!(get(r#"["user.name"]"#), Some(json!("flat")));
+    assert_eq!(get("user.name"), Some(json!("nested")));
+    assert_eq!(get(r"tags\[0\]"), Some(json!("literal")));
+}
+
+#[test]
+fn unterminated_quotes_and_escapes_are_errors() {
+    assert_eq!(parse_path(r#"["user.name"#), Err(PathError::InvalidSyntax));
+    assert_eq!(parse_path(r#"["user.name]"#), Err(PathError::InvalidSyntax));
+    assert_eq!(parse_path(r#"["user\"#), Err(PathError::InvalidEscape));
+}
+
+#[test]
+fn unknown_escapes_keep_the_backslash() {
+    assert_eq!(keys(r"user\name"), vec![key(r"user\name")]);
+    assert_eq!(keys(r"C:\dir.file"), vec![key(r"C:\dir"), key("file")]);
+    assert_eq!(keys(r"user\"), vec![key(r"user\")]);
+    assert_eq!(keys(r"a\\b"), vec![key(r"a\b")]);
+}
+
+#[test]
+fn mappings_read_escaped_sources() {
+    let rule = parse_rule_file(
+        r#"
+version: 1
+input:
+  format: json
+mappings:
+  - target: "escaped"
+    source: 'input.user\.name'
+  - target: "quoted"
+    source: 'input.meta["user.name"]'
+"#,
+    )
+    .expect("parse rule");
+    let output = transform(
+        &rule,
+        r#"[{ "user.name": "Ada", "meta": { "user.name": "Ada" } }]"#,
+        None,
+    )
+    .expect("transform");
+    assert_eq!(output, json!([{ "escaped": "Ada", "quoted": "Ada" }]));
+}
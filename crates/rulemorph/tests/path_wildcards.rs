@@ -0,0 +1,162 @@
+use rulemorph::{
+    PathError, PathToken, TransformErrorKind, get_path, parse_path, parse_rule_file, transform,
+};
+use serde_json::json;
+
+fn resolve(value: &serde_json::Value, path: &str) -> Option<serde_json::Value> {
+    try_resolve(value, path).expect("resolve path")
+}
+
+fn try_resolve(
+    value: &serde_json::Value,
+    path: &str,
+) -> Result<Option<serde_json::Value>, PathError> {
+    let tokens = parse_path(path).expect("parse path");
+    Ok(get_path(value, &tokens)?.map(|value| value.into_owned()))
+}
+
+#[test]
+fn parses_wildcards_and_slices() {
+    assert_eq!(
+        parse_path("items[*].price").unwrap(),
+        vec![
+            PathToken::Key("items".to_string()),
+            PathToken::Wildcard,
+            PathToken::Key("price".to_string()),
+        ]
+    );
+    assert_eq!(
+        parse_path("items[1:3]").unwrap(),
+        vec![
+            PathToken::Key("items".to_string()),
+            PathToken::Slice {
+                start: 1,
+                end: Some(3)
+            },
+        ]
+    );
+    assert_eq!(
+        parse_path("items[:2]").unwrap()[1],
+        PathToken::Slice {
+            start: 0,
+            end: Some(2)
+        }
+    );
+    assert_eq!(
+        parse_path("items[2:]").unwrap()[1],
+        PathToken::Slice {
+            start: 2,
+            end: None
+        }
+    );
+    assert_eq!(parse_path("items[*"), Err(PathError::InvalidSyntax));
+    assert_eq!(parse_path("items[1:2:3]"), Err(PathError::InvalidSyntax));
+    assert_eq!(parse_path("items[**]"), Err(PathError::InvalidSyntax));
+}
+
+#[test]
+fn wildcard_collects_a_field_from_every_element() {
+    let value = json!({
+        "items": [
+            { "price": 10 },
+            { "name": "no price" },
+            { "price": 30 }
+        ]
+    });
+    assert_eq!(resolve(&value, "items[*].price"), Some(json!([10, 30])));
+    assert_eq!(resolve(&value, "items[*]"), Some(value["items"].clone()));
+}
+
+#[test]
+fn wildcard_over_an_object_selects_its_values() {
+    let value = json!({ "prices": { "a": 1, "b": 2 } });
+    assert_eq!(resolve(&value, "prices[*]"), Some(json!([1, 2])));
+}
+
+#[test]
+fn nested_wildcards_flatten_matches() {
+    let value = json!({
+        "orders": [
+            { "lines": [{ "sku": "a" }, { "sku": "b" }] },
+            { "lines": [{ "sku": "c" }] }
+        ]
+    });
+    assert_eq!(
+        resolve(&value, "orders[*].lines[*].sku"),
+        Some(json!(["a", "b", "c"]))
+    );
+}
+
+#[test]
+fn slices_select_a_range_and_clamp_bounds() {
+    let value = json!({ "items": [0, 1, 2, 3, 4] });
+    assert_eq!(resolve(&value, "items[1:3]"), Some(json!([1, 2])));
+    assert_eq!(resolve(&value, "items[:2]"), Some(json!([0, 1])));
+    assert_eq!(resolve(&value, "items[3:]"), Some(json!([3, 4])));
+    assert_eq!(resolve(&value, "items[3:99]"), Some(json!([3, 4])));
+    assert_eq!(resolve(&value, "items[9:]"), Some(json!([])));
+}
+
+#[test]
+fn wildcards_and_slices_on_non_collections_are_errors() {
+    let value = json!({ "name": "widget", "count": 3, "lines": [{ "tags": "x" }] });
+    assert_eq!(
+        try_resolve(&value, "name[*]"),
+        Err(PathError::NotACollection)
+    );
+    assert_eq!(
+        try_resolve(&value, "count[0:1]"),
+        Err(PathError::NotACollection)
+    );
+    assert_eq!(
+        try_resolve(&value, "lines[*].tags[*]"),
+        Err(PathError::NotACollection)
+    );
+    assert_eq!(
+        try_resolve(&json!({ "prices": { "a": 1 } }), "prices[0:1]"),
+        Err(PathError::NotACollection)
+    );
+    assert_eq!(resolve(&value, "missing[*].price"), None);
+}
+
+#[test]
+fn mappings_read_wildcard_sources() {
+    let rule = parse_rule_file(
+        r#"
+version: 1
+input:
+  format: json
+mappings:
+  - target: "prices"
+    source: "input.items[*].price"
+  - target: "first_two"
+    source: "input.items[:2]"
+  - target: "tags"
+    source: "input.tags[*]"
+    required: true
+"#,
+    )
+    .expect("parse rule");
+
+    let output = transform(
+        &rule,
+        r#"[{ "items": [{ "price": 1 }, { "price": 2 }, { "price": 3 }], "tags": ["x"] }]"#,
+        None,
+    )
+    .expect("transform");
+    assert_eq!(
+        output,
+        json!([{
+            "prices": [1, 2, 3],
+            "first_two": [{ "price": 1 }, { "price": 2 }],
+            "tags": ["x"]
+        }])
+    );
+
+    let err = transform(&rule, r#"[{ "items": [] }]"#, None).unwrap_err();
+    assert_eq!(err.kind, TransformErrorKind::MissingRequired);
+
+    let err = transform(&rule, r#"[{ "items": [], "tags": "x" }]"#, None).unwrap_err();
+    assert_eq!(err.kind, TransformErrorKind::InvalidRef);
+    assert_eq!(err.path.as_deref(), Some("mappings[2].source"));
+}
@@ -0,0 +1,145 @@
+use rulemorph::{
+    ArrayColumns, OutputFormat, coerce_record_to_columns, coerce_records_to_columns,
+    parse_rule_file, records_to_csv,
+};
+use serde_json::json;
+
+#[test]
+fn heterogeneous_records_share_union_of_columns() {
+    let records = vec![
+        json!({ "id": 1, "user": { "name": "Ann", "address": { "city": "Tokyo" } } }),
+        json!({ "id": 2, "user": { "name": "Bob" }, "note": "vip" }),
+        json!({ "id": 3, "active": true, "score": 1.5, "user": null }),
+    ];
+    let table = coerce_records_to_columns(&records, &ArrayColumns::default());
+
+    assert_eq!(
+        table.columns,
+        vec![
+            "id",
+            "user.address.city",
+            "user.name",
+            "note",
+            "active",
+            "score",
+            "user"
+        ]
+    );
+    assert_eq!(
+        table.rows,
+        vec![
+            vec!["1", "Tokyo", "Ann", "", "", "", ""],
+            vec!["2", "", "Bob", "vip", "", "", ""],
+            vec!["3", "", "", "", "true", "1.5", ""],
+        ]
+    );
+}
+
+#[test]
+fn scalar_arrays_follow_policy() {
+    let records = vec![
+        json!({ "id": 1, "tags": ["a", "b", null] }),
+        json!({ "id": 2, "tags": [] }),
+        json!({ "id": 3, "tags": [1, 2] }),
+    ];
+
+    let joined = coerce_records_to_columns(&records, &ArrayColumns::Join("|".to_string()));
+    assert_eq!(joined.columns, vec!["id", "tags"]);
+    assert_eq!(
+        joined.rows,
+        vec![vec!["1", "a|b|"], vec!["2", ""], vec!["3", "1|2"]]
+    );
+
+    let indexed = coerce_records_to_columns(&records, &ArrayColumns::Index);
+    assert_eq!(indexed.columns, vec!["id", "tags[0]", "tags[1]", "tags[2]"]);
+    assert_eq!(
+        indexed.rows,
+        vec![
+            vec!["1", "a", "b", ""],
+            vec!["2", "", "", ""],
+            vec!["3", "1", "2", ""],
+        ]
+    );
+}
+
+#[test]
+fn nested_arrays_and_non_objects_become_single_cells() {
+    let records = vec![
+        json!({ "items": [{ "sku": "A" }, { "sku": "B" }] }),
+        json!("plain"),
+    ];
+    let table = coerce_records_to_columns(&records, &ArrayColumns::Index);
+    assert_eq!(table.columns, vec!["items", "value"]);
+    assert_eq!(
+        table.rows,
+        vec![vec![r#"[{"sku":"A"},{"sku":"B"}]"#, ""], vec!["", "plain"],]
+    );
+}
+
+#[test]
+fn single_record_uses_given_columns() {
+    let columns = vec!["name".to_string(), "id".to_string(), "missing".to_string()];
+    let row = coerce_record_to_columns(
+        &json!({ "id": 7, "name": "Ann", "extra": true }),
+        &columns,
+        &ArrayColumns::default(),
+    );
+    assert_eq!(row, vec!["Ann", "7", ""]);
+}
+
+#[test]
+fn csv_output_uses_union_of_top_level_keys() {
+    let records = vec![
+        json!({ "id": 1, "name": "Ann, Jr." }),
+        json!({ "id": 2, "active": true, "name": null }),
+        json!({ "note": "line\nbreak" }),
+    ];
+    let csv = records_to_csv(&records).expect("csv");
+    assert_eq!(
+        csv,
+        "id,name,active,note\n1,\"Ann, Jr.\",,\n2,,true,\n,,,\"line\nbreak\"\n"
+    );
+}
+
+#[test]
+fn csv_output_sorts_the_keys_of_each_record() {
+    let records = vec![
+        json!({ "name": "a", "id": 1 }),
+        json!({ "zone": 2, "code": 3 }),
+    ];
+    let csv = records_to_csv(&records).expect("csv");
+    assert_eq!(csv, "id,name,code,zone\n1,a,,\n,,3,2\n");
+}
+
+#[test]
+fn csv_output_writes_nested_values_as_json() {
+    let records = vec![json!({
+        "id": 1,
+        "user": { "name": "Ann" },
+        "tags": ["a", "b"]
+    })];
+    let csv = records_to_csv(&records).expect("csv");
+    assert_eq!(
+        csv,
+        "id,tags,user\n1,\"[\"\"a\"\",\"\"b\"\"]\",\"{\"\"name\"\":\"\"Ann\"\"}\"\n"
+    );
+}
+
+#[test]
+fn output_format_is_read_from_rule() {
+    let rule = parse_rule_file(
+        r#"
+version: 2
+input:
+  format: json
+  json: {}
+output:
+  format: csv
+mappings:
+  - target: "id"
+    source: "id"
+"#,
+    )
+    .expect("parse rule");
+    assert_eq!(rule.output_format(), OutputFormat::Csv);
+}
@@ -0,0 +1,46 @@
+use rulemorph::{
+    RULE_CACHE_CAPACITY, clear_rule_cache, parse_rule_file, rule_cache_len, set_rule_cache_capacity,
+};
+
+fn rule_yaml(target: &str) -> String {
+    format!(
+        r#"
+version: 1
+input:
+  format: json
+  json: {{}}
+mappings:
+  - target: "{target}"
+    source: "id"
+"#
+    )
+}
+
+// The cache is process-wide, so every step runs in one test.
+#[test]
+fn rule_cache_evicts_at_capacity_and_clears() {
+    set_rule_cache_capacity(2);
+    clear_rule_cache();
+    assert_eq!(rule_cache_len(), 0);
+
+    for target in ["a", "b", "c"] {
+        parse_rule_file(&rule_yaml(target)).expect("parse rule");
+    }
+    assert_eq!(rule_cache_len(), 2);
+
+    let cached = parse_rule_file(&rule_yaml("c")).expect("cached rule");
+    assert_eq!(cached.mappings[0].target, "c");
+    set_rule_cache_capacity(1);
+    assert_eq!(rule_cache_len(), 1);
+
+    clear_rule_cache();
+    assert_eq!(rule_cache_len(), 0);
+
+    set_rule_cache_capacity(0);
+    parse_rule_file(&rule_yaml("d")).expect("parse rule");
+    assert_eq!(rule_cache_len(), 0);
+
+    set_rule_cache_capacity(RULE_CACHE_CAPACITY);
+    parse_rule_file(&rule_yaml("e")).expect("parse rule");
+    assert_eq!(rule_cache_len(), 1);
+}
@@ -0,0 +1,71 @@
+use std::path::{Path, PathBuf};
+
+use rulemorph::{
+    ErrorCode, TransformErrorKind, load_rule_file_with_includes, parse_rule_file, transform,
+    validate_rule_file_with_includes,
+};
+use serde_json::json;
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+}
+
+#[test]
+fn i01_include_merge() {
+    let path = fixtures_dir().join("i01_include_merge").join("rules.yaml");
+    let resolved = load_rule_file_with_includes(&path).expect("failed to load rules");
+    validate_rule_file_with_includes(&resolved).expect("validation failed");
+
+    let targets: Vec<&str> = resolved
+        .rule
+        .mappings
+        .iter()
+        .map(|mapping| mapping.target.as_str())
+        .collect();
+    assert_eq!(targets, vec!["kind", "id", "status", "name"]);
+    assert!(resolved.rule.include.is_empty());
+    assert!(resolved.error_locator().is_none());
+
+    let output =
+        transform(&resolved.rule, r#"[{ "id": 7, "name": "a" }]"#, None).expect("transform failed");
+    assert_eq!(
+        output,
+        json!([{ "kind": "record", "id": 7, "status": "local", "name": "a" }])
+    );
+}
+
+#[test]
+fn unresolved_includes_fail_to_transform() {
+    let path = fixtures_dir().join("i01_include_merge").join("rules.yaml");
+    let rule =
+        parse_rule_file(&std::fs::read_to_string(path).expect("read rules")).expect("parse rules");
+    let err = transform(&rule, r#"[{ "id": 7 }]"#, None).expect_err("expected error");
+    assert_eq!(err.kind, TransformErrorKind::InvalidInput);
+    assert_eq!(err.path.as_deref(), Some("include"));
+}
+
+#[test]
+fn i02_include_cycle() {
+    let dir = fixtures_dir().join("i02_include_cycle");
+    let err = load_rule_file_with_includes(&dir.join("rules.yaml")).expect_err("expected cycle");
+    assert_eq!(err.code, ErrorCode::IncludeCycle);
+    assert_eq!(err.file.as_deref(), Some(dir.join("b.yaml").as_path()));
+    assert_eq!(err.path.as_deref(), Some("include[0]"));
+    assert_eq!(err.location.map(|location| location.line), Some(2));
+}
+
+#[test]
+fn i03_include_invalid() {
+    let dir = fixtures_dir().join("i03_include_invalid");
+    let resolved = load_rule_file_with_includes(&dir.join("rules.yaml")).expect("failed to load");
+    let errors = validate_rule_file_with_includes(&resolved).expect_err("expected errors");
+
+    assert_eq!(errors.len(), 1, "unexpected errors: {errors:?}");
+    let err = &errors[0];
+    assert_eq!(err.code, ErrorCode::MissingMappingValue);
+    assert_eq!(err.file.as_deref(), Some(dir.join("shared.yaml").as_path()));
+    assert_eq!(err.path.as_deref(), Some("mappings[1]"));
+    assert_eq!(err.location.as_ref().map(|location| location.line), Some(4));
+}
@@ -0,0 +1,75 @@
+use rulemorph::{parse_rule_file, rule_stats};
+use serde_json::json;
+
+#[test]
+fn collects_ops_paths_targets_and_depth() {
+    let yaml = r#"
+version: 2
+input:
+  format: json
+  json: {}
+record_when:
+  all:
+    - { gt: ["@input.age", 17] }
+    - { eq: ["@context.region", "jp"] }
+mappings:
+  - target: "id"
+    source: "id"
+  - target: "name"
+    expr: ["@input.first", { concat: [" ", ["@input.last", "trim"]] }, "trim"]
+  - target: "tier"
+    expr: ["@context.tiers", { lookup_first: ["code", "@input.tier", "name"] }]
+    when: { ne: ["@input.tier", null] }
+steps:
+  - mappings:
+      - target: "label"
+        expr: ["@out.name", "uppercase"]
+finalize:
+  filter: { ne: ["@out.id", null] }
+"#;
+    let rule = parse_rule_file(yaml).expect("failed to parse rules");
+    let stats = rule_stats(&rule);
+
+    assert_eq!(
+        stats.to_json(),
+        json!({
+            "ops": {
+                "all": 1,
+                "concat": 1,
+                "eq": 1,
+                "gt": 1,
+                "lookup_first": 1,
+                "ne": 2,
+                "trim": 2,
+                "uppercase": 1
+            },
+            "input_paths": ["age", "first", "id", "last", "tier"],
+            "context_paths": ["region", "tiers"],
+            "output_targets": ["id", "label", "name", "tier"],
+            "max_pipe_depth": 3
+        })
+    );
+}
+
+#[test]
+fn v1_rules_count_ops_and_refs() {
+    let yaml = r#"
+version: 1
+input:
+  format: json
+mappings:
+  - target: "full"
+    expr:
+      op: "concat"
+      args:
+        - { ref: "input.first" }
+        - { op: "trim", args: [{ ref: "context.suffix" }] }
+"#;
+    let rule = parse_rule_file(yaml).expect("failed to parse rules");
+    let stats = rule_stats(&rule);
+    assert_eq!(stats.ops.get("concat"), Some(&1));
+    assert_eq!(stats.ops.get("trim"), Some(&1));
+    assert!(stats.input_paths.contains("first"));
+    assert!(stats.context_paths.contains("suffix"));
+    assert_eq!(stats.max_pipe_depth, 3);
+}
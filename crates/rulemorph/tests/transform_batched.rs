@@ -0,0 +1,84 @@
+use rulemorph::{
+    BatchLimits, TransformBatch, TransformErrorKind, parse_rule_file, transform_stream_batched,
+};
+use serde_json::json;
+
+const RULE: &str = r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "id"
+    source: "id"
+"#;
+
+const INPUT: &str = r#"[{ "id": 1 }, { "id": 2 }, { "id": 3 }, { "id": 4 }, { "id": 5 }]"#;
+
+fn collect_batches(limits: BatchLimits) -> Vec<TransformBatch> {
+    let rule = parse_rule_file(RULE).expect("failed to parse rules");
+    let mut batches = Vec::new();
+    transform_stream_batched(&rule, INPUT, None, limits, |batch| {
+        batches.push(batch);
+        Ok(())
+    })
+    .expect("batched transform failed");
+    batches
+}
+
+#[test]
+fn batches_by_record_count() {
+    let batches = collect_batches(BatchLimits {
+        max_records: Some(2),
+        max_bytes: None,
+    });
+    let sizes: Vec<usize> = batches.iter().map(|batch| batch.records.len()).collect();
+    assert_eq!(sizes, vec![2, 2, 1]);
+    assert_eq!(batches[2].records, vec![json!({ "id": 5 })]);
+}
+
+#[test]
+fn batches_by_serialized_size() {
+    // Each record serializes to `{"id":N}` (8 bytes); two fit in `[a,b]` = 19 bytes.
+    let batches = collect_batches(BatchLimits {
+        max_records: None,
+        max_bytes: Some(19),
+    });
+    let sizes: Vec<usize> = batches.iter().map(|batch| batch.records.len()).collect();
+    assert_eq!(sizes, vec![2, 2, 1]);
+    for batch in &batches {
+        assert_eq!(
+            batch.bytes,
+            serde_json::to_vec(&batch.records).unwrap().len()
+        );
+    }
+}
+
+#[test]
+fn oversized_record_gets_its_own_batch() {
+    let batches = collect_batches(BatchLimits {
+        max_records: None,
+        max_bytes: Some(4),
+    });
+    assert_eq!(batches.len(), 5);
+}
+
+#[test]
+fn sink_error_stops_streaming() {
+    let rule = parse_rule_file(RULE).expect("failed to parse rules");
+    let mut calls = 0;
+    let limits = BatchLimits {
+        max_records: Some(1),
+        max_bytes: None,
+    };
+    let err = transform_stream_batched(&rule, INPUT, None, limits, |_| {
+        calls += 1;
+        Err(rulemorph::TransformError::new(
+            TransformErrorKind::InvalidInput,
+            "upstream rejected batch",
+        ))
+    })
+    .expect_err("expected sink error");
+    assert_eq!(err.message, "upstream rejected batch");
+    assert_eq!(calls, 1);
+}
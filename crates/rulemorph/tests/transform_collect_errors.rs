@@ -0,0 +1,88 @@
+use rulemorph::{TransformErrorKind, parse_rule_file, transform, transform_collect_errors};
+use serde_json::json;
+
+const RULE: &str = r#"
+version: 2
+input:
+  format: json
+record_when:
+  ne: ["@input.skip", true]
+mappings:
+  - target: "id"
+    source: "input.id"
+    required: true
+  - target: "ratio"
+    expr:
+      - "@input.total"
+      - divide: ["@input.count"]
+"#;
+
+const INPUT: &str = r#"[
+  { "id": 1, "total": 10, "count": 2 },
+  { "total": 10, "count": 5 },
+  { "id": 3, "skip": true },
+  { "id": 4, "total": 10, "count": 0 },
+  { "id": 5, "total": 9, "count": 3 }
+]"#;
+
+#[test]
+fn collects_every_failing_record_by_index() {
+    let rule = parse_rule_file(RULE).expect("parse rule");
+    assert!(transform(&rule, INPUT, None).is_err());
+
+    let (outputs, failures) = transform_collect_errors(&rule, INPUT, None).expect("transform");
+    assert_eq!(
+        outputs,
+        vec![
+            json!({ "id": 1, "ratio": 5.0 }),
+            json!({ "id": 5, "ratio": 3.0 })
+        ]
+    );
+    let failures: Vec<(usize, TransformErrorKind, Option<&str>)> = failures
+        .iter()
+        .map(|(index, err)| (*index, err.kind.clone(), err.path.as_deref()))
+        .collect();
+    assert_eq!(
+        failures,
+        vec![
+            (1, TransformErrorKind::MissingRequired, Some("mappings[0]")),
+            (
+                3,
+                TransformErrorKind::ExprError,
+                Some("mappings[1].expr[1].args[0]")
+            ),
+        ]
+    );
+}
+
+#[test]
+fn unreadable_csv_row_is_reported_at_its_index() {
+    let rule = parse_rule_file(
+        r#"
+version: 2
+input:
+  format: csv
+  csv:
+    has_header: false
+    columns:
+      - { name: "id" }
+mappings:
+  - target: "id"
+    source: "id"
+"#,
+    )
+    .expect("parse rule");
+    let (outputs, failures) =
+        transform_collect_errors(&rule, "1\n2\n3,extra\n4\n", None).expect("transform");
+    assert_eq!(outputs, vec![json!({ "id": "1" }), json!({ "id": "2" })]);
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0, 2);
+    assert_eq!(failures[0].1.kind, TransformErrorKind::InvalidInput);
+}
+
+#[test]
+fn unparsable_input_is_still_an_error() {
+    let rule = parse_rule_file(RULE).expect("parse rule");
+    let err = transform_collect_errors(&rule, "not json", None).unwrap_err();
+    assert_eq!(err.kind, TransformErrorKind::InvalidInput);
+}
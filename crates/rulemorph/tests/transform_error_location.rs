@@ -0,0 +1,99 @@
+use rulemorph::{
+    TransformErrorKind, TransformErrorLocator, YamlLocation, parse_rule_file, transform,
+    transform_collect_errors,
+};
+
+fn transform_error(yaml: &str) -> rulemorph::TransformError {
+    let rule = parse_rule_file(yaml).expect("parse rule");
+    transform(&rule, r#"[{ "id": 1, "total": 5 }]"#, None)
+        .unwrap_err()
+        .with_source_location(yaml)
+}
+
+#[test]
+fn divide_by_zero_reports_the_failing_step_line() {
+    let yaml = r#"
+version: 2
+input:
+  format: json
+mappings:
+  - target: "id"
+    source: "input.id"
+  - target: "ratio"
+    expr:
+      - "@input.total"
+      - divide: [0]
+"#;
+    let err = transform_error(yaml);
+    assert_eq!(err.kind, TransformErrorKind::ExprError);
+    assert_eq!(err.path.as_deref(), Some("mappings[1].expr[1].args[0]"));
+    assert_eq!(
+        err.location,
+        Some(YamlLocation {
+            line: 11,
+            column: 7
+        })
+    );
+}
+
+#[test]
+fn flow_style_pipes_report_the_expr_line() {
+    let yaml = r#"
+version: 2
+input:
+  format: json
+steps:
+  - mappings:
+      - target: "ratio"
+        expr: ["@input.total", { divide: [0] }]
+"#;
+    let err = transform_error(yaml);
+    assert_eq!(
+        err.path.as_deref(),
+        Some("steps[0].mappings[0].expr[1].args[0]")
+    );
+    assert_eq!(err.location, Some(YamlLocation { line: 8, column: 9 }));
+}
+
+#[test]
+fn errors_without_a_located_path_keep_no_location() {
+    let rule = parse_rule_file(
+        r#"
+version: 2
+input:
+  format: json
+mappings:
+  - target: "id"
+    source: "input.id"
+"#,
+    )
+    .expect("parse rule");
+    let err = transform(&rule, "not json", None)
+        .unwrap_err()
+        .with_source_location("version: 2\n");
+    assert_eq!(err.location, None);
+}
+
+#[test]
+fn one_locator_places_every_record_error() {
+    let yaml = r#"
+version: 2
+input:
+  format: json
+mappings:
+  - target: "ratio"
+    expr:
+      - "@input.total"
+      - divide: [0]
+"#;
+    let rule = parse_rule_file(yaml).expect("parse rule");
+    let (_, errors) = transform_collect_errors(&rule, r#"[{ "total": 1 }, { "total": 2 }]"#, None)
+        .expect("transform");
+    let locator = TransformErrorLocator::new(yaml);
+    let locations: Vec<_> = errors
+        .into_iter()
+        .map(|(_, err)| locator.locate(err).location)
+        .collect();
+    let expected = Some(YamlLocation { line: 9, column: 7 });
+    assert_eq!(locations, vec![expected.clone(), expected]);
+}
@@ -46,6 +46,8 @@ fn transform_kind_to_str(kind: &TransformErrorKind) -> &'static str {
         TransformErrorKind::TypeCastFailed => "TypeCastFailed",
         TransformErrorKind::ExprError => "ExprError",
         TransformErrorKind::AssertionFailed => "AssertionFailed",
+        TransformErrorKind::TypeAssertionFailed => "TypeAssertionFailed",
+        TransformErrorKind::Deprecated => "Deprecated",
     }
 }
 
@@ -0,0 +1,222 @@
+use rulemorph::{
+    DuplicateKeys, TransformErrorKind, TransformOptions, parse_rule_file, transform_preview,
+    transform_with_options,
+};
+use serde_json::json;
+
+const RULE: &str = r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "id"
+    source: "id"
+  - target: "name"
+    source: "name"
+  - target: "profile"
+    source: "profile"
+"#;
+
+const INPUT: &str = r#"[
+  { "id": 1, "name": null, "profile": { "email": null, "tags": ["a", null], "address": { "zip": null } } }
+]"#;
+
+#[test]
+fn drop_null_fields_removes_nested_null_keys() {
+    let rule = parse_rule_file(RULE).expect("failed to parse rules");
+    let options = TransformOptions {
+        drop_null_fields: true,
+        ..Default::default()
+    };
+    let (output, _) = transform_with_options(&rule, INPUT, None, &options).expect("transform");
+    assert_eq!(
+        output,
+        json!([{ "id": 1, "profile": { "tags": ["a", null], "address": {} } }])
+    );
+}
+
+#[test]
+fn drop_null_items_also_strips_array_nulls() {
+    let rule = parse_rule_file(RULE).expect("failed to parse rules");
+    let options = TransformOptions {
+        drop_null_fields: true,
+        drop_null_items: true,
+        ..Default::default()
+    };
+    let (output, _) = transform_with_options(&rule, INPUT, None, &options).expect("transform");
+    assert_eq!(
+        output,
+        json!([{ "id": 1, "profile": { "tags": ["a"], "address": {} } }])
+    );
+}
+
+#[test]
+fn default_options_keep_nulls() {
+    let rule = parse_rule_file(RULE).expect("failed to parse rules");
+    let (output, _) = transform_with_options(&rule, INPUT, None, &TransformOptions::default())
+        .expect("transform");
+    assert_eq!(output[0]["name"], json!(null));
+    assert_eq!(output[0]["profile"]["email"], json!(null));
+}
+
+const KEYED_RULE: &str = r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "user.id"
+    source: "id"
+  - target: "name"
+    source: "name"
+"#;
+
+#[test]
+fn key_by_returns_object_keyed_by_path() {
+    let rule = parse_rule_file(KEYED_RULE).expect("failed to parse rules");
+    let options = TransformOptions {
+        key_by: Some("user.id"),
+        ..Default::default()
+    };
+    let input = r#"[{ "id": "a", "name": "Ann" }, { "id": 7, "name": "Bob" }]"#;
+    let (output, warnings) =
+        transform_with_options(&rule, input, None, &options).expect("transform");
+    assert!(warnings.is_empty());
+    assert_eq!(
+        output,
+        json!({
+            "a": { "user": { "id": "a" }, "name": "Ann" },
+            "7": { "user": { "id": 7 }, "name": "Bob" }
+        })
+    );
+}
+
+#[test]
+fn key_by_duplicate_keys_error_by_default() {
+    let rule = parse_rule_file(KEYED_RULE).expect("failed to parse rules");
+    let options = TransformOptions {
+        key_by: Some("user.id"),
+        ..Default::default()
+    };
+    let input = r#"[{ "id": 1, "name": "Ann" }, { "id": 1, "name": "Bob" }]"#;
+    let err = transform_with_options(&rule, input, None, &options).expect_err("expected error");
+    assert_eq!(err.kind, TransformErrorKind::InvalidTarget);
+    assert_eq!(err.path.as_deref(), Some("records[1].user.id"));
+}
+
+#[test]
+fn key_by_duplicate_keys_warn_keeps_last() {
+    let rule = parse_rule_file(KEYED_RULE).expect("failed to parse rules");
+    let options = TransformOptions {
+        key_by: Some("user.id"),
+        duplicate_keys: DuplicateKeys::Warn,
+        ..Default::default()
+    };
+    let input = r#"[{ "id": 1, "name": "Ann" }, { "id": 1, "name": "Bob" }]"#;
+    let (output, warnings) =
+        transform_with_options(&rule, input, None, &options).expect("transform");
+    assert_eq!(
+        output,
+        json!({ "1": { "user": { "id": 1 }, "name": "Bob" } })
+    );
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].path.as_deref(), Some("records[1].user.id"));
+}
+
+#[test]
+fn key_by_missing_key_errors() {
+    let rule = parse_rule_file(KEYED_RULE).expect("failed to parse rules");
+    let options = TransformOptions {
+        key_by: Some("user.email"),
+        ..Default::default()
+    };
+    let input = r#"[{ "id": 1, "name": "Ann" }]"#;
+    let err = transform_with_options(&rule, input, None, &options).expect_err("expected error");
+    assert_eq!(err.kind, TransformErrorKind::MissingRequired);
+}
+
+const REQUIRED_RULE: &str = r#"
+version: 1
+input:
+  format: json
+mappings:
+  - target: "id"
+    source: "id"
+    required: true
+"#;
+
+#[test]
+fn max_records_stops_before_later_records() {
+    let rule = parse_rule_file(REQUIRED_RULE).expect("failed to parse rules");
+    let options = TransformOptions {
+        max_records: Some(2),
+        ..Default::default()
+    };
+    // The third record would fail the transform if it were evaluated.
+    let input = r#"[{ "id": 1 }, { "id": 2 }, {}]"#;
+    let preview = transform_preview(&rule, input, None, &options).expect("transform");
+    assert_eq!(preview.output, json!([{ "id": 1 }, { "id": 2 }]));
+    assert!(preview.has_more);
+
+    let options = TransformOptions {
+        max_records: Some(3),
+        ..Default::default()
+    };
+    let input = r#"[{ "id": 1 }, { "id": 2 }]"#;
+    let preview = transform_preview(&rule, input, None, &options).expect("transform");
+    assert_eq!(preview.output, json!([{ "id": 1 }, { "id": 2 }]));
+    assert!(!preview.has_more);
+}
+
+#[test]
+fn max_records_ignores_skipped_records_when_checking_for_more() {
+    let yaml = r#"
+version: 1
+input:
+  format: json
+record_when:
+  op: "=="
+  args:
+    - { ref: "input.keep" }
+    - true
+mappings:
+  - target: "id"
+    source: "id"
+"#;
+    let rule = parse_rule_file(yaml).expect("failed to parse rules");
+    let options = TransformOptions {
+        max_records: Some(1),
+        ..Default::default()
+    };
+    let input = r#"[{ "id": 1, "keep": true }, { "id": 2, "keep": false }]"#;
+    let preview = transform_preview(&rule, input, None, &options).expect("transform");
+    assert_eq!(preview.output, json!([{ "id": 1 }]));
+    assert!(!preview.has_more);
+}
+
+#[test]
+fn max_records_applies_after_finalize() {
+    let yaml = r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "id"
+    source: "id"
+finalize:
+  sort:
+    by: "id"
+    order: desc
+"#;
+    let rule = parse_rule_file(yaml).expect("failed to parse rules");
+    let options = TransformOptions {
+        max_records: Some(2),
+        ..Default::default()
+    };
+    let input = r#"[{ "id": 1 }, { "id": 3 }, { "id": 2 }]"#;
+    let preview = transform_preview(&rule, input, None, &options).expect("transform");
+    assert_eq!(preview.output, json!([{ "id": 3 }, { "id": 2 }]));
+    assert!(preview.has_more);
+}
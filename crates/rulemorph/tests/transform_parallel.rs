@@ -0,0 +1,100 @@
+use rulemorph::{TransformErrorKind, parse_rule_file, transform, transform_parallel};
+use serde_json::json;
+
+const STEPS_RULE: &str = r#"
+version: 2
+input:
+  format: json
+  json: {}
+steps:
+  - mappings:
+      - target: "id"
+        source: "id"
+      - target: "label"
+        expr: ["@input.name", { concat: ["-", "@out.id"] }]
+  - record_when:
+      ne: ["@input.skip", true]
+"#;
+
+fn numbered_input(count: usize) -> String {
+    let records: Vec<_> = (0..count)
+        .map(|i| json!({ "id": i.to_string(), "name": format!("item-{}", i), "skip": i % 7 == 3 }))
+        .collect();
+    serde_json::to_string(&records).unwrap()
+}
+
+#[test]
+fn parallel_output_matches_sequential_order() {
+    let rule = parse_rule_file(STEPS_RULE).expect("parse rule");
+    let input = numbered_input(2000);
+
+    let sequential = transform(&rule, &input, None).expect("sequential");
+    let parallel = transform_parallel(&rule, &input, None).expect("parallel");
+    assert_eq!(parallel, sequential);
+    assert_eq!(parallel[0], json!({ "id": "0", "label": "item-0-0" }));
+    assert_eq!(parallel.as_array().unwrap().len(), 1714);
+}
+
+#[test]
+fn parallel_applies_finalize_after_all_records() {
+    let rule = parse_rule_file(
+        r#"
+version: 2
+input:
+  format: csv
+  csv: {}
+mappings:
+  - target: "id"
+    source: "id"
+    type: "int"
+finalize:
+  sort:
+    by: "id"
+    order: "desc"
+  limit: 3
+"#,
+    )
+    .expect("parse rule");
+    let input = (0..500).fold("id\n".to_string(), |mut csv, i| {
+        csv.push_str(&format!("{}\n", (i * 37) % 500));
+        csv
+    });
+
+    let sequential = transform(&rule, &input, None).expect("sequential");
+    let parallel = transform_parallel(&rule, &input, None).expect("parallel");
+    assert_eq!(parallel, sequential);
+    assert_eq!(
+        parallel,
+        json!([{ "id": 499 }, { "id": 498 }, { "id": 497 }])
+    );
+}
+
+#[test]
+fn parallel_reports_the_earliest_failing_record() {
+    let rule = parse_rule_file(
+        r#"
+version: 1
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "id"
+    source: "input.id"
+    required: true
+  - target: "n"
+    source: "input.n"
+    type: "int"
+"#,
+    )
+    .expect("parse rule");
+    let mut records: Vec<_> = (0..1000).map(|i| json!({ "id": i, "n": i })).collect();
+    records[400] = json!({ "id": 400, "n": "not a number" });
+    records[900] = json!({ "n": 900 });
+    let input = serde_json::to_string(&records).unwrap();
+
+    let sequential = transform(&rule, &input, None).unwrap_err();
+    let parallel = transform_parallel(&rule, &input, None).unwrap_err();
+    assert_eq!(parallel.kind, TransformErrorKind::TypeCastFailed);
+    assert_eq!(parallel.kind, sequential.kind);
+    assert_eq!(parallel.to_string(), sequential.to_string());
+}
@@ -0,0 +1,188 @@
+use std::cell::Cell;
+use std::io::{BufReader, Read};
+use std::rc::Rc;
+
+use rulemorph::{
+    RuleFile, TransformErrorKind, parse_rule_file, transform_stream, transform_stream_reader,
+};
+use serde_json::{Value as JsonValue, json};
+
+const RULE: &str = r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "id"
+    source: "id"
+"#;
+
+fn rule() -> RuleFile {
+    parse_rule_file(RULE).expect("failed to parse rules")
+}
+
+fn read_all(rule: &RuleFile, input: &str) -> Result<Vec<JsonValue>, rulemorph::TransformError> {
+    transform_stream_reader(rule, input.as_bytes(), None)?
+        .filter_map(|item| item.map(|item| item.output).transpose())
+        .collect()
+}
+
+/// Generates `total` NDJSON (or JSON array) records on demand and counts how
+/// many bytes have been handed out so far.
+struct SyntheticRecords {
+    next: usize,
+    total: usize,
+    array: bool,
+    pending: Vec<u8>,
+    produced: Rc<Cell<usize>>,
+}
+
+impl SyntheticRecords {
+    fn new(total: usize, array: bool, produced: Rc<Cell<usize>>) -> Self {
+        let pending = if array { b"[".to_vec() } else { Vec::new() };
+        Self {
+            next: 0,
+            total,
+            array,
+            pending,
+            produced,
+        }
+    }
+}
+
+impl Read for SyntheticRecords {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.pending.is_empty() && self.next < self.total {
+            let separator = match (self.array, self.next) {
+                (true, 0) => "",
+                (true, _) => ",",
+                (false, _) => "",
+            };
+            let newline = if self.array { "" } else { "\n" };
+            self.pending = format!(
+                "{}{{\"id\":{},\"payload\":\"{}\"}}{}",
+                separator,
+                self.next,
+                "x".repeat(64),
+                newline
+            )
+            .into_bytes();
+            self.next += 1;
+            if self.array && self.next == self.total {
+                self.pending.push(b']');
+            }
+        }
+        let len = self.pending.len().min(buf.len());
+        buf[..len].copy_from_slice(&self.pending[..len]);
+        self.pending.drain(..len);
+        self.produced.set(self.produced.get() + len);
+        Ok(len)
+    }
+}
+
+fn assert_reads_lazily(array: bool) {
+    let rule = rule();
+    let total = 100_000;
+    let produced = Rc::new(Cell::new(0));
+    let reader = BufReader::new(SyntheticRecords::new(total, array, produced.clone()));
+    let mut stream = transform_stream_reader(&rule, reader, None).expect("stream");
+
+    let first = stream.next().expect("first item").expect("first record");
+    assert_eq!(first.output, Some(json!({ "id": 0 })));
+    // Only the first buffer of a ~9 MB input has been read.
+    assert!(produced.get() < 64 * 1024, "read {} bytes", produced.get());
+
+    let mut count = 1;
+    let mut last = None;
+    for item in stream {
+        last = item.expect("record").output;
+        count += 1;
+    }
+    assert_eq!(count, total);
+    assert_eq!(last, Some(json!({ "id": total - 1 })));
+}
+
+#[test]
+fn ndjson_reader_streams_record_by_record() {
+    assert_reads_lazily(false);
+}
+
+#[test]
+fn json_array_reader_streams_record_by_record() {
+    assert_reads_lazily(true);
+}
+
+#[test]
+fn reader_matches_string_stream() {
+    let rule = rule();
+    let input = r#"[ { "id": 1 }, { "id": "a,]}" }, { "id": [1, { "x": 2 }] }, 4 ]"#;
+    let expected: Vec<JsonValue> = transform_stream(&rule, input, None)
+        .expect("stream")
+        .filter_map(|item| item.expect("record").output)
+        .collect();
+    assert_eq!(read_all(&rule, input).expect("reader"), expected);
+    assert_eq!(
+        read_all(&rule, "{\"id\":1}\n{\"id\":2}\n").expect("ndjson"),
+        vec![json!({ "id": 1 }), json!({ "id": 2 })]
+    );
+    assert_eq!(
+        read_all(&rule, " [ ] ").expect("empty array"),
+        Vec::<JsonValue>::new()
+    );
+}
+
+#[test]
+fn malformed_records_report_their_index() {
+    let rule = rule();
+    let err = read_all(&rule, r#"[{ "id": 1 }, { "id": }]"#).unwrap_err();
+    assert_eq!(err.kind, TransformErrorKind::InvalidInput);
+    assert!(err.message.contains("record 1"), "{}", err.message);
+
+    let err = read_all(&rule, "{\"id\":1}\n{\"id\":2}\nnot json\n").unwrap_err();
+    assert!(err.message.contains("record 2"), "{}", err.message);
+
+    let err = read_all(&rule, r#"[{ "id": 1 }"#).unwrap_err();
+    assert!(err.message.contains("record 1"), "{}", err.message);
+
+    assert!(read_all(&rule, r#"[{ "id": 1 }] {}"#).is_err());
+    assert!(read_all(&rule, "").is_err());
+}
+
+#[test]
+fn records_path_and_csv_inputs_use_the_reader() {
+    let rule = parse_rule_file(
+        r#"
+version: 2
+input:
+  format: json
+  json:
+    records_path: "data"
+mappings:
+  - target: "id"
+    source: "id"
+"#,
+    )
+    .expect("failed to parse rules");
+    assert_eq!(
+        read_all(&rule, r#"{ "data": [{ "id": 1 }, { "id": 2 }] }"#).expect("records_path"),
+        vec![json!({ "id": 1 }), json!({ "id": 2 })]
+    );
+
+    let rule = parse_rule_file(
+        r#"
+version: 2
+input:
+  format: csv
+  csv:
+    has_header: true
+mappings:
+  - target: "id"
+    source: "id"
+"#,
+    )
+    .expect("failed to parse rules");
+    assert_eq!(
+        read_all(&rule, "id\n1\n2\n").expect("csv"),
+        vec![json!({ "id": "1" }), json!({ "id": "2" })]
+    );
+}
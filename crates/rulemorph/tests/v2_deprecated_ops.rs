@@ -0,0 +1,57 @@
+use rulemorph::v2_validator::with_deprecated_ops;
+use rulemorph::{
+    ErrorCode, TransformErrorKind, parse_rule_file, transform_strict, transform_with_warnings,
+    validate_rule_file, validate_rule_file_strict,
+};
+use serde_json::json;
+
+const ALIAS_RULE: &str = r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "is_admin"
+    expr: ["@input.role", { "~=": "^admin" }]
+"#;
+
+#[test]
+fn v2_op_aliases_are_not_deprecated() {
+    let rule = parse_rule_file(ALIAS_RULE).expect("failed to parse rules");
+    let input = r#"[{ "role": "admin-1" }, { "role": "user" }]"#;
+    let (output, warnings) = transform_with_warnings(&rule, input, None).expect("transform failed");
+
+    assert_eq!(output, json!([{ "is_admin": true }, { "is_admin": false }]));
+    assert!(warnings.is_empty(), "unexpected warnings: {warnings:?}");
+    assert_eq!(
+        transform_strict(&rule, input, None).expect("strict transform failed"),
+        json!([{ "is_admin": true }, { "is_admin": false }])
+    );
+    assert_eq!(validate_rule_file_strict(&rule), Ok(()));
+}
+
+#[test]
+fn deprecated_ops_warn_and_fail_strict_mode() {
+    let rule = parse_rule_file(ALIAS_RULE).expect("failed to parse rules");
+    let input = r#"[{ "role": "admin-1" }]"#;
+    with_deprecated_ops(&[("~=", "match")], || {
+        let (output, warnings) =
+            transform_with_warnings(&rule, input, None).expect("transform failed");
+        assert_eq!(output, json!([{ "is_admin": true }]));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, TransformErrorKind::Deprecated);
+        assert_eq!(warnings[0].path.as_deref(), Some("mappings[0].expr[1]"));
+        assert!(
+            warnings[0].message.contains("match"),
+            "{}",
+            warnings[0].message
+        );
+
+        let err = transform_strict(&rule, input, None).expect_err("strict transform must fail");
+        assert_eq!(err.kind, TransformErrorKind::Deprecated);
+
+        let errors = validate_rule_file_strict(&rule).expect_err("strict validation must fail");
+        assert_eq!(errors[0].code, ErrorCode::DeprecatedOp);
+        assert_eq!(validate_rule_file(&rule), Ok(()));
+    });
+}
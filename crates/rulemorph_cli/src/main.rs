@@ -1,19 +1,21 @@
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, Read, Write};
 use std::path::PathBuf;
 
 #[cfg(feature = "server")]
 use clap::ArgAction;
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use rulemorph::{
-    DtoLanguage, InputFormat, RuleError, RuleFile, TransformError, TransformErrorKind,
-    TransformWarning, generate_dto, parse_rule_file,
-    preflight_validate_with_warnings_with_base_dir, transform_stream_with_base_dir,
-    transform_with_warnings_with_base_dir, validate_rule_file_with_source,
+    DtoLanguage, Expr, InputFormat, Mapping, OutputFormat, ResolvedRuleFile, RuleError, RuleFile,
+    RuleStats, TransformError, TransformErrorKind, TransformErrorLocator, TransformWarning,
+    generate_dto, parse_rule_file, preflight_validate_with_warnings_with_base_dir, read_input_file,
+    records_to_csv, resolve_rule_includes, rule_stats, transform_stream_with_base_dir,
+    transform_with_warnings_with_base_dir, validate_rule_file_with_includes,
+    validate_rule_file_with_includes_strict,
 };
 #[cfg(feature = "server")]
 use rulemorph_server::{
-    ApiMode, RulesDirErrors, ServerConfig, run as run_server, validate_rules_dir,
+    ApiMode, RulesDirErrors, ServerConfig, TraceRetention, run as run_server, validate_rules_dir,
 };
 use serde_json::json;
 
@@ -32,6 +34,9 @@ enum Commands {
     ValidateRulesDir(ValidateRulesDirArgs),
     Preflight(PreflightArgs),
     Transform(TransformArgs),
+    Explain(ExplainArgs),
+    Stats(StatsArgs),
+    #[command(alias = "dto")]
     Generate(GenerateArgs),
     #[cfg(feature = "server")]
     Ui(UiArgs),
@@ -43,6 +48,8 @@ struct ValidateArgs {
     rules: PathBuf,
     #[arg(short = 'e', long, default_value = "text")]
     error_format: ErrorFormat,
+    #[arg(long)]
+    strict: bool,
 }
 
 #[cfg(feature = "server")]
@@ -58,8 +65,12 @@ struct ValidateRulesDirArgs {
 struct PreflightArgs {
     #[arg(short = 'r', long)]
     rules: PathBuf,
+    /// Input file; reads stdin when omitted or `-`.
     #[arg(short = 'i', long)]
-    input: PathBuf,
+    input: Option<PathBuf>,
+    /// Entry to read when the input is a zip archive with several files.
+    #[arg(long)]
+    input_entry: Option<String>,
     #[arg(short = 'f', long)]
     format: Option<FormatOverride>,
     #[arg(short = 'c', long)]
@@ -72,13 +83,17 @@ struct PreflightArgs {
 struct TransformArgs {
     #[arg(short = 'r', long)]
     rules: PathBuf,
+    /// Input file; reads stdin when omitted or `-`.
     #[arg(short = 'i', long)]
-    input: PathBuf,
+    input: Option<PathBuf>,
+    /// Entry to read when the input is a zip archive with several files.
+    #[arg(long)]
+    input_entry: Option<String>,
     #[arg(short = 'f', long)]
     format: Option<FormatOverride>,
     #[arg(short = 'c', long)]
     context: Option<PathBuf>,
-    #[arg(short = 'o', long)]
+    #[arg(short = 'o', long, visible_alias = "out")]
     output: Option<PathBuf>,
     #[arg(long)]
     ndjson: bool,
@@ -88,6 +103,26 @@ struct TransformArgs {
     error_format: ErrorFormat,
 }
 
+#[derive(Args)]
+struct ExplainArgs {
+    #[arg(short = 'r', long)]
+    rules: PathBuf,
+    /// Print the summary as JSON.
+    #[arg(long)]
+    json: bool,
+    #[arg(short = 'e', long, default_value = "text")]
+    error_format: ErrorFormat,
+}
+
+#[derive(Args)]
+struct StatsArgs {
+    #[arg(short = 'r', long)]
+    rules: PathBuf,
+    /// Print the stats as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
 #[derive(Args)]
 struct GenerateArgs {
     #[arg(short = 'r', long)]
@@ -96,13 +131,16 @@ struct GenerateArgs {
     lang: DtoLanguageArg,
     #[arg(short = 'n', long)]
     name: Option<String>,
-    #[arg(short = 'o', long)]
+    #[arg(short = 'o', long, visible_alias = "out")]
     output: Option<PathBuf>,
 }
 
 #[cfg(feature = "server")]
 #[derive(Args)]
 struct UiArgs {
+    /// Address to listen on, e.g. 0.0.0.0 to accept outside connections.
+    #[arg(long, default_value_t = ServerConfig::DEFAULT_BIND_ADDR)]
+    bind: std::net::IpAddr,
     #[arg(long, default_value_t = 8080)]
     port: u16,
     #[arg(long)]
@@ -115,6 +153,24 @@ struct UiArgs {
     rules_dir: Option<PathBuf>,
     #[arg(long, action = ArgAction::SetTrue, default_value_t = false)]
     no_ui: bool,
+    /// Trace notifications buffered per UI stream before older ones are dropped.
+    #[arg(long, default_value_t = ServerConfig::DEFAULT_TRACE_CHANNEL_CAPACITY)]
+    trace_channel_capacity: usize,
+    /// Origin allowed to call the rules API from a browser; repeat for more, `*` for any.
+    #[arg(long = "cors-origin")]
+    cors_origins: Vec<String>,
+    /// Delete traces older than this many days.
+    #[arg(long)]
+    trace_max_age_days: Option<u64>,
+    /// Keep at most this many traces, deleting the oldest.
+    #[arg(long)]
+    trace_max_count: Option<usize>,
+    /// Answer 504 when a request runs longer than this; 0 disables the limit.
+    #[arg(long, default_value_t = ServerConfig::DEFAULT_REQUEST_TIMEOUT_MS)]
+    request_timeout_ms: u64,
+    /// Answer 413 when a rules API request body is larger than this many bytes.
+    #[arg(long, default_value_t = ServerConfig::DEFAULT_MAX_BODY_BYTES)]
+    max_body_bytes: usize,
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
@@ -139,6 +195,8 @@ enum DtoLanguageArg {
     Java,
     Kotlin,
     Swift,
+    #[value(name = "json-schema", alias = "jsonschema")]
+    JsonSchema,
 }
 
 #[cfg(feature = "server")]
@@ -157,6 +215,8 @@ fn main() {
         Commands::ValidateRulesDir(args) => run_validate_rules_dir(args),
         Commands::Preflight(args) => run_preflight(args),
         Commands::Transform(args) => run_transform(args),
+        Commands::Explain(args) => run_explain(args),
+        Commands::Stats(args) => run_stats(args),
         Commands::Generate(args) => run_generate(args),
         #[cfg(feature = "server")]
         Commands::Ui(args) => run_ui(args),
@@ -165,12 +225,17 @@ fn main() {
 }
 
 fn run_validate(args: ValidateArgs) -> i32 {
-    let (rule, yaml) = match load_rule(&args.rules) {
+    let resolved = match load_rule(&args.rules) {
         Ok(value) => value,
         Err(code) => return code,
     };
 
-    match validate_rule_file_with_source(&rule, &yaml) {
+    let result = if args.strict {
+        validate_rule_file_with_includes_strict(&resolved)
+    } else {
+        validate_rule_file_with_includes(&resolved)
+    };
+    match result {
         Ok(()) => 0,
         Err(errors) => {
             emit_validation_errors(&errors, args.error_format);
@@ -191,14 +256,16 @@ fn run_validate_rules_dir(args: ValidateRulesDirArgs) -> i32 {
 }
 
 fn run_preflight(args: PreflightArgs) -> i32 {
-    let (mut rule, _) = match load_rule(&args.rules) {
+    let resolved = match load_rule(&args.rules) {
         Ok(value) => value,
         Err(code) => return code,
     };
+    let locator = resolved.error_locator();
+    let mut rule = resolved.rule;
 
     apply_format_override(&mut rule, args.format);
 
-    let input = match load_input(&args.input) {
+    let input = match load_input(&args.input, args.input_entry.as_deref()) {
         Ok(value) => value,
         Err(code) => return code,
     };
@@ -217,7 +284,7 @@ fn run_preflight(args: PreflightArgs) -> i32 {
     ) {
         Ok(warnings) => warnings,
         Err(err) => {
-            emit_transform_error(&err, args.error_format);
+            emit_transform_error(err, locator.as_ref(), args.error_format);
             return 3;
         }
     };
@@ -228,21 +295,23 @@ fn run_preflight(args: PreflightArgs) -> i32 {
 }
 
 fn run_transform(args: TransformArgs) -> i32 {
-    let (mut rule, yaml) = match load_rule(&args.rules) {
+    let mut resolved = match load_rule(&args.rules) {
         Ok(value) => value,
         Err(code) => return code,
     };
 
-    apply_format_override(&mut rule, args.format);
+    apply_format_override(&mut resolved.rule, args.format);
 
     if args.validate {
-        if let Err(errors) = validate_rule_file_with_source(&rule, &yaml) {
+        if let Err(errors) = validate_rule_file_with_includes(&resolved) {
             emit_validation_errors(&errors, args.error_format);
             return 2;
         }
     }
+    let locator = resolved.error_locator();
+    let rule = resolved.rule;
 
-    let input = match load_input(&args.input) {
+    let input = match load_input(&args.input, args.input_entry.as_deref()) {
         Ok(value) => value,
         Err(code) => return code,
     };
@@ -255,6 +324,7 @@ fn run_transform(args: TransformArgs) -> i32 {
     if args.ndjson {
         return run_transform_ndjson(
             &rule,
+            locator.as_ref(),
             &input,
             context_value.as_ref(),
             args.output,
@@ -272,15 +342,15 @@ fn run_transform(args: TransformArgs) -> i32 {
     ) {
         Ok(result) => result,
         Err(err) => {
-            emit_transform_error(&err, args.error_format);
+            emit_transform_error(err, locator.as_ref(), args.error_format);
             return 3;
         }
     };
 
-    let output_text = match serde_json::to_string(&output) {
+    let output_text = match serialize_output(&output, rule.output_format()) {
         Ok(text) => text,
-        Err(err) => {
-            eprintln!("failed to serialize output JSON: {}", err);
+        Err(message) => {
+            eprintln!("{}", message);
             return 1;
         }
     };
@@ -300,6 +370,8 @@ fn run_transform(args: TransformArgs) -> i32 {
             eprintln!("failed to write output: {}", err);
             return 1;
         }
+    } else if output_text.ends_with('\n') {
+        print!("{}", output_text);
     } else {
         println!("{}", output_text);
     }
@@ -307,8 +379,23 @@ fn run_transform(args: TransformArgs) -> i32 {
     0
 }
 
+fn serialize_output(output: &serde_json::Value, format: OutputFormat) -> Result<String, String> {
+    match format {
+        OutputFormat::Json => serde_json::to_string(output)
+            .map_err(|err| format!("failed to serialize output JSON: {}", err)),
+        OutputFormat::Csv => {
+            let records = match output {
+                serde_json::Value::Array(records) => records.as_slice(),
+                other => std::slice::from_ref(other),
+            };
+            records_to_csv(records).map_err(|err| format!("failed to write output CSV: {}", err))
+        }
+    }
+}
+
 fn run_transform_ndjson(
     rule: &RuleFile,
+    locator: Option<&TransformErrorLocator>,
     input: &str,
     context: Option<&serde_json::Value>,
     output: Option<PathBuf>,
@@ -319,7 +406,7 @@ fn run_transform_ndjson(
     let stream = match transform_stream_with_base_dir(rule, input, context, &base_dir) {
         Ok(stream) => stream,
         Err(err) => {
-            emit_transform_error(&err, error_format);
+            emit_transform_error(err, locator, error_format);
             return 3;
         }
     };
@@ -351,7 +438,7 @@ fn run_transform_ndjson(
         let item = match item {
             Ok(item) => item,
             Err(err) => {
-                emit_transform_error(&err, error_format);
+                emit_transform_error(err, locator, error_format);
                 return 3;
             }
         };
@@ -385,8 +472,8 @@ fn run_transform_ndjson(
 }
 
 fn run_generate(args: GenerateArgs) -> i32 {
-    let (rule, _) = match load_rule(&args.rules) {
-        Ok(value) => value,
+    let rule = match load_rule(&args.rules) {
+        Ok(value) => value.rule,
         Err(code) => return code,
     };
 
@@ -398,6 +485,7 @@ fn run_generate(args: GenerateArgs) -> i32 {
         DtoLanguageArg::Java => DtoLanguage::Java,
         DtoLanguageArg::Kotlin => DtoLanguage::Kotlin,
         DtoLanguageArg::Swift => DtoLanguage::Swift,
+        DtoLanguageArg::JsonSchema => DtoLanguage::JsonSchema,
     };
 
     let output = match generate_dto(&rule, lang, args.name.as_deref()) {
@@ -428,6 +516,295 @@ fn run_generate(args: GenerateArgs) -> i32 {
     0
 }
 
+fn run_explain(args: ExplainArgs) -> i32 {
+    let resolved = match load_rule(&args.rules) {
+        Ok(value) => value,
+        Err(code) => return code,
+    };
+
+    if let Err(errors) = validate_rule_file_with_includes(&resolved) {
+        emit_validation_errors(&errors, args.error_format);
+        return 2;
+    }
+
+    let summary = explain_rule(&resolved);
+    if args.json {
+        match serde_json::to_string_pretty(&summary) {
+            Ok(text) => println!("{}", text),
+            Err(err) => {
+                eprintln!("failed to serialize output JSON: {}", err);
+                return 1;
+            }
+        }
+    } else {
+        print!("{}", explain_text(&summary));
+    }
+
+    0
+}
+
+fn run_stats(args: StatsArgs) -> i32 {
+    let resolved = match load_rule(&args.rules) {
+        Ok(value) => value,
+        Err(code) => return code,
+    };
+
+    let stats = rule_stats(&resolved.rule);
+    if args.json {
+        match serde_json::to_string_pretty(&stats.to_json()) {
+            Ok(text) => println!("{}", text),
+            Err(err) => {
+                eprintln!("failed to serialize output JSON: {}", err);
+                return 1;
+            }
+        }
+    } else {
+        print!("{}", stats_text(&stats));
+    }
+
+    0
+}
+
+fn stats_text(stats: &RuleStats) -> String {
+    let join = |items: &std::collections::BTreeSet<String>| {
+        if items.is_empty() {
+            "-".to_string()
+        } else {
+            items.iter().cloned().collect::<Vec<_>>().join(", ")
+        }
+    };
+    let mut lines = Vec::new();
+    lines.push(format!("ops ({}):", stats.ops.values().sum::<usize>()));
+    for (op, count) in &stats.ops {
+        lines.push(format!("  {} x{}", op, count));
+    }
+    lines.push(format!("input paths: {}", join(&stats.input_paths)));
+    lines.push(format!("context paths: {}", join(&stats.context_paths)));
+    lines.push(format!("output targets: {}", join(&stats.output_targets)));
+    lines.push(format!("max pipe depth: {}", stats.max_pipe_depth));
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+fn explain_rule(resolved: &ResolvedRuleFile) -> serde_json::Value {
+    let rule = &resolved.rule;
+    let input = match rule.input.format {
+        InputFormat::Csv => {
+            let csv = rule.input.csv.as_ref();
+            json!({
+                "format": "csv",
+                "has_header": csv.map(|csv| csv.has_header).unwrap_or(true),
+                "delimiter": csv.map(|csv| csv.delimiter.as_str()).unwrap_or(","),
+                "columns": csv
+                    .and_then(|csv| csv.columns.as_ref())
+                    .map(|columns| columns.iter().map(|column| column.name.as_str()).collect::<Vec<_>>()),
+            })
+        }
+        InputFormat::Json => json!({
+            "format": "json",
+            "records_path": rule.input.json.as_ref().and_then(|json| json.records_path.as_deref()),
+        }),
+    };
+    let mappings: Vec<_> = rule
+        .mappings
+        .iter()
+        .enumerate()
+        .map(|(index, mapping)| {
+            let mut value = explain_mapping(mapping);
+            if let Some(file) = resolved
+                .mapping_file(index)
+                .filter(|file| *file != resolved.path())
+            {
+                value["file"] = json!(file.to_string_lossy());
+            }
+            value
+        })
+        .collect();
+    let steps: Option<Vec<_>> = rule.steps.as_ref().map(|steps| {
+        steps
+            .iter()
+            .enumerate()
+            .map(|(index, step)| {
+                let kind = if step.mappings.is_some() {
+                    "mappings"
+                } else if step.record_when.is_some() {
+                    "record_when"
+                } else if step.asserts.is_some() {
+                    "asserts"
+                } else {
+                    "branch"
+                };
+                json!({
+                    "name": step.name.clone().unwrap_or_else(|| format!("step {}", index)),
+                    "kind": kind,
+                    "mappings": step.mappings.as_ref().map(|mappings| mappings.iter().map(explain_mapping).collect::<Vec<_>>()),
+                })
+            })
+            .collect()
+    });
+    let finalize = rule.finalize.as_ref().map(|finalize| {
+        json!({
+            "filter": finalize.filter.as_ref().map(expr_to_json),
+            "sort": finalize.sort.as_ref().map(|sort| json!({ "by": sort.by, "order": sort.order })),
+            "offset": finalize.offset,
+            "limit": finalize.limit,
+            "wrap": finalize.wrap,
+        })
+    });
+
+    json!({
+        "rule": resolved.path().to_string_lossy(),
+        "version": rule.version,
+        "includes": resolved
+            .included_files()
+            .map(|path| path.to_string_lossy())
+            .collect::<Vec<_>>(),
+        "input": input,
+        "record_when": rule.record_when.as_ref().map(expr_to_json),
+        "mappings": mappings,
+        "steps": steps,
+        "finalize": finalize,
+    })
+}
+
+fn explain_mapping(mapping: &Mapping) -> serde_json::Value {
+    let (kind, from) = if let Some(source) = &mapping.source {
+        ("source", json!(source))
+    } else if let Some(value) = &mapping.value {
+        ("value", value.clone())
+    } else if let Some(expr) = &mapping.expr {
+        ("expr", expr_to_json(expr))
+    } else {
+        ("none", serde_json::Value::Null)
+    };
+    json!({
+        "target": mapping.target,
+        "kind": kind,
+        "from": from,
+        "type": mapping.value_type,
+        "required": mapping.required,
+        "default": mapping.default,
+        "when": mapping.when.as_ref().map(expr_to_json),
+    })
+}
+
+fn explain_text(summary: &serde_json::Value) -> String {
+    let compact = |value: &serde_json::Value| match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    let mut lines = Vec::new();
+    lines.push(format!(
+        "rule: {} (version {})",
+        compact(&summary["rule"]),
+        summary["version"]
+    ));
+    if let Some(includes) = summary["includes"].as_array().filter(|v| !v.is_empty()) {
+        let includes: Vec<String> = includes.iter().map(compact).collect();
+        lines.push(format!("includes: {}", includes.join(", ")));
+    }
+    let input = &summary["input"];
+    let mut input_parts = vec![compact(&input["format"])];
+    for key in ["records_path", "delimiter", "columns"] {
+        if !input[key].is_null() {
+            input_parts.push(format!("{}={}", key, compact(&input[key])));
+        }
+    }
+    lines.push(format!("input: {}", input_parts.join(" ")));
+    if !summary["record_when"].is_null() {
+        lines.push(format!("record_when: {}", compact(&summary["record_when"])));
+    }
+
+    let push_mappings = |lines: &mut Vec<String>, mappings: &[serde_json::Value], indent: &str| {
+        for mapping in mappings {
+            let mut line = format!(
+                "{}{} <- {} {}",
+                indent,
+                compact(&mapping["target"]),
+                compact(&mapping["kind"]),
+                compact(&mapping["from"])
+            );
+            let mut flags = Vec::new();
+            if !mapping["type"].is_null() {
+                flags.push(compact(&mapping["type"]));
+            }
+            if mapping["required"] == json!(true) {
+                flags.push("required".to_string());
+            }
+            if !mapping["default"].is_null() {
+                flags.push(format!("default={}", mapping["default"]));
+            }
+            if !mapping["when"].is_null() {
+                flags.push(format!("when={}", mapping["when"]));
+            }
+            if !flags.is_empty() {
+                line.push_str(&format!(" [{}]", flags.join(", ")));
+            }
+            if !mapping["file"].is_null() {
+                line.push_str(&format!(" (from {})", compact(&mapping["file"])));
+            }
+            lines.push(line);
+        }
+    };
+
+    if let Some(mappings) = summary["mappings"].as_array().filter(|v| !v.is_empty()) {
+        lines.push(format!("mappings ({}):", mappings.len()));
+        push_mappings(&mut lines, mappings, "  ");
+    }
+    if let Some(steps) = summary["steps"].as_array() {
+        lines.push(format!("steps ({}):", steps.len()));
+        for step in steps {
+            lines.push(format!(
+                "  {} [{}]",
+                compact(&step["name"]),
+                compact(&step["kind"])
+            ));
+            if let Some(mappings) = step["mappings"].as_array() {
+                push_mappings(&mut lines, mappings, "    ");
+            }
+        }
+    }
+    if let Some(finalize) = summary["finalize"].as_object() {
+        let parts: Vec<String> = finalize
+            .iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| format!("{}={}", key, compact(value)))
+            .collect();
+        lines.push(format!("finalize: {}", parts.join(" ")));
+    }
+
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+fn expr_to_json(expr: &Expr) -> serde_json::Value {
+    match expr {
+        Expr::Ref(expr_ref) => json!(expr_ref.ref_path),
+        Expr::Literal(value) => value.clone(),
+        Expr::Op(op) => {
+            let args: Vec<_> = op.args.iter().map(expr_to_json).collect();
+            json!({ op.op.clone(): args })
+        }
+        Expr::Chain(chain) => {
+            serde_json::Value::Array(chain.chain.iter().map(expr_to_json).collect())
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+fn trace_retention(max_age_days: Option<u64>, max_count: Option<usize>) -> Option<TraceRetention> {
+    if max_age_days.is_none() && max_count.is_none() {
+        return None;
+    }
+    Some(TraceRetention {
+        max_age: max_age_days
+            .map(|days| std::time::Duration::from_secs(days.saturating_mul(24 * 60 * 60))),
+        max_count,
+    })
+}
+
 #[cfg(feature = "server")]
 fn run_ui(args: UiArgs) -> i32 {
     let data_dir = args.data_dir.unwrap_or_else(ServerConfig::default_data_dir);
@@ -443,12 +820,18 @@ fn run_ui(args: UiArgs) -> i32 {
     }
 
     let config = ServerConfig {
+        bind_addr: args.bind,
         port: args.port,
         data_dir,
         ui_dir,
         rules_dir: args.rules_dir,
         api_mode,
         ui_enabled,
+        trace_channel_capacity: args.trace_channel_capacity,
+        cors_allowed_origins: args.cors_origins,
+        trace_retention: trace_retention(args.trace_max_age_days, args.trace_max_count),
+        request_timeout: ServerConfig::request_timeout_from_ms(args.request_timeout_ms),
+        max_body_bytes: args.max_body_bytes,
     };
 
     let runtime = match tokio::runtime::Runtime::new() {
@@ -471,7 +854,7 @@ fn run_ui(args: UiArgs) -> i32 {
     0
 }
 
-fn load_rule(path: &PathBuf) -> Result<(RuleFile, String), i32> {
+fn load_rule(path: &PathBuf) -> Result<ResolvedRuleFile, i32> {
     let yaml = match fs::read_to_string(path) {
         Ok(data) => data,
         Err(err) => {
@@ -488,7 +871,13 @@ fn load_rule(path: &PathBuf) -> Result<(RuleFile, String), i32> {
         }
     };
 
-    Ok((rule, yaml))
+    match resolve_rule_includes(rule, yaml, path) {
+        Ok(resolved) => Ok(resolved),
+        Err(err) => {
+            emit_validation_text(&err);
+            Err(1)
+        }
+    }
 }
 
 fn rule_base_dir(path: &PathBuf) -> PathBuf {
@@ -506,8 +895,15 @@ fn apply_format_override(rule: &mut RuleFile, format: Option<FormatOverride>) {
     }
 }
 
-fn load_input(path: &PathBuf) -> Result<String, i32> {
-    match fs::read_to_string(path) {
+fn load_input(path: &Option<PathBuf>, entry: Option<&str>) -> Result<String, i32> {
+    let result = match path {
+        Some(path) if path.as_os_str() != "-" => read_input_file(path, entry),
+        _ => {
+            let mut data = String::new();
+            io::stdin().read_to_string(&mut data).map(|_| data)
+        }
+    };
+    match result {
         Ok(value) => Ok(value),
         Err(err) => {
             eprintln!("failed to read input: {}", err);
@@ -572,6 +968,9 @@ fn emit_rules_dir_errors(errors: &RulesDirErrors, format: ErrorFormat) {
 fn emit_validation_text(err: &RuleError) {
     let mut parts = Vec::new();
     parts.push(format!("E {}", err.code.as_str()));
+    if let Some(file) = &err.file {
+        parts.push(format!("file={}", file.display()));
+    }
     if let Some(path) = &err.path {
         parts.push(format!("path={}", path));
     }
@@ -590,6 +989,9 @@ fn validation_error_json(err: &RuleError) -> serde_json::Value {
         "message": err.message,
     });
 
+    if let Some(file) = &err.file {
+        value["file"] = json!(file.to_string_lossy());
+    }
     if let Some(path) = &err.path {
         value["path"] = json!(path);
     }
@@ -621,7 +1023,15 @@ fn rules_dir_error_json(err: &rulemorph_server::RulesDirError) -> serde_json::Va
     value
 }
 
-fn emit_transform_error(err: &TransformError, format: ErrorFormat) {
+fn emit_transform_error(
+    err: TransformError,
+    locator: Option<&TransformErrorLocator>,
+    format: ErrorFormat,
+) {
+    let err = match locator {
+        Some(locator) => locator.locate(err),
+        None => err,
+    };
     match format {
         ErrorFormat::Text => {
             let mut parts = Vec::new();
@@ -629,6 +1039,10 @@ fn emit_transform_error(err: &TransformError, format: ErrorFormat) {
             if let Some(path) = &err.path {
                 parts.push(format!("path={}", path));
             }
+            if let Some(location) = &err.location {
+                parts.push(format!("line={}", location.line));
+                parts.push(format!("col={}", location.column));
+            }
             parts.push(format!("msg=\"{}\"", err.message));
             eprintln!("{}", parts.join(" "));
         }
@@ -641,6 +1055,10 @@ fn emit_transform_error(err: &TransformError, format: ErrorFormat) {
             if let Some(path) = &err.path {
                 value["path"] = json!(path);
             }
+            if let Some(location) = &err.location {
+                value["line"] = json!(location.line);
+                value["column"] = json!(location.column);
+            }
             eprintln!(
                 "{}",
                 serde_json::to_string(&vec![value]).unwrap_or_default()
@@ -698,5 +1116,7 @@ fn transform_kind_to_str(kind: &TransformErrorKind) -> &'static str {
         TransformErrorKind::TypeCastFailed => "TypeCastFailed",
         TransformErrorKind::ExprError => "ExprError",
         TransformErrorKind::AssertionFailed => "AssertionFailed",
+        TransformErrorKind::TypeAssertionFailed => "TypeAssertionFailed",
+        TransformErrorKind::Deprecated => "Deprecated",
     }
 }
@@ -88,6 +88,9 @@ fn preflight_json_errors() {
         serde_json::from_str(&stderr).unwrap_or_else(|_| panic!("invalid json stderr: {}", stderr));
     assert_eq!(value[0]["type"], "transform");
     assert_eq!(value[0]["kind"], "TypeCastFailed");
+    assert_eq!(value[0]["path"], "mappings[0].type");
+    assert_eq!(value[0]["line"], 7);
+    assert_eq!(value[0]["column"], 5);
 }
 
 #[test]
@@ -171,6 +174,32 @@ fn transform_writes_output_file() {
     assert_eq!(actual, expected);
 }
 
+#[test]
+fn transform_reads_stdin_and_writes_out() {
+    let base = fixtures_dir().join("t01_csv_basic");
+    let rules = base.join("rules.yaml");
+    let input = fs::read_to_string(base.join("input.csv")).unwrap();
+    let expected = read_json(&base.join("expected.json"));
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let out_path = temp_dir.path().join("out.json");
+
+    let mut cmd = cargo_bin_cmd!("rulemorph");
+    let output = cmd
+        .arg("transform")
+        .arg("--rules")
+        .arg(rules)
+        .arg("--out")
+        .arg(&out_path)
+        .write_stdin(input)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    let actual = read_json(&out_path);
+    assert_eq!(actual, expected);
+}
+
 #[test]
 fn transform_emits_warnings_json() {
     let base = fixtures_dir().join("t10_when_compare");
@@ -236,3 +265,97 @@ fn generate_outputs_rust_dto() {
     let stdout = String::from_utf8(output.stdout).unwrap();
     assert!(stdout.contains("struct Record"));
 }
+
+#[test]
+fn dto_alias_runs_generate() {
+    let rules = fixtures_dir().join("t01_csv_basic").join("rules.yaml");
+
+    let mut cmd = cargo_bin_cmd!("rulemorph");
+    let output = cmd
+        .arg("dto")
+        .arg("-r")
+        .arg(rules)
+        .arg("-l")
+        .arg("ts")
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("interface Record"));
+}
+
+#[test]
+fn explain_summarizes_rule() {
+    let rules = fixtures_dir().join("t01_csv_basic").join("rules.yaml");
+
+    let mut cmd = cargo_bin_cmd!("rulemorph");
+    let output = cmd.arg("explain").arg("-r").arg(&rules).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("input: csv"), "stdout: {}", stdout);
+    assert!(stdout.contains("mappings (3):"), "stdout: {}", stdout);
+    assert!(
+        stdout.contains("price <- source price [float]"),
+        "stdout: {}",
+        stdout
+    );
+
+    let mut cmd = cargo_bin_cmd!("rulemorph");
+    let output = cmd
+        .arg("explain")
+        .arg("-r")
+        .arg(&rules)
+        .arg("--json")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(value["input"]["format"], "csv");
+    assert_eq!(value["mappings"][2]["target"], "price");
+    assert_eq!(value["mappings"][2]["type"], "float");
+}
+
+#[test]
+fn explain_invalid_rule_returns_validation_code() {
+    let rules = fixtures_dir()
+        .join("v01_missing_mapping_value")
+        .join("rules.yaml");
+
+    let mut cmd = cargo_bin_cmd!("rulemorph");
+    let output = cmd.arg("explain").arg("-r").arg(rules).output().unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn stats_reports_ops_and_paths() {
+    let rules = fixtures_dir().join("tv25_lookup").join("rules.yaml");
+
+    let mut cmd = cargo_bin_cmd!("rulemorph");
+    let output = cmd.arg("stats").arg("-r").arg(&rules).output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("  lookup_first x2"), "stdout: {}", stdout);
+    assert!(
+        stdout.contains("context paths: departments, projects"),
+        "stdout: {}",
+        stdout
+    );
+
+    let mut cmd = cargo_bin_cmd!("rulemorph");
+    let output = cmd
+        .arg("stats")
+        .arg("-r")
+        .arg(&rules)
+        .arg("--json")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(value["ops"]["lookup"], 1);
+    assert_eq!(value["input_paths"], serde_json::json!(["dept_id", "id"]));
+    assert_eq!(
+        value["output_targets"],
+        serde_json::json!(["all_projects", "dept_info", "dept_name"])
+    );
+}
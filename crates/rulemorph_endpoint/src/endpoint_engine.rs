@@ -1,15 +1,17 @@
+use std::borrow::Cow;
 use std::collections::{BTreeSet, HashMap};
 use std::fmt;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result, anyhow};
 use axum::http::{HeaderMap, HeaderName, HeaderValue, Method, Request, StatusCode};
 use axum::response::Response;
 use chrono::{Datelike, Utc};
+use futures_util::future::join_all;
 use reqwest::Client;
-use rulemorph::PathToken;
 use rulemorph::v2_eval::{
     EvalValue, V2EvalContext, eval_v2_condition, eval_v2_expr, eval_v2_if_step, eval_v2_let_step,
     eval_v2_map_step, eval_v2_op_step, eval_v2_pipe, eval_v2_ref, eval_v2_start,
@@ -20,15 +22,19 @@ use rulemorph::v2_parser::{
     parse_v2_pipe_from_value,
 };
 use rulemorph::{
-    Expr, Mapping, RuleError, RuleFile, TransformError, TransformErrorKind, get_path, parse_path,
-    parse_rule_file, transform_record, transform_record_with_base_dir,
+    Expr, Mapping, RuleError, RuleFile, TransformError, TransformErrorKind, get_path,
+    load_rule_file_with_includes, parse_path, parse_rule_file, resolve_rule_includes,
+    transform_record, transform_record_with_base_dir, validate_rule_file_with_includes,
     validate_rule_file_with_source,
 };
-use serde::{Deserialize, de::DeserializeOwned};
+use rulemorph::{LruCache, PathToken};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use serde_json::{Map as JsonMap, Value as JsonValue, json};
 use tracing::warn;
 use uuid::Uuid;
 
+use crate::metrics::{LatencyHistogram, LatencySnapshot};
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ApiMode {
     UiOnly,
@@ -45,18 +51,70 @@ impl Default for ApiMode {
 pub struct EngineConfig {
     pub internal_base: String,
     pub data_dir: PathBuf,
+    pub circuit_breaker: CircuitBreakerConfig,
+    /// Largest request body accepted before answering 413.
+    pub max_body_bytes: usize,
+    /// Reads the variable named by an auth `token_env`. Defaults to the process environment.
+    pub token_lookup: fn(&str) -> Option<String>,
 }
 
+/// Default for [`EngineConfig::max_body_bytes`] (10 MiB).
+pub const DEFAULT_MAX_BODY_BYTES: usize = 10 * 1024 * 1024;
+
 impl EngineConfig {
     pub fn new(internal_base: String, data_dir: PathBuf) -> Self {
         Self {
             internal_base,
             data_dir,
+            circuit_breaker: CircuitBreakerConfig::default(),
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            token_lookup: env_token,
         }
     }
+
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
+
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    pub fn with_token_lookup(mut self, token_lookup: fn(&str) -> Option<String>) -> Self {
+        self.token_lookup = token_lookup;
+        self
+    }
 }
 
-#[derive(Debug, Clone)]
+fn env_token(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+/// Thresholds for the per-upstream circuit breaker applied to network rules.
+#[derive(Clone, Debug)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the circuit. `0` disables the breaker.
+    pub failure_threshold: u32,
+    /// Failures older than this (counted from the first one) start a new streak.
+    pub failure_window: Duration,
+    /// How long an open circuit fails fast before a half-open probe is allowed.
+    /// A probe that has not finished after another cooldown is given up on.
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            failure_window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct RulesDirError {
     pub code: String,
     pub file: PathBuf,
@@ -98,6 +156,44 @@ impl fmt::Display for RulesDirErrors {
 
 impl std::error::Error for RulesDirErrors {}
 
+/// Error returned by `handle_request` when an endpoint fails after matching.
+///
+/// Carries the HTTP status and a machine-readable body describing where the
+/// failure happened (rule ref, step index, field path).
+#[derive(Debug, Clone)]
+pub struct EndpointFailure {
+    pub status: StatusCode,
+    pub code: String,
+    pub message: String,
+    pub rule: Option<String>,
+    pub step: Option<usize>,
+    pub path: Option<String>,
+    /// Set when the client should back off, e.g. after hitting a rate limit.
+    pub retry_after: Option<Duration>,
+}
+
+impl EndpointFailure {
+    pub fn to_json(&self) -> JsonValue {
+        json!({
+            "error": {
+                "code": self.code,
+                "message": self.message,
+                "rule": self.rule,
+                "step": self.step,
+                "path": self.path,
+            }
+        })
+    }
+}
+
+impl fmt::Display for EndpointFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for EndpointFailure {}
+
 #[derive(Debug, Default, Clone, Copy)]
 struct RuleRefUsage {
     step: bool,
@@ -196,13 +292,15 @@ pub fn validate_rules_dir(rules_dir: &Path) -> std::result::Result<(), RulesDirE
     let mut refs: BTreeSet<PathBuf> = BTreeSet::new();
     let mut ref_usage: HashMap<PathBuf, RuleRefUsage> = HashMap::new();
     for endpoint in &raw.endpoints {
-        for step in &endpoint.steps {
-            let resolved = resolve_rule_path(base_dir, &step.rule);
-            refs.insert(resolved.clone());
-            ref_usage
-                .entry(resolved)
-                .and_modify(|usage| usage.merge(RuleRefUsage::step()))
-                .or_insert_with(RuleRefUsage::step);
+        for step in endpoint.steps.iter().flat_map(EndpointStep::with_branches) {
+            if let Some(rule) = &step.rule {
+                let resolved = resolve_rule_path(base_dir, rule);
+                refs.insert(resolved.clone());
+                ref_usage
+                    .entry(resolved)
+                    .and_modify(|usage| usage.merge(RuleRefUsage::step()))
+                    .or_insert_with(RuleRefUsage::step);
+            }
             if let Some(catch) = &step.catch {
                 for target in catch.values() {
                     let resolved = resolve_rule_path(base_dir, target);
@@ -244,6 +342,27 @@ pub struct EndpointEngine {
     raw_rule_source: JsonValue,
     config: EngineConfig,
     client: Client,
+    state: EngineState,
+    reply_cache: Mutex<LruCache<String, CachedOutput>>,
+}
+
+/// Runtime state that outlives a single engine, so reloading the rules keeps open
+/// circuits, rate-limit buckets and latency metrics.
+///
+/// The reply cache is not part of it: an edited rule may produce different output.
+#[derive(Clone, Default)]
+pub struct EngineState {
+    breakers: Arc<Mutex<HashMap<String, CircuitBreaker>>>,
+    rate_limits: Arc<Mutex<HashMap<String, TokenBucket>>>,
+    latency: Arc<LatencyHistogram>,
+}
+
+const REPLY_CACHE_CAPACITY: usize = 256;
+
+#[derive(Clone)]
+struct CachedOutput {
+    expires_at: Instant,
+    output: JsonValue,
 }
 
 struct RuleExecution {
@@ -281,6 +400,7 @@ struct NetworkExecution {
     request_us: u64,
     total_us: u64,
     body_rule_trace: Option<JsonValue>,
+    breaker_transitions: Vec<BreakerTransition>,
 }
 
 #[derive(Debug)]
@@ -291,6 +411,15 @@ struct LoadedRule {
 
 impl EndpointEngine {
     pub fn load(rules_dir: PathBuf, config: EngineConfig) -> Result<Self> {
+        Self::load_with_state(rules_dir, config, EngineState::default())
+    }
+
+    /// Load the rules while carrying over the runtime state of a previous engine.
+    pub fn load_with_state(
+        rules_dir: PathBuf,
+        config: EngineConfig,
+        state: EngineState,
+    ) -> Result<Self> {
         let endpoint_path = rules_dir.join("endpoint.yaml");
         let source = std::fs::read_to_string(&endpoint_path)
             .with_context(|| format!("failed to read {}", endpoint_path.display()))?;
@@ -315,9 +444,21 @@ impl EndpointEngine {
             raw_rule_source,
             config,
             client,
+            state,
+            reply_cache: Mutex::new(LruCache::new(REPLY_CACHE_CAPACITY)),
         })
     }
 
+    /// Runtime state to hand to the engine that replaces this one.
+    pub fn state(&self) -> EngineState {
+        self.state.clone()
+    }
+
+    /// Durations of the requests handled by this engine and the engines it replaced.
+    pub fn latency(&self) -> LatencySnapshot {
+        self.state.latency.snapshot()
+    }
+
     pub async fn handle_request(&self, request: Request<axum::body::Body>) -> Result<Response> {
         let started = Instant::now();
         let (parts, body) = request.into_parts();
@@ -327,9 +468,54 @@ impl EndpointEngine {
             .endpoint_rule
             .match_endpoint(&method, &path)
             .ok_or_else(|| anyhow!("no endpoint matched"))?;
-        let body_bytes = axum::body::to_bytes(body, usize::MAX)
-            .await
-            .map_err(|err| anyhow!(err.to_string()))?;
+        let endpoint = endpoint_match.endpoint;
+        // Headers are left out of rejection traces so a rejected token is not persisted;
+        // other traces have their credential headers redacted.
+        let rejected_input = || json!({ "method": method.as_str(), "path": endpoint_match.params });
+        if let Some(rate_limit) = &endpoint.rate_limit
+            && let Err(retry_after) = self.rate_limit_acquire(rate_limit)
+        {
+            let err = EndpointError::rate_limited(retry_after);
+            let mut failure = self
+                .reject_request(
+                    &method,
+                    &path,
+                    rejected_input(),
+                    "rate_limited",
+                    &err,
+                    started,
+                )
+                .await;
+            failure.retry_after = Some(retry_after);
+            return Err(failure.into());
+        }
+        if let Some(auth) = &endpoint.auth
+            && let Err(err) = auth.check(&parts.headers, self.config.token_lookup)
+        {
+            let failure = self
+                .reject_request(&method, &path, rejected_input(), "error", &err, started)
+                .await;
+            return Err(failure.into());
+        }
+        if let Some(message) = &endpoint_match.invalid_param {
+            let err = EndpointError::invalid(message.clone());
+            let failure = self
+                .reject_request(&method, &path, rejected_input(), "error", &err, started)
+                .await;
+            return Err(failure.into());
+        }
+        let max_body_bytes = self.config.max_body_bytes;
+        let body_bytes = match axum::body::to_bytes(body, max_body_bytes).await {
+            Ok(bytes) => bytes,
+            Err(err) if is_length_limit_error(&err) => {
+                let err = EndpointError::payload_too_large(max_body_bytes);
+                let failure = self
+                    .reject_request(&method, &path, rejected_input(), "error", &err, started)
+                    .await;
+                return Err(failure.into());
+            }
+            Err(err) => return Err(anyhow!(err.to_string())),
+        };
         let body_value = if body_bytes.is_empty() {
             Ok(None)
         } else {
@@ -338,11 +524,10 @@ impl EndpointEngine {
                 .map_err(|err| EndpointError::invalid(err.to_string()))
         };
 
-        let endpoint = endpoint_match.endpoint;
         let mut nodes: Vec<JsonValue> = Vec::new();
         let mut record_status = "ok".to_string();
         let mut record_error: Option<JsonValue> = None;
-        let mut last_error_message: Option<String> = None;
+        let mut last_failure: Option<EndpointFailure> = None;
         let mut skip_steps = false;
 
         let mut handle_input_error = |err: EndpointError,
@@ -369,13 +554,13 @@ impl EndpointEngine {
                 } else {
                     record_status = "error".to_string();
                     record_error = Some(self.endpoint_error_to_trace(&err));
-                    last_error_message = Some(err.message.clone());
+                    last_failure = Some(self.endpoint_failure(&err, None));
                     Ok((fallback_input.clone(), fallback_input))
                 }
             } else {
                 record_status = "error".to_string();
                 record_error = Some(self.endpoint_error_to_trace(&err));
-                last_error_message = Some(err.message.clone());
+                last_failure = Some(self.endpoint_failure(&err, None));
                 Ok((fallback_input.clone(), fallback_input))
             }
         };
@@ -403,6 +588,21 @@ impl EndpointEngine {
             Err(err) => handle_input_error(err, None, None),
         }?;
 
+        // The cache stores the output of the steps, keyed on the input they would receive.
+        let cache_key = match &endpoint.cache {
+            Some(cache) if !skip_steps => self.reply_cache_key(cache, &current),
+            _ => None,
+        };
+        let cache_hit = match cache_key.as_ref().and_then(|key| self.reply_cache_get(key)) {
+            Some(output) => {
+                current = output;
+                skip_steps = true;
+                true
+            }
+            None => false,
+        };
+        let mut caught = false;
+
         if !skip_steps {
             for (step_index, step) in endpoint.steps.iter().enumerate() {
                 let step_input = current.clone();
@@ -432,15 +632,13 @@ impl EndpointEngine {
                         continue;
                     }
                 }
-                let step_context = self.step_context(step.with.as_ref(), None);
-                let step_result = self
-                    .execute_rule(
-                        &step.rule,
-                        &current,
-                        Some(&step_context),
-                        &self.endpoint_rule.base_dir,
-                    )
-                    .await;
+                let step_result = match &step.target {
+                    StepTarget::Rule(rule) => self.execute_step_rule(step, rule, &current).await,
+                    StepTarget::Parallel(branches) => {
+                        self.execute_parallel(step_index, branches, &current, &mut nodes)
+                            .await
+                    }
+                };
                 match step_result {
                     Ok(execution) => {
                         current = execution.output.clone();
@@ -468,6 +666,7 @@ impl EndpointEngine {
                                 )
                                 .map_err(|err| anyhow!(err.to_string()))?
                             {
+                                caught = true;
                                 current = next.clone();
                                 let duration_us = step_started.elapsed().as_micros() as u64;
                                 nodes.push(self.build_step_trace(
@@ -495,6 +694,7 @@ impl EndpointEngine {
                                 )
                                 .map_err(|err| anyhow!(err.to_string()))?
                             {
+                                caught = true;
                                 current = next.clone();
                                 let duration_us = step_started.elapsed().as_micros() as u64;
                                 nodes.push(self.build_step_trace(
@@ -513,7 +713,9 @@ impl EndpointEngine {
 
                         record_status = "error".to_string();
                         record_error = Some(self.endpoint_error_to_trace(&err.error));
-                        last_error_message = Some(err.error.message.clone());
+                        last_failure = Some(
+                            self.endpoint_failure(&err.error, Some((step_index, step.rule_name()))),
+                        );
                         let duration_us = step_started.elapsed().as_micros() as u64;
                         nodes.push(self.build_step_trace(
                             step_index,
@@ -531,10 +733,20 @@ impl EndpointEngine {
             }
         }
 
+        // Outputs recovered through a catch are not cached.
+        if let (Some(cache), Some(key)) = (&endpoint.cache, cache_key)
+            && !cache_hit
+            && !caught
+            && record_status == "ok"
+        {
+            self.reply_cache_put(key, cache.ttl, current.clone());
+        }
+
         let response_result = if record_status == "error" {
-            Err(anyhow!(
-                last_error_message.unwrap_or_else(|| "endpoint error".to_string())
-            ))
+            Err(match last_failure {
+                Some(failure) => anyhow::Error::new(failure),
+                None => anyhow!("endpoint error"),
+            })
         } else {
             match self.build_reply(&endpoint.reply, &current) {
                 Ok(response) => Ok(response),
@@ -561,20 +773,21 @@ impl EndpointEngine {
                                 let reply_error = EndpointError::invalid(err.to_string());
                                 record_status = "error".to_string();
                                 record_error = Some(self.endpoint_error_to_trace(&reply_error));
-                                Err(anyhow!(reply_error.message))
+                                Err(self.endpoint_failure(&reply_error, None).into())
                             }
                         }
                     } else {
                         record_status = "error".to_string();
                         record_error = Some(self.endpoint_error_to_trace(&reply_error));
-                        Err(anyhow!(reply_error.message))
+                        Err(self.endpoint_failure(&reply_error, None).into())
                     }
                 }
             }
         };
 
         let duration_us = started.elapsed().as_micros() as u64;
-        let trace = self.build_trace(
+        self.state.latency.observe(duration_us);
+        let mut trace = self.build_trace(
             &method,
             &path,
             record_input,
@@ -584,6 +797,12 @@ impl EndpointEngine {
             nodes,
             duration_us,
         );
+        redact_trace_headers(&mut trace);
+        if endpoint.cache.is_some()
+            && let Some(obj) = trace.as_object_mut()
+        {
+            obj.insert("cache_hit".to_string(), JsonValue::Bool(cache_hit));
+        }
         if let Err(err) = self.write_trace(&trace).await {
             warn!("failed to write trace: {}", err);
         }
@@ -591,6 +810,87 @@ impl EndpointEngine {
         response_result
     }
 
+    /// Record a trace for a request refused before any step ran.
+    async fn reject_request(
+        &self,
+        method: &Method,
+        path: &str,
+        input: JsonValue,
+        status: &str,
+        err: &EndpointError,
+        started: Instant,
+    ) -> EndpointFailure {
+        let duration_us = started.elapsed().as_micros() as u64;
+        self.state.latency.observe(duration_us);
+        let trace = self.build_trace(
+            method,
+            path,
+            input.clone(),
+            input,
+            status.to_string(),
+            Some(self.endpoint_error_to_trace(err)),
+            Vec::new(),
+            duration_us,
+        );
+        if let Err(err) = self.write_trace(&trace).await {
+            warn!("failed to write trace: {}", err);
+        }
+        self.endpoint_failure(err, None)
+    }
+
+    fn reply_cache_key(&self, cache: &ReplyCache, input: &JsonValue) -> Option<String> {
+        let mut values = Vec::with_capacity(cache.key.len());
+        for expr in &cache.key {
+            match eval_expr_value(expr, input, Some(&self.config_json())) {
+                Ok(value) => values.push(reply_cache_key_component(value)),
+                Err(err) => {
+                    warn!("cache key eval failed; bypassing cache: {}", err);
+                    return None;
+                }
+            }
+        }
+        Some(format!("{}\n{}", cache.prefix, JsonValue::Array(values)))
+    }
+
+    fn reply_cache_get(&self, key: &str) -> Option<JsonValue> {
+        let mut cache = self
+            .reply_cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        cache
+            .get_cloned(&key.to_string())
+            .filter(|entry| entry.expires_at > Instant::now())
+            .map(|entry| entry.output)
+    }
+
+    fn reply_cache_put(&self, key: String, ttl: Duration, output: JsonValue) {
+        let mut cache = self
+            .reply_cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        cache.insert(
+            key,
+            CachedOutput {
+                expires_at: Instant::now() + ttl,
+                output,
+            },
+        );
+    }
+
+    /// Takes a token for the endpoint, or returns how long until one is available.
+    fn rate_limit_acquire(&self, limit: &RateLimit) -> Result<(), Duration> {
+        let mut buckets = self
+            .state
+            .rate_limits
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = Instant::now();
+        buckets
+            .entry(limit.key.clone())
+            .or_insert_with(|| TokenBucket::full(limit, now))
+            .try_acquire(limit, now)
+    }
+
     fn build_trace(
         &self,
         method: &Method,
@@ -650,8 +950,13 @@ impl EndpointEngine {
         duration_us: u64,
         child_trace: Option<JsonValue>,
     ) -> JsonValue {
-        let label = step_label(&step.rule);
-        let rule_ref = rule_ref_from_rule(&self.endpoint_rule.base_dir, &step.rule);
+        let (label, rule_ref) = match &step.target {
+            StepTarget::Rule(rule) => (
+                step_label(rule),
+                Some(rule_ref_from_rule(&self.endpoint_rule.base_dir, rule)),
+            ),
+            StepTarget::Parallel(_) => ("parallel".to_string(), None),
+        };
         let mut node = json!({
             "id": format!("step-{}", step_index),
             "kind": "endpoint",
@@ -678,16 +983,61 @@ impl EndpointEngine {
         node
     }
 
+    fn endpoint_failure(
+        &self,
+        err: &EndpointError,
+        step: Option<(usize, &str)>,
+    ) -> EndpointFailure {
+        let base_dir = &self.endpoint_rule.base_dir;
+        let rule = err
+            .path
+            .as_ref()
+            .and_then(|path| safe_rule_ref_from_path(base_dir, path))
+            .or_else(|| step.map(|(_, rule)| rule_ref_from_rule(base_dir, rule)));
+        let (code, message, path) = match &err.transform {
+            Some(transform) => (
+                format!("{:?}", transform.kind),
+                transform.message.clone(),
+                transform.path.clone(),
+            ),
+            None => (format!("{:?}", err.kind), err.message.clone(), None),
+        };
+        EndpointFailure {
+            status: endpoint_error_status(&err.kind),
+            code,
+            message,
+            rule,
+            step: step.map(|(index, _)| index),
+            path,
+            retry_after: None,
+        }
+    }
+
     fn endpoint_error_to_trace(&self, err: &EndpointError) -> JsonValue {
         let path = err
             .path
             .as_ref()
             .and_then(|path| safe_rule_ref_from_path(&self.endpoint_rule.base_dir, path));
-        json!({
+        let mut value = json!({
             "code": format!("{:?}", err.kind),
             "message": err.message,
             "path": path
-        })
+        });
+        if let (Some(obj), Some(breaker)) = (value.as_object_mut(), err.breaker.as_ref()) {
+            if let Some(retry_after) = breaker.retry_after {
+                obj.insert(
+                    "retry_after_ms".to_string(),
+                    json!(retry_after.as_millis() as u64),
+                );
+            }
+            if !breaker.transitions.is_empty() {
+                obj.insert(
+                    "circuit_breaker".to_string(),
+                    breaker_transitions_json(&breaker.transitions),
+                );
+            }
+        }
+        value
     }
 
     async fn write_trace(&self, trace: &JsonValue) -> Result<()> {
@@ -714,6 +1064,161 @@ impl EndpointEngine {
         Ok(())
     }
 
+    /// Runs a step's rule, bounded by its `timeout_ms`.
+    async fn execute_step_rule(
+        &self,
+        step: &CompiledStep,
+        rule: &str,
+        input: &JsonValue,
+    ) -> Result<RuleExecution, RuleExecutionError> {
+        let step_context = self.step_context(step.with.as_ref(), None);
+        let execution = self.execute_rule(
+            rule,
+            input,
+            Some(&step_context),
+            &self.endpoint_rule.base_dir,
+        );
+        match step.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, execution).await {
+                Ok(result) => result,
+                Err(_) => Err(RuleExecutionError::new(EndpointError::step_timeout(
+                    timeout,
+                ))),
+            },
+            None => execution.await,
+        }
+    }
+
+    /// Runs the branches of a `parallel` step concurrently on the same input and
+    /// shallow-merges their object outputs over it in branch order. Every branch gets
+    /// its own trace node; the first branch error its own `catch` does not handle
+    /// fails the whole step.
+    async fn execute_parallel(
+        &self,
+        step_index: usize,
+        branches: &[CompiledStep],
+        input: &JsonValue,
+        nodes: &mut Vec<JsonValue>,
+    ) -> Result<RuleExecution, RuleExecutionError> {
+        let base_dir = &self.endpoint_rule.base_dir;
+        let Some(mut merged) = input.as_object().cloned() else {
+            return Err(RuleExecutionError::new(EndpointError::invalid(
+                "parallel step input must be an object",
+            )));
+        };
+        let runs = branches.iter().map(|branch| async move {
+            let started = Instant::now();
+            let result = match (&branch.target, &branch.when) {
+                (StepTarget::Rule(rule), Some(condition)) => {
+                    let ctx = V2EvalContext::new();
+                    match eval_v2_condition(
+                        condition,
+                        input,
+                        Some(&self.config_json()),
+                        &empty_object(),
+                        "steps.when",
+                        &ctx,
+                    ) {
+                        Ok(true) => Some(self.execute_step_rule(branch, rule, input).await),
+                        Ok(false) => None,
+                        Err(err) => Some(Err(RuleExecutionError::new(
+                            EndpointError::from_transform(err),
+                        ))),
+                    }
+                }
+                (StepTarget::Rule(rule), None) => {
+                    Some(self.execute_step_rule(branch, rule, input).await)
+                }
+                (StepTarget::Parallel(_), _) => Some(Err(RuleExecutionError::new(
+                    EndpointError::invalid("steps[].parallel branches cannot be parallel"),
+                ))),
+            };
+            (result, started.elapsed().as_micros() as u64)
+        });
+        let results = join_all(runs).await;
+
+        let mut failure: Option<RuleExecutionError> = None;
+        for (branch_index, (branch, (result, duration_us))) in
+            branches.iter().zip(results).enumerate()
+        {
+            let result = match (result, &branch.catch) {
+                (Some(Err(err)), Some(catch)) => Some(
+                    match self.run_catch(catch, &err.error, input, branch.with.as_ref(), base_dir) {
+                        Ok(Some(output)) => Ok(RuleExecution {
+                            output,
+                            child_trace: None,
+                        }),
+                        Ok(None) => Err(err),
+                        Err(catch_err) => Err(RuleExecutionError::new(catch_err)),
+                    },
+                ),
+                (result, _) => result,
+            };
+            let mut node = match result.map(|result| {
+                result.and_then(|execution| match &execution.output {
+                    JsonValue::Object(map) => {
+                        merged.extend(map.clone());
+                        Ok(execution)
+                    }
+                    _ => Err(RuleExecutionError {
+                        error: EndpointError::invalid("parallel branch output must be an object"),
+                        child_trace: execution.child_trace,
+                    }),
+                })
+            }) {
+                None => self.build_step_trace(
+                    step_index,
+                    branch,
+                    "skipped",
+                    input.clone(),
+                    Some(input.clone()),
+                    None,
+                    duration_us,
+                    None,
+                ),
+                Some(Ok(execution)) => self.build_step_trace(
+                    step_index,
+                    branch,
+                    "ok",
+                    input.clone(),
+                    Some(execution.output),
+                    None,
+                    duration_us,
+                    execution.child_trace,
+                ),
+                Some(Err(mut err)) => {
+                    if err.error.path.is_none()
+                        && let StepTarget::Rule(rule) = &branch.target
+                    {
+                        err.error.path = Some(resolve_rule_path(base_dir, rule));
+                    }
+                    let node = self.build_step_trace(
+                        step_index,
+                        branch,
+                        "error",
+                        input.clone(),
+                        None,
+                        Some(err.error.clone()),
+                        duration_us,
+                        err.child_trace.take(),
+                    );
+                    failure.get_or_insert(err);
+                    node
+                }
+            };
+            node["id"] = json!(format!("step-{}-{}", step_index, branch_index));
+            node["meta"]["branch_index"] = json!(branch_index);
+            nodes.push(node);
+        }
+        match failure {
+            Some(err) => Err(err),
+            None => Ok(RuleExecution {
+                output: JsonValue::Object(merged),
+                child_trace: None,
+            }),
+        }
+    }
+
     async fn execute_rule(
         &self,
         rule_path: &str,
@@ -836,7 +1341,8 @@ impl EndpointEngine {
         let total_started = Instant::now();
         let run_catch = |err: EndpointError,
                          request_us: u64,
-                         body_rule_trace: Option<JsonValue>|
+                         body_rule_trace: Option<JsonValue>,
+                         breaker_transitions: Vec<BreakerTransition>|
          -> Result<NetworkExecution, EndpointError> {
             if let Some(catch) = &rule.catch {
                 if let Some(output) = self.run_catch(catch, &err, input, None, &rule.base_dir)? {
@@ -845,77 +1351,88 @@ impl EndpointEngine {
                         request_us,
                         total_us: total_started.elapsed().as_micros() as u64,
                         body_rule_trace,
+                        breaker_transitions,
                     });
                 }
             }
-            Err(err)
+            Err(err.with_breaker_transitions(breaker_transitions))
         };
 
         let url = match eval_expr_string(&rule.request.url, input, context) {
             Ok(url) => url,
-            Err(err) => return run_catch(err, 0, None),
+            Err(err) => return run_catch(err, 0, None, Vec::new()),
         };
         let headers = match build_headers(&rule.request.headers) {
             Ok(headers) => headers,
-            Err(err) => return run_catch(err, 0, None),
+            Err(err) => return run_catch(err, 0, None, Vec::new()),
         };
         let body = match self.build_network_body(rule, input, context) {
             Ok(body) => body,
-            Err(err) => return run_catch(err, 0, None),
+            Err(err) => return run_catch(err, 0, None, Vec::new()),
         };
         let body_rule_trace = Self::build_body_rule_trace(rule, input, context, body.as_ref());
 
+        let mut transitions = Vec::new();
         let mut attempt = 0;
         loop {
+            if let Err(err) = self.breaker_before_call(&breaker_key(&url), &mut transitions) {
+                return run_catch(err, 0, body_rule_trace, transitions);
+            }
             let request_started = Instant::now();
             let result = self
                 .send_network_request(rule, &url, &headers, body.as_ref())
                 .await;
             let request_us = request_started.elapsed().as_micros() as u64;
-            let run_catch_with_body =
-                |err: EndpointError, request_us: u64| -> Result<NetworkExecution, EndpointError> {
-                    run_catch(err, request_us, body_rule_trace.clone())
-                };
+            self.breaker_record(&breaker_key(&url), &result, &mut transitions);
 
             match result {
                 Ok(value) => {
-                    if let Some(select) = &rule.select {
-                        let tokens = match parse_path(select) {
-                            Ok(tokens) => tokens,
-                            Err(_) => {
-                                return run_catch_with_body(
+                    let output = match &rule.select {
+                        Some(select) => {
+                            let selected = parse_path(select)
+                                .map_err(|_| {
                                     EndpointError::invalid(format!(
                                         "invalid select path: {}",
                                         select
-                                    )),
-                                    request_us,
-                                );
-                            }
-                        };
-                        let selected = match get_path(&value, &tokens) {
-                            Some(selected) => selected,
-                            None => {
-                                return run_catch_with_body(
-                                    EndpointError::invalid(format!(
-                                        "select path not found: {}",
-                                        select
-                                    )),
-                                    request_us,
-                                );
+                                    ))
+                                })
+                                .and_then(|tokens| {
+                                    get_path(&value, &tokens)
+                                        .map_err(|err| {
+                                            EndpointError::invalid(format!(
+                                                "{}: {}",
+                                                err.message(),
+                                                select
+                                            ))
+                                        })?
+                                        .map(Cow::into_owned)
+                                        .ok_or_else(|| {
+                                            EndpointError::invalid(format!(
+                                                "select path not found: {}",
+                                                select
+                                            ))
+                                        })
+                                });
+                            match selected {
+                                Ok(selected) => selected,
+                                Err(err) => {
+                                    return run_catch(
+                                        err,
+                                        request_us,
+                                        body_rule_trace,
+                                        transitions,
+                                    );
+                                }
                             }
-                        };
-                        return Ok(NetworkExecution {
-                            output: selected.clone(),
-                            request_us,
-                            total_us: total_started.elapsed().as_micros() as u64,
-                            body_rule_trace: body_rule_trace.clone(),
-                        });
-                    }
+                        }
+                        None => value,
+                    };
                     return Ok(NetworkExecution {
-                        output: value,
+                        output,
                         request_us,
                         total_us: total_started.elapsed().as_micros() as u64,
-                        body_rule_trace: body_rule_trace.clone(),
+                        body_rule_trace,
+                        breaker_transitions: transitions,
                     });
                 }
                 Err(err) => {
@@ -931,12 +1448,72 @@ impl EndpointEngine {
                             }
                         }
                     }
-                    return run_catch_with_body(err, request_us);
+                    return run_catch(err, request_us, body_rule_trace, transitions);
                 }
             }
         }
     }
 
+    /// Fails fast while the circuit for `upstream` is open; moves it to half-open once
+    /// the cooldown has elapsed so the next request acts as the single probe.
+    fn breaker_before_call(
+        &self,
+        upstream: &str,
+        transitions: &mut Vec<BreakerTransition>,
+    ) -> Result<(), EndpointError> {
+        let config = &self.config.circuit_breaker;
+        if config.failure_threshold == 0 {
+            return Ok(());
+        }
+        let mut breakers = self
+            .state
+            .breakers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(breaker) = breakers.get_mut(upstream) else {
+            return Ok(());
+        };
+        breaker
+            .before_call(config, Instant::now(), upstream, transitions)
+            .map_err(|retry_after| EndpointError::circuit_open(upstream, retry_after))
+    }
+
+    /// Entries exist only while an upstream has a failure streak or a non-closed
+    /// circuit: a success that closes the circuit removes the entry, and closed
+    /// entries whose streak has expired are pruned when a new failure is recorded.
+    fn breaker_record(
+        &self,
+        upstream: &str,
+        result: &Result<JsonValue, EndpointError>,
+        transitions: &mut Vec<BreakerTransition>,
+    ) {
+        let config = &self.config.circuit_breaker;
+        if config.failure_threshold == 0 {
+            return;
+        }
+        let mut breakers = self
+            .state
+            .breakers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let failed = result
+            .as_ref()
+            .is_err_and(EndpointError::is_upstream_failure);
+        let now = Instant::now();
+        if failed {
+            breakers.retain(|key, breaker| key == upstream || !breaker.is_idle(config, now));
+            breakers
+                .entry(upstream.to_string())
+                .or_default()
+                .record_failure(config, now, upstream, transitions);
+        } else if let Some(breaker) = breakers.get_mut(upstream) {
+            breaker.record_success(upstream, transitions);
+            if breaker.state == BreakerState::Closed {
+                breakers.remove(upstream);
+            }
+        }
+    }
+
     fn build_network_body(
         &self,
         rule: &CompiledNetworkRule,
@@ -1087,20 +1664,25 @@ impl EndpointEngine {
     }
 
     fn build_reply(&self, reply: &CompiledReply, input: &JsonValue) -> Result<Response> {
-        let status_value = eval_expr_value(&reply.status, input, Some(&self.config_json()))?;
-        let status = match status_value {
-            EvalValue::Value(JsonValue::Number(num)) => num
-                .as_u64()
-                .ok_or_else(|| anyhow!("status must be integer"))?,
-            EvalValue::Value(JsonValue::String(s)) => s
-                .parse::<u64>()
-                .map_err(|_| anyhow!("status must be integer"))?,
-            _ => return Err(anyhow!("status must be integer")),
+        let status = match &reply.status {
+            Some(status_expr) => {
+                let status_value = eval_expr_value(status_expr, input, Some(&self.config_json()))?;
+                let status = match status_value {
+                    EvalValue::Value(JsonValue::Number(num)) => num
+                        .as_u64()
+                        .ok_or_else(|| anyhow!("status must be integer"))?,
+                    EvalValue::Value(JsonValue::String(s)) => s
+                        .parse::<u64>()
+                        .map_err(|_| anyhow!("status must be integer"))?,
+                    _ => return Err(anyhow!("status must be integer")),
+                };
+                if !(100..=599).contains(&status) {
+                    return Err(anyhow!("status out of range"));
+                }
+                StatusCode::from_u16(status as u16).context("invalid status")?
+            }
+            None => StatusCode::OK,
         };
-        if !(100..=599).contains(&status) {
-            return Err(anyhow!("status out of range"));
-        }
-        let status = StatusCode::from_u16(status as u16).context("invalid status")?;
 
         let body = if let Some(body_expr) = &reply.body {
             match eval_expr_value(body_expr, input, Some(&self.config_json()))? {
@@ -1112,12 +1694,23 @@ impl EndpointEngine {
         };
 
         let mut headers = HeaderMap::new();
-        for (key, value) in &reply.headers {
-            let name = HeaderName::from_bytes(key.as_bytes())
-                .map_err(|_| anyhow!("invalid header name"))?;
-            let header_value =
-                HeaderValue::from_str(value).map_err(|_| anyhow!("invalid header value"))?;
-            headers.insert(name, header_value);
+        for (name, value_expr) in &reply.headers {
+            let value = match eval_expr_value(value_expr, input, Some(&self.config_json()))? {
+                EvalValue::Missing | EvalValue::Value(JsonValue::Null) => continue,
+                EvalValue::Value(JsonValue::String(value)) => value,
+                EvalValue::Value(value @ (JsonValue::Number(_) | JsonValue::Bool(_))) => {
+                    value.to_string()
+                }
+                EvalValue::Value(_) => {
+                    return Err(anyhow!(
+                        "reply header {} must be a string, number or bool",
+                        name
+                    ));
+                }
+            };
+            let header_value = HeaderValue::from_str(&value)
+                .map_err(|_| anyhow!("invalid value for reply header {}: {:?}", name, value))?;
+            headers.insert(name.clone(), header_value);
         }
         if body.is_some() && !headers.contains_key("content-type") {
             headers.insert(
@@ -1171,6 +1764,11 @@ struct CompiledEndpointRule {
 
 impl CompiledEndpointRule {
     fn compile(raw: EndpointRuleFile, source_path: &Path) -> Result<Self> {
+        if raw.include.is_some() {
+            return Err(anyhow!(
+                "include is not supported in endpoint files; use it in the step rules instead"
+            ));
+        }
         let base_dir = source_path
             .parent()
             .unwrap_or_else(|| Path::new("."))
@@ -1178,7 +1776,12 @@ impl CompiledEndpointRule {
         let endpoints = raw
             .endpoints
             .into_iter()
-            .map(|endpoint| CompiledEndpoint::compile(endpoint, &base_dir))
+            .map(|mut endpoint| {
+                if endpoint.auth.is_none() {
+                    endpoint.auth = raw.auth.clone();
+                }
+                CompiledEndpoint::compile(endpoint, &base_dir)
+            })
             .collect::<Result<Vec<_>>>()?;
         Ok(Self {
             base_dir,
@@ -1191,16 +1794,22 @@ impl CompiledEndpointRule {
         self.endpoints
             .iter()
             .find(|endpoint| endpoint.matches(method, path))
-            .map(|endpoint| EndpointMatch {
-                params: endpoint.matcher.capture(path),
-                endpoint,
+            .map(|endpoint| {
+                let (params, invalid_param) = endpoint.matcher.capture(path);
+                EndpointMatch {
+                    endpoint,
+                    params,
+                    invalid_param,
+                }
             })
     }
 }
 
 struct EndpointMatch<'a> {
     endpoint: &'a CompiledEndpoint,
-    params: HashMap<String, String>,
+    params: HashMap<String, JsonValue>,
+    /// Set when a typed path param did not parse; the request is answered with 400.
+    invalid_param: Option<String>,
 }
 
 #[derive(Debug)]
@@ -1211,6 +1820,9 @@ struct CompiledEndpoint {
     steps: Vec<CompiledStep>,
     reply: CompiledReply,
     catch: Option<CatchSpec>,
+    auth: Option<EndpointAuth>,
+    rate_limit: Option<RateLimit>,
+    cache: Option<ReplyCache>,
 }
 
 impl CompiledEndpoint {
@@ -1224,6 +1836,52 @@ impl CompiledEndpoint {
             .map(CompiledStep::compile)
             .collect::<Result<Vec<_>>>()?;
         let reply = CompiledReply::compile(raw.reply)?;
+        if let Some(EndpointAuth::Bearer { token_env }) = &raw.auth
+            && token_env.is_empty()
+        {
+            return Err(anyhow!("auth.token_env must not be empty"));
+        }
+        let rate_limit = match raw.rate_limit {
+            Some(raw_limit) => {
+                if raw_limit.per_second == 0 || raw_limit.burst == 0 {
+                    return Err(anyhow!(
+                        "rate_limit.per_second and rate_limit.burst must be greater than 0"
+                    ));
+                }
+                Some(RateLimit {
+                    key: format!("{} {}", method, raw.path),
+                    per_second: f64::from(raw_limit.per_second),
+                    burst: f64::from(raw_limit.burst),
+                })
+            }
+            None => None,
+        };
+        let cache = match raw.cache {
+            Some(raw_cache) => {
+                if method != Method::GET {
+                    return Err(anyhow!("cache is only supported on GET endpoints"));
+                }
+                if raw_cache.ttl_ms == 0 {
+                    return Err(anyhow!("cache.ttl_ms must be > 0"));
+                }
+                if raw_cache.key.is_empty() {
+                    return Err(anyhow!(
+                        "cache.key must list at least one expr; without it every request shares one entry"
+                    ));
+                }
+                let key = raw_cache
+                    .key
+                    .iter()
+                    .map(|value| parse_v2_expr(value).map_err(|err| anyhow!(err)))
+                    .collect::<Result<Vec<_>>>()?;
+                Some(ReplyCache {
+                    prefix: format!("{} {}", method, raw.path),
+                    ttl: Duration::from_millis(raw_cache.ttl_ms),
+                    key,
+                })
+            }
+            None => None,
+        };
         Ok(Self {
             method,
             matcher,
@@ -1231,6 +1889,9 @@ impl CompiledEndpoint {
             steps,
             reply,
             catch: raw.catch.map(CatchSpec::from),
+            auth: raw.auth,
+            rate_limit,
+            cache,
         })
     }
 
@@ -1244,10 +1905,17 @@ impl CompiledEndpoint {
 
 #[derive(Debug)]
 struct CompiledStep {
-    rule: String,
+    target: StepTarget,
     with: Option<JsonValue>,
     when: Option<rulemorph::v2_model::V2Condition>,
     catch: Option<CatchSpec>,
+    timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+enum StepTarget {
+    Rule(String),
+    Parallel(Vec<CompiledStep>),
 }
 
 impl CompiledStep {
@@ -1256,25 +1924,70 @@ impl CompiledStep {
             Some(value) => Some(parse_v2_condition(&value).map_err(|err| anyhow!(err))?),
             None => None,
         };
-        Ok(Self {
-            rule: raw.rule,
-            with: raw.with,
-            when,
-            catch: raw.catch.map(CatchSpec::from),
-        })
+        if raw.timeout_ms == Some(0) {
+            return Err(anyhow!("steps[].timeout_ms must be > 0"));
+        }
+        let target = match (raw.rule, raw.parallel) {
+            (Some(rule), None) => StepTarget::Rule(rule),
+            (None, Some(branches)) => {
+                if branches.is_empty() {
+                    return Err(anyhow!("steps[].parallel must not be empty"));
+                }
+                if raw.with.is_some() || raw.timeout_ms.is_some() {
+                    return Err(anyhow!(
+                        "steps[].parallel does not support with or timeout_ms; set them on each branch"
+                    ));
+                }
+                if branches.iter().any(|branch| branch.parallel.is_some()) {
+                    return Err(anyhow!("steps[].parallel branches cannot be parallel"));
+                }
+                StepTarget::Parallel(
+                    branches
+                        .into_iter()
+                        .map(CompiledStep::compile)
+                        .collect::<Result<Vec<_>>>()?,
+                )
+            }
+            (Some(_), Some(_)) => {
+                return Err(anyhow!(
+                    "steps[] must have either rule or parallel, not both"
+                ));
+            }
+            (None, None) => return Err(anyhow!("steps[] must have rule or parallel")),
+        };
+        Ok(Self {
+            target,
+            with: raw.with,
+            when,
+            catch: raw.catch.map(CatchSpec::from),
+            timeout: raw.timeout_ms.map(Duration::from_millis),
+        })
+    }
+
+    /// Rule reported for errors that do not carry their own rule path.
+    fn rule_name(&self) -> &str {
+        match &self.target {
+            StepTarget::Rule(rule) => rule,
+            StepTarget::Parallel(_) => "parallel",
+        }
     }
 }
 
 #[derive(Debug)]
 struct CompiledReply {
-    status: rulemorph::v2_model::V2Expr,
-    headers: HashMap<String, String>,
+    /// `None` replies with 200.
+    status: Option<rulemorph::v2_model::V2Expr>,
+    /// Values are v2 exprs; a missing or `null` value leaves the header out.
+    headers: Vec<(HeaderName, rulemorph::v2_model::V2Expr)>,
     body: Option<rulemorph::v2_model::V2Expr>,
 }
 
 impl CompiledReply {
     fn compile(raw: EndpointReply) -> Result<Self> {
-        let status = parse_v2_expr(&raw.status).map_err(|err| anyhow!(err))?;
+        let status = match raw.status {
+            Some(value) => Some(parse_v2_expr(&value).map_err(|err| anyhow!(err))?),
+            None => None,
+        };
         let body = match raw.body {
             Some(value) => Some(parse_v2_expr(&value).map_err(|err| anyhow!(err))?),
             None => None,
@@ -1283,8 +1996,13 @@ impl CompiledReply {
             .headers
             .unwrap_or_default()
             .into_iter()
-            .map(|(k, v)| (k.to_lowercase(), v))
-            .collect();
+            .map(|(key, value)| {
+                let name = HeaderName::from_bytes(key.to_lowercase().as_bytes())
+                    .map_err(|_| anyhow!("invalid reply header name: {}", key))?;
+                let value = parse_v2_expr(&value).map_err(|err| anyhow!(err))?;
+                Ok((name, value))
+            })
+            .collect::<Result<Vec<_>>>()?;
         Ok(Self {
             status,
             headers,
@@ -1301,7 +2019,53 @@ struct EndpointPath {
 #[derive(Debug)]
 enum PathSegment {
     Literal(String),
-    Param(String),
+    Param(String, ParamType),
+}
+
+/// Type of a path param, written as `{name:type}`; `{name}` is a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParamType {
+    String,
+    Int,
+    Float,
+    Bool,
+}
+
+impl ParamType {
+    fn parse(name: &str) -> Result<Self> {
+        match name {
+            "string" => Ok(Self::String),
+            "int" => Ok(Self::Int),
+            "float" => Ok(Self::Float),
+            "bool" => Ok(Self::Bool),
+            other => Err(anyhow!(
+                "unknown path param type: {} (expected string, int, float or bool)",
+                other
+            )),
+        }
+    }
+
+    fn convert(self, value: &str) -> Option<JsonValue> {
+        match self {
+            Self::String => Some(JsonValue::String(value.to_string())),
+            Self::Int => value.parse::<i64>().ok().map(JsonValue::from),
+            Self::Float => value
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(JsonValue::Number),
+            Self::Bool => value.parse::<bool>().ok().map(JsonValue::Bool),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::Bool => "bool",
+        }
+    }
 }
 
 impl EndpointPath {
@@ -1315,10 +2079,14 @@ impl EndpointPath {
             .filter(|seg| !seg.is_empty())
             .map(|seg| {
                 if let Some(param) = seg.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
-                    if param.is_empty() {
+                    let (name, param_type) = match param.split_once(':') {
+                        Some((name, param_type)) => (name, ParamType::parse(param_type)?),
+                        None => (param, ParamType::String),
+                    };
+                    if name.is_empty() {
                         return Err(anyhow!("empty path param"));
                     }
-                    Ok(PathSegment::Param(param.to_string()))
+                    Ok(PathSegment::Param(name.to_string(), param_type))
                 } else {
                     Ok(PathSegment::Literal(seg.to_string()))
                 }
@@ -1345,19 +2113,33 @@ impl EndpointPath {
         true
     }
 
-    fn capture(&self, path: &str) -> HashMap<String, String> {
+    /// Captures the params of a matching path. A typed param that does not parse is
+    /// kept as a string and reported in the returned error message.
+    fn capture(&self, path: &str) -> (HashMap<String, JsonValue>, Option<String>) {
         let parts: Vec<&str> = path
             .trim_start_matches('/')
             .split('/')
             .filter(|seg| !seg.is_empty())
             .collect();
         let mut params = HashMap::new();
+        let mut invalid = None;
         for (seg, part) in self.segments.iter().zip(parts.iter()) {
-            if let PathSegment::Param(name) = seg {
-                params.insert(name.clone(), (*part).to_string());
+            if let PathSegment::Param(name, param_type) = seg {
+                let value = param_type.convert(part).unwrap_or_else(|| {
+                    invalid.get_or_insert_with(|| {
+                        format!(
+                            "path param {} must be {}, got {:?}",
+                            name,
+                            param_type.name(),
+                            part
+                        )
+                    });
+                    JsonValue::String((*part).to_string())
+                });
+                params.insert(name.clone(), value);
             }
         }
-        params
+        (params, invalid)
     }
 }
 
@@ -1366,9 +2148,61 @@ struct EndpointRuleFile {
     version: u8,
     #[serde(rename = "type")]
     rule_type: String,
+    /// Applies to every endpoint that does not set its own `auth`.
+    #[serde(default)]
+    auth: Option<EndpointAuth>,
+    /// Only rejected; endpoint files cannot include other files.
+    #[serde(default)]
+    include: Option<JsonValue>,
     endpoints: Vec<EndpointDef>,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum EndpointAuth {
+    /// `Authorization: Bearer <token>` where the token is read from `token_env`.
+    Bearer { token_env: String },
+}
+
+impl EndpointAuth {
+    fn check(
+        &self,
+        headers: &HeaderMap,
+        token_lookup: fn(&str) -> Option<String>,
+    ) -> Result<(), EndpointError> {
+        match self {
+            EndpointAuth::Bearer { token_env } => {
+                let Some(expected) = token_lookup(token_env) else {
+                    warn!("auth token env {} is not set; rejecting request", token_env);
+                    return Err(EndpointError::unauthorized(
+                        "authentication is not configured",
+                    ));
+                };
+                let provided = headers
+                    .get(axum::http::header::AUTHORIZATION)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.strip_prefix("Bearer "))
+                    .ok_or_else(|| EndpointError::unauthorized("missing bearer token"))?;
+                if expected.is_empty()
+                    || !constant_time_eq(provided.as_bytes(), expected.as_bytes())
+                {
+                    return Err(EndpointError::unauthorized("invalid bearer token"));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct EndpointDef {
     method: String,
@@ -1379,24 +2213,112 @@ struct EndpointDef {
     reply: EndpointReply,
     #[serde(default)]
     catch: Option<HashMap<String, String>>,
+    #[serde(default)]
+    auth: Option<EndpointAuth>,
+    #[serde(default)]
+    rate_limit: Option<EndpointRateLimit>,
+    #[serde(default)]
+    cache: Option<EndpointCache>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct EndpointCache {
+    ttl_ms: u64,
+    #[serde(default)]
+    key: Vec<JsonValue>,
+}
+
+/// Wraps a present value in a one-element array so `missing` (`[]`) and
+/// `null` (`[null]`) produce different cache keys.
+fn reply_cache_key_component(value: EvalValue) -> JsonValue {
+    match value {
+        EvalValue::Value(value) => JsonValue::Array(vec![value]),
+        EvalValue::Missing => JsonValue::Array(Vec::new()),
+    }
+}
+
+#[derive(Debug)]
+struct ReplyCache {
+    prefix: String,
+    ttl: Duration,
+    key: Vec<rulemorph::v2_model::V2Expr>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct EndpointRateLimit {
+    per_second: u32,
+    burst: u32,
+}
+
+#[derive(Debug)]
+struct RateLimit {
+    key: String,
+    per_second: f64,
+    burst: f64,
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn full(limit: &RateLimit, now: Instant) -> Self {
+        Self {
+            tokens: limit.burst,
+            refilled_at: now,
+        }
+    }
+
+    fn try_acquire(&mut self, limit: &RateLimit, now: Instant) -> Result<(), Duration> {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.per_second).min(limit.burst);
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / limit.per_second,
+            ))
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 struct EndpointStep {
-    rule: String,
+    #[serde(default)]
+    rule: Option<String>,
+    /// Branches run concurrently; their outputs are merged into the step input.
+    #[serde(default)]
+    parallel: Option<Vec<EndpointStep>>,
     #[serde(default)]
     with: Option<JsonValue>,
     #[serde(default)]
     when: Option<JsonValue>,
     #[serde(default)]
     catch: Option<HashMap<String, String>>,
+    /// Upper bound on the whole step, including any network calls it makes.
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+}
+
+impl EndpointStep {
+    /// The step followed by its `parallel` branches, if any.
+    fn with_branches(&self) -> impl Iterator<Item = &EndpointStep> {
+        std::iter::once(self).chain(self.parallel.iter().flatten())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 struct EndpointReply {
-    status: JsonValue,
     #[serde(default)]
-    headers: Option<HashMap<String, String>>,
+    status: Option<JsonValue>,
+    #[serde(default)]
+    headers: Option<HashMap<String, JsonValue>>,
     #[serde(default)]
     body: Option<JsonValue>,
 }
@@ -1510,7 +2432,24 @@ impl CatchSpec {
                 return Some(PathBuf::from(value));
             }
         }
-        map.get("default").map(PathBuf::from)
+        if error.kind == EndpointErrorKind::CircuitOpen
+            && let Some(value) = map.get("circuit_open")
+        {
+            return Some(PathBuf::from(value));
+        }
+        // Error kinds use the `code` reported in error replies, e.g. `MissingRequired`
+        // for a transform error or `Timeout` / `Network` for the rest.
+        if let Some(transform) = &error.transform
+            && let Some(value) = map.get(&format!("{:?}", transform.kind))
+        {
+            return Some(PathBuf::from(value));
+        }
+        if let Some(value) = map.get(&format!("{:?}", error.kind)) {
+            return Some(PathBuf::from(value));
+        }
+        map.get("default")
+            .or_else(|| map.get("*"))
+            .map(PathBuf::from)
     }
 }
 
@@ -1520,6 +2459,15 @@ struct EndpointError {
     status: Option<u16>,
     message: String,
     path: Option<PathBuf>,
+    transform: Option<Box<TransformError>>,
+    breaker: Option<Box<BreakerErrorInfo>>,
+}
+
+/// Circuit breaker details attached to errors from network rules.
+#[derive(Debug, Clone, Default)]
+struct BreakerErrorInfo {
+    retry_after: Option<Duration>,
+    transitions: Vec<BreakerTransition>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -1527,8 +2475,12 @@ enum EndpointErrorKind {
     Timeout,
     HttpStatus,
     Network,
+    CircuitOpen,
     Transform,
     Invalid,
+    Unauthorized,
+    RateLimited,
+    PayloadTooLarge,
 }
 
 impl EndpointError {
@@ -1538,6 +2490,15 @@ impl EndpointError {
             status: None,
             message: "timeout".to_string(),
             path: None,
+            transform: None,
+            breaker: None,
+        }
+    }
+
+    fn step_timeout(timeout: Duration) -> Self {
+        Self {
+            message: format!("step timed out after {}ms", timeout.as_millis()),
+            ..Self::timeout()
         }
     }
 
@@ -1547,6 +2508,8 @@ impl EndpointError {
             status: Some(status),
             message: format!("http status {}", status),
             path: None,
+            transform: None,
+            breaker: None,
         }
     }
 
@@ -1556,6 +2519,8 @@ impl EndpointError {
             status: None,
             message,
             path: None,
+            transform: None,
+            breaker: None,
         }
     }
 
@@ -1565,6 +2530,44 @@ impl EndpointError {
             status: None,
             message: message.into(),
             path: None,
+            transform: None,
+            breaker: None,
+        }
+    }
+
+    fn unauthorized(message: impl Into<String>) -> Self {
+        Self {
+            kind: EndpointErrorKind::Unauthorized,
+            status: None,
+            message: message.into(),
+            path: None,
+            transform: None,
+            breaker: None,
+        }
+    }
+
+    fn payload_too_large(limit: usize) -> Self {
+        Self {
+            kind: EndpointErrorKind::PayloadTooLarge,
+            status: None,
+            message: format!("request body exceeds the {} byte limit", limit),
+            path: None,
+            transform: None,
+            breaker: None,
+        }
+    }
+
+    fn rate_limited(retry_after: Duration) -> Self {
+        Self {
+            kind: EndpointErrorKind::RateLimited,
+            status: None,
+            message: format!(
+                "rate limit exceeded; retry after {}ms",
+                retry_after.as_millis()
+            ),
+            path: None,
+            transform: None,
+            breaker: None,
         }
     }
 
@@ -1574,6 +2577,26 @@ impl EndpointError {
             status: None,
             message: err.to_string(),
             path: None,
+            transform: Some(Box::new(err)),
+            breaker: None,
+        }
+    }
+
+    fn circuit_open(url: &str, retry_after: Duration) -> Self {
+        Self {
+            kind: EndpointErrorKind::CircuitOpen,
+            status: None,
+            message: format!(
+                "circuit open for {}; retry after {}ms",
+                url,
+                retry_after.as_millis()
+            ),
+            path: None,
+            transform: None,
+            breaker: Some(Box::new(BreakerErrorInfo {
+                retry_after: Some(retry_after),
+                transitions: Vec::new(),
+            })),
         }
     }
 
@@ -1582,6 +2605,27 @@ impl EndpointError {
         self
     }
 
+    fn with_breaker_transitions(mut self, transitions: Vec<BreakerTransition>) -> Self {
+        if !transitions.is_empty() {
+            self.breaker
+                .get_or_insert_with(Default::default)
+                .transitions
+                .extend(transitions);
+        }
+        self
+    }
+
+    /// Whether the error counts against the upstream's circuit breaker.
+    fn is_upstream_failure(&self) -> bool {
+        match self.kind {
+            EndpointErrorKind::Timeout | EndpointErrorKind::Network => true,
+            EndpointErrorKind::HttpStatus => self
+                .status
+                .is_some_and(|status| status >= 500 || status == 429),
+            _ => false,
+        }
+    }
+
     fn to_json(&self) -> JsonValue {
         json!({
             "kind": format!("{:?}", self.kind),
@@ -1592,6 +2636,30 @@ impl EndpointError {
     }
 }
 
+fn is_length_limit_error(err: &axum::Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(current) = source {
+        if current.is::<http_body_util::LengthLimitError>() {
+            return true;
+        }
+        source = current.source();
+    }
+    false
+}
+
+fn endpoint_error_status(kind: &EndpointErrorKind) -> StatusCode {
+    match kind {
+        EndpointErrorKind::Invalid => StatusCode::BAD_REQUEST,
+        EndpointErrorKind::Unauthorized => StatusCode::UNAUTHORIZED,
+        EndpointErrorKind::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+        EndpointErrorKind::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+        EndpointErrorKind::Transform => StatusCode::UNPROCESSABLE_ENTITY,
+        EndpointErrorKind::Timeout => StatusCode::GATEWAY_TIMEOUT,
+        EndpointErrorKind::CircuitOpen => StatusCode::SERVICE_UNAVAILABLE,
+        EndpointErrorKind::Network | EndpointErrorKind::HttpStatus => StatusCode::BAD_GATEWAY,
+    }
+}
+
 impl std::fmt::Display for EndpointError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.message)
@@ -1600,7 +2668,7 @@ impl std::fmt::Display for EndpointError {
 
 fn build_input(
     parts: &axum::http::request::Parts,
-    path_params: &HashMap<String, String>,
+    path_params: &HashMap<String, JsonValue>,
     body: Option<JsonValue>,
 ) -> Result<JsonValue, EndpointError> {
     let query = parse_query(parts.uri.query())?;
@@ -1609,7 +2677,7 @@ fn build_input(
 
 fn build_input_from_parts(
     parts: &axum::http::request::Parts,
-    path_params: &HashMap<String, String>,
+    path_params: &HashMap<String, JsonValue>,
     body: Option<JsonValue>,
     query: JsonValue,
 ) -> JsonValue {
@@ -1641,6 +2709,31 @@ fn build_input_from_parts(
     input
 }
 
+/// Request headers replaced with [`REDACTED`] in traces, which are stored on disk and
+/// served without authentication.
+const REDACTED_TRACE_HEADERS: &[&str] = &["authorization", "proxy-authorization"];
+
+const REDACTED: &str = "[redacted]";
+
+/// Redact credential headers in every `headers` object of a trace, covering the request
+/// input, step inputs and outputs, and their child traces.
+fn redact_trace_headers(value: &mut JsonValue) {
+    match value {
+        JsonValue::Object(map) => {
+            if let Some(JsonValue::Object(headers)) = map.get_mut("headers") {
+                for (name, header) in headers.iter_mut() {
+                    if REDACTED_TRACE_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+                        *header = JsonValue::String(REDACTED.to_string());
+                    }
+                }
+            }
+            map.values_mut().for_each(redact_trace_headers);
+        }
+        JsonValue::Array(items) => items.iter_mut().for_each(redact_trace_headers),
+        _ => {}
+    }
+}
+
 fn build_headers(headers: &HashMap<String, String>) -> Result<HeaderMap, EndpointError> {
     let mut map = HeaderMap::new();
     for (key, value) in headers {
@@ -1678,6 +2771,7 @@ fn apply_mappings_via_rule(
 ) -> Result<Option<JsonValue>, TransformError> {
     let rule = RuleFile {
         version: 2,
+        include: Vec::new(),
         input: rulemorph::InputSpec {
             format: rulemorph::InputFormat::Json,
             csv: None,
@@ -1808,7 +2902,7 @@ fn push_rule_error(errors: &mut Vec<RulesDirError>, path: &Path, err: &RuleError
     push_error(
         errors,
         err.code.as_str(),
-        path,
+        err.file.as_deref().unwrap_or(path),
         err.message.clone(),
         err.path.clone(),
         location,
@@ -1895,7 +2989,18 @@ fn validate_normal_rule(
             return;
         }
     };
-    if let Err(rule_errors) = validate_rule_file_with_source(&rule, source) {
+    if !rule.include.is_empty() {
+        match resolve_rule_includes(rule.clone(), source.to_string(), path) {
+            Ok(resolved) => {
+                if let Err(rule_errors) = validate_rule_file_with_includes(&resolved) {
+                    for err in rule_errors {
+                        push_rule_error(errors, path, &err);
+                    }
+                }
+            }
+            Err(err) => push_rule_error(errors, path, &err),
+        }
+    } else if let Err(rule_errors) = validate_rule_file_with_source(&rule, source) {
         for err in rule_errors {
             push_rule_error(errors, path, &err);
         }
@@ -2110,10 +3215,11 @@ fn load_rule_kind(path: &Path) -> Result<RuleKind> {
         }
         "endpoint" => Err(anyhow!("endpoint rule not allowed as step")),
         _ => {
-            let rule = parse_rule_file(&source)
-                .with_context(|| format!("failed to parse {}", path.display()))?;
-            validate_rule_file_with_source(&rule, &source)
+            let resolved = load_rule_file_with_includes(path)
+                .map_err(|err| anyhow!("failed to load {}: {:?}", path.display(), err))?;
+            validate_rule_file_with_includes(&resolved)
                 .map_err(|err| anyhow!("failed to validate {}: {:?}", path.display(), err))?;
+            let rule = resolved.rule;
             let base_dir = path
                 .parent()
                 .unwrap_or_else(|| Path::new("."))
@@ -2167,12 +3273,11 @@ fn compile_network_rule(raw: NetworkRuleFile, path: &Path) -> Result<CompiledNet
         Some(path_str) => {
             let resolved =
                 resolve_rule_path(path.parent().unwrap_or_else(|| Path::new(".")), path_str);
-            let source = std::fs::read_to_string(&resolved)
-                .with_context(|| format!("failed to read {}", resolved.display()))?;
-            let rule = parse_rule_file(&source)
-                .with_context(|| format!("failed to parse {}", resolved.display()))?;
-            validate_rule_file_with_source(&rule, &source)
+            let loaded = load_rule_file_with_includes(&resolved)
+                .map_err(|err| anyhow!("failed to load {}: {:?}", resolved.display(), err))?;
+            validate_rule_file_with_includes(&loaded)
                 .map_err(|err| anyhow!("failed to validate {}: {:?}", resolved.display(), err))?;
+            let rule = loaded.rule;
             let base_dir = resolved
                 .parent()
                 .unwrap_or_else(|| Path::new("."))
@@ -2264,6 +3369,170 @@ impl RetryConfig {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum BreakerState {
+    #[default]
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl BreakerState {
+    fn as_str(self) -> &'static str {
+        match self {
+            BreakerState::Closed => "closed",
+            BreakerState::Open => "open",
+            BreakerState::HalfOpen => "half_open",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BreakerTransition {
+    url: String,
+    from: BreakerState,
+    to: BreakerState,
+    failures: u32,
+}
+
+impl BreakerTransition {
+    fn to_json(&self) -> JsonValue {
+        json!({
+            "url": self.url,
+            "from": self.from.as_str(),
+            "to": self.to.as_str(),
+            "failures": self.failures,
+        })
+    }
+}
+
+/// Identifies the upstream a circuit belongs to: scheme, host and port of `url`.
+fn breaker_key(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(parsed) => format!(
+            "{}://{}:{}",
+            parsed.scheme(),
+            parsed.host_str().unwrap_or_default(),
+            parsed.port_or_known_default().unwrap_or_default()
+        ),
+        Err(_) => url.to_string(),
+    }
+}
+
+/// Circuit state for a single upstream (scheme, host and port).
+#[derive(Debug, Default)]
+struct CircuitBreaker {
+    state: BreakerState,
+    failures: u32,
+    streak_started: Option<Instant>,
+    opened_at: Option<Instant>,
+    /// When the half-open probe was let through; other callers fail fast until it finishes.
+    probe_started: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    /// Returns the remaining cooldown when the call must fail fast.
+    fn before_call(
+        &mut self,
+        config: &CircuitBreakerConfig,
+        now: Instant,
+        upstream: &str,
+        transitions: &mut Vec<BreakerTransition>,
+    ) -> Result<(), Duration> {
+        match self.state {
+            BreakerState::Closed => Ok(()),
+            BreakerState::HalfOpen => {
+                // A probe whose caller went away never reports back; replace it after a cooldown.
+                let probe_elapsed = self
+                    .probe_started
+                    .map(|started| now.saturating_duration_since(started))
+                    .unwrap_or(config.cooldown);
+                if probe_elapsed < config.cooldown {
+                    return Err(config.cooldown - probe_elapsed);
+                }
+                self.probe_started = Some(now);
+                Ok(())
+            }
+            BreakerState::Open => {
+                let elapsed = self
+                    .opened_at
+                    .map(|opened_at| now.saturating_duration_since(opened_at))
+                    .unwrap_or(config.cooldown);
+                if elapsed < config.cooldown {
+                    return Err(config.cooldown - elapsed);
+                }
+                self.probe_started = Some(now);
+                self.transition(BreakerState::HalfOpen, upstream, transitions);
+                Ok(())
+            }
+        }
+    }
+
+    /// A closed circuit whose failure streak has expired carries no state worth keeping.
+    fn is_idle(&self, config: &CircuitBreakerConfig, now: Instant) -> bool {
+        self.state == BreakerState::Closed
+            && self.streak_started.is_none_or(|started| {
+                now.saturating_duration_since(started) > config.failure_window
+            })
+    }
+
+    fn record_success(&mut self, url: &str, transitions: &mut Vec<BreakerTransition>) {
+        self.failures = 0;
+        self.streak_started = None;
+        self.probe_started = None;
+        if self.state != BreakerState::Closed {
+            self.opened_at = None;
+            self.transition(BreakerState::Closed, url, transitions);
+        }
+    }
+
+    fn record_failure(
+        &mut self,
+        config: &CircuitBreakerConfig,
+        now: Instant,
+        url: &str,
+        transitions: &mut Vec<BreakerTransition>,
+    ) {
+        let streak_expired = self
+            .streak_started
+            .is_none_or(|started| now.saturating_duration_since(started) > config.failure_window);
+        if streak_expired {
+            self.failures = 0;
+            self.streak_started = Some(now);
+        }
+        self.failures = self.failures.saturating_add(1);
+        let should_open = match self.state {
+            BreakerState::Closed => self.failures >= config.failure_threshold,
+            BreakerState::HalfOpen => true,
+            BreakerState::Open => false,
+        };
+        if should_open {
+            self.opened_at = Some(now);
+            self.probe_started = None;
+            self.transition(BreakerState::Open, url, transitions);
+            warn!(
+                "circuit opened for {} after {} consecutive failures",
+                url, self.failures
+            );
+        }
+    }
+
+    fn transition(
+        &mut self,
+        to: BreakerState,
+        url: &str,
+        transitions: &mut Vec<BreakerTransition>,
+    ) {
+        transitions.push(BreakerTransition {
+            url: url.to_string(),
+            from: self.state,
+            to,
+            failures: self.failures,
+        });
+        self.state = to;
+    }
+}
+
 fn empty_object() -> JsonValue {
     JsonValue::Object(serde_json::Map::new())
 }
@@ -2946,6 +4215,16 @@ fn build_network_nodes_with_timing(
             }
         }));
     }
+    if !timing.breaker_transitions.is_empty() {
+        children.push(json!({
+            "id": "op-circuit-breaker",
+            "kind": "op",
+            "label": "circuit_breaker",
+            "status": "ok",
+            "meta": { "op": "circuit_breaker" },
+            "args": { "transitions": breaker_transitions_json(&timing.breaker_transitions) }
+        }));
+    }
 
     let mut node = json!({
         "id": "step-0",
@@ -2976,6 +4255,10 @@ fn build_network_nodes_with_timing(
     vec![node]
 }
 
+fn breaker_transitions_json(transitions: &[BreakerTransition]) -> JsonValue {
+    JsonValue::Array(transitions.iter().map(BreakerTransition::to_json).collect())
+}
+
 fn build_mapping_ops_with_values(
     mappings: &[Mapping],
     record: &JsonValue,
@@ -3344,7 +4627,7 @@ fn resolve_source_value(
         _ => Some(record),
     }?;
     let tokens = parse_path(path).ok()?;
-    get_path(target, &tokens).cloned()
+    get_path(target, &tokens).ok()?.map(Cow::into_owned)
 }
 
 fn set_path_value(root: &mut JsonValue, path: &str, value: JsonValue) -> Result<(), ()> {
@@ -3357,7 +4640,7 @@ fn set_path_value(root: &mut JsonValue, path: &str, value: JsonValue) -> Result<
         let is_last = index == tokens.len() - 1;
         let key = match token {
             PathToken::Key(key) => key,
-            PathToken::Index(_) => return Err(()),
+            _ => return Err(()),
         };
 
         if is_last {
@@ -3417,14 +4700,164 @@ enum RuleKind {
 mod tests {
     use super::*;
     use serde_json::json;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
     #[test]
     fn endpoint_path_matches_and_captures() {
         let path = EndpointPath::parse("/api/traces/{id}").unwrap();
         assert!(path.matches("/api/traces/abc"));
-        let params = path.capture("/api/traces/abc");
-        assert_eq!(params.get("id"), Some(&"abc".to_string()));
+        let (params, invalid) = path.capture("/api/traces/abc");
+        assert_eq!(params.get("id"), Some(&json!("abc")));
+        assert_eq!(invalid, None);
+    }
+
+    #[test]
+    fn circuit_breaker_transitions() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 2,
+            failure_window: Duration::from_secs(10),
+            cooldown: Duration::from_secs(5),
+        };
+        let url = "http://upstream/api";
+        let start = Instant::now();
+        let mut breaker = CircuitBreaker::default();
+        let mut transitions = Vec::new();
+
+        breaker.record_failure(&config, start, url, &mut transitions);
+        assert!(
+            breaker
+                .before_call(&config, start, url, &mut transitions)
+                .is_ok()
+        );
+        breaker.record_failure(&config, start, url, &mut transitions);
+        assert_eq!(breaker.state, BreakerState::Open);
+
+        let retry_after = breaker
+            .before_call(
+                &config,
+                start + Duration::from_secs(2),
+                url,
+                &mut transitions,
+            )
+            .expect_err("circuit should be open");
+        assert_eq!(retry_after, Duration::from_secs(3));
+
+        let probe_at = start + Duration::from_secs(5);
+        assert!(
+            breaker
+                .before_call(&config, probe_at, url, &mut transitions)
+                .is_ok()
+        );
+        assert_eq!(breaker.state, BreakerState::HalfOpen);
+        // Only one probe is admitted while it is in flight.
+        assert!(
+            breaker
+                .before_call(&config, probe_at, url, &mut transitions)
+                .is_err()
+        );
+        breaker.record_failure(&config, probe_at, url, &mut transitions);
+        assert_eq!(breaker.state, BreakerState::Open);
+
+        let probe_at = probe_at + Duration::from_secs(5);
+        assert!(
+            breaker
+                .before_call(&config, probe_at, url, &mut transitions)
+                .is_ok()
+        );
+        breaker.record_success(url, &mut transitions);
+        assert_eq!(breaker.state, BreakerState::Closed);
+
+        let states: Vec<(&str, &str)> = transitions
+            .iter()
+            .map(|t| (t.from.as_str(), t.to.as_str()))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                ("closed", "open"),
+                ("open", "half_open"),
+                ("half_open", "open"),
+                ("open", "half_open"),
+                ("half_open", "closed"),
+            ]
+        );
+    }
+
+    #[test]
+    fn circuit_breaker_replaces_abandoned_probe() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 1,
+            failure_window: Duration::from_secs(10),
+            cooldown: Duration::from_secs(5),
+        };
+        let start = Instant::now();
+        let mut breaker = CircuitBreaker::default();
+        let mut transitions = Vec::new();
+        breaker.record_failure(&config, start, "u", &mut transitions);
+        let probe_at = start + Duration::from_secs(5);
+        assert!(
+            breaker
+                .before_call(&config, probe_at, "u", &mut transitions)
+                .is_ok()
+        );
+        assert_eq!(
+            breaker.before_call(
+                &config,
+                probe_at + Duration::from_secs(1),
+                "u",
+                &mut transitions
+            ),
+            Err(Duration::from_secs(4))
+        );
+        assert!(
+            breaker
+                .before_call(
+                    &config,
+                    probe_at + Duration::from_secs(5),
+                    "u",
+                    &mut transitions
+                )
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn breaker_key_is_the_upstream_origin() {
+        assert_eq!(
+            breaker_key("https://api.example.com/items/1?lang=en"),
+            "https://api.example.com:443"
+        );
+        assert_eq!(
+            breaker_key("http://127.0.0.1:8080/a"),
+            breaker_key("http://127.0.0.1:8080/b?x=1")
+        );
+        assert_ne!(
+            breaker_key("http://127.0.0.1:8080/a"),
+            breaker_key("http://127.0.0.1:8081/a")
+        );
+    }
+
+    #[test]
+    fn circuit_breaker_failure_window_resets_streak() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 2,
+            failure_window: Duration::from_secs(1),
+            cooldown: Duration::from_secs(5),
+        };
+        let start = Instant::now();
+        let mut breaker = CircuitBreaker::default();
+        let mut transitions = Vec::new();
+        breaker.record_failure(&config, start, "u", &mut transitions);
+        breaker.record_failure(
+            &config,
+            start + Duration::from_secs(2),
+            "u",
+            &mut transitions,
+        );
+        assert_eq!(breaker.state, BreakerState::Closed);
+        assert!(transitions.is_empty());
     }
 
     #[test]
@@ -3686,9 +5119,951 @@ endpoints:
         assert!(bytes.is_empty());
     }
 
-    #[tokio::test]
-    async fn step_catch_inherits_with_params() {
-        let temp = tempfile::tempdir().expect("tempdir");
+    fn collect_traces(dir: &Path, traces: &mut Vec<JsonValue>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                collect_traces(&path, traces);
+            } else {
+                let text = std::fs::read_to_string(&path).expect("read trace");
+                traces.push(serde_json::from_str(&text).expect("parse trace"));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn bearer_auth_rejects_missing_and_wrong_tokens() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let rules_dir = temp.path();
+        std::fs::write(
+            rules_dir.join("endpoint.yaml"),
+            r#"
+version: 2
+type: endpoint
+auth:
+  type: bearer
+  token_env: RULEMORPH_TEST_BEARER_TOKEN
+endpoints:
+  - method: GET
+    path: /api/secure
+    steps: []
+    reply:
+      status: 200
+      body: { ok: true }
+"#,
+        )
+        .expect("write endpoint.yaml");
+
+        let data_dir = rules_dir.join(".data");
+        let engine = EndpointEngine::load(
+            rules_dir.to_path_buf(),
+            EngineConfig::new("http://localhost".to_string(), data_dir.clone()).with_token_lookup(
+                |name| (name == "RULEMORPH_TEST_BEARER_TOKEN").then(|| "s3cret".to_string()),
+            ),
+        )
+        .expect("load engine");
+
+        let request = |token: Option<&str>| {
+            let mut builder = Request::builder().method("GET").uri("/api/secure");
+            if let Some(token) = token {
+                builder = builder.header("authorization", token);
+            }
+            builder
+                .body(axum::body::Body::empty())
+                .expect("build request")
+        };
+
+        for (token, message) in [
+            (None, "missing bearer token"),
+            (Some("Bearer wrong"), "invalid bearer token"),
+            (Some("s3cret"), "missing bearer token"),
+        ] {
+            let err = engine
+                .handle_request(request(token))
+                .await
+                .expect_err("expected auth failure");
+            let failure = err.downcast_ref::<EndpointFailure>().expect("failure");
+            assert_eq!(failure.status, StatusCode::UNAUTHORIZED);
+            assert_eq!(failure.code, "Unauthorized");
+            assert_eq!(failure.message, message);
+        }
+
+        let response = engine
+            .handle_request(request(Some("Bearer s3cret")))
+            .await
+            .expect("authorized request");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut traces = Vec::new();
+        collect_traces(&data_dir, &mut traces);
+        let mut statuses: Vec<&str> = traces
+            .iter()
+            .filter_map(|trace| trace.get("status").and_then(|value| value.as_str()))
+            .collect();
+        statuses.sort_unstable();
+        assert_eq!(statuses, vec!["error", "error", "error", "ok"]);
+        for trace in traces.iter().filter(|trace| trace["status"] == "error") {
+            assert!(trace.to_string().contains("Unauthorized"));
+            assert!(!trace.to_string().contains("wrong"));
+        }
+        let accepted = traces
+            .iter()
+            .find(|trace| trace["status"] == "ok")
+            .expect("accepted trace");
+        assert!(!accepted.to_string().contains("s3cret"), "{accepted}");
+        assert_eq!(
+            accepted["records"][0]["input"]["headers"]["authorization"],
+            "[redacted]"
+        );
+    }
+
+    #[tokio::test]
+    async fn rate_limit_rejects_requests_after_burst() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let rules_dir = temp.path();
+        std::fs::write(
+            rules_dir.join("endpoint.yaml"),
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: GET
+    path: /api/limited
+    rate_limit: { per_second: 1, burst: 2 }
+    steps: []
+    reply:
+      status: 200
+  - method: GET
+    path: /api/open
+    steps: []
+    reply:
+      status: 200
+"#,
+        )
+        .expect("write endpoint.yaml");
+
+        let data_dir = rules_dir.join(".data");
+        let engine = EndpointEngine::load(
+            rules_dir.to_path_buf(),
+            EngineConfig::new("http://localhost".to_string(), data_dir.clone()),
+        )
+        .expect("load engine");
+        let request = |uri: &str| {
+            Request::builder()
+                .method("GET")
+                .uri(uri)
+                .body(axum::body::Body::empty())
+                .expect("build request")
+        };
+
+        for _ in 0..2 {
+            let response = engine
+                .handle_request(request("/api/limited"))
+                .await
+                .expect("within burst");
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        for _ in 0..2 {
+            let err = engine
+                .handle_request(request("/api/limited"))
+                .await
+                .expect_err("expected rate limit");
+            let failure = err.downcast_ref::<EndpointFailure>().expect("failure");
+            assert_eq!(failure.status, StatusCode::TOO_MANY_REQUESTS);
+            assert_eq!(failure.code, "RateLimited");
+            let retry_after = failure.retry_after.expect("retry after");
+            assert!(retry_after > Duration::ZERO && retry_after <= Duration::from_secs(1));
+        }
+        let response = engine
+            .handle_request(request("/api/open"))
+            .await
+            .expect("other endpoints are not limited");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut traces = Vec::new();
+        collect_traces(&data_dir, &mut traces);
+        let limited = traces
+            .iter()
+            .filter(|trace| trace["status"] == "rate_limited")
+            .count();
+        assert_eq!(limited, 2);
+    }
+
+    #[tokio::test]
+    async fn oversized_body_is_rejected_with_413() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let rules_dir = temp.path();
+        std::fs::write(
+            rules_dir.join("endpoint.yaml"),
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: POST
+    path: /api/echo
+    steps: []
+    reply:
+      status: 200
+      body: "@input.body"
+"#,
+        )
+        .expect("write endpoint.yaml");
+
+        let data_dir = rules_dir.join(".data");
+        let engine = EndpointEngine::load(
+            rules_dir.to_path_buf(),
+            EngineConfig::new("http://localhost".to_string(), data_dir.clone())
+                .with_max_body_bytes(32),
+        )
+        .expect("load engine");
+        let request = |body: String| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/echo")
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body))
+                .expect("build request")
+        };
+
+        let response = engine
+            .handle_request(request(r#"{"name":"small"}"#.to_string()))
+            .await
+            .expect("small body");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let err = engine
+            .handle_request(request(format!(r#"{{"name":"{}"}}"#, "x".repeat(64))))
+            .await
+            .expect_err("expected payload too large");
+        let failure = err.downcast_ref::<EndpointFailure>().expect("failure");
+        assert_eq!(failure.status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(failure.code, "PayloadTooLarge");
+        assert_eq!(failure.message, "request body exceeds the 32 byte limit");
+
+        let mut traces = Vec::new();
+        collect_traces(&data_dir, &mut traces);
+        let rejected: Vec<&JsonValue> = traces
+            .iter()
+            .filter(|trace| trace["status"] == "error")
+            .collect();
+        assert_eq!(rejected.len(), 1);
+        assert!(
+            rejected[0]
+                .to_string()
+                .contains("exceeds the 32 byte limit")
+        );
+    }
+
+    #[tokio::test]
+    async fn reply_status_is_literal_expression_or_default() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let rules_dir = temp.path();
+        std::fs::write(
+            rules_dir.join("endpoint.yaml"),
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: POST
+    path: /api/items
+    steps: []
+    reply:
+      status: 201
+      body: "@input.body"
+  - method: GET
+    path: /api/items/{id}
+    input:
+      - target: "found"
+        expr:
+          - "@input.path.id"
+          - eq: ["1"]
+    steps: []
+    reply:
+      status:
+        - "@input.found"
+        - if:
+            cond: { eq: ["$", true] }
+            then: 200
+            else: 404
+      body: "@input"
+  - method: GET
+    path: /api/health
+    steps: []
+    reply:
+      body: { ok: true }
+"#,
+        )
+        .expect("write endpoint.yaml");
+
+        let engine = EndpointEngine::load(
+            rules_dir.to_path_buf(),
+            EngineConfig::new("http://localhost".to_string(), rules_dir.join(".data")),
+        )
+        .expect("load engine");
+        let status = |method: &'static str, uri: &'static str, body: &'static str| {
+            let engine = &engine;
+            async move {
+                let request = Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .body(axum::body::Body::from(body))
+                    .expect("build request");
+                engine
+                    .handle_request(request)
+                    .await
+                    .expect("handle")
+                    .status()
+            }
+        };
+
+        assert_eq!(
+            status("POST", "/api/items", r#"{"name":"a"}"#).await,
+            StatusCode::CREATED
+        );
+        assert_eq!(status("GET", "/api/items/1", "").await, StatusCode::OK);
+        assert_eq!(
+            status("GET", "/api/items/2", "").await,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(status("GET", "/api/health", "").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn reply_headers_are_evaluated() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let rules_dir = temp.path();
+        std::fs::write(
+            rules_dir.join("endpoint.yaml"),
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: POST
+    path: /api/items
+    steps: []
+    reply:
+      headers:
+        Cache-Control: "max-age=60"
+        X-Total-Count:
+          - "@input.body.items"
+          - length
+        X-Tenant: "@input.headers.x-tenant-id"
+        X-Broken: "@input.body.broken"
+      body: "@input.body.items"
+"#,
+        )
+        .expect("write endpoint.yaml");
+
+        let engine = EndpointEngine::load(
+            rules_dir.to_path_buf(),
+            EngineConfig::new("http://localhost".to_string(), rules_dir.join(".data")),
+        )
+        .expect("load engine");
+        let request = |body: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/items")
+                .header("x-tenant-id", "acme")
+                .body(axum::body::Body::from(body.to_string()))
+                .expect("build request")
+        };
+
+        let response = engine
+            .handle_request(request(r#"{"items":[1,2,3]}"#))
+            .await
+            .expect("handle");
+        let headers = response.headers();
+        assert_eq!(headers["cache-control"], "max-age=60");
+        assert_eq!(headers["x-total-count"], "3");
+        assert_eq!(headers["x-tenant"], "acme");
+        assert!(!headers.contains_key("x-broken"));
+        assert_eq!(headers["content-type"], "application/json");
+
+        let err = engine
+            .handle_request(request(r#"{"items":[],"broken":"a\nb"}"#))
+            .await
+            .expect_err("expected invalid header value");
+        let failure = err.downcast_ref::<EndpointFailure>().expect("failure");
+        assert_eq!(failure.status, StatusCode::BAD_REQUEST);
+        assert!(
+            failure
+                .message
+                .contains("invalid value for reply header x-broken"),
+            "{}",
+            failure.message
+        );
+    }
+
+    #[test]
+    fn reply_headers_reject_invalid_names() {
+        let raw: EndpointRuleFile = serde_yaml::from_str(
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: GET
+    path: /api/test
+    steps: []
+    reply:
+      headers:
+        "bad header": "x"
+"#,
+        )
+        .expect("parse endpoint");
+        let err = CompiledEndpointRule::compile(raw, Path::new("endpoint.yaml")).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("invalid reply header name: bad header")
+        );
+    }
+
+    #[tokio::test]
+    async fn typed_path_params_are_converted() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let rules_dir = temp.path();
+        std::fs::write(
+            rules_dir.join("endpoint.yaml"),
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: GET
+    path: /api/users/{id:int}/flags/{enabled:bool}
+    steps: []
+    reply:
+      status: 200
+      body: "@input.path"
+  - method: GET
+    path: /api/prices/{amount:float}/{currency}
+    steps: []
+    reply:
+      status: 200
+      body: "@input.path"
+"#,
+        )
+        .expect("write endpoint.yaml");
+
+        let engine = EndpointEngine::load(
+            rules_dir.to_path_buf(),
+            EngineConfig::new("http://localhost".to_string(), rules_dir.join(".data")),
+        )
+        .expect("load engine");
+        let request = |uri: &str| {
+            Request::builder()
+                .method("GET")
+                .uri(uri)
+                .body(axum::body::Body::empty())
+                .expect("build request")
+        };
+        let params = |uri: &'static str| {
+            let engine = &engine;
+            async move {
+                let response = engine.handle_request(request(uri)).await.expect("handle");
+                assert_eq!(response.status(), StatusCode::OK);
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .expect("read body");
+                serde_json::from_slice::<JsonValue>(&bytes).expect("parse body")
+            }
+        };
+
+        assert_eq!(
+            params("/api/users/42/flags/true").await,
+            json!({ "id": 42, "enabled": true })
+        );
+        assert_eq!(
+            params("/api/prices/9.5/usd").await,
+            json!({ "amount": 9.5, "currency": "usd" })
+        );
+
+        for (uri, message) in [
+            (
+                "/api/users/abc/flags/true",
+                r#"path param id must be int, got "abc""#,
+            ),
+            (
+                "/api/users/1/flags/yes",
+                r#"path param enabled must be bool, got "yes""#,
+            ),
+        ] {
+            let err = engine
+                .handle_request(request(uri))
+                .await
+                .expect_err("expected invalid param");
+            let failure = err.downcast_ref::<EndpointFailure>().expect("failure");
+            assert_eq!(failure.status, StatusCode::BAD_REQUEST);
+            assert_eq!(failure.code, "Invalid");
+            assert_eq!(failure.message, message);
+        }
+    }
+
+    #[test]
+    fn typed_path_params_reject_unknown_types() {
+        let err = EndpointPath::parse("/api/users/{id:uuid}").unwrap_err();
+        assert!(err.to_string().contains("unknown path param type: uuid"));
+        assert!(EndpointPath::parse("/api/users/{:int}").is_err());
+    }
+
+    #[test]
+    fn rate_limit_rejects_zero_values() {
+        let raw: EndpointRuleFile = serde_yaml::from_str(
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: GET
+    path: /api/limited
+    rate_limit: { per_second: 0, burst: 1 }
+    steps: []
+    reply:
+      status: 200
+"#,
+        )
+        .expect("parse endpoint");
+        let err = CompiledEndpointRule::compile(raw, Path::new("endpoint.yaml")).unwrap_err();
+        assert!(err.to_string().contains("rate_limit.per_second"));
+    }
+
+    #[tokio::test]
+    async fn step_catch_inherits_with_params() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let rules_dir = temp.path();
+        let rules_subdir = rules_dir.join("rules");
+        std::fs::create_dir_all(&rules_subdir).expect("create rules dir");
+
+        std::fs::write(
+            rules_dir.join("endpoint.yaml"),
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: GET
+    path: /api/test
+    steps:
+      - rule: ./rules/failing_network.yaml
+        with:
+          fields: ["name"]
+        catch:
+          default: ./rules/catch.yaml
+    reply:
+      status: 200
+      body: "@input"
+"#,
+        )
+        .expect("write endpoint.yaml");
+
+        std::fs::write(
+            rules_subdir.join("failing_network.yaml"),
+            r#"
+version: 2
+type: network
+request:
+  method: GET
+  url: "http://example.com"
+timeout: 1s
+body: "@input"
+"#,
+        )
+        .expect("write failing network rule");
+
+        std::fs::write(
+            rules_subdir.join("catch.yaml"),
+            r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "params"
+    expr: "@context.params"
+    required: true
+"#,
+        )
+        .expect("write catch rule");
+
+        let engine = EndpointEngine::load(
+            rules_dir.to_path_buf(),
+            EngineConfig::new("http://localhost".to_string(), rules_dir.to_path_buf()),
+        )
+        .expect("load engine");
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/test")
+            .body(axum::body::Body::empty())
+            .expect("build request");
+
+        let response = engine
+            .handle_request(request)
+            .await
+            .expect("handle request");
+        assert_eq!(response.status().as_u16(), 200);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("read body");
+        let body: JsonValue = serde_json::from_slice(&bytes).expect("parse body");
+        assert_eq!(body, json!({ "params": { "fields": ["name"] } }));
+    }
+
+    #[tokio::test]
+    async fn endpoint_duplicate_query_runs_catch() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let rules_dir = temp.path();
+        let rules_subdir = rules_dir.join("rules");
+        std::fs::create_dir_all(&rules_subdir).expect("create rules dir");
+
+        std::fs::write(
+            rules_dir.join("endpoint.yaml"),
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: GET
+    path: /api/test
+    catch:
+      default: ./rules/catch.yaml
+    steps: []
+    reply:
+      status: 200
+      body: "@input"
+"#,
+        )
+        .expect("write endpoint.yaml");
+
+        std::fs::write(
+            rules_subdir.join("catch.yaml"),
+            r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "handled"
+    value: true
+"#,
+        )
+        .expect("write catch.yaml");
+
+        let engine = EndpointEngine::load(
+            rules_dir.to_path_buf(),
+            EngineConfig::new("http://localhost".to_string(), rules_dir.to_path_buf()),
+        )
+        .expect("load engine");
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/api/test?dup=1&dup=2")
+            .body(axum::body::Body::empty())
+            .expect("build request");
+
+        let response = engine
+            .handle_request(request)
+            .await
+            .expect("handle request");
+        assert_eq!(response.status().as_u16(), 200);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("read body");
+        let body: JsonValue = serde_json::from_slice(&bytes).expect("parse body");
+        assert_eq!(body, json!({ "handled": true }));
+    }
+
+    #[tokio::test]
+    async fn endpoint_invalid_json_runs_catch() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let rules_dir = temp.path();
+        let rules_subdir = rules_dir.join("rules");
+        std::fs::create_dir_all(&rules_subdir).expect("create rules dir");
+
+        std::fs::write(
+            rules_dir.join("endpoint.yaml"),
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: POST
+    path: /api/test
+    catch:
+      default: ./rules/catch.yaml
+    steps: []
+    reply:
+      status: 200
+      body: "@input"
+"#,
+        )
+        .expect("write endpoint.yaml");
+
+        std::fs::write(
+            rules_subdir.join("catch.yaml"),
+            r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "handled"
+    value: true
+"#,
+        )
+        .expect("write catch.yaml");
+
+        let engine = EndpointEngine::load(
+            rules_dir.to_path_buf(),
+            EngineConfig::new("http://localhost".to_string(), rules_dir.to_path_buf()),
+        )
+        .expect("load engine");
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/test")
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from("{\"bad\":}"))
+            .expect("build request");
+
+        let response = engine
+            .handle_request(request)
+            .await
+            .expect("handle request");
+        assert_eq!(response.status().as_u16(), 200);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("read body");
+        let body: JsonValue = serde_json::from_slice(&bytes).expect("parse body");
+        assert_eq!(body, json!({ "handled": true }));
+    }
+
+    #[tokio::test]
+    async fn endpoint_invalid_json_keeps_query_in_catch() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let rules_dir = temp.path();
+        let rules_subdir = rules_dir.join("rules");
+        std::fs::create_dir_all(&rules_subdir).expect("create rules dir");
+
+        std::fs::write(
+            rules_dir.join("endpoint.yaml"),
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: POST
+    path: /api/test
+    catch:
+      default: ./rules/catch.yaml
+    steps: []
+    reply:
+      status: 200
+      body: "@input"
+"#,
+        )
+        .expect("write endpoint.yaml");
+
+        std::fs::write(
+            rules_subdir.join("catch.yaml"),
+            r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "query"
+    expr: "@input.query"
+"#,
+        )
+        .expect("write catch.yaml");
+
+        let engine = EndpointEngine::load(
+            rules_dir.to_path_buf(),
+            EngineConfig::new("http://localhost".to_string(), rules_dir.to_path_buf()),
+        )
+        .expect("load engine");
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/test?token=abc")
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from("{\"bad\":}"))
+            .expect("build request");
+
+        let response = engine
+            .handle_request(request)
+            .await
+            .expect("handle request");
+        assert_eq!(response.status().as_u16(), 200);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("read body");
+        let body: JsonValue = serde_json::from_slice(&bytes).expect("parse body");
+        assert_eq!(body, json!({ "query": { "token": "abc" } }));
+    }
+
+    #[tokio::test]
+    async fn endpoint_input_binds_query_and_headers() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let rules_dir = temp.path();
+        std::fs::write(
+            rules_dir.join("endpoint.yaml"),
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: POST
+    path: /api/items/{id}
+    steps: []
+    reply:
+      status: 200
+      body: "@input"
+  - method: GET
+    path: /api/tenants/items
+    input:
+      - target: "page"
+        source: "input.query.page"
+        type: "int"
+      - target: "tenant"
+        source: "input.headers.x-tenant-id"
+    steps: []
+    reply:
+      status: 200
+      body: "@input"
+"#,
+        )
+        .expect("write endpoint.yaml");
+
+        let engine = EndpointEngine::load(
+            rules_dir.to_path_buf(),
+            EngineConfig::new("http://localhost".to_string(), rules_dir.join(".data")),
+        )
+        .expect("load engine");
+        let send = |request: Request<axum::body::Body>| {
+            let engine = &engine;
+            async move {
+                let response = engine.handle_request(request).await.expect("handle");
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .expect("read body");
+                serde_json::from_slice::<JsonValue>(&bytes).expect("parse body")
+            }
+        };
+
+        let input = send(
+            Request::builder()
+                .method("POST")
+                .uri("/api/items/42?page=2&sort=name%20asc")
+                .header("X-Tenant-Id", "acme")
+                .header("accept", "application/json")
+                .header("accept", "text/plain")
+                .body(axum::body::Body::from(r#"{"qty":1}"#))
+                .expect("build request"),
+        )
+        .await;
+        assert_eq!(input["method"], "POST");
+        assert_eq!(input["path"], json!({ "id": "42" }));
+        assert_eq!(input["query"], json!({ "page": "2", "sort": "name asc" }));
+        assert_eq!(input["headers"]["x-tenant-id"], "acme");
+        assert_eq!(input["headers"]["accept"], "application/json,text/plain");
+        assert_eq!(input["body"], json!({ "qty": 1 }));
+
+        let input = send(
+            Request::builder()
+                .method("GET")
+                .uri("/api/tenants/items?page=3")
+                .header("x-tenant-id", "acme")
+                .body(axum::body::Body::empty())
+                .expect("build request"),
+        )
+        .await;
+        assert_eq!(input, json!({ "page": 3, "tenant": "acme" }));
+
+        let input = send(
+            Request::builder()
+                .method("POST")
+                .uri("/api/items/7")
+                .body(axum::body::Body::empty())
+                .expect("build request"),
+        )
+        .await;
+        assert_eq!(input["query"], json!({}));
+        assert_eq!(input["headers"], json!({}));
+        assert!(input.get("body").is_none());
+    }
+
+    #[tokio::test]
+    async fn endpoint_input_mapping_error_runs_catch() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let rules_dir = temp.path();
+        let rules_subdir = rules_dir.join("rules");
+        std::fs::create_dir_all(&rules_subdir).expect("create rules dir");
+
+        std::fs::write(
+            rules_dir.join("endpoint.yaml"),
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: POST
+    path: /api/test
+    input:
+      - target: "user_id"
+        source: "input.body.user_id"
+        required: true
+    catch:
+      default: ./rules/catch.yaml
+    steps: []
+    reply:
+      status: 200
+      body: "@input"
+"#,
+        )
+        .expect("write endpoint.yaml");
+
+        std::fs::write(
+            rules_subdir.join("catch.yaml"),
+            r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "handled"
+    value: true
+"#,
+        )
+        .expect("write catch.yaml");
+
+        let engine = EndpointEngine::load(
+            rules_dir.to_path_buf(),
+            EngineConfig::new("http://localhost".to_string(), rules_dir.to_path_buf()),
+        )
+        .expect("load engine");
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/test")
+            .body(axum::body::Body::empty())
+            .expect("build request");
+
+        let response = engine
+            .handle_request(request)
+            .await
+            .expect("handle request");
+        assert_eq!(response.status().as_u16(), 200);
+
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("read body");
+        let body: JsonValue = serde_json::from_slice(&bytes).expect("parse body");
+        assert_eq!(body, json!({ "handled": true }));
+    }
+
+    #[tokio::test]
+    async fn reply_eval_error_runs_catch() {
+        let temp = tempfile::tempdir().expect("tempdir");
         let rules_dir = temp.path();
         let rules_subdir = rules_dir.join("rules");
         std::fs::create_dir_all(&rules_subdir).expect("create rules dir");
@@ -3701,33 +6076,16 @@ type: endpoint
 endpoints:
   - method: GET
     path: /api/test
-    steps:
-      - rule: ./rules/failing_network.yaml
-        with:
-          fields: ["name"]
-        catch:
-          default: ./rules/catch.yaml
+    catch:
+      default: ./rules/catch.yaml
+    steps: []
     reply:
-      status: 200
-      body: "@input"
+      status: "@input.status"
+      body: "@input.body"
 "#,
         )
         .expect("write endpoint.yaml");
 
-        std::fs::write(
-            rules_subdir.join("failing_network.yaml"),
-            r#"
-version: 2
-type: network
-request:
-  method: GET
-  url: "http://example.com"
-timeout: 1s
-body: "@input"
-"#,
-        )
-        .expect("write failing network rule");
-
         std::fs::write(
             rules_subdir.join("catch.yaml"),
             r#"
@@ -3736,12 +6094,14 @@ input:
   format: json
   json: {}
 mappings:
-  - target: "params"
-    expr: "@context.params"
-    required: true
+  - target: "status"
+    value: 200
+  - target: "body"
+    value:
+      handled: true
 "#,
         )
-        .expect("write catch rule");
+        .expect("write catch.yaml");
 
         let engine = EndpointEngine::load(
             rules_dir.to_path_buf(),
@@ -3765,11 +6125,11 @@ mappings:
             .await
             .expect("read body");
         let body: JsonValue = serde_json::from_slice(&bytes).expect("parse body");
-        assert_eq!(body, json!({ "params": { "fields": ["name"] } }));
+        assert_eq!(body, json!({ "handled": true }));
     }
 
     #[tokio::test]
-    async fn endpoint_duplicate_query_runs_catch() {
+    async fn network_url_eval_error_runs_catch() {
         let temp = tempfile::tempdir().expect("tempdir");
         let rules_dir = temp.path();
         let rules_subdir = rules_dir.join("rules");
@@ -3783,9 +6143,8 @@ type: endpoint
 endpoints:
   - method: GET
     path: /api/test
-    catch:
-      default: ./rules/catch.yaml
-    steps: []
+    steps:
+      - rule: ./rules/network.yaml
     reply:
       status: 200
       body: "@input"
@@ -3793,6 +6152,21 @@ endpoints:
         )
         .expect("write endpoint.yaml");
 
+        std::fs::write(
+            rules_subdir.join("network.yaml"),
+            r#"
+version: 2
+type: network
+request:
+  method: GET
+  url: "@input.url"
+timeout: 1s
+catch:
+  default: ./catch.yaml
+"#,
+        )
+        .expect("write network.yaml");
+
         std::fs::write(
             rules_subdir.join("catch.yaml"),
             r#"
@@ -3815,7 +6189,7 @@ mappings:
 
         let request = Request::builder()
             .method("GET")
-            .uri("/api/test?dup=1&dup=2")
+            .uri("/api/test")
             .body(axum::body::Body::empty())
             .expect("build request");
 
@@ -3833,7 +6207,7 @@ mappings:
     }
 
     #[tokio::test]
-    async fn endpoint_invalid_json_runs_catch() {
+    async fn network_body_build_error_runs_catch() {
         let temp = tempfile::tempdir().expect("tempdir");
         let rules_dir = temp.path();
         let rules_subdir = rules_dir.join("rules");
@@ -3847,9 +6221,8 @@ type: endpoint
 endpoints:
   - method: POST
     path: /api/test
-    catch:
-      default: ./rules/catch.yaml
-    steps: []
+    steps:
+      - rule: ./rules/network.yaml
     reply:
       status: 200
       body: "@input"
@@ -3857,6 +6230,25 @@ endpoints:
         )
         .expect("write endpoint.yaml");
 
+        std::fs::write(
+            rules_subdir.join("network.yaml"),
+            r#"
+version: 2
+type: network
+request:
+  method: POST
+  url: "https://example.com"
+timeout: 1s
+body_map:
+  - target: "required"
+    source: "input.missing"
+    required: true
+catch:
+  default: ./catch.yaml
+"#,
+        )
+        .expect("write network.yaml");
+
         std::fs::write(
             rules_subdir.join("catch.yaml"),
             r#"
@@ -3880,8 +6272,7 @@ mappings:
         let request = Request::builder()
             .method("POST")
             .uri("/api/test")
-            .header("content-type", "application/json")
-            .body(axum::body::Body::from("{\"bad\":}"))
+            .body(axum::body::Body::empty())
             .expect("build request");
 
         let response = engine
@@ -3898,7 +6289,23 @@ mappings:
     }
 
     #[tokio::test]
-    async fn endpoint_invalid_json_keeps_query_in_catch() {
+    async fn network_select_error_runs_catch() {
+        let app = axum::Router::new().route(
+            "/data",
+            axum::routing::get(|| async { axum::Json(json!({ "data": { "value": 1 } })) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = axum::serve(listener, app.into_make_service()).with_graceful_shutdown(async {
+            let _ = shutdown_rx.await;
+        });
+        let server_handle = tokio::spawn(async move {
+            let _ = server.await;
+        });
+
         let temp = tempfile::tempdir().expect("tempdir");
         let rules_dir = temp.path();
         let rules_subdir = rules_dir.join("rules");
@@ -3910,11 +6317,10 @@ mappings:
 version: 2
 type: endpoint
 endpoints:
-  - method: POST
+  - method: GET
     path: /api/test
-    catch:
-      default: ./rules/catch.yaml
-    steps: []
+    steps:
+      - rule: ./rules/network.yaml
     reply:
       status: 200
       body: "@input"
@@ -3922,6 +6328,25 @@ endpoints:
         )
         .expect("write endpoint.yaml");
 
+        std::fs::write(
+            rules_subdir.join("network.yaml"),
+            format!(
+                r#"
+version: 2
+type: network
+request:
+  method: GET
+  url: "http://{}/data"
+timeout: 1s
+select: "missing.path"
+catch:
+  default: ./catch.yaml
+"#,
+                addr
+            ),
+        )
+        .expect("write network.yaml");
+
         std::fs::write(
             rules_subdir.join("catch.yaml"),
             r#"
@@ -3930,8 +6355,8 @@ input:
   format: json
   json: {}
 mappings:
-  - target: "query"
-    expr: "@input.query"
+  - target: "handled"
+    value: true
 "#,
         )
         .expect("write catch.yaml");
@@ -3943,10 +6368,9 @@ mappings:
         .expect("load engine");
 
         let request = Request::builder()
-            .method("POST")
-            .uri("/api/test?token=abc")
-            .header("content-type", "application/json")
-            .body(axum::body::Body::from("{\"bad\":}"))
+            .method("GET")
+            .uri("/api/test")
+            .body(axum::body::Body::empty())
             .expect("build request");
 
         let response = engine
@@ -3959,11 +6383,35 @@ mappings:
             .await
             .expect("read body");
         let body: JsonValue = serde_json::from_slice(&bytes).expect("parse body");
-        assert_eq!(body, json!({ "query": { "token": "abc" } }));
+        assert_eq!(body, json!({ "handled": true }));
+
+        let _ = shutdown_tx.send(());
+        let _ = server_handle.await;
     }
 
     #[tokio::test]
-    async fn endpoint_input_mapping_error_runs_catch() {
+    async fn network_timeout_on_slow_body_runs_catch() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+
+        let server_handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.expect("accept");
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let body = b"{\"value\":1}";
+            let headers = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n",
+                body.len()
+            );
+            let _ = socket.write_all(headers.as_bytes()).await;
+            let _ = socket.flush().await;
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            let _ = socket.write_all(body).await;
+            let _ = socket.shutdown().await;
+        });
+
         let temp = tempfile::tempdir().expect("tempdir");
         let rules_dir = temp.path();
         let rules_subdir = rules_dir.join("rules");
@@ -3975,15 +6423,10 @@ mappings:
 version: 2
 type: endpoint
 endpoints:
-  - method: POST
+  - method: GET
     path: /api/test
-    input:
-      - target: "user_id"
-        source: "input.body.user_id"
-        required: true
-    catch:
-      default: ./rules/catch.yaml
-    steps: []
+    steps:
+      - rule: ./rules/network.yaml
     reply:
       status: 200
       body: "@input"
@@ -3991,6 +6434,24 @@ endpoints:
         )
         .expect("write endpoint.yaml");
 
+        std::fs::write(
+            rules_subdir.join("network.yaml"),
+            format!(
+                r#"
+version: 2
+type: network
+request:
+  method: GET
+  url: "http://{}/slow"
+timeout: 100ms
+catch:
+  timeout: ./catch.yaml
+"#,
+                addr
+            ),
+        )
+        .expect("write network.yaml");
+
         std::fs::write(
             rules_subdir.join("catch.yaml"),
             r#"
@@ -4012,7 +6473,7 @@ mappings:
         .expect("load engine");
 
         let request = Request::builder()
-            .method("POST")
+            .method("GET")
             .uri("/api/test")
             .body(axum::body::Body::empty())
             .expect("build request");
@@ -4028,15 +6489,36 @@ mappings:
             .expect("read body");
         let body: JsonValue = serde_json::from_slice(&bytes).expect("parse body");
         assert_eq!(body, json!({ "handled": true }));
+
+        let _ = server_handle.await;
     }
 
     #[tokio::test]
-    async fn reply_eval_error_runs_catch() {
+    async fn step_timeout_stops_slow_network_and_runs_catch() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        let server_handle = tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    break;
+                };
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    let _ = socket.read(&mut buf).await;
+                    tokio::time::sleep(Duration::from_secs(2)).await;
+                    let _ = socket
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\n{}")
+                        .await;
+                });
+            }
+        });
+
         let temp = tempfile::tempdir().expect("tempdir");
         let rules_dir = temp.path();
         let rules_subdir = rules_dir.join("rules");
         std::fs::create_dir_all(&rules_subdir).expect("create rules dir");
-
         std::fs::write(
             rules_dir.join("endpoint.yaml"),
             r#"
@@ -4044,17 +6526,40 @@ version: 2
 type: endpoint
 endpoints:
   - method: GET
-    path: /api/test
-    catch:
-      default: ./rules/catch.yaml
-    steps: []
+    path: /api/plain
+    steps:
+      - rule: ./rules/network.yaml
+        timeout_ms: 100
     reply:
-      status: "@input.status"
-      body: "@input.body"
+      status: 200
+  - method: GET
+    path: /api/caught
+    steps:
+      - rule: ./rules/network.yaml
+        timeout_ms: 100
+        catch:
+          timeout: ./rules/catch.yaml
+    reply:
+      status: 200
+      body: "@input"
 "#,
         )
         .expect("write endpoint.yaml");
-
+        std::fs::write(
+            rules_subdir.join("network.yaml"),
+            format!(
+                r#"
+version: 2
+type: network
+request:
+  method: GET
+  url: "http://{}/slow"
+timeout: 10s
+"#,
+                addr
+            ),
+        )
+        .expect("write network.yaml");
         std::fs::write(
             rules_subdir.join("catch.yaml"),
             r#"
@@ -4063,47 +6568,85 @@ input:
   format: json
   json: {}
 mappings:
-  - target: "status"
-    value: 200
-  - target: "body"
-    value:
-      handled: true
+  - target: "handled"
+    value: true
 "#,
         )
         .expect("write catch.yaml");
 
+        let data_dir = rules_dir.join(".data");
         let engine = EndpointEngine::load(
             rules_dir.to_path_buf(),
-            EngineConfig::new("http://localhost".to_string(), rules_dir.to_path_buf()),
+            EngineConfig::new("http://localhost".to_string(), data_dir.clone()),
         )
         .expect("load engine");
+        let request = |uri: &str| {
+            Request::builder()
+                .method("GET")
+                .uri(uri)
+                .body(axum::body::Body::empty())
+                .expect("build request")
+        };
 
-        let request = Request::builder()
-            .method("GET")
-            .uri("/api/test")
-            .body(axum::body::Body::empty())
-            .expect("build request");
+        let started = Instant::now();
+        let err = engine
+            .handle_request(request("/api/plain"))
+            .await
+            .expect_err("expected timeout");
+        assert!(started.elapsed() < Duration::from_secs(1));
+        let failure = err.downcast_ref::<EndpointFailure>().expect("failure");
+        assert_eq!(failure.status, StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(failure.code, "Timeout");
+        assert_eq!(failure.message, "step timed out after 100ms");
+
+        let mut traces = Vec::new();
+        collect_traces(&data_dir, &mut traces);
+        assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0]["status"], "error");
+        assert_eq!(
+            traces[0]["records"][0]["error"]["message"],
+            "step timed out after 100ms"
+        );
 
         let response = engine
-            .handle_request(request)
+            .handle_request(request("/api/caught"))
             .await
-            .expect("handle request");
-        assert_eq!(response.status().as_u16(), 200);
-
+            .expect("catch handles timeout");
         let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
             .await
             .expect("read body");
         let body: JsonValue = serde_json::from_slice(&bytes).expect("parse body");
         assert_eq!(body, json!({ "handled": true }));
+
+        server_handle.abort();
     }
 
     #[tokio::test]
-    async fn network_url_eval_error_runs_catch() {
+    async fn catch_keys_select_error_kinds() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        let server_handle = tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    break;
+                };
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    let _ = socket.read(&mut buf).await;
+                    tokio::time::sleep(Duration::from_secs(2)).await;
+                    let _ = socket
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\n{}")
+                        .await;
+                });
+            }
+        });
+
         let temp = tempfile::tempdir().expect("tempdir");
         let rules_dir = temp.path();
         let rules_subdir = rules_dir.join("rules");
         std::fs::create_dir_all(&rules_subdir).expect("create rules dir");
-
         std::fs::write(
             rules_dir.join("endpoint.yaml"),
             r#"
@@ -4111,157 +6654,567 @@ version: 2
 type: endpoint
 endpoints:
   - method: GET
-    path: /api/test
+    path: /api/slow/timeout_caught
+    steps:
+      - rule: ./rules/network.yaml
+        timeout_ms: 100
+        catch:
+          Timeout: ./rules/caught_timeout.yaml
+    reply:
+      status: 200
+      body: "@input"
+  - method: GET
+    path: /api/slow/invalid_caught
     steps:
       - rule: ./rules/network.yaml
+        timeout_ms: 100
+        catch:
+          MissingRequired: ./rules/caught_invalid.yaml
+    reply:
+      status: 200
+  - method: GET
+    path: /api/invalid/timeout_caught
+    steps:
+      - rule: ./rules/validate.yaml
+        catch:
+          Timeout: ./rules/caught_timeout.yaml
+    reply:
+      status: 200
+  - method: GET
+    path: /api/invalid/invalid_caught
+    steps:
+      - rule: ./rules/validate.yaml
+        catch:
+          MissingRequired: ./rules/caught_invalid.yaml
+    reply:
+      status: 200
+      body: "@input"
+  - method: GET
+    path: /api/invalid/wildcard
+    catch:
+      "*": ./rules/caught_any.yaml
+    steps:
+      - rule: ./rules/validate.yaml
+        catch:
+          Timeout: ./rules/caught_timeout.yaml
     reply:
       status: 200
       body: "@input"
 "#,
         )
         .expect("write endpoint.yaml");
-
         std::fs::write(
             rules_subdir.join("network.yaml"),
-            r#"
+            format!(
+                r#"
 version: 2
 type: network
 request:
   method: GET
-  url: "@input.url"
-timeout: 1s
-catch:
-  default: ./catch.yaml
+  url: "http://{}/slow"
+timeout: 10s
 "#,
+                addr
+            ),
         )
         .expect("write network.yaml");
-
         std::fs::write(
-            rules_subdir.join("catch.yaml"),
+            rules_subdir.join("validate.yaml"),
             r#"
 version: 2
 input:
   format: json
   json: {}
 mappings:
-  - target: "handled"
-    value: true
+  - target: "user_id"
+    source: "input.user_id"
+    required: true
 "#,
         )
-        .expect("write catch.yaml");
+        .expect("write validate.yaml");
+        for name in ["timeout", "invalid", "any"] {
+            std::fs::write(
+                rules_subdir.join(format!("caught_{}.yaml", name)),
+                format!(
+                    r#"
+version: 2
+input:
+  format: json
+  json: {{}}
+mappings:
+  - target: "caught"
+    value: "{}"
+"#,
+                    name
+                ),
+            )
+            .expect("write catch rule");
+        }
 
         let engine = EndpointEngine::load(
             rules_dir.to_path_buf(),
-            EngineConfig::new("http://localhost".to_string(), rules_dir.to_path_buf()),
+            EngineConfig::new("http://localhost".to_string(), rules_dir.join(".data")),
         )
         .expect("load engine");
+        let request = |uri: &str| {
+            Request::builder()
+                .method("GET")
+                .uri(uri)
+                .body(axum::body::Body::empty())
+                .expect("build request")
+        };
+        let caught = |uri: &'static str| {
+            let engine = &engine;
+            async move {
+                let response = engine
+                    .handle_request(request(uri))
+                    .await
+                    .expect("caught error");
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .expect("read body");
+                serde_json::from_slice::<JsonValue>(&bytes).expect("parse body")
+            }
+        };
+        let uncaught = |uri: &'static str| {
+            let engine = &engine;
+            async move {
+                let err = engine
+                    .handle_request(request(uri))
+                    .await
+                    .expect_err("uncaught error");
+                let failure = err.downcast_ref::<EndpointFailure>().expect("failure");
+                (failure.status, failure.code.clone())
+            }
+        };
 
-        let request = Request::builder()
-            .method("GET")
-            .uri("/api/test")
-            .body(axum::body::Body::empty())
-            .expect("build request");
+        assert_eq!(
+            caught("/api/slow/timeout_caught").await,
+            json!({ "caught": "timeout" })
+        );
+        assert_eq!(
+            uncaught("/api/slow/invalid_caught").await,
+            (StatusCode::GATEWAY_TIMEOUT, "Timeout".to_string())
+        );
+        assert_eq!(
+            uncaught("/api/invalid/timeout_caught").await,
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "MissingRequired".to_string()
+            )
+        );
+        assert_eq!(
+            caught("/api/invalid/invalid_caught").await,
+            json!({ "caught": "invalid" })
+        );
+        assert_eq!(
+            caught("/api/invalid/wildcard").await,
+            json!({ "caught": "any" })
+        );
 
-        let response = engine
-            .handle_request(request)
-            .await
-            .expect("handle request");
-        assert_eq!(response.status().as_u16(), 200);
+        server_handle.abort();
+    }
 
-        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+    #[tokio::test]
+    async fn reply_cache_hits_misses_and_expires() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let server_hits = hits.clone();
+        let app = axum::Router::new().route(
+            "/count",
+            axum::routing::get(move || {
+                let hits = server_hits.clone();
+                async move {
+                    let n = hits.fetch_add(1, Ordering::SeqCst) + 1;
+                    axum::Json(json!({ "n": n }))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
             .await
-            .expect("read body");
-        let body: JsonValue = serde_json::from_slice(&bytes).expect("parse body");
-        assert_eq!(body, json!({ "handled": true }));
+            .expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = axum::serve(listener, app.into_make_service()).with_graceful_shutdown(async {
+            let _ = shutdown_rx.await;
+        });
+        let server_handle = tokio::spawn(async move {
+            let _ = server.await;
+        });
+
+        let temp = tempfile::tempdir().expect("tempdir");
+        let rules_dir = temp.path();
+        let rules_subdir = rules_dir.join("rules");
+        std::fs::create_dir_all(&rules_subdir).expect("create rules dir");
+        std::fs::write(
+            rules_dir.join("endpoint.yaml"),
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: GET
+    path: /api/items/{id}
+    cache:
+      ttl_ms: 300
+      key: ["@input.path.id"]
+    steps:
+      - rule: ./rules/network.yaml
+    reply:
+      status: 200
+      body: "@input"
+"#,
+        )
+        .expect("write endpoint.yaml");
+        std::fs::write(
+            rules_subdir.join("network.yaml"),
+            format!(
+                r#"
+version: 2
+type: network
+request:
+  method: GET
+  url: "http://{}/count"
+timeout: 1s
+"#,
+                addr
+            ),
+        )
+        .expect("write network.yaml");
+
+        let data_dir = rules_dir.join(".data");
+        let engine = EndpointEngine::load(
+            rules_dir.to_path_buf(),
+            EngineConfig::new("http://localhost".to_string(), data_dir.clone()),
+        )
+        .expect("load engine");
+        let fetch = |uri: &'static str| {
+            let engine = &engine;
+            async move {
+                let request = Request::builder()
+                    .method("GET")
+                    .uri(uri)
+                    .body(axum::body::Body::empty())
+                    .expect("build request");
+                let response = engine.handle_request(request).await.expect("handle");
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .expect("read body");
+                serde_json::from_slice::<JsonValue>(&bytes).expect("parse body")
+            }
+        };
+
+        assert_eq!(fetch("/api/items/1").await, json!({ "n": 1 }));
+        assert_eq!(fetch("/api/items/1").await, json!({ "n": 1 }));
+        assert_eq!(fetch("/api/items/2").await, json!({ "n": 2 }));
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        assert_eq!(fetch("/api/items/1").await, json!({ "n": 3 }));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        let mut traces = Vec::new();
+        collect_traces(&data_dir, &mut traces);
+        let mut flags: Vec<bool> = traces
+            .iter()
+            .map(|trace| trace["cache_hit"].as_bool().expect("cache_hit flag"))
+            .collect();
+        flags.sort_unstable();
+        assert_eq!(flags, vec![false, false, false, true]);
+
+        let _ = shutdown_tx.send(());
+        let _ = server_handle.await;
     }
 
     #[tokio::test]
-    async fn network_body_build_error_runs_catch() {
+    async fn parallel_steps_run_concurrently_and_merge() {
+        let delayed = |body: JsonValue| {
+            axum::routing::get(move || async move {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                axum::Json(body)
+            })
+        };
+        let app = axum::Router::new()
+            .route("/profile", delayed(json!({ "profile": { "name": "Ada" } })))
+            .route("/orders", delayed(json!({ "orders": [1, 2] })))
+            .route(
+                "/broken",
+                axum::routing::get(|| async { StatusCode::INTERNAL_SERVER_ERROR }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+        let server = axum::serve(listener, app.into_make_service()).with_graceful_shutdown(async {
+            let _ = shutdown_rx.await;
+        });
+        let server_handle = tokio::spawn(async move {
+            let _ = server.await;
+        });
+
         let temp = tempfile::tempdir().expect("tempdir");
         let rules_dir = temp.path();
         let rules_subdir = rules_dir.join("rules");
         std::fs::create_dir_all(&rules_subdir).expect("create rules dir");
-
         std::fs::write(
             rules_dir.join("endpoint.yaml"),
             r#"
 version: 2
 type: endpoint
 endpoints:
-  - method: POST
-    path: /api/test
+  - method: GET
+    path: /api/users/{id}
+    input:
+      - target: "id"
+        source: "input.path.id"
     steps:
-      - rule: ./rules/network.yaml
+      - parallel:
+          - rule: ./rules/profile.yaml
+          - rule: ./rules/orders.yaml
+    reply:
+      status: 200
+      body: "@input"
+  - method: GET
+    path: /api/broken/{id}
+    input:
+      - target: "id"
+        source: "input.path.id"
+    steps:
+      - parallel:
+          - rule: ./rules/profile.yaml
+          - rule: ./rules/broken.yaml
+        catch:
+          5xx: ./rules/fallback.yaml
     reply:
       status: 200
       body: "@input"
 "#,
         )
         .expect("write endpoint.yaml");
-
-        std::fs::write(
-            rules_subdir.join("network.yaml"),
-            r#"
+        for name in ["profile", "orders", "broken"] {
+            std::fs::write(
+                rules_subdir.join(format!("{}.yaml", name)),
+                format!(
+                    r#"
 version: 2
 type: network
 request:
-  method: POST
-  url: "https://example.com"
-timeout: 1s
-body_map:
-  - target: "required"
-    source: "input.missing"
-    required: true
-catch:
-  default: ./catch.yaml
+  method: GET
+  url: "http://{}/{}"
+timeout: 2s
 "#,
-        )
-        .expect("write network.yaml");
-
+                    addr, name
+                ),
+            )
+            .expect("write network rule");
+        }
         std::fs::write(
-            rules_subdir.join("catch.yaml"),
+            rules_subdir.join("fallback.yaml"),
             r#"
 version: 2
 input:
   format: json
   json: {}
 mappings:
-  - target: "handled"
+  - target: "fallback"
     value: true
 "#,
         )
-        .expect("write catch.yaml");
+        .expect("write fallback.yaml");
 
+        let data_dir = rules_dir.join(".data");
         let engine = EndpointEngine::load(
             rules_dir.to_path_buf(),
-            EngineConfig::new("http://localhost".to_string(), rules_dir.to_path_buf()),
+            EngineConfig::new("http://localhost".to_string(), data_dir.clone()),
         )
         .expect("load engine");
+        let fetch = |uri: &'static str| {
+            let engine = &engine;
+            async move {
+                let request = Request::builder()
+                    .method("GET")
+                    .uri(uri)
+                    .body(axum::body::Body::empty())
+                    .expect("build request");
+                let response = engine.handle_request(request).await.expect("handle");
+                let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .expect("read body");
+                serde_json::from_slice::<JsonValue>(&bytes).expect("parse body")
+            }
+        };
 
-        let request = Request::builder()
-            .method("POST")
-            .uri("/api/test")
-            .body(axum::body::Body::empty())
-            .expect("build request");
+        let started = Instant::now();
+        assert_eq!(
+            fetch("/api/users/7").await,
+            json!({ "id": "7", "profile": { "name": "Ada" }, "orders": [1, 2] })
+        );
+        // Two 300ms calls in series would take at least 600ms.
+        assert!(
+            started.elapsed() < Duration::from_millis(550),
+            "took {:?}",
+            started.elapsed()
+        );
 
-        let response = engine
-            .handle_request(request)
-            .await
-            .expect("handle request");
-        assert_eq!(response.status().as_u16(), 200);
+        let mut traces = Vec::new();
+        collect_traces(&data_dir, &mut traces);
+        assert_eq!(traces.len(), 1);
+        let nodes = traces[0]["records"][0]["nodes"].as_array().expect("nodes");
+        let summary: Vec<(&str, &str, &str)> = nodes
+            .iter()
+            .map(|node| {
+                (
+                    node["id"].as_str().unwrap_or_default(),
+                    node["label"].as_str().unwrap_or_default(),
+                    node["status"].as_str().unwrap_or_default(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("step-0-0", "profile", "ok"),
+                ("step-0-1", "orders", "ok"),
+                ("step-0", "parallel", "ok"),
+            ]
+        );
 
-        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
-            .await
-            .expect("read body");
-        let body: JsonValue = serde_json::from_slice(&bytes).expect("parse body");
-        assert_eq!(body, json!({ "handled": true }));
+        assert_eq!(fetch("/api/broken/7").await, json!({ "fallback": true }));
+
+        let _ = shutdown_tx.send(());
+        let _ = server_handle.await;
+    }
+
+    #[test]
+    fn parallel_steps_reject_invalid_shapes() {
+        let compile = |steps: &str| {
+            let raw: EndpointRuleFile = serde_yaml::from_str(&format!(
+                r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: GET
+    path: /api/test
+    steps: {}
+    reply:
+      status: 200
+"#,
+                steps
+            ))
+            .expect("parse endpoint");
+            CompiledEndpointRule::compile(raw, Path::new("endpoint.yaml"))
+                .err()
+                .map(|err| err.to_string())
+        };
+        assert_eq!(
+            compile(r#"[{ parallel: [{ rule: a.yaml }, { rule: b.yaml }] }]"#),
+            None
+        );
+        let cases = [
+            (r#"[{ parallel: [] }]"#, "must not be empty"),
+            (
+                r#"[{ rule: a.yaml, parallel: [{ rule: b.yaml }] }]"#,
+                "not both",
+            ),
+            (r#"[{ with: {} }]"#, "must have rule or parallel"),
+            (
+                r#"[{ parallel: [{ parallel: [{ rule: a.yaml }] }] }]"#,
+                "cannot be parallel",
+            ),
+            (
+                r#"[{ parallel: [{ rule: a.yaml }], timeout_ms: 100 }]"#,
+                "set them on each branch",
+            ),
+        ];
+        for (steps, message) in cases {
+            let err = compile(steps).expect("expected compile error");
+            assert!(err.contains(message), "{}: {}", steps, err);
+        }
+    }
+
+    #[test]
+    fn reply_cache_requires_get() {
+        let raw: EndpointRuleFile = serde_yaml::from_str(
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: POST
+    path: /api/items
+    cache: { ttl_ms: 1000 }
+    steps: []
+    reply:
+      status: 200
+"#,
+        )
+        .expect("parse endpoint");
+        let err = CompiledEndpointRule::compile(raw, Path::new("endpoint.yaml")).unwrap_err();
+        assert_eq!(err.to_string(), "cache is only supported on GET endpoints");
+    }
+
+    #[test]
+    fn endpoint_files_reject_include() {
+        let raw: EndpointRuleFile = serde_yaml::from_str(
+            r#"
+version: 2
+type: endpoint
+include: [shared.yaml]
+endpoints: []
+"#,
+        )
+        .expect("parse endpoint");
+        let err = CompiledEndpointRule::compile(raw, Path::new("endpoint.yaml")).unwrap_err();
+        assert!(
+            err.to_string().starts_with("include is not supported"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn reply_cache_requires_key() {
+        for cache in ["{ ttl_ms: 1000 }", "{ ttl_ms: 1000, key: [] }"] {
+            let raw: EndpointRuleFile = serde_yaml::from_str(&format!(
+                r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: GET
+    path: /api/items/{{id}}
+    cache: {}
+    steps: []
+    reply:
+      status: 200
+"#,
+                cache
+            ))
+            .expect("parse endpoint");
+            let err = CompiledEndpointRule::compile(raw, Path::new("endpoint.yaml")).unwrap_err();
+            assert!(
+                err.to_string().starts_with("cache.key must list"),
+                "{}",
+                err
+            );
+        }
+
+        assert_ne!(
+            reply_cache_key_component(EvalValue::Missing),
+            reply_cache_key_component(EvalValue::Value(JsonValue::Null))
+        );
     }
 
     #[tokio::test]
-    async fn network_select_error_runs_catch() {
+    async fn network_circuit_opens_after_consecutive_failures() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let server_hits = hits.clone();
         let app = axum::Router::new().route(
-            "/data",
-            axum::routing::get(|| async { axum::Json(json!({ "data": { "value": 1 } })) }),
+            "/flaky",
+            axum::routing::get(move || {
+                let hits = server_hits.clone();
+                async move {
+                    hits.fetch_add(1, Ordering::SeqCst);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            }),
         );
         let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
             .await
@@ -4305,82 +7258,87 @@ version: 2
 type: network
 request:
   method: GET
-  url: "http://{}/data"
+  url: "http://{}/flaky"
 timeout: 1s
-select: "missing.path"
 catch:
-  default: ./catch.yaml
+  circuit_open: ./open.yaml
+  default: ./failed.yaml
 "#,
                 addr
             ),
         )
         .expect("write network.yaml");
 
-        std::fs::write(
-            rules_subdir.join("catch.yaml"),
-            r#"
+        for (name, value) in [("open.yaml", "open"), ("failed.yaml", "failed")] {
+            std::fs::write(
+                rules_subdir.join(name),
+                format!(
+                    r#"
 version: 2
 input:
   format: json
-  json: {}
+  json: {{}}
 mappings:
   - target: "handled"
-    value: true
+    value: "{}"
 "#,
-        )
-        .expect("write catch.yaml");
+                    value
+                ),
+            )
+            .expect("write catch rule");
+        }
 
-        let engine = EndpointEngine::load(
-            rules_dir.to_path_buf(),
-            EngineConfig::new("http://localhost".to_string(), rules_dir.to_path_buf()),
-        )
-        .expect("load engine");
+        let config = EngineConfig::new("http://localhost".to_string(), rules_dir.to_path_buf())
+            .with_circuit_breaker(CircuitBreakerConfig {
+                failure_threshold: 2,
+                failure_window: Duration::from_secs(60),
+                cooldown: Duration::from_secs(60),
+            });
+        let engine =
+            EndpointEngine::load(rules_dir.to_path_buf(), config.clone()).expect("load engine");
+
+        async fn handled(engine: &EndpointEngine) -> JsonValue {
+            let request = Request::builder()
+                .method("GET")
+                .uri("/api/test")
+                .body(axum::body::Body::empty())
+                .expect("build request");
+            let response = engine
+                .handle_request(request)
+                .await
+                .expect("handle request");
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .expect("read body");
+            let body: JsonValue = serde_json::from_slice(&bytes).expect("parse body");
+            body["handled"].clone()
+        }
 
-        let request = Request::builder()
-            .method("GET")
-            .uri("/api/test")
-            .body(axum::body::Body::empty())
-            .expect("build request");
+        let mut results = Vec::new();
+        for _ in 0..3 {
+            results.push(handled(&engine).await);
+        }
 
-        let response = engine
-            .handle_request(request)
-            .await
-            .expect("handle request");
-        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(
+            results,
+            vec![json!("failed"), json!("failed"), json!("open")]
+        );
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
 
-        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
-            .await
-            .expect("read body");
-        let body: JsonValue = serde_json::from_slice(&bytes).expect("parse body");
-        assert_eq!(body, json!({ "handled": true }));
+        // A reloaded engine keeps the open circuit instead of calling the upstream again.
+        let reloaded =
+            EndpointEngine::load_with_state(rules_dir.to_path_buf(), config, engine.state())
+                .expect("reload engine");
+        assert_eq!(handled(&reloaded).await, json!("open"));
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert_eq!(reloaded.latency().count, 4);
 
         let _ = shutdown_tx.send(());
         let _ = server_handle.await;
     }
 
     #[tokio::test]
-    async fn network_timeout_on_slow_body_runs_catch() {
-        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
-            .await
-            .expect("bind");
-        let addr = listener.local_addr().expect("local addr");
-
-        let server_handle = tokio::spawn(async move {
-            let (mut socket, _) = listener.accept().await.expect("accept");
-            let mut buf = [0u8; 1024];
-            let _ = socket.read(&mut buf).await;
-            let body = b"{\"value\":1}";
-            let headers = format!(
-                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n",
-                body.len()
-            );
-            let _ = socket.write_all(headers.as_bytes()).await;
-            let _ = socket.flush().await;
-            tokio::time::sleep(Duration::from_millis(200)).await;
-            let _ = socket.write_all(body).await;
-            let _ = socket.shutdown().await;
-        });
-
+    async fn step_rule_record_when_false_returns_error() {
         let temp = tempfile::tempdir().expect("tempdir");
         let rules_dir = temp.path();
         let rules_subdir = rules_dir.join("rules");
@@ -4393,9 +7351,9 @@ version: 2
 type: endpoint
 endpoints:
   - method: GET
-    path: /api/test
+    path: /api/filter
     steps:
-      - rule: ./rules/network.yaml
+      - rule: ./rules/filter.yaml
     reply:
       status: 200
       body: "@input"
@@ -4404,36 +7362,20 @@ endpoints:
         .expect("write endpoint.yaml");
 
         std::fs::write(
-            rules_subdir.join("network.yaml"),
-            format!(
-                r#"
-version: 2
-type: network
-request:
-  method: GET
-  url: "http://{}/slow"
-timeout: 100ms
-catch:
-  timeout: ./catch.yaml
-"#,
-                addr
-            ),
-        )
-        .expect("write network.yaml");
-
-        std::fs::write(
-            rules_subdir.join("catch.yaml"),
+            rules_subdir.join("filter.yaml"),
             r#"
 version: 2
 input:
   format: json
   json: {}
+record_when:
+  eq: [1, 2]
 mappings:
-  - target: "handled"
-    value: true
+  - target: "ignored"
+    value: "nope"
 "#,
         )
-        .expect("write catch.yaml");
+        .expect("write filter rule");
 
         let engine = EndpointEngine::load(
             rules_dir.to_path_buf(),
@@ -4443,27 +7385,19 @@ mappings:
 
         let request = Request::builder()
             .method("GET")
-            .uri("/api/test")
+            .uri("/api/filter")
             .body(axum::body::Body::empty())
             .expect("build request");
 
-        let response = engine
+        let err = engine
             .handle_request(request)
             .await
-            .expect("handle request");
-        assert_eq!(response.status().as_u16(), 200);
-
-        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
-            .await
-            .expect("read body");
-        let body: JsonValue = serde_json::from_slice(&bytes).expect("parse body");
-        assert_eq!(body, json!({ "handled": true }));
-
-        let _ = server_handle.await;
+            .expect_err("expected error");
+        assert!(err.to_string().contains("record"));
     }
 
     #[tokio::test]
-    async fn step_rule_record_when_false_returns_error() {
+    async fn step_type_cast_failure_returns_structured_error() {
         let temp = tempfile::tempdir().expect("tempdir");
         let rules_dir = temp.path();
         let rules_subdir = rules_dir.join("rules");
@@ -4475,10 +7409,11 @@ mappings:
 version: 2
 type: endpoint
 endpoints:
-  - method: GET
-    path: /api/filter
+  - method: POST
+    path: /api/orders
     steps:
-      - rule: ./rules/filter.yaml
+      - rule: ./rules/validate.yaml
+      - rule: ./rules/cast.yaml
     reply:
       status: 200
       body: "@input"
@@ -4487,20 +7422,33 @@ endpoints:
         .expect("write endpoint.yaml");
 
         std::fs::write(
-            rules_subdir.join("filter.yaml"),
+            rules_subdir.join("validate.yaml"),
             r#"
 version: 2
 input:
   format: json
   json: {}
-record_when:
-  eq: [1, 2]
 mappings:
-  - target: "ignored"
-    value: "nope"
+  - target: "price"
+    source: "input.body.price"
 "#,
         )
-        .expect("write filter rule");
+        .expect("write validate rule");
+
+        std::fs::write(
+            rules_subdir.join("cast.yaml"),
+            r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "price"
+    source: "price"
+    type: "int"
+"#,
+        )
+        .expect("write cast rule");
 
         let engine = EndpointEngine::load(
             rules_dir.to_path_buf(),
@@ -4509,16 +7457,31 @@ mappings:
         .expect("load engine");
 
         let request = Request::builder()
-            .method("GET")
-            .uri("/api/filter")
-            .body(axum::body::Body::empty())
+            .method("POST")
+            .uri("/api/orders")
+            .body(axum::body::Body::from(r#"{"price": "abc"}"#))
             .expect("build request");
 
         let err = engine
             .handle_request(request)
             .await
             .expect_err("expected error");
-        assert!(err.to_string().contains("record"));
+        let failure = err
+            .downcast_ref::<EndpointFailure>()
+            .expect("structured failure");
+        assert_eq!(failure.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            failure.to_json(),
+            json!({
+                "error": {
+                    "code": "TypeCastFailed",
+                    "message": "failed to cast to int",
+                    "rule": "rules/rules/cast.yaml",
+                    "step": 1,
+                    "path": "mappings[0].type",
+                }
+            })
+        );
     }
 
     #[test]
@@ -4577,6 +7540,7 @@ mappings: []
                 "rule": { "path": "rules/body.yaml" },
                 "records": []
             })),
+            breaker_transitions: Vec::new(),
         };
 
         let nodes = build_network_nodes_with_timing(&rule, &timing);
@@ -1,5 +1,8 @@
 mod endpoint_engine;
+mod metrics;
 
 pub use endpoint_engine::{
-    ApiMode, EndpointEngine, EngineConfig, RulesDirError, RulesDirErrors, validate_rules_dir,
+    ApiMode, CircuitBreakerConfig, DEFAULT_MAX_BODY_BYTES, EndpointEngine, EndpointFailure,
+    EngineConfig, EngineState, RulesDirError, RulesDirErrors, validate_rules_dir,
 };
+pub use metrics::{LATENCY_BUCKETS, LatencyHistogram, LatencySnapshot};
@@ -0,0 +1,77 @@
+//! Latency histogram for endpoint executions.
+
+use std::sync::Mutex;
+
+/// Upper bounds of the latency buckets, in seconds.
+pub const LATENCY_BUCKETS: [f64; 12] = [
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Counts endpoint durations into [`LATENCY_BUCKETS`].
+#[derive(Debug, Default)]
+pub struct LatencyHistogram {
+    inner: Mutex<LatencySnapshot>,
+}
+
+/// Point-in-time copy of a [`LatencyHistogram`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LatencySnapshot {
+    /// Non-cumulative count per bucket; observations above the last bound are only in `count`.
+    pub buckets: [u64; LATENCY_BUCKETS.len()],
+    pub sum_us: u64,
+    pub count: u64,
+}
+
+impl LatencyHistogram {
+    pub fn observe(&self, duration_us: u64) {
+        let seconds = duration_us as f64 / 1_000_000.0;
+        let mut inner = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(index) = LATENCY_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            inner.buckets[index] += 1;
+        }
+        inner.sum_us += duration_us;
+        inner.count += 1;
+    }
+
+    pub fn snapshot(&self) -> LatencySnapshot {
+        self.inner
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+}
+
+impl LatencySnapshot {
+    /// Cumulative counts per bucket bound, as Prometheus expects.
+    pub fn cumulative(&self) -> impl Iterator<Item = (f64, u64)> + '_ {
+        LATENCY_BUCKETS
+            .iter()
+            .zip(self.buckets.iter())
+            .scan(0, |total, (bound, count)| {
+                *total += count;
+                Some((*bound, *total))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn observations_land_in_cumulative_buckets() {
+        let histogram = LatencyHistogram::default();
+        histogram.observe(500);
+        histogram.observe(20_000);
+        histogram.observe(60_000_000);
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.count, 3);
+        assert_eq!(snapshot.sum_us, 60_020_500);
+        let cumulative: Vec<(f64, u64)> = snapshot.cumulative().collect();
+        assert_eq!(cumulative[0], (0.001, 1));
+        assert_eq!(cumulative[2], (0.01, 1));
+        assert_eq!(cumulative[3], (0.025, 2));
+        assert_eq!(cumulative[11], (10.0, 2));
+    }
+}
@@ -1,14 +1,17 @@
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::{self, BufRead, BufReader, Write};
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 use csv::ReaderBuilder;
 use rulemorph::{
-    DtoLanguage, Expr, ExprChain, ExprOp, InputFormat, RuleError, RuleFile, TransformError,
-    TransformErrorKind, TransformWarning, generate_dto, parse_rule_file, transform_stream,
+    DtoLanguage, Expr, ExprChain, ExprOp, InputFormat, OutputFormat, ResolvedRuleFile, RuleError,
+    RuleFile, TransformError, TransformErrorKind, TransformErrorLocator, TransformOptions,
+    TransformWarning, ValidationResult, generate_dto, parse_rule_file, records_to_csv,
+    resolve_rule_includes, rule_stats, transform_collect_errors,
+    transform_collect_errors_with_base_dir, transform_preview, transform_stream,
     transform_stream_with_base_dir, transform_with_warnings, transform_with_warnings_with_base_dir,
-    validate_rule_file_with_source,
+    validate_rule_file_with_includes, validate_rule_file_with_source,
 };
 use serde_json::{Map, Value, json};
 use serde_yaml::{Mapping as YamlMapping, Value as YamlValue};
@@ -20,6 +23,8 @@ const RESOURCE_URI_README: &str = "rulemorph://docs/readme";
 const RESOURCE_RULES_SPEC_EN: &str = include_str!("../../../docs/rules_spec_en.md");
 const RESOURCE_RULES_SPEC_JA: &str = include_str!("../../../docs/rules_spec_ja.md");
 const RESOURCE_README: &str = include_str!("../../../README.md");
+const RULE_RESOURCE_SCHEME: &str = "rule://";
+const USAGE: &str = "usage: rulemorph-mcp [--rules-dir <path>]\n\n  --rules-dir <path>  Expose .yaml/.yml rule files under <path> as rule:// resources";
 
 fn main() {
     if let Err(err) = run() {
@@ -34,7 +39,38 @@ enum OutputMode {
     ContentLength,
 }
 
+#[derive(Debug, Default)]
+struct ServerOptions {
+    /// Directory whose rule files are listed as `rule://` resources.
+    rules_dir: Option<PathBuf>,
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Option<ServerOptions>, String> {
+    let mut options = ServerOptions::default();
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--rules-dir" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| "--rules-dir requires a path".to_string())?;
+                options.rules_dir = Some(PathBuf::from(value));
+            }
+            other => match other.strip_prefix("--rules-dir=") {
+                Some(value) => options.rules_dir = Some(PathBuf::from(value)),
+                None => return Err(format!("unknown argument: {}\n{}", other, USAGE)),
+            },
+        }
+    }
+    Ok(Some(options))
+}
+
 fn run() -> Result<(), String> {
+    let Some(options) = parse_args(std::env::args().skip(1))? else {
+        println!("{}", USAGE);
+        return Ok(());
+    };
     let stdin = io::stdin();
     let stdout = io::stdout();
     let mut reader = BufReader::new(stdin.lock());
@@ -56,7 +92,7 @@ fn run() -> Result<(), String> {
             }
         };
 
-        if let Some(response) = handle_message(value) {
+        if let Some(response) = handle_message(value, &options) {
             write_message(&mut writer, output_mode, &response).map_err(|err| err.to_string())?;
         }
     }
@@ -127,7 +163,7 @@ fn write_message(
     writer.flush()
 }
 
-fn handle_message(message: Value) -> Option<Value> {
+fn handle_message(message: Value, options: &ServerOptions) -> Option<Value> {
     let obj = message.as_object()?;
     let id = obj.get("id").cloned();
     let method = obj.get("method").and_then(|value| value.as_str());
@@ -152,13 +188,22 @@ fn handle_message(message: Value) -> Option<Value> {
                 }
             }
         }
-        "resources/list" => id.map(|id| ok_response(id, resources_list_result())),
+        "resources/list" => {
+            let id = id?;
+            match resources_list_result(options) {
+                Ok(result) => Some(ok_response(id, result)),
+                Err(message) => Some(error_response(id, -32603, &message)),
+            }
+        }
         "resources/read" => {
             let id = id?;
             let params = obj.get("params").cloned().unwrap_or(Value::Null);
-            match resources_read_result(&params) {
+            match resources_read_result(&params, options) {
                 Ok(result) => Some(ok_response(id, result)),
-                Err(message) => Some(error_response(id, -32602, &message)),
+                Err(ResourceError::InvalidParams(message)) => {
+                    Some(error_response(id, -32602, &message))
+                }
+                Err(ResourceError::NotFound(message)) => Some(error_response(id, -32002, &message)),
             }
         }
         "prompts/list" => id.map(|id| ok_response(id, prompts_list_result())),
@@ -225,11 +270,21 @@ fn tools_list_result() -> Value {
                 "description": "Transform CSV/JSON input with a YAML rule file.",
                 "inputSchema": transform_input_schema()
             },
+            {
+                "name": "batch_transform",
+                "description": "Transform several inputs with one YAML rule file; each input gets its own result.",
+                "inputSchema": batch_transform_input_schema()
+            },
             {
                 "name": "validate_rules",
                 "description": "Validate a YAML rule file.",
                 "inputSchema": validate_rules_input_schema()
             },
+            {
+                "name": "stats",
+                "description": "Summarize the ops, input/context paths, output targets, and pipe depth a rule file uses.",
+                "inputSchema": validate_rules_input_schema()
+            },
             {
                 "name": "generate_dto",
                 "description": "Generate DTO definitions from a YAML rule file.",
@@ -259,44 +314,107 @@ fn tools_list_result() -> Value {
     })
 }
 
-fn resources_list_result() -> Value {
-    json!({
-        "resources": [
-            {
-                "uri": RESOURCE_URI_RULES_SPEC_EN,
-                "name": "rules_spec_en",
-                "description": "Rule specification (English).",
-                "mimeType": "text/markdown"
-            },
-            {
-                "uri": RESOURCE_URI_RULES_SPEC_JA,
-                "name": "rules_spec_ja",
-                "description": "ルール仕様 (日本語).",
-                "mimeType": "text/markdown"
-            },
-            {
-                "uri": RESOURCE_URI_README,
-                "name": "readme",
-                "description": "Project README.",
-                "mimeType": "text/markdown"
-            }
-        ]
-    })
+fn resources_list_result(options: &ServerOptions) -> Result<Value, String> {
+    let mut resources = vec![
+        json!({
+            "uri": RESOURCE_URI_RULES_SPEC_EN,
+            "name": "rules_spec_en",
+            "description": "Rule specification (English).",
+            "mimeType": "text/markdown"
+        }),
+        json!({
+            "uri": RESOURCE_URI_RULES_SPEC_JA,
+            "name": "rules_spec_ja",
+            "description": "ルール仕様 (日本語).",
+            "mimeType": "text/markdown"
+        }),
+        json!({
+            "uri": RESOURCE_URI_README,
+            "name": "readme",
+            "description": "Project README.",
+            "mimeType": "text/markdown"
+        }),
+    ];
+
+    if let Some(rules_dir) = &options.rules_dir {
+        let mut files = Vec::new();
+        collect_rule_files(rules_dir, rules_dir, &mut files)
+            .map_err(|err| format!("failed to list rules dir: {}", err))?;
+        files.sort();
+        for relative in files {
+            resources.push(json!({
+                "uri": format!("{}{}", RULE_RESOURCE_SCHEME, relative),
+                "name": relative,
+                "description": "Rule file.",
+                "mimeType": "application/yaml"
+            }));
+        }
+    }
+
+    Ok(json!({ "resources": resources }))
 }
 
-fn resources_read_result(params: &Value) -> Result<Value, String> {
+/// Collect `.yaml`/`.yml` files under `dir` as `/`-separated paths relative to `root`.
+fn collect_rule_files(dir: &Path, root: &Path, files: &mut Vec<String>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_rule_files(&path, root, files)?;
+            continue;
+        }
+        let is_yaml = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext == "yaml" || ext == "yml");
+        if !is_yaml {
+            continue;
+        }
+        if let Ok(relative) = path.strip_prefix(root) {
+            let parts: Vec<String> = relative
+                .components()
+                .map(|part| part.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            files.push(parts.join("/"));
+        }
+    }
+    Ok(())
+}
+
+enum ResourceError {
+    InvalidParams(String),
+    NotFound(String),
+}
+
+fn resources_read_result(params: &Value, options: &ServerOptions) -> Result<Value, ResourceError> {
     let obj = params
         .as_object()
-        .ok_or_else(|| "params must be an object".to_string())?;
+        .ok_or_else(|| ResourceError::InvalidParams("params must be an object".to_string()))?;
     let uri = obj
         .get("uri")
         .and_then(|value| value.as_str())
-        .ok_or_else(|| "params.uri is required".to_string())?;
+        .ok_or_else(|| ResourceError::InvalidParams("params.uri is required".to_string()))?;
+    if let Some(relative) = uri.strip_prefix(RULE_RESOURCE_SCHEME) {
+        let text = read_rule_resource(relative, options)?;
+        return Ok(json!({
+            "contents": [
+                {
+                    "uri": uri,
+                    "mimeType": "application/yaml",
+                    "text": text
+                }
+            ]
+        }));
+    }
     let text = match uri {
         RESOURCE_URI_RULES_SPEC_EN => RESOURCE_RULES_SPEC_EN,
         RESOURCE_URI_RULES_SPEC_JA => RESOURCE_RULES_SPEC_JA,
         RESOURCE_URI_README => RESOURCE_README,
-        _ => return Err("unknown resource uri".to_string()),
+        _ => {
+            return Err(ResourceError::NotFound(format!(
+                "unknown resource uri: {}",
+                uri
+            )));
+        }
     };
 
     Ok(json!({
@@ -310,6 +428,31 @@ fn resources_read_result(params: &Value) -> Result<Value, String> {
     }))
 }
 
+/// Read a rule file by its path relative to the rules dir; paths leaving the dir are rejected.
+fn read_rule_resource(relative: &str, options: &ServerOptions) -> Result<String, ResourceError> {
+    let not_found = || ResourceError::NotFound(format!("rule resource not found: {}", relative));
+    let rules_dir = options.rules_dir.as_ref().ok_or_else(not_found)?;
+    let relative_path = Path::new(relative);
+    if !relative_path
+        .components()
+        .all(|part| matches!(part, Component::Normal(_)))
+    {
+        return Err(ResourceError::InvalidParams(format!(
+            "invalid rule resource path: {}",
+            relative
+        )));
+    }
+    let root = rules_dir.canonicalize().map_err(|_| not_found())?;
+    let path = root
+        .join(relative_path)
+        .canonicalize()
+        .map_err(|_| not_found())?;
+    if !path.starts_with(&root) || !path.is_file() {
+        return Err(not_found());
+    }
+    fs::read_to_string(&path).map_err(|_| not_found())
+}
+
 fn prompts_list_result() -> Value {
     json!({
         "prompts": [
@@ -448,7 +591,7 @@ fn transform_input_schema() -> Value {
             },
             "input_path": {
                 "type": "string",
-                "description": "Path to the input CSV/JSON file. Mutually exclusive with input_text and input_json.",
+                "description": "Path to the input CSV/JSON file (.gz and single-file .zip are decompressed). Mutually exclusive with input_text and input_json.",
                 "examples": ["input.json"]
             },
             "input_text": {
@@ -482,6 +625,12 @@ fn transform_input_schema() -> Value {
                 "description": "Emit NDJSON output (one JSON object per line).",
                 "examples": [false]
             },
+            "output_format": {
+                "type": "string",
+                "enum": ["json", "csv"],
+                "description": "Output format; defaults to output.format in the rule file. csv writes one column per top-level field with nested values as JSON. Cannot be combined with ndjson.",
+                "examples": ["csv"]
+            },
             "validate": {
                 "type": "boolean",
                 "description": "Validate the rule file before transforming.",
@@ -501,18 +650,71 @@ fn transform_input_schema() -> Value {
             "preview_rows": {
                 "type": "integer",
                 "minimum": 1,
-                "description": "Maximum rows to return when ndjson=true.",
+                "description": "Maximum records to return. Without output_path the transform stops after this many records; with output_path the file gets the full output.",
                 "examples": [100]
             },
             "return_output_json": {
                 "type": "boolean",
                 "description": "Include parsed output JSON in meta.output when ndjson=false and within size limits.",
                 "examples": [false]
+            },
+            "continue_on_error": {
+                "type": "boolean",
+                "description": "Keep transforming past failing records. Output holds the successful records; each failure is listed in meta.failures with its input index. Cannot be combined with ndjson.",
+                "examples": [false]
+            },
+            "strict": {
+                "type": "boolean",
+                "description": "Fail when the transform produces any warning (such as a deprecated op), listing the warnings as errors.",
+                "examples": [false]
             }
         }
     })
 }
 
+fn batch_transform_input_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "rules_path": {
+                "type": "string",
+                "description": "Path to the YAML rules file. Mutually exclusive with rules_text.",
+                "examples": ["rules.yaml"]
+            },
+            "rules_text": {
+                "type": "string",
+                "description": "Inline YAML rules content. Mutually exclusive with rules_path.",
+                "examples": ["version: 1\ninput:\n  format: json\n  json: {}\nmappings:\n  - target: \"id\"\n    source: \"id\""]
+            },
+            "inputs": {
+                "type": "array",
+                "description": "Inputs to transform. Each item has exactly one of input_text or input_json, and an optional context_json.",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "input_text": { "type": "string" },
+                        "input_json": { "type": ["object", "array"] },
+                        "context_json": { "type": "object" }
+                    }
+                },
+                "examples": [[{"input_json": [{"id": 1}]}, {"input_text": "[{\"id\":2}]"}]]
+            },
+            "validate": {
+                "type": "boolean",
+                "description": "Validate the rule file before transforming.",
+                "examples": [true]
+            },
+            "max_output_bytes": {
+                "type": "integer",
+                "minimum": 1,
+                "description": "Maximum total size of all outputs in bytes. Outputs past the limit are omitted and marked truncated.",
+                "examples": [1000000]
+            }
+        },
+        "required": ["inputs"]
+    })
+}
+
 fn validate_rules_input_schema() -> Value {
     json!({
         "type": "object",
@@ -547,7 +749,7 @@ fn generate_dto_input_schema() -> Value {
             },
             "language": {
                 "type": "string",
-                "enum": ["rust", "typescript", "python", "go", "java", "kotlin", "swift"],
+                "enum": ["rust", "typescript", "python", "go", "java", "kotlin", "swift", "json_schema"],
                 "description": "DTO output language.",
                 "examples": ["typescript"]
             },
@@ -574,7 +776,7 @@ fn analyze_input_input_schema() -> Value {
         "properties": {
             "input_path": {
                 "type": "string",
-                "description": "Path to the input CSV/JSON file. Mutually exclusive with input_text and input_json.",
+                "description": "Path to the input CSV/JSON file (.gz and single-file .zip are decompressed). Mutually exclusive with input_text and input_json.",
                 "examples": ["input.json"]
             },
             "input_text": {
@@ -624,7 +826,7 @@ fn generate_rules_from_base_input_schema() -> Value {
             },
             "input_path": {
                 "type": "string",
-                "description": "Path to the input CSV/JSON file. Mutually exclusive with input_text and input_json.",
+                "description": "Path to the input CSV/JSON file (.gz and single-file .zip are decompressed). Mutually exclusive with input_text and input_json.",
                 "examples": ["input.json"]
             },
             "input_text": {
@@ -675,7 +877,7 @@ fn generate_rules_from_dto_input_schema() -> Value {
             },
             "input_path": {
                 "type": "string",
-                "description": "Path to the input CSV/JSON file. Mutually exclusive with input_text and input_json.",
+                "description": "Path to the input CSV/JSON file (.gz and single-file .zip are decompressed). Mutually exclusive with input_text and input_json.",
                 "examples": ["input.json"]
             },
             "input_text": {
@@ -735,7 +937,9 @@ fn handle_tools_call(params: &Value) -> Result<Value, CallError> {
 
     match name {
         "transform" => run_transform_tool(args),
+        "batch_transform" => run_batch_transform_tool(args),
         "validate_rules" => run_validate_rules_tool(args),
+        "stats" => run_stats_tool(args),
         "generate_dto" => run_generate_dto_tool(args),
         "list_ops" => run_list_ops_tool(),
         "analyze_input" => run_analyze_input_tool(args),
@@ -760,6 +964,8 @@ fn run_transform_tool(args: &Map<String, Value>) -> Result<Value, CallError> {
     let ndjson = get_optional_bool(args, "ndjson")
         .map_err(CallError::InvalidParams)?
         .unwrap_or(false);
+    let output_format =
+        get_optional_string(args, "output_format").map_err(CallError::InvalidParams)?;
     let validate = get_optional_bool(args, "validate")
         .map_err(CallError::InvalidParams)?
         .unwrap_or(false);
@@ -771,6 +977,12 @@ fn run_transform_tool(args: &Map<String, Value>) -> Result<Value, CallError> {
     let return_output_json = get_optional_bool(args, "return_output_json")
         .map_err(CallError::InvalidParams)?
         .unwrap_or(false);
+    let continue_on_error = get_optional_bool(args, "continue_on_error")
+        .map_err(CallError::InvalidParams)?
+        .unwrap_or(false);
+    let strict = get_optional_bool(args, "strict")
+        .map_err(CallError::InvalidParams)?
+        .unwrap_or(false);
 
     let rule_source_count = rules_path.is_some() as u8 + rules_text.is_some() as u8;
     if rule_source_count == 0 {
@@ -819,8 +1031,29 @@ fn run_transform_tool(args: &Map<String, Value>) -> Result<Value, CallError> {
             "format must be csv or json".to_string(),
         ));
     }
+    let output_format = match output_format.as_deref() {
+        None => None,
+        Some(value) if value.eq_ignore_ascii_case("json") => Some(OutputFormat::Json),
+        Some(value) if value.eq_ignore_ascii_case("csv") => Some(OutputFormat::Csv),
+        Some(_) => {
+            return Err(CallError::InvalidParams(
+                "output_format must be json or csv".to_string(),
+            ));
+        }
+    };
 
-    let (mut rule, yaml) = load_rule_from_source(rules_path.as_deref(), rules_text.as_deref())?;
+    let mut loaded = load_rule_from_source(rules_path.as_deref(), rules_text.as_deref())?;
+    let output_format = output_format.unwrap_or_else(|| loaded.rule().output_format());
+    if ndjson && output_format == OutputFormat::Csv {
+        return Err(CallError::InvalidParams(
+            "ndjson cannot be combined with csv output".to_string(),
+        ));
+    }
+    if ndjson && continue_on_error {
+        return Err(CallError::InvalidParams(
+            "continue_on_error cannot be combined with ndjson".to_string(),
+        ));
+    }
     let base_dir = rules_path.as_deref().and_then(|path| {
         let parent = Path::new(path).parent()?;
         if parent.as_os_str().is_empty() {
@@ -835,13 +1068,15 @@ fn run_transform_tool(args: &Map<String, Value>) -> Result<Value, CallError> {
         input_text.as_deref(),
         input_json.as_ref(),
     ) {
-        (Some(path), None, None) => fs::read_to_string(path).map_err(|err| {
-            let message = format!("failed to read input: {}", err);
-            CallError::Tool {
-                message: message.clone(),
-                errors: Some(vec![io_error_json(&message, Some(path))]),
-            }
-        })?,
+        (Some(path), None, None) => {
+            rulemorph::read_input_file(Path::new(path), None).map_err(|err| {
+                let message = format!("failed to read input: {}", err);
+                CallError::Tool {
+                    message: message.clone(),
+                    errors: Some(vec![io_error_json(&message, Some(path))]),
+                }
+            })?
+        }
         (None, Some(text), None) => text.to_string(),
         (None, None, Some(value)) => serde_json::to_string(value).map_err(|err| {
             let message = format!("failed to serialize input JSON: {}", err);
@@ -884,11 +1119,11 @@ fn run_transform_tool(args: &Map<String, Value>) -> Result<Value, CallError> {
     } else {
         format
     };
-    apply_format_override(&mut rule, format_override.as_deref())
+    apply_format_override(loaded.rule_mut(), format_override.as_deref())
         .map_err(CallError::InvalidParams)?;
 
     if validate {
-        if let Err(errors) = validate_rule_file_with_source(&rule, &yaml) {
+        if let Err(errors) = loaded.validate() {
             let error_text = validation_errors_to_text(&errors);
             let error_values = validation_errors_to_values(&errors);
             return Err(CallError::Tool {
@@ -897,36 +1132,107 @@ fn run_transform_tool(args: &Map<String, Value>) -> Result<Value, CallError> {
             });
         }
     }
+    let rule = loaded.rule();
+    let locator = loaded.error_locator();
 
-    let (output_value, output_text, warnings) = if ndjson {
-        let (output_text, warnings) =
-            transform_to_ndjson(&rule, &input, context_value.as_ref(), base_dir.as_deref())?;
-        (None, output_text, warnings)
-    } else {
-        let (output, warnings) = match base_dir.as_deref() {
-            Some(base_dir) => transform_with_warnings_with_base_dir(
-                &rule,
+    // Cap during production unless the full output is written to a file.
+    let max_records = preview_rows.filter(|_| output_path.is_none());
+    let mut failures = Vec::new();
+    let (output_value, output_text, output_bytes, warnings, has_more) = if ndjson {
+        // With output_path, lines go straight to the file and only the reply preview is kept.
+        let sink = output_path.as_deref().map(|path| NdjsonSink {
+            path,
+            keep_rows: preview_rows,
+            keep_bytes: max_output_bytes,
+        });
+        let output = transform_to_ndjson(
+            rule,
+            locator.as_ref(),
+            &input,
+            context_value.as_ref(),
+            base_dir.as_deref(),
+            max_records,
+            sink,
+        )?;
+        (
+            None,
+            output.text,
+            output.bytes,
+            output.warnings,
+            output.has_more,
+        )
+    } else if continue_on_error {
+        let (mut records, failed) = match base_dir.as_deref() {
+            Some(base_dir) => transform_collect_errors_with_base_dir(
+                rule,
                 &input,
                 context_value.as_ref(),
                 base_dir,
             ),
-            None => transform_with_warnings(&rule, &input, context_value.as_ref()),
-        }
-        .map_err(|err| CallError::Tool {
-            message: transform_error_to_text(&err),
-            errors: Some(vec![transform_error_json(&err)]),
-        })?;
-        let output_text = serde_json::to_string(&output).map_err(|err| {
-            let message = format!("failed to serialize output JSON: {}", err);
-            CallError::Tool {
-                message: message.clone(),
-                errors: Some(vec![parse_error_json(&message, None)]),
-            }
-        })?;
-        (Some(output), output_text, warnings)
+            None => transform_collect_errors(rule, &input, context_value.as_ref()),
+        }
+        .map_err(|err| transform_call_error(err, locator.as_ref()))?;
+        failures = failed
+            .into_iter()
+            .map(|(index, err)| {
+                let mut value =
+                    transform_error_json(&locate_transform_error(err, locator.as_ref()));
+                value["index"] = json!(index);
+                value
+            })
+            .collect();
+        let has_more = max_records.is_some_and(|max| records.len() > max);
+        if let Some(max) = max_records {
+            records.truncate(max);
+        }
+        let output = Value::Array(records);
+        let output_text = serialize_output(&output, output_format)?;
+        let output_bytes = output_text.len();
+        (
+            Some(output),
+            output_text,
+            output_bytes,
+            Vec::new(),
+            has_more,
+        )
+    } else {
+        let options = TransformOptions {
+            base_dir: base_dir.as_deref(),
+            max_records,
+            ..Default::default()
+        };
+        let preview = transform_preview(rule, &input, context_value.as_ref(), &options)
+            .map_err(|err| transform_call_error(err, locator.as_ref()))?;
+        let output_text = serialize_output(&preview.output, output_format)?;
+        let output_bytes = output_text.len();
+        (
+            Some(preview.output),
+            output_text,
+            output_bytes,
+            preview.warnings,
+            preview.has_more,
+        )
     };
 
-    if let Some(path) = output_path.as_deref() {
+    if strict && !warnings.is_empty() {
+        let errors: Vec<Value> = warnings
+            .iter()
+            .map(|warning| {
+                transform_error_json(&locate_transform_error(
+                    TransformError::from(warning.clone()),
+                    locator.as_ref(),
+                ))
+            })
+            .collect();
+        return Err(CallError::Tool {
+            message: serde_json::to_string(&errors).unwrap_or_default(),
+            errors: Some(errors),
+        });
+    }
+
+    if let Some(path) = output_path.as_deref()
+        && !ndjson
+    {
         write_output(path, &output_text).map_err(|err| {
             let message = err;
             CallError::Tool {
@@ -936,18 +1242,24 @@ fn run_transform_tool(args: &Map<String, Value>) -> Result<Value, CallError> {
         })?;
     }
 
-    let output_bytes = output_text.as_bytes().len();
     let mut response_text = output_text.clone();
-    let mut truncated = false;
+    let mut truncated = has_more;
 
-    if ndjson {
-        if let Some(limit) = preview_rows {
-            let preview = preview_ndjson(&output_text, limit);
-            if preview.len() != output_text.len() {
-                truncated = true;
+    if let (Some(limit), None) = (preview_rows, max_records) {
+        let preview = if ndjson {
+            preview_ndjson(&output_text, limit)
+        } else {
+            match &output_value {
+                Some(Value::Array(records)) if records.len() > limit => {
+                    serialize_output(&Value::Array(records[..limit].to_vec()), output_format)?
+                }
+                _ => output_text.clone(),
             }
-            response_text = preview;
+        };
+        if preview.len() != output_bytes {
+            truncated = true;
         }
+        response_text = preview;
     }
 
     if let Some(max_bytes) = max_output_bytes {
@@ -981,6 +1293,13 @@ fn run_transform_tool(args: &Map<String, Value>) -> Result<Value, CallError> {
         meta.insert("output_bytes".to_string(), json!(output_bytes));
         meta.insert("truncated".to_string(), json!(true));
     }
+    if has_more {
+        meta.insert("has_more".to_string(), json!(true));
+    }
+    if continue_on_error {
+        meta.insert("failed".to_string(), json!(failures.len()));
+        meta.insert("failures".to_string(), Value::Array(failures));
+    }
     if return_output_json && !ndjson && !exceeds_max {
         if let Some(output) = output_value {
             meta.insert("output".to_string(), output);
@@ -993,6 +1312,137 @@ fn run_transform_tool(args: &Map<String, Value>) -> Result<Value, CallError> {
     Ok(result)
 }
 
+fn run_batch_transform_tool(args: &Map<String, Value>) -> Result<Value, CallError> {
+    let rules_path = get_optional_string(args, "rules_path").map_err(CallError::InvalidParams)?;
+    let rules_text = get_optional_string(args, "rules_text").map_err(CallError::InvalidParams)?;
+    let validate = get_optional_bool(args, "validate")
+        .map_err(CallError::InvalidParams)?
+        .unwrap_or(false);
+    let max_output_bytes =
+        get_optional_usize(args, "max_output_bytes").map_err(CallError::InvalidParams)?;
+    let inputs = args
+        .get("inputs")
+        .and_then(|value| value.as_array())
+        .ok_or_else(|| CallError::InvalidParams("inputs must be an array".to_string()))?;
+
+    let rule_source_count = rules_path.is_some() as u8 + rules_text.is_some() as u8;
+    if rule_source_count == 0 {
+        return Err(CallError::InvalidParams(
+            "rules_path or rules_text is required".to_string(),
+        ));
+    }
+    if rule_source_count > 1 {
+        return Err(CallError::InvalidParams(
+            "rules_path and rules_text are mutually exclusive".to_string(),
+        ));
+    }
+
+    let loaded = load_rule_from_source(rules_path.as_deref(), rules_text.as_deref())?;
+    let rule = loaded.rule();
+    let locator = loaded.error_locator();
+    let base_dir = rules_path.as_deref().and_then(|path| {
+        let parent = Path::new(path).parent()?;
+        if parent.as_os_str().is_empty() {
+            None
+        } else {
+            Some(parent.to_path_buf())
+        }
+    });
+
+    if validate && let Err(errors) = loaded.validate() {
+        let error_text = validation_errors_to_text(&errors);
+        let error_values = validation_errors_to_values(&errors);
+        return Err(CallError::Tool {
+            message: error_text,
+            errors: Some(error_values),
+        });
+    }
+
+    let mut json_rule = rule.clone();
+    json_rule.input.format = InputFormat::Json;
+
+    let mut results = Vec::with_capacity(inputs.len());
+    let mut failed = 0;
+    let mut output_bytes = 0;
+    let mut truncated = false;
+    for (index, item) in inputs.iter().enumerate() {
+        let mut result = json!({ "index": index });
+        let outcome = batch_input(item).and_then(|(input, context, is_json)| {
+            let rule = if is_json { &json_rule } else { rule };
+            match base_dir.as_deref() {
+                Some(base_dir) => {
+                    transform_with_warnings_with_base_dir(rule, &input, context, base_dir)
+                }
+                None => transform_with_warnings(rule, &input, context),
+            }
+            .map_err(|err| transform_error_json(&locate_transform_error(err, locator.as_ref())))
+        });
+        match outcome {
+            Ok((output, warnings)) => {
+                let size = serialize_output_json(&output)?.len();
+                output_bytes += size;
+                result["ok"] = json!(true);
+                if max_output_bytes.is_some_and(|max| output_bytes > max) {
+                    result["truncated"] = json!(true);
+                    truncated = true;
+                } else {
+                    result["output"] = output;
+                }
+                if !warnings.is_empty() {
+                    result["warnings"] = warnings_to_json(&warnings);
+                }
+            }
+            Err(error) => {
+                failed += 1;
+                result["ok"] = json!(false);
+                result["errors"] = json!([error]);
+            }
+        }
+        results.push(result);
+    }
+
+    let text = serialize_output_json(&Value::Array(results))?;
+    let mut meta = serde_json::Map::new();
+    meta.insert("succeeded".to_string(), json!(inputs.len() - failed));
+    meta.insert("failed".to_string(), json!(failed));
+    if truncated {
+        meta.insert("output_bytes".to_string(), json!(output_bytes));
+        meta.insert("truncated".to_string(), json!(true));
+    }
+
+    Ok(json!({
+        "content": [
+            {
+                "type": "text",
+                "text": text
+            }
+        ],
+        "meta": meta
+    }))
+}
+
+/// Input text, context, and whether the input came from `input_json` for one batch item.
+fn batch_input(item: &Value) -> Result<(String, Option<&Value>, bool), Value> {
+    let invalid = |message: &str| json!({ "type": "params", "message": message });
+    let obj = item
+        .as_object()
+        .ok_or_else(|| invalid("input item must be an object"))?;
+    let context = match obj.get("context_json") {
+        None | Some(Value::Null) => None,
+        Some(value) if value.is_object() => Some(value),
+        Some(_) => return Err(invalid("context_json must be an object")),
+    };
+    match (obj.get("input_text"), obj.get("input_json")) {
+        (Some(Value::String(text)), None) => Ok((text.clone(), context, false)),
+        (Some(_), None) => Err(invalid("input_text must be a string")),
+        (None, Some(value)) => serde_json::to_string(value)
+            .map(|text| (text, context, true))
+            .map_err(|err| invalid(&format!("failed to serialize input JSON: {}", err))),
+        (Some(_), Some(_)) => Err(invalid("input_text and input_json are mutually exclusive")),
+        (None, None) => Err(invalid("input_text or input_json is required")),
+    }
+}
+
 fn run_validate_rules_tool(args: &Map<String, Value>) -> Result<Value, CallError> {
     let rules_path = get_optional_string(args, "rules_path").map_err(CallError::InvalidParams)?;
     let rules_text = get_optional_string(args, "rules_text").map_err(CallError::InvalidParams)?;
@@ -1009,10 +1459,10 @@ fn run_validate_rules_tool(args: &Map<String, Value>) -> Result<Value, CallError
         ));
     }
 
-    let (rule, yaml) = load_rule_from_source(rules_path.as_deref(), rules_text.as_deref())?;
-    match validate_rule_file_with_source(&rule, &yaml) {
+    let loaded = load_rule_from_source(rules_path.as_deref(), rules_text.as_deref())?;
+    match loaded.validate() {
         Ok(_) => {
-            let warnings = collect_rule_warnings(&rule);
+            let warnings = collect_rule_warnings(loaded.rule());
             let mut result = json!({
                 "content": [
                     {
@@ -1046,6 +1496,38 @@ fn run_validate_rules_tool(args: &Map<String, Value>) -> Result<Value, CallError
     }
 }
 
+fn run_stats_tool(args: &Map<String, Value>) -> Result<Value, CallError> {
+    let rules_path = get_optional_string(args, "rules_path").map_err(CallError::InvalidParams)?;
+    let rules_text = get_optional_string(args, "rules_text").map_err(CallError::InvalidParams)?;
+
+    let rule_source_count = rules_path.is_some() as u8 + rules_text.is_some() as u8;
+    if rule_source_count == 0 {
+        return Err(CallError::InvalidParams(
+            "rules_path or rules_text is required".to_string(),
+        ));
+    }
+    if rule_source_count > 1 {
+        return Err(CallError::InvalidParams(
+            "rules_path and rules_text are mutually exclusive".to_string(),
+        ));
+    }
+
+    let loaded = load_rule_from_source(rules_path.as_deref(), rules_text.as_deref())?;
+    let meta = rule_stats(loaded.rule()).to_json();
+    let text = serde_json::to_string_pretty(&meta)
+        .unwrap_or_else(|_| "{\"error\":\"failed to serialize stats\"}".to_string());
+
+    Ok(json!({
+        "content": [
+            {
+                "type": "text",
+                "text": text
+            }
+        ],
+        "meta": meta
+    }))
+}
+
 fn run_generate_dto_tool(args: &Map<String, Value>) -> Result<Value, CallError> {
     let rules_path = get_optional_string(args, "rules_path").map_err(CallError::InvalidParams)?;
     let rules_text = get_optional_string(args, "rules_text").map_err(CallError::InvalidParams)?;
@@ -1068,8 +1550,8 @@ fn run_generate_dto_tool(args: &Map<String, Value>) -> Result<Value, CallError>
         language.ok_or_else(|| CallError::InvalidParams("language is required".to_string()))?;
     let language = parse_dto_language(&language).map_err(CallError::InvalidParams)?;
 
-    let (rule, _) = load_rule_from_source(rules_path.as_deref(), rules_text.as_deref())?;
-    let dto = generate_dto(&rule, language, name.as_deref()).map_err(|err| {
+    let loaded = load_rule_from_source(rules_path.as_deref(), rules_text.as_deref())?;
+    let dto = generate_dto(loaded.rule(), language, name.as_deref()).map_err(|err| {
         let message = format!("failed to generate dto: {}", err);
         CallError::Tool {
             message: message.clone(),
@@ -1346,7 +1828,7 @@ fn run_analyze_input_tool(args: &Map<String, Value>) -> Result<Value, CallError>
     }
 
     let input_text = match (input_path.as_deref(), input_text.as_deref()) {
-        (Some(path), None) => fs::read_to_string(path).map_err(|err| {
+        (Some(path), None) => rulemorph::read_input_file(Path::new(path), None).map_err(|err| {
             let message = format!("failed to read input: {}", err);
             CallError::Tool {
                 message: message.clone(),
@@ -1467,8 +1949,9 @@ fn run_generate_rules_from_base_tool(args: &Map<String, Value>) -> Result<Value,
         ));
     }
 
-    let (rule, yaml) = load_rule_from_source(rules_path.as_deref(), rules_text.as_deref())?;
-    let mut yaml_value: YamlValue = serde_yaml::from_str(&yaml).map_err(|err| {
+    let loaded = load_rule_from_source(rules_path.as_deref(), rules_text.as_deref())?;
+    let rule = loaded.rule();
+    let mut yaml_value: YamlValue = serde_yaml::from_str(loaded.yaml()).map_err(|err| {
         let message = format!("failed to parse rules yaml: {}", err);
         CallError::Tool {
             message: message.clone(),
@@ -1477,7 +1960,7 @@ fn run_generate_rules_from_base_tool(args: &Map<String, Value>) -> Result<Value,
     })?;
 
     let input_text = match (input_path.as_deref(), input_text.as_deref()) {
-        (Some(path), None) => fs::read_to_string(path).map_err(|err| {
+        (Some(path), None) => rulemorph::read_input_file(Path::new(path), None).map_err(|err| {
             let message = format!("failed to read input: {}", err);
             CallError::Tool {
                 message: message.clone(),
@@ -1717,7 +2200,7 @@ fn run_generate_rules_from_dto_tool(args: &Map<String, Value>) -> Result<Value,
     }
 
     let input_text = match (input_path.as_deref(), input_text.as_deref()) {
-        (Some(path), None) => fs::read_to_string(path).map_err(|err| {
+        (Some(path), None) => rulemorph::read_input_file(Path::new(path), None).map_err(|err| {
             let message = format!("failed to read input: {}", err);
             CallError::Tool {
                 message: message.clone(),
@@ -1971,10 +2454,60 @@ fn get_optional_object(args: &Map<String, Value>, key: &str) -> Result<Option<Va
     }
 }
 
+/// A rule loaded for a tool call.
+enum LoadedRule {
+    Plain {
+        rule: RuleFile,
+        yaml: String,
+    },
+    /// A `rules_path` file whose `include` entries were merged in.
+    Included(ResolvedRuleFile),
+}
+
+impl LoadedRule {
+    fn rule(&self) -> &RuleFile {
+        match self {
+            LoadedRule::Plain { rule, .. } => rule,
+            LoadedRule::Included(resolved) => &resolved.rule,
+        }
+    }
+
+    fn rule_mut(&mut self) -> &mut RuleFile {
+        match self {
+            LoadedRule::Plain { rule, .. } => rule,
+            LoadedRule::Included(resolved) => &mut resolved.rule,
+        }
+    }
+
+    /// YAML source of the root rule file.
+    fn yaml(&self) -> &str {
+        match self {
+            LoadedRule::Plain { yaml, .. } => yaml,
+            LoadedRule::Included(resolved) => resolved.source(),
+        }
+    }
+
+    /// Locator for transform errors. Merged rules span several files,
+    /// so their errors keep the path only.
+    fn error_locator(&self) -> Option<TransformErrorLocator> {
+        match self {
+            LoadedRule::Plain { yaml, .. } => Some(TransformErrorLocator::new(yaml)),
+            LoadedRule::Included(resolved) => resolved.error_locator(),
+        }
+    }
+
+    fn validate(&self) -> ValidationResult {
+        match self {
+            LoadedRule::Plain { rule, yaml } => validate_rule_file_with_source(rule, yaml),
+            LoadedRule::Included(resolved) => validate_rule_file_with_includes(resolved),
+        }
+    }
+}
+
 fn load_rule_from_source(
     rules_path: Option<&str>,
     rules_text: Option<&str>,
-) -> Result<(RuleFile, String), CallError> {
+) -> Result<LoadedRule, CallError> {
     match (rules_path, rules_text) {
         (Some(path), None) => {
             let yaml = fs::read_to_string(path).map_err(|err| {
@@ -1991,7 +2524,16 @@ fn load_rule_from_source(
                     errors: Some(vec![parse_error_json(&message, Some(path))]),
                 }
             })?;
-            Ok((rule, yaml))
+            if rule.include.is_empty() {
+                return Ok(LoadedRule::Plain { rule, yaml });
+            }
+            let resolved = resolve_rule_includes(rule, yaml, Path::new(path)).map_err(|err| {
+                CallError::Tool {
+                    message: validation_errors_to_text(std::slice::from_ref(&err)),
+                    errors: Some(validation_errors_to_values(std::slice::from_ref(&err))),
+                }
+            })?;
+            Ok(LoadedRule::Included(resolved))
         }
         (None, Some(text)) => {
             let rule = parse_rule_file(text).map_err(|err| {
@@ -2001,7 +2543,17 @@ fn load_rule_from_source(
                     errors: Some(vec![parse_error_json(&message, None)]),
                 }
             })?;
-            Ok((rule, text.to_string()))
+            if !rule.include.is_empty() {
+                let message = "include needs rules_path to resolve relative paths".to_string();
+                return Err(CallError::Tool {
+                    message: message.clone(),
+                    errors: Some(vec![parse_error_json(&message, None)]),
+                });
+            }
+            Ok(LoadedRule::Plain {
+                rule,
+                yaml: text.to_string(),
+            })
         }
         _ => Err(CallError::InvalidParams(
             "rules_path or rules_text is required".to_string(),
@@ -2018,8 +2570,10 @@ fn parse_dto_language(value: &str) -> Result<DtoLanguage, String> {
         "java" => Ok(DtoLanguage::Java),
         "kotlin" => Ok(DtoLanguage::Kotlin),
         "swift" => Ok(DtoLanguage::Swift),
+        "json_schema" | "jsonschema" => Ok(DtoLanguage::JsonSchema),
         _ => Err(
-            "language must be one of rust, typescript, python, go, java, kotlin, swift".to_string(),
+            "language must be one of rust, typescript, python, go, java, kotlin, swift, json_schema"
+                .to_string(),
         ),
     }
 }
@@ -2033,6 +2587,7 @@ fn dto_language_to_str(language: DtoLanguage) -> &'static str {
         DtoLanguage::Java => "java",
         DtoLanguage::Kotlin => "kotlin",
         DtoLanguage::Swift => "swift",
+        DtoLanguage::JsonSchema => "json_schema",
     }
 }
 
@@ -4563,6 +5118,12 @@ fn apply_format_override(rule: &mut RuleFile, format: Option<&str>) -> Result<()
 }
 
 fn write_output(path: &str, output: &str) -> Result<(), String> {
+    create_output_file(path)?
+        .write_all(output.as_bytes())
+        .map_err(|err| format!("failed to write output: {}", err))
+}
+
+fn create_output_file(path: &str) -> Result<fs::File, String> {
     let path = std::path::Path::new(path);
     if let Some(parent) = path.parent() {
         if !parent.as_os_str().is_empty() {
@@ -4570,48 +5131,132 @@ fn write_output(path: &str, output: &str) -> Result<(), String> {
                 .map_err(|err| format!("failed to create output directory: {}", err))?;
         }
     }
-    fs::write(path, output.as_bytes()).map_err(|err| format!("failed to write output: {}", err))
+    fs::File::create(path).map_err(|err| format!("failed to write output: {}", err))
+}
+
+/// File that NDJSON lines are written to as they are produced.
+struct NdjsonSink<'a> {
+    path: &'a str,
+    /// Lines kept in memory for the reply; all are kept when unset.
+    keep_rows: Option<usize>,
+    /// Stop keeping lines once the kept text exceeds this many bytes.
+    keep_bytes: Option<usize>,
+}
+
+struct NdjsonOutput {
+    /// All lines, or only the kept ones when writing to a sink.
+    text: String,
+    /// Size of the full output in bytes.
+    bytes: usize,
+    warnings: Vec<TransformWarning>,
+    has_more: bool,
 }
 
 fn transform_to_ndjson(
     rule: &RuleFile,
+    locator: Option<&TransformErrorLocator>,
     input: &str,
     context: Option<&serde_json::Value>,
     base_dir: Option<&Path>,
-) -> Result<(String, Vec<TransformWarning>), CallError> {
+    max_records: Option<usize>,
+    sink: Option<NdjsonSink<'_>>,
+) -> Result<NdjsonOutput, CallError> {
     let stream = match base_dir {
         Some(base_dir) => transform_stream_with_base_dir(rule, input, context, base_dir),
         None => transform_stream(rule, input, context),
     }
-    .map_err(|err| CallError::Tool {
-        message: transform_error_to_text(&err),
-        errors: Some(vec![transform_error_json(&err)]),
-    })?;
-    let mut output = String::new();
-    let mut warnings = Vec::new();
+    .map_err(|err| transform_call_error(err, locator))?;
+    let mut writer = match &sink {
+        Some(sink) => Some(io::BufWriter::new(
+            create_output_file(sink.path).map_err(|message| ndjson_io_error(message, sink.path))?,
+        )),
+        None => None,
+    };
+    let mut output = NdjsonOutput {
+        text: String::new(),
+        bytes: 0,
+        warnings: Vec::new(),
+        has_more: false,
+    };
+    let mut records = 0;
 
     for item in stream {
-        let item = item.map_err(|err| CallError::Tool {
-            message: transform_error_to_text(&err),
-            errors: Some(vec![transform_error_json(&err)]),
-        })?;
-        warnings.extend(item.warnings);
+        if max_records.is_some_and(|max| records >= max) {
+            match item {
+                Ok(item) if item.output.is_none() => continue,
+                _ => {
+                    output.has_more = true;
+                    break;
+                }
+            }
+        }
+        let item = item.map_err(|err| transform_call_error(err, locator))?;
+        output.warnings.extend(item.warnings);
         let output_value = match item.output {
             Some(output_value) => output_value,
             None => continue,
         };
-        let line = serde_json::to_string(&output_value).map_err(|err| {
-            let message = format!("failed to serialize output JSON: {}", err);
-            CallError::Tool {
-                message: message.clone(),
-                errors: Some(vec![parse_error_json(&message, None)]),
+        let mut line = serialize_output_json(&output_value)?;
+        line.push('\n');
+        output.bytes += line.len();
+        if let (Some(writer), Some(sink)) = (writer.as_mut(), sink.as_ref()) {
+            writer.write_all(line.as_bytes()).map_err(|err| {
+                ndjson_io_error(format!("failed to write output: {}", err), sink.path)
+            })?;
+            let keep = sink.keep_rows.is_none_or(|max| records < max)
+                && sink.keep_bytes.is_none_or(|max| output.text.len() <= max);
+            if keep {
+                output.text.push_str(&line);
             }
+        } else {
+            output.text.push_str(&line);
+        }
+        records += 1;
+    }
+
+    if let (Some(mut writer), Some(sink)) = (writer, sink) {
+        writer.flush().map_err(|err| {
+            ndjson_io_error(format!("failed to write output: {}", err), sink.path)
         })?;
-        output.push_str(&line);
-        output.push('\n');
     }
 
-    Ok((output, warnings))
+    Ok(output)
+}
+
+fn ndjson_io_error(message: String, path: &str) -> CallError {
+    CallError::Tool {
+        errors: Some(vec![io_error_json(&message, Some(path))]),
+        message,
+    }
+}
+
+fn serialize_output(output: &Value, format: OutputFormat) -> Result<String, CallError> {
+    match format {
+        OutputFormat::Json => serialize_output_json(output),
+        OutputFormat::Csv => {
+            let records = match output {
+                Value::Array(records) => records.as_slice(),
+                other => std::slice::from_ref(other),
+            };
+            records_to_csv(records).map_err(|err| {
+                let message = format!("failed to write output CSV: {}", err);
+                CallError::Tool {
+                    message: message.clone(),
+                    errors: Some(vec![io_error_json(&message, None)]),
+                }
+            })
+        }
+    }
+}
+
+fn serialize_output_json(output: &Value) -> Result<String, CallError> {
+    serde_json::to_string(output).map_err(|err| {
+        let message = format!("failed to serialize output JSON: {}", err);
+        CallError::Tool {
+            message: message.clone(),
+            errors: Some(vec![parse_error_json(&message, None)]),
+        }
+    })
 }
 
 struct RuleWarning {
@@ -4810,6 +5455,26 @@ fn preview_ndjson(text: &str, max_rows: usize) -> String {
     preview
 }
 
+/// Attach the rule line and column to `err` when the rule has a single source.
+fn locate_transform_error(
+    err: TransformError,
+    locator: Option<&TransformErrorLocator>,
+) -> TransformError {
+    match locator {
+        Some(locator) => locator.locate(err),
+        None => err,
+    }
+}
+
+/// Tool error for a failed transform, located in the rule source.
+fn transform_call_error(err: TransformError, locator: Option<&TransformErrorLocator>) -> CallError {
+    let err = locate_transform_error(err, locator);
+    CallError::Tool {
+        message: transform_error_to_text(&err),
+        errors: Some(vec![transform_error_json(&err)]),
+    }
+}
+
 fn transform_error_to_text(err: &TransformError) -> String {
     let value = transform_error_json(err);
     serde_json::to_string(&vec![value]).unwrap_or_else(|_| err.message.clone())
@@ -4824,6 +5489,10 @@ fn transform_error_json(err: &TransformError) -> Value {
     if let Some(path) = &err.path {
         value["path"] = json!(path);
     }
+    if let Some(location) = &err.location {
+        value["line"] = json!(location.line);
+        value["column"] = json!(location.column);
+    }
     value
 }
 
@@ -4854,5 +5523,7 @@ fn transform_kind_to_str(kind: &TransformErrorKind) -> &'static str {
         TransformErrorKind::TypeCastFailed => "TypeCastFailed",
         TransformErrorKind::ExprError => "ExprError",
         TransformErrorKind::AssertionFailed => "AssertionFailed",
+        TransformErrorKind::TypeAssertionFailed => "TypeAssertionFailed",
+        TransformErrorKind::Deprecated => "Deprecated",
     }
 }
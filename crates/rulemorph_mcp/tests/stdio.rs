@@ -14,8 +14,13 @@ struct McpServer {
 
 impl McpServer {
     fn start() -> Self {
+        Self::start_with_args(&[])
+    }
+
+    fn start_with_args(args: &[&str]) -> Self {
         let bin = env!("CARGO_BIN_EXE_rulemorph-mcp");
         let mut child = Command::new(bin)
+            .args(args)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::inherit())
@@ -83,7 +88,9 @@ fn initialize_and_list_tools() {
     let tools = response["result"]["tools"].as_array().expect("tools array");
     let expected = [
         "transform",
+        "batch_transform",
         "validate_rules",
+        "stats",
         "generate_dto",
         "list_ops",
         "analyze_input",
@@ -145,6 +152,70 @@ mappings:
     server.shutdown();
 }
 
+#[test]
+fn transform_rules_path_resolves_includes() {
+    let mut server = McpServer::start();
+    initialize(&mut server);
+
+    let dir = tempdir().expect("temp dir");
+    let rules_path = dir.path().join("rules.yaml");
+    let rules = r#"version: 2
+include:
+  - shared.yaml
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "id"
+    source: "id"
+"#;
+    fs::write(&rules_path, rules).expect("write rules");
+    fs::write(
+        dir.path().join("shared.yaml"),
+        r#"mappings:
+  - target: "kind"
+    value: "shared"
+"#,
+    )
+    .expect("write shared");
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 3,
+        "method": "tools/call",
+        "params": {
+            "name": "transform",
+            "arguments": {
+                "rules_path": rules_path.to_string_lossy(),
+                "input_json": [{ "id": 1 }]
+            }
+        }
+    });
+    let response = server.send(&request);
+    let output_text = response["result"]["content"][0]["text"]
+        .as_str()
+        .expect("output text");
+    let output: Value = serde_json::from_str(output_text).expect("output json");
+    assert_eq!(output, json!([{ "kind": "shared", "id": 1 }]));
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 4,
+        "method": "tools/call",
+        "params": {
+            "name": "transform",
+            "arguments": {
+                "rules_text": rules,
+                "input_json": [{ "id": 1 }]
+            }
+        }
+    });
+    let response = server.send(&request);
+    assert_eq!(response["result"]["isError"], true);
+
+    server.shutdown();
+}
+
 #[test]
 fn transform_rules_path_resolves_branch_relative_paths() {
     let mut server = McpServer::start();
@@ -222,6 +293,150 @@ mappings:
     server.shutdown();
 }
 
+#[test]
+fn transform_errors_report_rule_lines() {
+    let mut server = McpServer::start();
+    initialize(&mut server);
+
+    let rules_text = r#"version: 2
+input:
+  format: json
+mappings:
+  - target: "ratio"
+    expr:
+      - "@input.total"
+      - divide: [0]
+"#;
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 10,
+        "method": "tools/call",
+        "params": {
+            "name": "transform",
+            "arguments": {
+                "rules_text": rules_text,
+                "input_json": [{ "total": 5 }]
+            }
+        }
+    });
+
+    let response = server.send(&request);
+    assert_eq!(response["result"]["isError"], true);
+    let error = &response["result"]["meta"]["errors"][0];
+    assert_eq!(error["type"], "transform");
+    assert_eq!(error["path"], "mappings[0].expr[1].args[0]");
+    assert_eq!(error["line"], 8);
+    assert_eq!(error["column"], 7);
+
+    server.shutdown();
+}
+
+#[test]
+fn transform_continue_on_error_reports_failures() {
+    let mut server = McpServer::start();
+    initialize(&mut server);
+
+    let rules_text = r#"version: 2
+input:
+  format: json
+mappings:
+  - target: "id"
+    source: "input.id"
+    required: true
+"#;
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 11,
+        "method": "tools/call",
+        "params": {
+            "name": "transform",
+            "arguments": {
+                "rules_text": rules_text,
+                "input_json": [{ "id": 1 }, { "name": "no id" }, { "id": 3 }],
+                "continue_on_error": true,
+                "return_output_json": true
+            }
+        }
+    });
+
+    let response = server.send(&request);
+    assert!(response["result"]["isError"].is_null());
+    let meta = &response["result"]["meta"];
+    assert_eq!(meta["output"], json!([{ "id": 1 }, { "id": 3 }]));
+    assert_eq!(meta["failed"], 1);
+    assert_eq!(meta["failures"][0]["index"], 1);
+    assert_eq!(meta["failures"][0]["kind"], "MissingRequired");
+    assert_eq!(meta["failures"][0]["line"], 5);
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 12,
+        "method": "tools/call",
+        "params": {
+            "name": "transform",
+            "arguments": {
+                "rules_text": rules_text,
+                "input_json": [{ "id": 1 }],
+                "continue_on_error": true,
+                "ndjson": true
+            }
+        }
+    });
+    let response = server.send(&request);
+    assert_eq!(response["error"]["code"], -32602);
+
+    server.shutdown();
+}
+
+#[test]
+fn transform_strict_fails_on_warnings() {
+    let mut server = McpServer::start();
+    initialize(&mut server);
+
+    let rules_text = r#"version: 1
+input:
+  format: json
+record_when:
+  ref: "input.name"
+mappings:
+  - target: "name"
+    source: "name"
+"#;
+    let call = |id: u64, strict: bool| {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {
+                "name": "transform",
+                "arguments": {
+                    "rules_text": rules_text,
+                    "input_json": [{ "name": "aaa" }],
+                    "strict": strict
+                }
+            }
+        })
+    };
+
+    let response = server.send(&call(13, false));
+    assert!(response["result"]["isError"].is_null());
+    assert_eq!(
+        response["result"]["meta"]["warnings"][0]["kind"],
+        "ExprError"
+    );
+
+    let response = server.send(&call(14, true));
+    assert_eq!(response["result"]["isError"], true);
+    let error = &response["result"]["meta"]["errors"][0];
+    assert_eq!(error["kind"], "ExprError");
+    assert_eq!(error["path"], "record_when");
+    assert_eq!(error["line"], 4);
+
+    server.shutdown();
+}
+
 #[test]
 fn tools_call_invalid_params_returns_error() {
     let mut server = McpServer::start();
@@ -270,6 +485,94 @@ fn tools_call_missing_files_returns_tool_error() {
     server.shutdown();
 }
 
+#[test]
+fn batch_transform_reports_each_input() {
+    let mut server = McpServer::start();
+    initialize(&mut server);
+
+    let rules = r#"version: 1
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "id"
+    source: "id"
+    required: true
+  - target: "tenant"
+    source: "context.tenant"
+"#;
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 5,
+        "method": "tools/call",
+        "params": {
+            "name": "batch_transform",
+            "arguments": {
+                "rules_text": rules,
+                "inputs": [
+                    { "input_json": [{ "id": 1 }], "context_json": { "tenant": "a" } },
+                    { "input_json": [{ "name": "no id" }] },
+                    { "input_text": "not json" },
+                    { "input_text": "[{\"id\": 2}]", "input_json": [] },
+                    { "input_text": "[{\"id\": 3}]" }
+                ]
+            }
+        }
+    });
+
+    let response = server.send(&request);
+    let text = response["result"]["content"][0]["text"]
+        .as_str()
+        .expect("output text");
+    let results: Value = serde_json::from_str(text).expect("results json");
+    let results = results.as_array().expect("results array");
+    assert_eq!(results.len(), 5);
+
+    assert_eq!(results[0]["ok"], true);
+    assert_eq!(results[0]["output"], json!([{ "id": 1, "tenant": "a" }]));
+    assert_eq!(results[1]["ok"], false);
+    assert_eq!(results[1]["errors"][0]["kind"], "MissingRequired");
+    assert_eq!(results[2]["ok"], false);
+    assert_eq!(results[2]["errors"][0]["type"], "transform");
+    assert_eq!(results[3]["ok"], false);
+    assert_eq!(results[3]["errors"][0]["type"], "params");
+    assert_eq!(results[4]["ok"], true);
+    assert_eq!(results[4]["output"], json!([{ "id": 3 }]));
+
+    assert_eq!(response["result"]["meta"]["succeeded"], 2);
+    assert_eq!(response["result"]["meta"]["failed"], 3);
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 6,
+        "method": "tools/call",
+        "params": {
+            "name": "batch_transform",
+            "arguments": {
+                "rules_text": rules,
+                "max_output_bytes": 15,
+                "inputs": [
+                    { "input_json": [{ "id": 1 }] },
+                    { "input_json": [{ "id": 2 }] }
+                ]
+            }
+        }
+    });
+
+    let response = server.send(&request);
+    let text = response["result"]["content"][0]["text"]
+        .as_str()
+        .expect("output text");
+    let results: Value = serde_json::from_str(text).expect("results json");
+    assert_eq!(results[0]["output"], json!([{ "id": 1 }]));
+    assert!(results[1].get("output").is_none());
+    assert_eq!(results[1]["truncated"], true);
+    assert_eq!(response["result"]["meta"]["truncated"], true);
+
+    server.shutdown();
+}
+
 #[test]
 fn ndjson_and_output_path() {
     let mut server = McpServer::start();
@@ -327,6 +630,155 @@ mappings:
     server.shutdown();
 }
 
+#[test]
+fn ndjson_output_path_streams_full_output_with_capped_preview() {
+    let mut server = McpServer::start();
+    initialize(&mut server);
+
+    let dir = tempdir().expect("temp dir");
+    let input_path = dir.path().join("input.json");
+    let output_path = dir.path().join("out/large.ndjson");
+    let records: Vec<Value> = (0..5000).map(|id| json!({ "id": id })).collect();
+    fs::write(&input_path, serde_json::to_string(&records).unwrap()).expect("write input");
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 7,
+        "method": "tools/call",
+        "params": {
+            "name": "transform",
+            "arguments": {
+                "rules_text": "version: 1\ninput:\n  format: json\n  json: {}\nmappings:\n  - target: \"id\"\n    source: \"id\"\n",
+                "input_path": input_path.to_string_lossy(),
+                "ndjson": true,
+                "output_path": output_path.to_string_lossy(),
+                "preview_rows": 3
+            }
+        }
+    });
+
+    let response = server.send(&request);
+    assert_eq!(
+        response["result"]["content"][0]["text"].as_str(),
+        Some("{\"id\":0}\n{\"id\":1}\n{\"id\":2}\n")
+    );
+    assert_eq!(response["result"]["meta"]["truncated"], json!(true));
+
+    let output_file = fs::read_to_string(&output_path).expect("read output file");
+    assert_eq!(output_file.lines().count(), 5000);
+    assert_eq!(output_file.lines().last(), Some("{\"id\":4999}"));
+    assert_eq!(
+        response["result"]["meta"]["output_bytes"],
+        json!(output_file.len())
+    );
+
+    server.shutdown();
+}
+
+#[test]
+fn preview_rows_stops_transform_early() {
+    let mut server = McpServer::start();
+    initialize(&mut server);
+
+    let rules = r#"version: 1
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "id"
+    source: "id"
+    required: true
+"#;
+    // The last record would fail the transform if it were reached.
+    let input = r#"[{"id": 1}, {"id": 2}, {"id": 3}, {}]"#;
+
+    for (id, ndjson, expected_text) in [
+        (7, false, "[{\"id\":1},{\"id\":2}]"),
+        (8, true, "{\"id\":1}\n{\"id\":2}\n"),
+    ] {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": {
+                "name": "transform",
+                "arguments": {
+                    "rules_text": rules,
+                    "input_text": input,
+                    "ndjson": ndjson,
+                    "preview_rows": 2
+                }
+            }
+        });
+
+        let response = server.send(&request);
+        assert_eq!(
+            response["result"]["content"][0]["text"].as_str(),
+            Some(expected_text)
+        );
+        assert_eq!(response["result"]["meta"]["has_more"], json!(true));
+        assert_eq!(response["result"]["meta"]["truncated"], json!(true));
+    }
+
+    server.shutdown();
+}
+
+#[test]
+fn transform_csv_output_format() {
+    let mut server = McpServer::start();
+    initialize(&mut server);
+
+    let rules = r#"version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "id"
+    source: "id"
+  - target: "meta"
+    source: "meta"
+"#;
+    let input = r#"[{"id": 1, "meta": {"tag": "a"}}, {"id": 2}]"#;
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 9,
+        "method": "tools/call",
+        "params": {
+            "name": "transform",
+            "arguments": {
+                "rules_text": rules,
+                "input_text": input,
+                "output_format": "csv"
+            }
+        }
+    });
+    let response = server.send(&request);
+    assert_eq!(
+        response["result"]["content"][0]["text"].as_str(),
+        Some("id,meta\n1,\"{\"\"tag\"\":\"\"a\"\"}\"\n2,\n")
+    );
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 10,
+        "method": "tools/call",
+        "params": {
+            "name": "transform",
+            "arguments": {
+                "rules_text": rules,
+                "input_text": input,
+                "output_format": "csv",
+                "ndjson": true
+            }
+        }
+    });
+    let response = server.send(&request);
+    assert_eq!(response["error"]["code"], -32602);
+
+    server.shutdown();
+}
+
 #[test]
 fn ndjson_rules_path_resolves_branch_relative_paths() {
     let mut server = McpServer::start();
@@ -499,6 +951,45 @@ mappings:
     server.shutdown();
 }
 
+#[test]
+fn stats_summarizes_rule() {
+    let mut server = McpServer::start();
+    initialize(&mut server);
+
+    let rules_text = r#"version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "name"
+    expr: ["@input.name", "trim", "uppercase"]
+  - target: "region"
+    expr: ["@context.region"]
+"#;
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 9,
+        "method": "tools/call",
+        "params": {
+            "name": "stats",
+            "arguments": {
+                "rules_text": rules_text
+            }
+        }
+    });
+
+    let response = server.send(&request);
+    let meta = &response["result"]["meta"];
+    assert_eq!(meta["ops"], json!({ "trim": 1, "uppercase": 1 }));
+    assert_eq!(meta["input_paths"], json!(["name"]));
+    assert_eq!(meta["context_paths"], json!(["region"]));
+    assert_eq!(meta["output_targets"], json!(["name", "region"]));
+    assert_eq!(meta["max_pipe_depth"], json!(1));
+
+    server.shutdown();
+}
+
 #[test]
 fn validate_rules_failure() {
     let mut server = McpServer::start();
@@ -529,6 +1020,55 @@ mappings: []
     server.shutdown();
 }
 
+#[test]
+fn validate_rules_reports_codes_and_lines() {
+    let mut server = McpServer::start();
+    initialize(&mut server);
+
+    let rules_text = r#"version: 1
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "id"
+    source: "id"
+  - target: "id"
+    expr:
+      op: "no_such_op"
+      args: [1]
+"#;
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 9,
+        "method": "tools/call",
+        "params": {
+            "name": "validate_rules",
+            "arguments": {
+                "rules_text": rules_text
+            }
+        }
+    });
+
+    let response = server.send(&request);
+    assert_eq!(response["result"]["isError"], true);
+    let errors = response["result"]["meta"]["errors"]
+        .as_array()
+        .expect("errors array");
+    let summary: Vec<(&str, u64)> = errors
+        .iter()
+        .map(|err| {
+            (
+                err["code"].as_str().expect("code"),
+                err["line"].as_u64().expect("line"),
+            )
+        })
+        .collect();
+    assert_eq!(summary, vec![("DuplicateTarget", 8), ("UnknownOp", 10)]);
+
+    server.shutdown();
+}
+
 #[test]
 fn generate_dto_typescript() {
     let mut server = McpServer::start();
@@ -565,6 +1105,49 @@ mappings:
     server.shutdown();
 }
 
+#[test]
+fn generate_dto_reports_dto_errors() {
+    let mut server = McpServer::start();
+    initialize(&mut server);
+
+    let rules_text = r#"version: 1
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "items[0]"
+    source: "id"
+"#;
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 10,
+        "method": "tools/call",
+        "params": {
+            "name": "generate_dto",
+            "arguments": {
+                "rules_text": rules_text,
+                "language": "rust"
+            }
+        }
+    });
+
+    let response = server.send(&request);
+    assert_eq!(response["result"]["isError"], true);
+    let errors = response["result"]["meta"]["errors"]
+        .as_array()
+        .expect("errors array");
+    assert_eq!(errors[0]["type"], "dto");
+    assert!(
+        errors[0]["message"]
+            .as_str()
+            .expect("error message")
+            .contains("target path must not include indexes")
+    );
+
+    server.shutdown();
+}
+
 #[test]
 fn list_ops_success() {
     let mut server = McpServer::start();
@@ -1112,6 +1695,65 @@ fn resources_list_and_read() {
     server.shutdown();
 }
 
+#[test]
+fn rule_resources_from_rules_dir() {
+    let dir = tempdir().expect("temp dir");
+    fs::create_dir_all(dir.path().join("nested")).expect("create nested dir");
+    fs::write(dir.path().join("orders.yaml"), "version: 1\n").expect("write rule");
+    fs::write(dir.path().join("nested/users.yml"), "version: 2\n").expect("write rule");
+    fs::write(dir.path().join("notes.txt"), "not a rule").expect("write note");
+
+    let rules_dir = dir.path().to_string_lossy().to_string();
+    let mut server = McpServer::start_with_args(&["--rules-dir", &rules_dir]);
+    initialize(&mut server);
+
+    let list_response = server.send(&json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "resources/list"
+    }));
+    let rule_uris: Vec<&str> = list_response["result"]["resources"]
+        .as_array()
+        .expect("resources array")
+        .iter()
+        .filter_map(|item| item["uri"].as_str())
+        .filter(|uri| uri.starts_with("rule://"))
+        .collect();
+    assert_eq!(
+        rule_uris,
+        vec!["rule://nested/users.yml", "rule://orders.yaml"]
+    );
+
+    let read_response = server.send(&json!({
+        "jsonrpc": "2.0",
+        "id": 3,
+        "method": "resources/read",
+        "params": { "uri": "rule://nested/users.yml" }
+    }));
+    assert_eq!(
+        read_response["result"]["contents"][0]["text"].as_str(),
+        Some("version: 2\n")
+    );
+
+    let missing_response = server.send(&json!({
+        "jsonrpc": "2.0",
+        "id": 4,
+        "method": "resources/read",
+        "params": { "uri": "rule://missing.yaml" }
+    }));
+    assert_eq!(missing_response["error"]["code"], -32002);
+
+    let escape_response = server.send(&json!({
+        "jsonrpc": "2.0",
+        "id": 5,
+        "method": "resources/read",
+        "params": { "uri": "rule://../outside.yaml" }
+    }));
+    assert_eq!(escape_response["error"]["code"], -32602);
+
+    server.shutdown();
+}
+
 #[test]
 fn prompts_list_and_get() {
     let mut server = McpServer::start();
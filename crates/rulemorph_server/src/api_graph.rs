@@ -59,7 +59,20 @@ struct EndpointDef {
 
 #[derive(Debug, serde::Deserialize)]
 struct EndpointStep {
-    rule: String,
+    #[serde(default)]
+    rule: Option<String>,
+    #[serde(default)]
+    parallel: Vec<EndpointStep>,
+}
+
+impl EndpointDef {
+    /// Rules referenced by the steps, including those inside `parallel` steps.
+    fn step_rules(&self) -> impl Iterator<Item = &str> {
+        self.steps
+            .iter()
+            .flat_map(|step| std::iter::once(step).chain(&step.parallel))
+            .filter_map(|step| step.rule.as_deref())
+    }
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -119,8 +132,8 @@ pub fn build_api_graph(data_dir: &Path) -> Result<ApiGraphResponse> {
             let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
             for endpoint_def in &endpoint.endpoints {
                 let label = format!("{} {}", endpoint_def.method, endpoint_def.path);
-                for step in &endpoint_def.steps {
-                    let target_path = normalize_path(&resolve_rule_path(base_dir, &step.rule));
+                for rule in endpoint_def.step_rules() {
+                    let target_path = normalize_path(&resolve_rule_path(base_dir, rule));
                     let target_id = rule_id(&data_dir, &target_path);
                     if !nodes.contains_key(&target_id) {
                         insert_placeholder(&mut nodes, &data_dir, &target_path);
@@ -323,10 +336,9 @@ fn endpoint_ops(rule: &EndpointRuleFile, data_dir: &Path, endpoint_path: &Path)
         .iter()
         .map(|endpoint| {
             let refs = endpoint
-                .steps
-                .iter()
-                .map(|step| {
-                    let target = normalize_path(&resolve_rule_path(base_dir, &step.rule));
+                .step_rules()
+                .map(|rule| {
+                    let target = normalize_path(&resolve_rule_path(base_dir, rule));
                     rule_id(data_dir, &target)
                 })
                 .collect::<Vec<_>>();
@@ -498,10 +510,19 @@ mod tests {
                 path: "/users/{id}".to_string(),
                 steps: vec![
                     EndpointStep {
-                        rule: "./a.yaml".to_string(),
+                        rule: Some("./a.yaml".to_string()),
+                        parallel: Vec::new(),
+                    },
+                    EndpointStep {
+                        rule: Some("./b.yaml".to_string()),
+                        parallel: Vec::new(),
                     },
                     EndpointStep {
-                        rule: "./b.yaml".to_string(),
+                        rule: None,
+                        parallel: vec![EndpointStep {
+                            rule: Some("./c.yaml".to_string()),
+                            parallel: Vec::new(),
+                        }],
                     },
                 ],
             }],
@@ -513,6 +534,7 @@ mod tests {
         let refs = &ops[0].refs;
         assert!(refs.contains(&"api_rules/a.yaml".to_string()));
         assert!(refs.contains(&"api_rules/b.yaml".to_string()));
+        assert!(refs.contains(&"api_rules/c.yaml".to_string()));
     }
 
     #[test]
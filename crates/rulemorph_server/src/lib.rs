@@ -1,29 +1,85 @@
 mod api_graph;
+mod metrics;
+mod profile;
+mod rules_watch;
 mod server;
+mod test_transform;
 
-use std::net::SocketAddr;
+use std::future::{Future, IntoFuture};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
+use axum::Router;
+use axum::http::HeaderValue;
 pub use rulemorph_endpoint::{ApiMode, RulesDirError, RulesDirErrors, validate_rules_dir};
 use rulemorph_endpoint::{EndpointEngine, EngineConfig};
+pub use rulemorph_trace::TraceRetention;
 use rulemorph_trace::{TraceStore, start_trace_watcher};
-use tokio::sync::broadcast;
+use tokio::sync::{Notify, broadcast};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 
+use rules_watch::{EngineHandle, start_rules_watcher};
 use server::{AppState, UiSource, build_router};
 
+pub const TRACE_PRUNE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// How long in-flight requests may keep running after a shutdown signal.
+const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
+    /// Interface to listen on; defaults to loopback.
+    pub bind_addr: IpAddr,
     pub port: u16,
     pub data_dir: PathBuf,
     pub ui_dir: Option<PathBuf>,
     pub rules_dir: Option<PathBuf>,
     pub api_mode: ApiMode,
     pub ui_enabled: bool,
+    /// Pending trace notifications kept per UI stream before it starts lagging.
+    pub trace_channel_capacity: usize,
+    /// Origins allowed to call the rules API from a browser; empty disables CORS and
+    /// `["*"]` allows any origin.
+    pub cors_allowed_origins: Vec<String>,
+    /// When set, old traces are pruned at startup and every [`TRACE_PRUNE_INTERVAL`].
+    pub trace_retention: Option<TraceRetention>,
+    /// Requests whose handler runs longer than this get `504 Gateway Timeout`; `None`
+    /// disables the limit. Keep it above the longest endpoint `steps[].timeout_ms` so a
+    /// slow network chain reports its own step timeout instead.
+    pub request_timeout: Option<Duration>,
+    /// Largest request body the rules API accepts before answering `413`.
+    pub max_body_bytes: usize,
 }
 
 impl ServerConfig {
+    pub const DEFAULT_BIND_ADDR: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    pub const DEFAULT_TRACE_CHANNEL_CAPACITY: usize = 64;
+    pub const DEFAULT_REQUEST_TIMEOUT_MS: u64 = 300_000;
+    pub const DEFAULT_MAX_BODY_BYTES: usize = rulemorph_endpoint::DEFAULT_MAX_BODY_BYTES;
+
+    /// Maps a `--request-timeout-ms` value to [`ServerConfig::request_timeout`]; `0`
+    /// disables the limit.
+    pub fn request_timeout_from_ms(ms: u64) -> Option<Duration> {
+        (ms > 0).then(|| Duration::from_millis(ms))
+    }
+
+    pub fn socket_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_addr, self.port)
+    }
+
+    /// Address the server uses to call its own endpoints.
+    fn internal_addr(&self) -> SocketAddr {
+        let ip = match self.bind_addr {
+            IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+            ip => ip,
+        };
+        SocketAddr::new(ip, self.port)
+    }
+
     pub fn default_data_dir() -> PathBuf {
         let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
         cwd.join(".rulemorph")
@@ -50,11 +106,18 @@ pub async fn run(config: ServerConfig) -> Result<()> {
     if !config.ui_enabled && config.api_mode == ApiMode::UiOnly {
         anyhow::bail!("ui-only mode cannot be used with UI disabled");
     }
+    if config.trace_channel_capacity == 0 {
+        anyhow::bail!("trace channel capacity must be at least 1");
+    }
+    let cors = cors_layer(&config.cors_allowed_origins)?;
 
     let store = TraceStore::new(config.data_dir.clone())
         .await
         .context("failed to init trace store")?;
-    let (trace_events, _) = broadcast::channel(64);
+    if let Some(retention) = config.trace_retention.clone() {
+        start_trace_pruner(store.clone(), retention);
+    }
+    let (trace_events, _) = broadcast::channel(config.trace_channel_capacity);
     if config.ui_enabled {
         start_trace_watcher(config.data_dir.clone(), trace_events.clone());
     }
@@ -68,11 +131,15 @@ pub async fn run(config: ServerConfig) -> Result<()> {
             if let Err(errs) = validate_rules_dir(&rules_dir) {
                 return Err(errs.into());
             }
-            let internal_base = format!("http://127.0.0.1:{}", config.port);
-            Some(EndpointEngine::load(
-                rules_dir,
-                EngineConfig::new(internal_base, config.data_dir.clone()),
-            )?)
+            let internal_base = format!("http://{}", config.internal_addr());
+            let engine_config = EngineConfig::new(internal_base, config.data_dir.clone())
+                .with_max_body_bytes(config.max_body_bytes);
+            let engine = EngineHandle::new(EndpointEngine::load(
+                rules_dir.clone(),
+                engine_config.clone(),
+            )?);
+            start_rules_watcher(rules_dir, engine_config, engine.clone());
+            Some(engine)
         }
     };
     let ui_source = if config.ui_enabled {
@@ -85,19 +152,130 @@ pub async fn run(config: ServerConfig) -> Result<()> {
         store: Arc::new(store),
         ui_source,
         api_mode: config.api_mode,
-        api_engine: api_engine.map(Arc::new),
+        api_engine,
         trace_events,
+        metrics: Arc::default(),
+        cors,
+        request_timeout: config.request_timeout,
     };
 
     let app = build_router(state, config.ui_enabled);
-    let addr = SocketAddr::from(([127, 0, 0, 1], config.port));
-    tracing::info!("rulemorph server listening on {}", addr);
+    let listener = bind_listener(&config).await?;
+    tracing::info!(
+        "rulemorph server listening on {}",
+        listener
+            .local_addr()
+            .unwrap_or_else(|_| config.socket_addr())
+    );
+    serve_until(listener, app, shutdown_signal(), SHUTDOWN_DRAIN_TIMEOUT).await
+}
+
+/// Serve `app` until `signal` resolves, then stop accepting connections and wait up to
+/// `drain` for in-flight requests before returning.
+async fn serve_until<F>(
+    listener: tokio::net::TcpListener,
+    app: Router,
+    signal: F,
+    drain: Duration,
+) -> Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let signalled = Arc::new(Notify::new());
+    let notify = signalled.clone();
+    let server = axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            signal.await;
+            tracing::info!("shutting down");
+            notify.notify_one();
+        })
+        .into_future();
+    let drain_elapsed = async {
+        signalled.notified().await;
+        tokio::time::sleep(drain).await;
+    };
+    tokio::select! {
+        result = server => result.context("server error"),
+        _ = drain_elapsed => {
+            tracing::warn!("requests still running after {:?}; exiting", drain);
+            Ok(())
+        }
+    }
+}
+
+/// Resolves on Ctrl-C, or SIGTERM on Unix.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            tracing::error!("failed to listen for Ctrl-C: {}", err);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut stream) => {
+                stream.recv().await;
+            }
+            Err(err) => {
+                tracing::error!("failed to listen for SIGTERM: {}", err);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
 
-    let listener = tokio::net::TcpListener::bind(addr)
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+fn start_trace_pruner(store: TraceStore, retention: TraceRetention) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TRACE_PRUNE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match store.prune(&retention).await {
+                Ok(0) => {}
+                Ok(removed) => tracing::info!("pruned {} traces", removed),
+                Err(err) => tracing::warn!("trace pruning failed: {}", err),
+            }
+        }
+    });
+}
+
+fn cors_layer(origins: &[String]) -> Result<Option<CorsLayer>> {
+    if origins.is_empty() {
+        return Ok(None);
+    }
+    if origins.iter().any(|origin| origin == "*") {
+        return Ok(Some(CorsLayer::permissive()));
+    }
+    let origins = origins
+        .iter()
+        .map(|origin| {
+            HeaderValue::from_str(origin)
+                .with_context(|| format!("invalid CORS origin: {}", origin))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::list(origins))
+            .allow_methods(Any)
+            .allow_headers(Any),
+    ))
+}
+
+async fn bind_listener(config: &ServerConfig) -> Result<tokio::net::TcpListener> {
+    let addr = config.socket_addr();
+    tokio::net::TcpListener::bind(addr)
         .await
-        .context("failed to bind port")?;
-    axum::serve(listener, app).await.context("server error")?;
-    Ok(())
+        .with_context(|| format!("failed to bind {}", addr))
 }
 
 fn resolve_ui_source(config: &ServerConfig) -> Result<UiSource> {
@@ -126,3 +304,74 @@ fn resolve_ui_source(config: &ServerConfig) -> Result<UiSource> {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(bind_addr: IpAddr, port: u16) -> ServerConfig {
+        ServerConfig {
+            bind_addr,
+            port,
+            data_dir: PathBuf::from("."),
+            ui_dir: None,
+            rules_dir: None,
+            api_mode: ApiMode::UiOnly,
+            ui_enabled: true,
+            trace_channel_capacity: ServerConfig::DEFAULT_TRACE_CHANNEL_CAPACITY,
+            cors_allowed_origins: Vec::new(),
+            trace_retention: None,
+            request_timeout: None,
+            max_body_bytes: ServerConfig::DEFAULT_MAX_BODY_BYTES,
+        }
+    }
+
+    #[tokio::test]
+    async fn binds_to_configured_address() {
+        let config = test_config(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0);
+        let listener = bind_listener(&config).await.expect("bind");
+        let local = listener.local_addr().expect("local addr");
+        assert_eq!(local.ip(), IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        assert_ne!(local.port(), 0);
+    }
+
+    #[test]
+    fn internal_addr_uses_loopback_for_unspecified_bind() {
+        let config = test_config(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 8080);
+        assert_eq!(config.internal_addr().to_string(), "127.0.0.1:8080");
+
+        let config = test_config(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 8080);
+        assert_eq!(config.internal_addr().to_string(), "[::1]:8080");
+
+        let config = test_config("10.0.0.5".parse().unwrap(), 8080);
+        assert_eq!(config.internal_addr().to_string(), "10.0.0.5:8080");
+    }
+
+    #[tokio::test]
+    async fn shutdown_signal_stays_pending_without_a_signal() {
+        let result = tokio::time::timeout(Duration::from_millis(20), shutdown_signal()).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn serve_until_returns_after_signal() {
+        let listener = bind_listener(&test_config(IpAddr::V4(Ipv4Addr::LOCALHOST), 0))
+            .await
+            .expect("bind");
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(serve_until(
+            listener,
+            Router::new(),
+            async move {
+                let _ = rx.await;
+            },
+            Duration::from_secs(5),
+        ));
+        tx.send(()).expect("send signal");
+        let result = tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("server stopped")
+            .expect("join");
+        assert!(result.is_ok());
+    }
+}
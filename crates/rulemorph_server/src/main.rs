@@ -1,24 +1,144 @@
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
 
-use clap::{ArgAction, Parser, ValueEnum};
-use rulemorph_server::{ApiMode, ServerConfig, run};
+use anyhow::{Context, anyhow};
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use rulemorph_server::{
+    ApiMode, RulesDirErrors, ServerConfig, TraceRetention, run, validate_rules_dir,
+};
+use serde::Deserialize;
 
 #[derive(Parser)]
 #[command(name = "rulemorph-server")]
 #[command(version, about = "Rulemorph UI/API server")]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
-    #[arg(long, default_value_t = 8080)]
-    port: u16,
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Running without a subcommand is the same as `serve`.
+    #[command(flatten)]
+    serve: ServeArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the UI/API server.
+    Serve(ServeArgs),
+    /// Validate a rules directory once and exit non-zero if it has errors.
+    Validate(ValidateArgs),
+}
+
+#[derive(Args)]
+struct ValidateArgs {
+    #[arg(long)]
+    rules_dir: PathBuf,
+    #[arg(long, value_enum, default_value = "text")]
+    format: ValidateFormat,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ValidateFormat {
+    Text,
+    Json,
+}
+
+#[derive(Args)]
+struct ServeArgs {
+    /// TOML file with server settings; flags given on the command line override it.
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Address to listen on, e.g. 0.0.0.0 to accept outside connections [default: 127.0.0.1]
+    #[arg(long)]
+    bind: Option<IpAddr>,
+    /// [default: 8080]
+    #[arg(long)]
+    port: Option<u16>,
     #[arg(long)]
     data_dir: Option<PathBuf>,
     #[arg(long)]
     ui_dir: Option<PathBuf>,
-    #[arg(long, value_enum, default_value_t = ApiModeArg::Rules)]
-    api_mode: ApiModeArg,
+    /// [default: rules]
+    #[arg(long, value_enum)]
+    api_mode: Option<ApiModeArg>,
     #[arg(long)]
     rules_dir: Option<PathBuf>,
     #[arg(long, action = ArgAction::SetTrue, default_value_t = false)]
     no_ui: bool,
+    /// Serve the UI even when the config file sets `no_ui = true`.
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with = "no_ui")]
+    ui: bool,
+    /// Trace notifications buffered per UI stream before older ones are dropped [default: 64]
+    #[arg(long)]
+    trace_channel_capacity: Option<usize>,
+    /// Origin allowed to call the rules API from a browser; repeat for more, `*` for any.
+    #[arg(long = "cors-origin")]
+    cors_origins: Vec<String>,
+    /// Delete traces older than this many days.
+    #[arg(long)]
+    trace_max_age_days: Option<u64>,
+    /// Keep at most this many traces, deleting the oldest.
+    #[arg(long)]
+    trace_max_count: Option<usize>,
+    /// Answer 504 when a request runs longer than this; 0 disables the limit [default: 300000]
+    #[arg(long)]
+    request_timeout_ms: Option<u64>,
+    /// Answer 413 when a rules API request body is larger than this many bytes [default: 10485760]
+    #[arg(long)]
+    max_body_bytes: Option<usize>,
+}
+
+/// Settings read from `--config`. Keys match the long flag names with `_` for `-`.
+#[derive(Debug, Default, Deserialize)]
+struct FileConfig {
+    bind: Option<IpAddr>,
+    port: Option<u16>,
+    data_dir: Option<PathBuf>,
+    ui_dir: Option<PathBuf>,
+    api_mode: Option<String>,
+    rules_dir: Option<PathBuf>,
+    no_ui: Option<bool>,
+    trace_channel_capacity: Option<usize>,
+    cors_origins: Option<Vec<String>>,
+    trace_max_age_days: Option<u64>,
+    trace_max_count: Option<usize>,
+    request_timeout_ms: Option<u64>,
+    max_body_bytes: Option<usize>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, toml::Value>,
+}
+
+impl FileConfig {
+    /// Reads a config file, resolving relative paths against the file's directory.
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let mut config: FileConfig = toml::from_str(&source)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        for key in config.unknown.keys() {
+            eprintln!(
+                "warning: ignoring unknown key `{}` in {}",
+                key,
+                path.display()
+            );
+        }
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        for dir in [
+            &mut config.data_dir,
+            &mut config.ui_dir,
+            &mut config.rules_dir,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if dir.is_relative() {
+                *dir = base_dir.join(&*dir);
+            }
+        }
+        Ok(config)
+    }
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
@@ -37,16 +157,246 @@ impl From<ApiModeArg> for ApiMode {
     }
 }
 
+fn trace_retention(max_age_days: Option<u64>, max_count: Option<usize>) -> Option<TraceRetention> {
+    if max_age_days.is_none() && max_count.is_none() {
+        return None;
+    }
+    Some(TraceRetention {
+        max_age: max_age_days.map(|days| Duration::from_secs(days.saturating_mul(24 * 60 * 60))),
+        max_count,
+    })
+}
+
+fn server_config(cli: ServeArgs) -> anyhow::Result<ServerConfig> {
+    let file = match &cli.config {
+        Some(path) => FileConfig::load(path)?,
+        None => FileConfig::default(),
+    };
+    let api_mode = match (cli.api_mode, file.api_mode) {
+        (Some(mode), _) => mode,
+        (None, Some(mode)) => ApiModeArg::from_str(&mode, true)
+            .map_err(|_| anyhow!("invalid api_mode `{}` in config file", mode))?,
+        (None, None) => ApiModeArg::Rules,
+    };
+    let cors_origins = if cli.cors_origins.is_empty() {
+        file.cors_origins.unwrap_or_default()
+    } else {
+        cli.cors_origins
+    };
+    Ok(ServerConfig {
+        bind_addr: cli
+            .bind
+            .or(file.bind)
+            .unwrap_or(ServerConfig::DEFAULT_BIND_ADDR),
+        port: cli.port.or(file.port).unwrap_or(8080),
+        data_dir: cli
+            .data_dir
+            .or(file.data_dir)
+            .unwrap_or_else(ServerConfig::default_data_dir),
+        ui_dir: cli.ui_dir.or(file.ui_dir),
+        rules_dir: cli.rules_dir.or(file.rules_dir),
+        api_mode: api_mode.into(),
+        ui_enabled: if cli.ui || cli.no_ui {
+            cli.ui
+        } else {
+            !file.no_ui.unwrap_or(false)
+        },
+        trace_channel_capacity: cli
+            .trace_channel_capacity
+            .or(file.trace_channel_capacity)
+            .unwrap_or(ServerConfig::DEFAULT_TRACE_CHANNEL_CAPACITY),
+        cors_allowed_origins: cors_origins,
+        trace_retention: trace_retention(
+            cli.trace_max_age_days.or(file.trace_max_age_days),
+            cli.trace_max_count.or(file.trace_max_count),
+        ),
+        request_timeout: ServerConfig::request_timeout_from_ms(
+            cli.request_timeout_ms
+                .or(file.request_timeout_ms)
+                .unwrap_or(ServerConfig::DEFAULT_REQUEST_TIMEOUT_MS),
+        ),
+        max_body_bytes: cli
+            .max_body_bytes
+            .or(file.max_body_bytes)
+            .unwrap_or(ServerConfig::DEFAULT_MAX_BODY_BYTES),
+    })
+}
+
+/// Prints the errors of an invalid rules directory to stdout.
+fn print_rules_dir_errors(errors: &RulesDirErrors, format: ValidateFormat) -> anyhow::Result<()> {
+    match format {
+        ValidateFormat::Text => println!("{}", errors),
+        ValidateFormat::Json => println!("{}", serde_json::to_string(&errors.errors)?),
+    }
+    Ok(())
+}
+
+fn validate(args: ValidateArgs) -> anyhow::Result<ExitCode> {
+    match validate_rules_dir(&args.rules_dir) {
+        Ok(()) => {
+            if let ValidateFormat::Json = args.format {
+                println!("[]");
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Err(errors) => {
+            print_rules_dir_errors(&errors, args.format)?;
+            Ok(ExitCode::from(2))
+        }
+    }
+}
+
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() -> anyhow::Result<ExitCode> {
     let cli = Cli::parse();
-    let config = ServerConfig {
-        port: cli.port,
-        data_dir: cli.data_dir.unwrap_or_else(ServerConfig::default_data_dir),
-        ui_dir: cli.ui_dir,
-        rules_dir: cli.rules_dir,
-        api_mode: cli.api_mode.into(),
-        ui_enabled: !cli.no_ui,
+    let args = match cli.command {
+        Some(Command::Validate(args)) => return validate(args),
+        Some(Command::Serve(args)) => args,
+        None => cli.serve,
     };
-    run(config).await
+    run(server_config(args)?).await?;
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serve_args<const N: usize>(argv: [&str; N]) -> ServeArgs {
+        let cli = Cli::try_parse_from(argv).expect("parse");
+        match cli.command {
+            Some(Command::Serve(args)) => args,
+            Some(Command::Validate(_)) => panic!("expected serve arguments"),
+            None => cli.serve,
+        }
+    }
+
+    #[test]
+    fn config_file_values_are_overridden_by_flags() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let path = temp.path().join("server.toml");
+        std::fs::write(
+            &path,
+            r#"
+bind = "0.0.0.0"
+port = 9000
+data_dir = "data"
+rules_dir = "/srv/rules"
+api_mode = "ui-only"
+trace_channel_capacity = 16
+cors_origins = ["https://app.example.com"]
+trace_max_count = 500
+request_timeout_ms = 0
+max_body_bytes = 4096
+unknown_key = true
+"#,
+        )
+        .expect("write config");
+        let config_arg = path.to_str().expect("utf-8 path");
+
+        let config = server_config(serve_args(["rulemorph-server", "--config", config_arg]))
+            .expect("config");
+        assert_eq!(config.bind_addr, "0.0.0.0".parse::<IpAddr>().unwrap());
+        assert_eq!(config.port, 9000);
+        assert_eq!(config.data_dir, temp.path().join("data"));
+        assert_eq!(config.rules_dir, Some(PathBuf::from("/srv/rules")));
+        assert_eq!(config.ui_dir, None);
+        assert_eq!(config.api_mode, ApiMode::UiOnly);
+        assert!(config.ui_enabled);
+        assert_eq!(config.trace_channel_capacity, 16);
+        assert_eq!(config.cors_allowed_origins, vec!["https://app.example.com"]);
+        let retention = config.trace_retention.expect("retention");
+        assert_eq!(retention.max_count, Some(500));
+        assert_eq!(retention.max_age, None);
+        assert_eq!(config.request_timeout, None);
+        assert_eq!(config.max_body_bytes, 4096);
+
+        let config = server_config(serve_args([
+            "rulemorph-server",
+            "--config",
+            config_arg,
+            "--port",
+            "9100",
+            "--api-mode",
+            "rules",
+            "--request-timeout-ms",
+            "1000",
+            "--max-body-bytes",
+            "1024",
+        ]))
+        .expect("config");
+        assert_eq!(config.max_body_bytes, 1024);
+        assert_eq!(config.port, 9100);
+        assert_eq!(config.api_mode, ApiMode::Rules);
+        assert_eq!(config.request_timeout, Some(Duration::from_millis(1000)));
+        assert_eq!(config.trace_channel_capacity, 16);
+    }
+
+    #[test]
+    fn defaults_apply_without_a_config_file() {
+        let config = server_config(serve_args(["rulemorph-server"])).expect("config");
+        let explicit = server_config(serve_args(["rulemorph-server", "serve"])).expect("config");
+        assert_eq!(explicit.port, config.port);
+        assert_eq!(
+            serve_args(["rulemorph-server", "serve", "--port", "9100"]).port,
+            Some(9100)
+        );
+        assert_eq!(config.bind_addr, ServerConfig::DEFAULT_BIND_ADDR);
+        assert_eq!(config.port, 8080);
+        assert_eq!(config.api_mode, ApiMode::Rules);
+        assert!(config.trace_retention.is_none());
+        assert_eq!(config.max_body_bytes, ServerConfig::DEFAULT_MAX_BODY_BYTES);
+        assert_eq!(
+            config.request_timeout,
+            Some(Duration::from_millis(
+                ServerConfig::DEFAULT_REQUEST_TIMEOUT_MS
+            ))
+        );
+    }
+
+    #[test]
+    fn ui_flag_overrides_no_ui_in_the_config_file() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let path = temp.path().join("server.toml");
+        std::fs::write(&path, "no_ui = true\n").expect("write config");
+        let config_arg = path.to_str().expect("utf-8 path");
+
+        let config = server_config(serve_args(["rulemorph-server", "--config", config_arg]))
+            .expect("config");
+        assert!(!config.ui_enabled);
+        let config = server_config(serve_args([
+            "rulemorph-server",
+            "--config",
+            config_arg,
+            "--ui",
+        ]))
+        .expect("config");
+        assert!(config.ui_enabled);
+        assert!(Cli::try_parse_from(["rulemorph-server", "--ui", "--no-ui"]).is_err());
+    }
+
+    #[test]
+    fn large_trace_ages_saturate() {
+        let config = server_config(serve_args([
+            "rulemorph-server",
+            "--trace-max-age-days",
+            &u64::MAX.to_string(),
+        ]))
+        .expect("config");
+        let retention = config.trace_retention.expect("retention");
+        assert_eq!(retention.max_age, Some(Duration::from_secs(u64::MAX)));
+    }
+
+    #[test]
+    fn invalid_config_files_are_errors() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let path = temp.path().join("server.toml");
+        std::fs::write(&path, "port = \"not a number\"\n").expect("write config");
+        let args = serve_args(["rulemorph-server", "--config", path.to_str().unwrap()]);
+        assert!(server_config(args).is_err());
+
+        std::fs::write(&path, "api_mode = \"native-ish\"\n").expect("write config");
+        let args = serve_args(["rulemorph-server", "--config", path.to_str().unwrap()]);
+        assert!(server_config(args).is_err());
+    }
 }
@@ -0,0 +1,114 @@
+//! Request counters and the Prometheus text rendering behind `GET /metrics`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+use axum::{
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use rulemorph_endpoint::LatencySnapshot;
+
+use crate::server::AppState;
+
+pub(crate) const METRICS_PATH: &str = "/metrics";
+
+#[derive(Debug, Default)]
+pub struct RequestMetrics {
+    inner: Mutex<Counters>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    total: u64,
+    by_status: BTreeMap<u16, u64>,
+}
+
+impl RequestMetrics {
+    fn record(&self, status: u16) {
+        let mut counters = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+        counters.total += 1;
+        *counters.by_status.entry(status).or_default() += 1;
+    }
+
+    fn render(&self, latency: Option<&LatencySnapshot>) -> String {
+        let counters = self.inner.lock().unwrap_or_else(|err| err.into_inner());
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "# HELP rulemorph_http_requests_total Total HTTP requests handled."
+        );
+        let _ = writeln!(out, "# TYPE rulemorph_http_requests_total counter");
+        let _ = writeln!(out, "rulemorph_http_requests_total {}", counters.total);
+        let _ = writeln!(
+            out,
+            "# HELP rulemorph_http_responses_total HTTP responses by status code."
+        );
+        let _ = writeln!(out, "# TYPE rulemorph_http_responses_total counter");
+        for (status, count) in &counters.by_status {
+            let _ = writeln!(
+                out,
+                "rulemorph_http_responses_total{{status=\"{}\"}} {}",
+                status, count
+            );
+        }
+        if let Some(latency) = latency {
+            let _ = writeln!(
+                out,
+                "# HELP rulemorph_endpoint_duration_seconds Endpoint execution time."
+            );
+            let _ = writeln!(out, "# TYPE rulemorph_endpoint_duration_seconds histogram");
+            for (bound, count) in latency.cumulative() {
+                let _ = writeln!(
+                    out,
+                    "rulemorph_endpoint_duration_seconds_bucket{{le=\"{}\"}} {}",
+                    bound, count
+                );
+            }
+            let _ = writeln!(
+                out,
+                "rulemorph_endpoint_duration_seconds_bucket{{le=\"+Inf\"}} {}",
+                latency.count
+            );
+            let _ = writeln!(
+                out,
+                "rulemorph_endpoint_duration_seconds_sum {}",
+                latency.sum_us as f64 / 1_000_000.0
+            );
+            let _ = writeln!(
+                out,
+                "rulemorph_endpoint_duration_seconds_count {}",
+                latency.count
+            );
+        }
+        out
+    }
+}
+
+/// Counts every response except scrapes of `/metrics` itself.
+pub(crate) async fn track_requests(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let scrape = request.uri().path() == METRICS_PATH;
+    let response = next.run(request).await;
+    if !scrape {
+        state.metrics.record(response.status().as_u16());
+    }
+    response
+}
+
+pub(crate) async fn get_metrics(State(state): State<AppState>) -> impl IntoResponse {
+    let latency = state
+        .api_engine
+        .as_ref()
+        .map(|engine| engine.current().latency());
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(latency.as_ref()),
+    )
+}
@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, FixedOffset};
+use rulemorph_trace::TraceMeta;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+pub(crate) const DEFAULT_TRACE_LIMIT: usize = 500;
+pub(crate) const MAX_TRACE_LIMIT: usize = 5000;
+
+#[derive(Deserialize)]
+pub(crate) struct ProfileQuery {
+    #[serde(default)]
+    since: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+impl ProfileQuery {
+    pub(crate) fn since(&self) -> Result<Option<DateTime<FixedOffset>>, String> {
+        self.since
+            .as_deref()
+            .map(|since| {
+                DateTime::parse_from_rfc3339(since)
+                    .map_err(|err| format!("invalid since (expected RFC 3339): {}", err))
+            })
+            .transpose()
+    }
+
+    pub(crate) fn limit(&self) -> usize {
+        self.limit
+            .unwrap_or(DEFAULT_TRACE_LIMIT)
+            .min(MAX_TRACE_LIMIT)
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct ProfileResponse {
+    scanned: usize,
+    endpoints: Vec<ProfileEntry>,
+    rules: Vec<ProfileEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ProfileEntry {
+    key: String,
+    count: usize,
+    total_us: u64,
+    p50_us: u64,
+    p90_us: u64,
+    p99_us: u64,
+    max_us: u64,
+}
+
+/// Pick the traces to scan from a newest-first listing.
+pub(crate) fn select_traces(
+    traces: Vec<TraceMeta>,
+    since: Option<DateTime<FixedOffset>>,
+    limit: usize,
+) -> Vec<TraceMeta> {
+    traces
+        .into_iter()
+        .filter(|meta| match since {
+            Some(since) => meta
+                .timestamp
+                .as_deref()
+                .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+                .is_some_and(|timestamp| timestamp >= since),
+            None => true,
+        })
+        .take(limit)
+        .collect()
+}
+
+/// Aggregates trace durations by endpoint and by rule ref.
+#[derive(Default)]
+pub(crate) struct ProfileBuilder {
+    scanned: usize,
+    endpoints: BTreeMap<String, Vec<u64>>,
+    rules: BTreeMap<String, Vec<u64>>,
+}
+
+impl ProfileBuilder {
+    pub(crate) fn add_trace(&mut self, trace: &JsonValue) {
+        self.scanned += 1;
+        let rule = &trace["rule"];
+        let duration_us = trace["summary"]["duration_us"].as_u64();
+
+        if rule["type"].as_str() == Some("endpoint") {
+            if let (Some(name), Some(duration_us)) = (rule["name"].as_str(), duration_us) {
+                push_duration(&mut self.endpoints, name, duration_us);
+            }
+            let records = trace["records"].as_array().into_iter().flatten();
+            for node in records.flat_map(|record| record["nodes"].as_array().into_iter().flatten())
+            {
+                if let (Some(rule_ref), Some(duration_us)) = (
+                    node["meta"]["rule_ref"].as_str(),
+                    node["duration_us"].as_u64(),
+                ) {
+                    push_duration(&mut self.rules, rule_ref, duration_us);
+                }
+            }
+        } else if let (Some(key), Some(duration_us)) =
+            (rule["path"].as_str().or(rule["name"].as_str()), duration_us)
+        {
+            push_duration(&mut self.rules, key, duration_us);
+        }
+    }
+
+    pub(crate) fn finish(self) -> ProfileResponse {
+        ProfileResponse {
+            scanned: self.scanned,
+            endpoints: profile_entries(self.endpoints),
+            rules: profile_entries(self.rules),
+        }
+    }
+}
+
+fn push_duration(map: &mut BTreeMap<String, Vec<u64>>, key: &str, duration_us: u64) {
+    map.entry(key.to_string()).or_default().push(duration_us);
+}
+
+/// Build entries sorted slowest first (by p90).
+fn profile_entries(map: BTreeMap<String, Vec<u64>>) -> Vec<ProfileEntry> {
+    let mut entries: Vec<ProfileEntry> = map
+        .into_iter()
+        .map(|(key, mut durations)| {
+            durations.sort_unstable();
+            ProfileEntry {
+                key,
+                count: durations.len(),
+                total_us: durations.iter().sum(),
+                p50_us: percentile(&durations, 50),
+                p90_us: percentile(&durations, 90),
+                p99_us: percentile(&durations, 99),
+                max_us: durations.last().copied().unwrap_or(0),
+            }
+        })
+        .collect();
+    entries.sort_by(|a, b| b.p90_us.cmp(&a.p90_us).then_with(|| a.key.cmp(&b.key)));
+    entries
+}
+
+/// Nearest-rank percentile of sorted durations.
+fn percentile(sorted: &[u64], pct: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn endpoint_trace(name: &str, duration_us: u64, steps: &[(&str, u64)]) -> JsonValue {
+        let nodes: Vec<JsonValue> = steps
+            .iter()
+            .map(|(rule_ref, duration_us)| {
+                json!({ "duration_us": duration_us, "meta": { "rule_ref": rule_ref } })
+            })
+            .collect();
+        json!({
+            "rule": { "type": "endpoint", "name": name },
+            "records": [{ "nodes": nodes }],
+            "summary": { "duration_us": duration_us }
+        })
+    }
+
+    fn meta(trace_id: &str, timestamp: &str) -> TraceMeta {
+        TraceMeta {
+            trace_id: trace_id.to_string(),
+            status: "ok".to_string(),
+            timestamp: Some(timestamp.to_string()),
+            duration_us: None,
+            rule: None,
+            summary: None,
+            path: String::new(),
+        }
+    }
+
+    #[test]
+    fn aggregates_endpoints_and_rules_slowest_first() {
+        let mut builder = ProfileBuilder::default();
+        for duration in [100, 200, 300, 400] {
+            builder.add_trace(&endpoint_trace(
+                "GET /api/users",
+                duration,
+                &[("rules/fetch.yaml", duration - 50)],
+            ));
+        }
+        builder.add_trace(&endpoint_trace(
+            "GET /api/health",
+            10,
+            &[("rules/ok.yaml", 5)],
+        ));
+        builder.add_trace(&json!({
+            "rule": { "type": "normal", "path": "rules/batch.yaml" },
+            "summary": { "duration_us": 900 }
+        }));
+
+        let response = builder.finish();
+        assert_eq!(response.scanned, 6);
+        let users = &response.endpoints[0];
+        assert_eq!(users.key, "GET /api/users");
+        assert_eq!(users.count, 4);
+        assert_eq!(users.total_us, 1000);
+        assert_eq!((users.p50_us, users.p90_us, users.max_us), (200, 400, 400));
+        assert_eq!(response.endpoints[1].key, "GET /api/health");
+
+        let rule_keys: Vec<&str> = response.rules.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(
+            rule_keys,
+            vec!["rules/batch.yaml", "rules/fetch.yaml", "rules/ok.yaml"]
+        );
+    }
+
+    #[test]
+    fn select_traces_applies_since_and_limit() {
+        let traces = vec![
+            meta("c", "2024-01-03T00:00:00+00:00"),
+            meta("b", "2024-01-02T00:00:00+00:00"),
+            meta("a", "2024-01-01T00:00:00+00:00"),
+        ];
+        let since = DateTime::parse_from_rfc3339("2024-01-02T00:00:00Z").unwrap();
+        let selected = select_traces(traces.clone(), Some(since), 10);
+        let ids: Vec<&str> = selected.iter().map(|m| m.trace_id.as_str()).collect();
+        assert_eq!(ids, vec!["c", "b"]);
+
+        let selected = select_traces(traces, None, 1);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].trace_id, "c");
+    }
+
+    #[test]
+    fn limit_is_bounded() {
+        let query = ProfileQuery {
+            since: None,
+            limit: Some(1_000_000),
+        };
+        assert_eq!(query.limit(), MAX_TRACE_LIMIT);
+        let query = ProfileQuery {
+            since: Some("yesterday".to_string()),
+            limit: None,
+        };
+        assert!(query.since().is_err());
+    }
+}
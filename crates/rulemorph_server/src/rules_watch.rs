@@ -0,0 +1,159 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use notify::{RecursiveMode, Watcher};
+use rulemorph_endpoint::{EndpointEngine, EngineConfig, validate_rules_dir};
+use tracing::{info, warn};
+use walkdir::WalkDir;
+
+/// How long to wait for a burst of file events to settle before reloading.
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// The endpoint engine serving requests, replaceable while requests are running.
+///
+/// Requests hold on to the engine they started with, so a reload never affects a
+/// request that is already in flight.
+#[derive(Clone)]
+pub(crate) struct EngineHandle(Arc<RwLock<Arc<EndpointEngine>>>);
+
+impl EngineHandle {
+    pub(crate) fn new(engine: EndpointEngine) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(engine))))
+    }
+
+    pub(crate) fn current(&self) -> Arc<EndpointEngine> {
+        self.0.read().unwrap_or_else(|err| err.into_inner()).clone()
+    }
+
+    fn replace(&self, engine: EndpointEngine) {
+        *self.0.write().unwrap_or_else(|err| err.into_inner()) = Arc::new(engine);
+    }
+}
+
+/// Reload the engine whenever a rule file under `rules_dir` changes. Invalid rules are
+/// logged and the previous engine keeps serving. The new engine takes over the runtime
+/// state (circuit breakers, rate limits, latency metrics) of the one it replaces.
+pub(crate) fn start_rules_watcher(rules_dir: PathBuf, config: EngineConfig, engine: EngineHandle) {
+    tokio::spawn(async move {
+        let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut watcher = match notify::recommended_watcher(move |res| {
+            let _ = event_tx.send(res);
+        }) {
+            Ok(watcher) => Some(watcher),
+            Err(err) => {
+                warn!("rules watcher disabled: {}", err);
+                None
+            }
+        };
+
+        if let Some(watcher_ref) = watcher.as_mut()
+            && let Err(err) = watcher_ref.watch(&rules_dir, RecursiveMode::Recursive)
+        {
+            warn!("rules watcher disabled: {}", err);
+        }
+
+        let mut last_mtime = latest_mtime(&rules_dir).await;
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    let latest = latest_mtime(&rules_dir).await;
+                    if latest == last_mtime {
+                        continue;
+                    }
+                }
+                Some(_event) = event_rx.recv() => {
+                    tokio::time::sleep(RELOAD_DEBOUNCE).await;
+                    while event_rx.try_recv().is_ok() {}
+                }
+            }
+            last_mtime = latest_mtime(&rules_dir).await;
+            reload(&rules_dir, &config, &engine).await;
+        }
+    });
+}
+
+async fn reload(rules_dir: &Path, config: &EngineConfig, engine: &EngineHandle) {
+    let rules_dir = rules_dir.to_path_buf();
+    let config = config.clone();
+    let state = engine.current().state();
+    let loaded = tokio::task::spawn_blocking(move || {
+        validate_rules_dir(&rules_dir).map_err(|errs| errs.to_string())?;
+        EndpointEngine::load_with_state(rules_dir, config, state).map_err(|err| err.to_string())
+    })
+    .await;
+    match loaded {
+        Ok(Ok(loaded)) => {
+            engine.replace(loaded);
+            info!("reloaded api rules");
+        }
+        Ok(Err(err)) => warn!(
+            "api rules not reloaded; keeping the previous rules:\n{}",
+            err
+        ),
+        Err(err) => warn!("api rules reload failed: {}", err),
+    }
+}
+
+async fn latest_mtime(dir: &Path) -> Option<SystemTime> {
+    let dir = dir.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        WalkDir::new(&dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .filter(|entry| {
+                matches!(
+                    entry.path().extension().and_then(|s| s.to_str()),
+                    Some("yaml" | "yml")
+                )
+            })
+            .filter_map(|entry| entry.metadata().ok()?.modified().ok())
+            .max()
+    })
+    .await
+    .ok()
+    .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+
+    const ENDPOINT_YAML: &str = r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: GET
+    path: /api/test
+    steps: []
+    reply:
+      status: 200
+"#;
+
+    #[tokio::test]
+    async fn reload_keeps_the_engine_state() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("endpoint.yaml"), ENDPOINT_YAML).expect("write");
+        let config = EngineConfig::new("http://localhost".to_string(), dir.path().join("data"));
+        let engine = EngineHandle::new(
+            EndpointEngine::load(dir.path().to_path_buf(), config.clone()).expect("load"),
+        );
+        let request = Request::get("/api/test").body(Body::empty()).unwrap();
+        engine
+            .current()
+            .handle_request(request)
+            .await
+            .expect("response");
+        let before = engine.current();
+
+        reload(dir.path(), &config, &engine).await;
+
+        assert!(!Arc::ptr_eq(&before, &engine.current()));
+        assert_eq!(engine.current().latency().count, 1);
+    }
+}
@@ -1,12 +1,15 @@
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use axum::{
     Json, Router,
-    extract::{Path as AxumPath, State},
-    http::StatusCode,
+    body::{Body, Bytes},
+    extract::{DefaultBodyLimit, Path as AxumPath, Query, Request, State},
+    http::{StatusCode, header},
+    middleware::{self, Next},
     response::{
-        IntoResponse,
+        IntoResponse, Response,
         sse::{Event, Sse},
     },
     routing::{any, get, post},
@@ -15,13 +18,21 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::convert::Infallible;
 use std::time::Duration;
-use tokio::sync::broadcast;
-use tokio_stream::{StreamExt, wrappers::BroadcastStream};
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::StreamExt;
+use tokio_stream::wrappers::{BroadcastStream, ReceiverStream, errors::BroadcastStreamRecvError};
+use tower_http::cors::CorsLayer;
 use tower_http::services::{ServeDir, ServeFile};
 
 use crate::api_graph::{ApiGraphResponse, build_api_graph};
-use rulemorph_endpoint::{ApiMode, EndpointEngine};
-use rulemorph_trace::{ImportResult, TraceMeta, TraceStore};
+use crate::metrics::{METRICS_PATH, RequestMetrics, get_metrics, track_requests};
+use crate::profile::{ProfileBuilder, ProfileQuery, ProfileResponse, select_traces};
+use crate::rules_watch::EngineHandle;
+use crate::test_transform::{
+    MAX_BODY_BYTES, TestTransformRequest, TestTransformResponse, run_test_transform,
+};
+use rulemorph_endpoint::{ApiMode, EndpointFailure};
+use rulemorph_trace::{ImportResult, TraceFilter, TraceMeta, TraceStore};
 
 #[cfg(feature = "embedded-ui")]
 use axum::{extract::OriginalUri, http::HeaderMap};
@@ -43,30 +54,60 @@ pub struct AppState {
     pub store: Arc<TraceStore>,
     pub ui_source: Option<UiSource>,
     pub api_mode: ApiMode,
-    pub api_engine: Option<Arc<EndpointEngine>>,
+    pub api_engine: Option<EngineHandle>,
     pub trace_events: broadcast::Sender<()>,
+    pub metrics: Arc<RequestMetrics>,
+    /// Applied to the rules API only; the UI is served same-origin.
+    pub cors: Option<CorsLayer>,
+    pub request_timeout: Option<Duration>,
+}
+
+impl AppState {
+    /// The trace store is initialized before the state exists, so readiness only
+    /// depends on the endpoint engine having loaded in `Rules` mode.
+    fn is_ready(&self) -> bool {
+        match self.api_mode {
+            ApiMode::UiOnly => true,
+            ApiMode::Rules => self.api_engine.is_some(),
+        }
+    }
 }
 
 pub fn build_router(state: AppState, ui_enabled: bool) -> Router {
-    let api = match state.api_mode {
+    let mut api = match state.api_mode {
         ApiMode::UiOnly => Router::new(),
         ApiMode::Rules => Router::new().route("/api/*path", any(handle_rules_api)),
     };
+    if let Some(cors) = state.cors.clone() {
+        api = api.layer(cors);
+    }
 
-    let mut app = Router::new().merge(api);
+    let mut app = Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route(METRICS_PATH, get(get_metrics))
+        .merge(api);
 
     if ui_enabled {
         let internal = Router::new()
             .route("/internal/traces", get(list_traces))
             .route("/internal/traces/:id", get(get_trace))
             .route("/internal/stream", get(stream_traces))
+            .route("/internal/traces/query", get(query_traces))
+            .route("/internal/traces/stream", get(stream_new_traces))
+            .route("/internal/traces/export", get(export_traces))
             .route("/internal/api-graph", get(get_api_graph))
-            .route("/internal/import", post(import_bundle_path));
+            .route("/internal/import", post(import_bundle_path))
+            .route("/internal/profile", get(get_profile))
+            .route(
+                "/internal/test-transform",
+                post(test_transform).layer(DefaultBodyLimit::max(MAX_BODY_BYTES)),
+            );
 
         let ui_source = match state.ui_source.clone() {
             Some(source) => source,
             None => {
-                return app.merge(internal).with_state(state);
+                return with_global_layers(app.merge(internal), state);
             }
         };
 
@@ -82,7 +123,42 @@ pub fn build_router(state: AppState, ui_enabled: bool) -> Router {
         };
     }
 
-    app.with_state(state)
+    with_global_layers(app, state)
+}
+
+/// The request timeout sits inside the metrics layer so timed-out requests are counted as 504s.
+fn with_global_layers(app: Router<AppState>, state: AppState) -> Router {
+    let app = match state.request_timeout {
+        Some(timeout) => app.layer(middleware::from_fn_with_state(
+            timeout,
+            enforce_request_timeout,
+        )),
+        None => app,
+    };
+    app.layer(middleware::from_fn_with_state(
+        state.clone(),
+        track_requests,
+    ))
+    .with_state(state)
+}
+
+/// Answer `504 Gateway Timeout` when the handler has not produced a response within
+/// `timeout`. Only the time until the response starts counts, so SSE streams stay open.
+async fn enforce_request_timeout(
+    State(timeout): State<Duration>,
+    request: Request,
+    next: Next,
+) -> Response {
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => (
+            StatusCode::GATEWAY_TIMEOUT,
+            Json(json!({
+                "error": format!("request timed out after {}ms", timeout.as_millis())
+            })),
+        )
+            .into_response(),
+    }
 }
 
 #[cfg(feature = "embedded-ui")]
@@ -131,10 +207,27 @@ async fn handle_rules_api(
     let engine = state
         .api_engine
         .as_ref()
-        .ok_or_else(|| ApiError::internal("api engine not configured"))?;
+        .ok_or_else(|| ApiError::internal("api engine not configured"))?
+        .current();
     match engine.handle_request(request).await {
         Ok(response) => Ok(response),
         Err(err) => {
+            if let Some(failure) = err.downcast_ref::<EndpointFailure>() {
+                let mut response = (failure.status, Json(failure.to_json())).into_response();
+                if let Some(retry_after) = failure.retry_after {
+                    let seconds = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+                    response
+                        .headers_mut()
+                        .insert(axum::http::header::RETRY_AFTER, seconds.into());
+                }
+                if failure.status == StatusCode::UNAUTHORIZED {
+                    response.headers_mut().insert(
+                        axum::http::header::WWW_AUTHENTICATE,
+                        axum::http::HeaderValue::from_static("Bearer"),
+                    );
+                }
+                return Ok(response);
+            }
             let message = err.to_string();
             if message.contains("no endpoint matched") {
                 Err(ApiError::not_found(message))
@@ -145,6 +238,22 @@ async fn handle_rules_api(
     }
 }
 
+async fn healthz() -> Json<serde_json::Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+async fn readyz(state: State<AppState>) -> axum::response::Response {
+    if state.is_ready() {
+        (StatusCode::OK, Json(json!({ "status": "ready" }))).into_response()
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "status": "not_ready" })),
+        )
+            .into_response()
+    }
+}
+
 #[derive(Serialize)]
 struct TraceListResponse {
     traces: Vec<TraceMeta>,
@@ -166,6 +275,117 @@ async fn list_traces(
     Ok(Json(TraceListResponse { traces }))
 }
 
+const DEFAULT_QUERY_LIMIT: usize = 100;
+const MAX_QUERY_LIMIT: usize = 1000;
+
+#[derive(Deserialize)]
+struct TraceQueryParams {
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    rule_name: Option<String>,
+    #[serde(default)]
+    from: Option<String>,
+    #[serde(default)]
+    to: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+    #[serde(default)]
+    offset: Option<usize>,
+}
+
+impl TraceQueryParams {
+    fn into_filter(self) -> std::result::Result<TraceFilter, ApiError> {
+        let timestamp = |name: &str, value: Option<String>| {
+            value
+                .map(|value| {
+                    chrono::DateTime::parse_from_rfc3339(&value).map_err(|err| {
+                        ApiError::bad_request(format!(
+                            "invalid {} (expected RFC 3339): {}",
+                            name, err
+                        ))
+                    })
+                })
+                .transpose()
+        };
+        Ok(TraceFilter {
+            status: self.status,
+            rule_name: self.rule_name,
+            from: timestamp("from", self.from)?,
+            to: timestamp("to", self.to)?,
+            limit: Some(
+                self.limit
+                    .unwrap_or(DEFAULT_QUERY_LIMIT)
+                    .min(MAX_QUERY_LIMIT),
+            ),
+            offset: self.offset.unwrap_or(0),
+        })
+    }
+}
+
+async fn query_traces(
+    state: State<AppState>,
+    Query(params): Query<TraceQueryParams>,
+) -> std::result::Result<Json<TraceListResponse>, ApiError> {
+    let filter = params.into_filter()?;
+    let traces = state
+        .store
+        .query(&filter)
+        .await
+        .map_err(ApiError::internal)?;
+    Ok(Json(TraceListResponse { traces }))
+}
+
+#[derive(Deserialize)]
+struct TraceExportParams {
+    date: String,
+}
+
+/// Streams every trace written on `date` as NDJSON, one file at a time.
+async fn export_traces(
+    state: State<AppState>,
+    Query(params): Query<TraceExportParams>,
+) -> std::result::Result<axum::response::Response, ApiError> {
+    let date = chrono::NaiveDate::parse_from_str(&params.date, "%Y-%m-%d").map_err(|err| {
+        ApiError::bad_request(format!("invalid date (expected YYYY-MM-DD): {}", err))
+    })?;
+    let paths = state
+        .store
+        .day_trace_paths(date)
+        .await
+        .map_err(ApiError::internal)?
+        .ok_or_else(|| ApiError::not_found(format!("no traces for {}", date)))?;
+
+    let lines = tokio_stream::iter(paths)
+        .then(|path| async move {
+            let raw = match tokio::fs::read(&path).await {
+                Ok(raw) => raw,
+                Err(err) => return Some(Err(err)),
+            };
+            match serde_json::from_slice::<serde_json::Value>(&raw) {
+                Ok(trace) => {
+                    let mut line = trace.to_string();
+                    line.push('\n');
+                    Some(Ok(Bytes::from(line)))
+                }
+                Err(err) => {
+                    tracing::warn!("skipping invalid trace {}: {}", path.display(), err);
+                    None
+                }
+            }
+        })
+        .filter_map(|line| line);
+    let disposition = format!("attachment; filename=\"traces-{}.ndjson\"", date);
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(lines),
+    )
+        .into_response())
+}
+
 async fn get_trace(
     state: State<AppState>,
     AxumPath(id): AxumPath<String>,
@@ -200,15 +420,96 @@ async fn import_bundle_path(
 async fn stream_traces(
     state: State<AppState>,
 ) -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
-    let stream =
-        BroadcastStream::new(state.trace_events.subscribe()).filter_map(|message| match message {
-            Ok(_) => Some(Ok(Event::default().event("traces").data("updated"))),
-            Err(_) => None,
-        });
-    Sse::new(stream)
+    Sse::new(trace_event_stream(state.trace_events.subscribe()))
         .keep_alive(axum::response::sse::KeepAlive::new().interval(Duration::from_secs(15)))
 }
 
+/// A lagging client still gets an "updated" event, since it has missed at least one.
+fn trace_event_stream(
+    receiver: broadcast::Receiver<()>,
+) -> impl tokio_stream::Stream<Item = Result<Event, Infallible>> {
+    BroadcastStream::new(receiver).map(|message| {
+        if let Err(BroadcastStreamRecvError::Lagged(skipped)) = message {
+            tracing::warn!("trace stream lagged; skipped {} notifications", skipped);
+        }
+        Ok(Event::default().event("traces").data("updated"))
+    })
+}
+
+/// Streams each newly written trace as a `trace` event carrying its summary.
+async fn stream_new_traces(
+    state: State<AppState>,
+) -> std::result::Result<Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>>, ApiError>
+{
+    // Subscribe and snapshot before responding so nothing written afterwards is missed.
+    let receiver = state.trace_events.subscribe();
+    let seen = state
+        .store
+        .list()
+        .await
+        .map_err(ApiError::internal)?
+        .into_iter()
+        .map(|meta| meta.trace_id)
+        .collect();
+    let (sender, events) = mpsc::channel(16);
+    tokio::spawn(forward_new_traces(
+        state.store.clone(),
+        receiver,
+        seen,
+        sender,
+    ));
+    Ok(Sse::new(ReceiverStream::new(events).map(Ok))
+        .keep_alive(axum::response::sse::KeepAlive::new().interval(Duration::from_secs(15))))
+}
+
+/// Runs until the client disconnects or the trace channel closes.
+async fn forward_new_traces(
+    store: Arc<TraceStore>,
+    mut receiver: broadcast::Receiver<()>,
+    mut seen: HashSet<String>,
+    sender: mpsc::Sender<Event>,
+) {
+    loop {
+        let message = tokio::select! {
+            message = receiver.recv() => message,
+            _ = sender.closed() => return,
+        };
+        match message {
+            Ok(()) => {}
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                let lag = Event::default().event("lag").data(skipped.to_string());
+                if sender.send(lag).await.is_err() {
+                    return;
+                }
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+        let traces = match store.list().await {
+            Ok(traces) => traces,
+            Err(err) => {
+                tracing::warn!("failed to list traces for stream: {}", err);
+                continue;
+            }
+        };
+        // `list` is newest-first; send the new ones oldest-first.
+        for meta in traces.into_iter().rev() {
+            if !seen.insert(meta.trace_id.clone()) {
+                continue;
+            }
+            let event = match Event::default().event("trace").json_data(&meta) {
+                Ok(event) => event,
+                Err(err) => {
+                    tracing::warn!("failed to encode trace event: {}", err);
+                    continue;
+                }
+            };
+            if sender.send(event).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
 async fn get_api_graph(
     state: State<AppState>,
 ) -> std::result::Result<Json<ApiGraphResponse>, ApiError> {
@@ -217,6 +518,37 @@ async fn get_api_graph(
     Ok(Json(graph))
 }
 
+async fn get_profile(
+    state: State<AppState>,
+    Query(query): Query<ProfileQuery>,
+) -> std::result::Result<Json<ProfileResponse>, ApiError> {
+    let state = state.0;
+    let since = query.since().map_err(ApiError::bad_request)?;
+    let traces = state.store.list().await.map_err(ApiError::internal)?;
+    let mut builder = ProfileBuilder::default();
+    for meta in select_traces(traces, since, query.limit()) {
+        if let Some(trace) = state
+            .store
+            .get(&meta.trace_id)
+            .await
+            .map_err(ApiError::internal)?
+        {
+            builder.add_trace(&trace);
+        }
+    }
+    Ok(Json(builder.finish()))
+}
+
+/// Served under `/internal/` rather than `/api/`, which belongs to user endpoints.
+async fn test_transform(
+    Json(payload): Json<TestTransformRequest>,
+) -> std::result::Result<Json<TestTransformResponse>, ApiError> {
+    let response = tokio::task::spawn_blocking(move || run_test_transform(&payload))
+        .await
+        .map_err(ApiError::internal)?;
+    Ok(Json(response))
+}
+
 struct ApiError {
     status: StatusCode,
     message: String,
@@ -230,6 +562,13 @@ impl ApiError {
         }
     }
 
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message: message.into(),
+        }
+    }
+
     fn not_found(message: impl Into<String>) -> Self {
         Self {
             status: StatusCode::NOT_FOUND,
@@ -244,3 +583,547 @@ impl IntoResponse for ApiError {
         (self.status, body).into_response()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+    use http_body_util::BodyExt;
+    use rulemorph_endpoint::EndpointEngine;
+    use tower::ServiceExt;
+
+    async fn test_state(dir: &std::path::Path, api_mode: ApiMode) -> AppState {
+        let store = TraceStore::new(dir.join("data"))
+            .await
+            .expect("trace store");
+        AppState {
+            store: Arc::new(store),
+            ui_source: Some(UiSource::Filesystem(dir.to_path_buf())),
+            api_mode,
+            api_engine: None,
+            trace_events: broadcast::channel(1).0,
+            metrics: Arc::default(),
+            cors: None,
+            request_timeout: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn trace_stream_survives_lag() {
+        let (sender, receiver) = broadcast::channel(2);
+        let mut stream = Box::pin(trace_event_stream(receiver));
+        for _ in 0..5 {
+            sender.send(()).unwrap();
+        }
+
+        let mut events = 0;
+        while let Ok(Some(event)) =
+            tokio::time::timeout(Duration::from_millis(50), stream.next()).await
+        {
+            assert!(event.is_ok());
+            events += 1;
+        }
+        // The lag notice plus the two notifications still buffered.
+        assert_eq!(events, 3);
+
+        sender.send(()).unwrap();
+        assert!(stream.next().await.is_some());
+    }
+
+    async fn get_body(app: Router, uri: &str) -> (StatusCode, Vec<u8>) {
+        let response = app
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .expect("response");
+        let status = response.status();
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .expect("body")
+            .to_bytes();
+        (status, body.to_vec())
+    }
+
+    async fn get_json(app: Router, uri: &str) -> (StatusCode, serde_json::Value) {
+        let (status, body) = get_body(app, uri).await;
+        (status, serde_json::from_slice(&body).expect("json body"))
+    }
+
+    #[tokio::test]
+    async fn healthz_and_readyz_are_not_swallowed_by_ui_fallback() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("index.html"), "<html></html>").unwrap();
+        let app = build_router(test_state(dir.path(), ApiMode::UiOnly).await, true);
+
+        let (status, body) = get_json(app.clone(), "/healthz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({ "status": "ok" }));
+
+        let (status, body) = get_json(app, "/readyz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({ "status": "ready" }));
+    }
+
+    #[tokio::test]
+    async fn readyz_reports_unavailable_without_endpoint_engine() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let app = build_router(test_state(dir.path(), ApiMode::Rules).await, false);
+
+        let (status, body) = get_json(app.clone(), "/healthz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({ "status": "ok" }));
+
+        let (status, body) = get_json(app, "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body, json!({ "status": "not_ready" }));
+    }
+
+    #[tokio::test]
+    async fn slow_requests_time_out_with_504() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut state = test_state(dir.path(), ApiMode::UiOnly).await;
+        state.request_timeout = Some(Duration::from_millis(50));
+        let routes = Router::new()
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    "done"
+                }),
+            )
+            .route("/fast", get(|| async { "done" }));
+        let app = with_global_layers(routes, state);
+
+        let (status, body) = get_json(app.clone(), "/slow").await;
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(body, json!({ "error": "request timed out after 50ms" }));
+
+        let (status, body) = get_body(app, "/fast").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, b"done");
+    }
+
+    async fn rules_app(dir: &std::path::Path, endpoint_yaml: &str) -> Router {
+        rules_app_with_cors(dir, endpoint_yaml, &[]).await
+    }
+
+    async fn rules_app_with_cors(
+        dir: &std::path::Path,
+        endpoint_yaml: &str,
+        cors_origins: &[&str],
+    ) -> Router {
+        build_router(rules_state(dir, endpoint_yaml, cors_origins).await, false)
+    }
+
+    async fn rules_state(
+        dir: &std::path::Path,
+        endpoint_yaml: &str,
+        cors_origins: &[&str],
+    ) -> AppState {
+        let rules_dir = dir.join("api_rules");
+        std::fs::create_dir_all(&rules_dir).unwrap();
+        std::fs::write(rules_dir.join("endpoint.yaml"), endpoint_yaml).unwrap();
+        let engine = EndpointEngine::load(
+            rules_dir,
+            rulemorph_endpoint::EngineConfig::new("http://localhost".to_string(), dir.join("data")),
+        )
+        .expect("load engine");
+        let mut state = test_state(dir, ApiMode::Rules).await;
+        state.api_engine = Some(EngineHandle::new(engine));
+        let origins: Vec<String> = cors_origins
+            .iter()
+            .map(|origin| origin.to_string())
+            .collect();
+        state.cors = crate::cors_layer(&origins).expect("cors layer");
+        state
+    }
+
+    #[tokio::test]
+    async fn ui_routes_leave_the_rules_api_namespace_to_endpoints() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut endpoints = String::from("version: 2\ntype: endpoint\nendpoints:\n");
+        for path in [
+            "/api/traces",
+            "/api/traces/stream",
+            "/api/traces/export",
+            "/api/profile",
+        ] {
+            endpoints.push_str(&format!(
+                "  - method: GET\n    path: {path}\n    steps: []\n    reply:\n      status: 200\n      body: {{ endpoint: \"{path}\" }}\n"
+            ));
+        }
+        endpoints.push_str(
+            "  - method: POST\n    path: /api/test-transform\n    steps: []\n    reply:\n      status: 201\n",
+        );
+        let app = build_router(rules_state(dir.path(), &endpoints, &[]).await, true);
+
+        for path in [
+            "/api/traces",
+            "/api/traces/stream",
+            "/api/traces/export",
+            "/api/profile",
+        ] {
+            let (status, body) = get_json(app.clone(), path).await;
+            assert_eq!(status, StatusCode::OK, "{path}");
+            assert_eq!(body, json!({ "endpoint": path }));
+        }
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/api/test-transform")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let (status, _) = get_json(app, "/internal/traces/query").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn metrics_count_requests_but_not_scrapes() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let app = rules_app(
+            dir.path(),
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: GET
+    path: /api/test
+    steps: []
+    reply:
+      status: 200
+"#,
+        )
+        .await;
+
+        let (status, _) = get_body(app.clone(), "/api/test").await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = get_body(app.clone(), "/api/missing").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        get_body(app.clone(), "/metrics").await;
+
+        let (status, body) = get_body(app, "/metrics").await;
+        assert_eq!(status, StatusCode::OK);
+        let text = String::from_utf8(body).unwrap();
+        assert!(text.contains("rulemorph_http_requests_total 2\n"), "{text}");
+        assert!(text.contains("rulemorph_http_responses_total{status=\"200\"} 1\n"));
+        assert!(text.contains("rulemorph_http_responses_total{status=\"404\"} 1\n"));
+        assert!(text.contains("rulemorph_endpoint_duration_seconds_count 1\n"));
+        assert!(text.contains("rulemorph_endpoint_duration_seconds_bucket{le=\"+Inf\"} 1\n"));
+    }
+
+    #[tokio::test]
+    async fn rate_limited_response_sets_retry_after() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let app = rules_app(
+            dir.path(),
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: GET
+    path: /api/limited
+    rate_limit: { per_second: 1, burst: 1 }
+    steps: []
+    reply:
+      status: 200
+"#,
+        )
+        .await;
+
+        let (status, _) = get_body(app.clone(), "/api/limited").await;
+        assert_eq!(status, StatusCode::OK);
+        let response = app
+            .oneshot(Request::get("/api/limited").body(Body::empty()).unwrap())
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()["retry-after"], "1");
+    }
+
+    #[tokio::test]
+    async fn unauthorized_response_sets_www_authenticate() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let app = rules_app(
+            dir.path(),
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: GET
+    path: /api/secure
+    auth: { type: bearer, token_env: RULEMORPH_SERVER_TEST_UNSET_TOKEN }
+    steps: []
+    reply:
+      status: 200
+"#,
+        )
+        .await;
+
+        let response = app
+            .oneshot(Request::get("/api/secure").body(Body::empty()).unwrap())
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()["www-authenticate"], "Bearer");
+    }
+
+    async fn preflight(app: Router, uri: &str, origin: &str) -> axum::response::Response {
+        app.oneshot(
+            Request::options(uri)
+                .header("origin", origin)
+                .header("access-control-request-method", "POST")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .expect("response")
+    }
+
+    #[tokio::test]
+    async fn cors_preflight_allows_configured_origins_on_api_routes() {
+        let endpoint_yaml = r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: POST
+    path: /api/test
+    steps: []
+    reply:
+      status: 200
+"#;
+        let dir = tempfile::tempdir().expect("tempdir");
+        let app = rules_app_with_cors(dir.path(), endpoint_yaml, &["https://app.example"]).await;
+
+        let response = preflight(app.clone(), "/api/test", "https://app.example").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["access-control-allow-origin"],
+            "https://app.example"
+        );
+
+        let response = preflight(app.clone(), "/api/test", "https://other.example").await;
+        assert!(
+            response
+                .headers()
+                .get("access-control-allow-origin")
+                .is_none()
+        );
+
+        let response = preflight(app, "/healthz", "https://app.example").await;
+        assert!(
+            response
+                .headers()
+                .get("access-control-allow-origin")
+                .is_none()
+        );
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let app = rules_app_with_cors(dir.path(), endpoint_yaml, &["*"]).await;
+        let response = preflight(app, "/api/test", "https://any.example").await;
+        assert_eq!(response.headers()["access-control-allow-origin"], "*");
+    }
+
+    #[tokio::test]
+    async fn trace_query_route_filters_and_paginates() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("index.html"), "<html></html>").unwrap();
+        let state = test_state(dir.path(), ApiMode::UiOnly).await;
+        let traces_dir = dir.path().join("data").join("traces");
+        for (id, status, timestamp) in [
+            ("t1", "ok", "2026-01-01T00:00:00Z"),
+            ("t2", "error", "2026-01-02T00:00:00Z"),
+            ("t3", "error", "2026-01-03T00:00:00Z"),
+        ] {
+            let trace = json!({ "trace_id": id, "status": status, "timestamp": timestamp });
+            std::fs::write(traces_dir.join(format!("{id}.json")), trace.to_string()).unwrap();
+        }
+        let app = build_router(state, true);
+
+        let trace_ids = |body: serde_json::Value| -> Vec<String> {
+            body["traces"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|trace| trace["trace_id"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let (status, body) = get_json(app.clone(), "/internal/traces/query?status=error").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(trace_ids(body), ["t3", "t2"]);
+
+        let (_, body) = get_json(app.clone(), "/internal/traces/query?limit=1&offset=1").await;
+        assert_eq!(trace_ids(body), ["t2"]);
+
+        let (_, body) = get_json(
+            app.clone(),
+            "/internal/traces/query?from=2026-01-02T00:00:00Z&to=2026-01-03T00:00:00Z",
+        )
+        .await;
+        assert_eq!(trace_ids(body), ["t2"]);
+
+        let (status, body) = get_json(app, "/internal/traces/query?from=yesterday").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().starts_with("invalid from"));
+    }
+
+    #[tokio::test]
+    async fn trace_stream_emits_new_trace_summaries() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("index.html"), "<html></html>").unwrap();
+        let state = test_state(dir.path(), ApiMode::UiOnly).await;
+        let trace_events = state.trace_events.clone();
+        let traces_dir = dir.path().join("data").join("traces");
+        std::fs::write(
+            traces_dir.join("old.json"),
+            json!({ "trace_id": "old", "status": "ok" }).to_string(),
+        )
+        .unwrap();
+        let app = build_router(state, true);
+
+        let response = app
+            .oneshot(
+                Request::get("/internal/traces/stream")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        let mut body = response.into_body();
+
+        std::fs::write(
+            traces_dir.join("new.json"),
+            json!({ "trace_id": "new", "status": "error" }).to_string(),
+        )
+        .unwrap();
+        trace_events.send(()).unwrap();
+
+        let frame = tokio::time::timeout(Duration::from_secs(5), body.frame())
+            .await
+            .expect("event before timeout")
+            .expect("frame")
+            .expect("frame ok");
+        let text = String::from_utf8(frame.into_data().unwrap().to_vec()).unwrap();
+        assert!(text.starts_with("event: trace\n"), "{text}");
+        assert!(text.contains("\"trace_id\":\"new\""), "{text}");
+        assert!(text.contains("\"status\":\"error\""), "{text}");
+        assert!(!text.contains("\"old\""), "{text}");
+    }
+
+    #[tokio::test]
+    async fn trace_export_streams_a_day_as_ndjson() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let day_dir = dir.path().join("data/traces/2026/03/14");
+        std::fs::create_dir_all(&day_dir).unwrap();
+        for id in ["a", "b"] {
+            let trace = json!({ "trace_id": id, "status": "ok", "records": [] });
+            std::fs::write(
+                day_dir.join(format!("{}.json", id)),
+                serde_json::to_string_pretty(&trace).unwrap(),
+            )
+            .unwrap();
+        }
+        let app = build_router(test_state(dir.path(), ApiMode::UiOnly).await, true);
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::get("/internal/traces/export?date=2026-03-14")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"traces-2026-03-14.ndjson\""
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let lines: Vec<serde_json::Value> = std::str::from_utf8(&body)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("json line"))
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["trace_id"], "a");
+        assert_eq!(lines[1]["trace_id"], "b");
+
+        let (status, _) = get_json(app.clone(), "/internal/traces/export?date=2026-03-15").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = get_json(app, "/internal/traces/export?date=03/14/2026").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    fn endpoint_with_status(status: u16) -> String {
+        format!(
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: GET
+    path: /api/test
+    steps: []
+    reply:
+      status: {status}
+"#
+        )
+    }
+
+    async fn wait_for_status(app: &Router, expected: StatusCode) -> bool {
+        for _ in 0..100 {
+            let (status, _) = get_body(app.clone(), "/api/test").await;
+            if status == expected {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        false
+    }
+
+    #[tokio::test]
+    async fn rules_reload_when_the_rules_dir_changes() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let rules_dir = dir.path().join("api_rules");
+        std::fs::create_dir_all(&rules_dir).unwrap();
+        let endpoint_path = rules_dir.join("endpoint.yaml");
+        std::fs::write(&endpoint_path, endpoint_with_status(200)).unwrap();
+        let config = rulemorph_endpoint::EngineConfig::new(
+            "http://localhost".to_string(),
+            dir.path().join("data"),
+        );
+        let engine = EngineHandle::new(
+            EndpointEngine::load(rules_dir.clone(), config.clone()).expect("load engine"),
+        );
+        crate::rules_watch::start_rules_watcher(rules_dir, config, engine.clone());
+        let mut state = test_state(dir.path(), ApiMode::Rules).await;
+        state.api_engine = Some(engine);
+        let app = build_router(state, false);
+
+        let (status, _) = get_body(app.clone(), "/api/test").await;
+        assert_eq!(status, StatusCode::OK);
+
+        std::fs::write(&endpoint_path, endpoint_with_status(201)).unwrap();
+        assert!(wait_for_status(&app, StatusCode::CREATED).await);
+
+        // An invalid edit is rejected and the last good rules keep serving.
+        std::fs::write(
+            &endpoint_path,
+            "version: 3\ntype: endpoint\nendpoints: []\n",
+        )
+        .unwrap();
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        let (status, _) = get_body(app.clone(), "/api/test").await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        std::fs::write(&endpoint_path, endpoint_with_status(202)).unwrap();
+        assert!(wait_for_status(&app, StatusCode::ACCEPTED).await);
+    }
+}
@@ -0,0 +1,278 @@
+use rulemorph::{
+    RuleError, RuleFile, TransformError, TransformOptions, TransformWarning, parse_rule_file,
+    transform_preview, transform_stream, validate_rule_file_with_source,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+
+pub(crate) const DEFAULT_MAX_ROWS: usize = 100;
+pub(crate) const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+#[derive(Deserialize)]
+pub(crate) struct TestTransformRequest {
+    rules: String,
+    input: String,
+    #[serde(default)]
+    context: Option<JsonValue>,
+    #[serde(default)]
+    max_rows: Option<usize>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct TestTransformResponse {
+    records: Vec<JsonValue>,
+    truncated: bool,
+    /// Output records of the whole input. `None` when a record past `max_rows` failed,
+    /// which leaves the count unknown.
+    total: Option<usize>,
+    warnings: Vec<JsonValue>,
+    errors: Vec<JsonValue>,
+}
+
+/// Transform sample input, returning the first `max_rows` records and the total count.
+///
+/// Records past `max_rows` are counted but not kept. Rules with `finalize` are
+/// transformed in full first, since finalize sees every record.
+pub(crate) fn run_test_transform(request: &TestTransformRequest) -> TestTransformResponse {
+    let max_rows = request.max_rows.unwrap_or(DEFAULT_MAX_ROWS);
+    let mut response = TestTransformResponse::default();
+
+    let rule = match parse_rule_file(&request.rules) {
+        Ok(rule) => rule,
+        Err(err) => {
+            response.errors.push(json!({
+                "code": "ParseError",
+                "message": err.to_string(),
+            }));
+            return response;
+        }
+    };
+    if let Err(errors) = validate_rule_file_with_source(&rule, &request.rules) {
+        response.errors = errors.iter().map(rule_error_json).collect();
+        return response;
+    }
+
+    let result = if rule.finalize.is_some() {
+        transform_finalized(&rule, request, max_rows, &mut response)
+    } else {
+        transform_streamed(&rule, request, max_rows, &mut response)
+    };
+    if let Err(err) = result {
+        response.records.clear();
+        response.warnings.clear();
+        response.truncated = false;
+        response.total = None;
+        response.errors.push(transform_error_json(
+            &err.with_source_location(&request.rules),
+        ));
+    }
+    response
+}
+
+fn transform_finalized(
+    rule: &RuleFile,
+    request: &TestTransformRequest,
+    max_rows: usize,
+    response: &mut TestTransformResponse,
+) -> Result<(), TransformError> {
+    let preview = transform_preview(
+        rule,
+        &request.input,
+        request.context.as_ref(),
+        &TransformOptions::default(),
+    )?;
+    let mut records = match preview.output {
+        JsonValue::Array(records) => records,
+        other => vec![other],
+    };
+    response.total = Some(records.len());
+    response.truncated = records.len() > max_rows;
+    records.truncate(max_rows);
+    response.records = records;
+    response.warnings = preview.warnings.iter().map(warning_json).collect();
+    Ok(())
+}
+
+fn transform_streamed(
+    rule: &RuleFile,
+    request: &TestTransformRequest,
+    max_rows: usize,
+    response: &mut TestTransformResponse,
+) -> Result<(), TransformError> {
+    let mut total = 0;
+    for item in transform_stream(rule, &request.input, request.context.as_ref())? {
+        if total >= max_rows {
+            match item {
+                Ok(item) => total += usize::from(item.output.is_some()),
+                Err(_) => {
+                    response.truncated = true;
+                    return Ok(());
+                }
+            }
+            continue;
+        }
+        let item = item?;
+        response
+            .warnings
+            .extend(item.warnings.iter().map(warning_json));
+        if let Some(output) = item.output {
+            response.records.push(output);
+            total += 1;
+        }
+    }
+    response.truncated = total > max_rows;
+    response.total = Some(total);
+    Ok(())
+}
+
+fn rule_error_json(err: &RuleError) -> JsonValue {
+    let mut value = json!({
+        "code": err.code.as_str(),
+        "message": err.message,
+    });
+    if let Some(path) = &err.path {
+        value["path"] = json!(path);
+    }
+    if let Some(location) = &err.location {
+        value["line"] = json!(location.line);
+        value["column"] = json!(location.column);
+    }
+    value
+}
+
+fn transform_error_json(err: &TransformError) -> JsonValue {
+    let mut value = json!({
+        "code": format!("{:?}", err.kind),
+        "message": err.message,
+        "path": err.path,
+    });
+    if let Some(location) = &err.location {
+        value["line"] = json!(location.line);
+        value["column"] = json!(location.column);
+    }
+    value
+}
+
+fn warning_json(warning: &TransformWarning) -> JsonValue {
+    json!({
+        "code": format!("{:?}", warning.kind),
+        "message": warning.message,
+        "path": warning.path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "id"
+    source: "id"
+"#;
+
+    fn request(rules: &str, input: &str, max_rows: Option<usize>) -> TestTransformRequest {
+        TestTransformRequest {
+            rules: rules.to_string(),
+            input: input.to_string(),
+            context: None,
+            max_rows,
+        }
+    }
+
+    #[test]
+    fn stops_at_max_rows() {
+        let input = r#"[{"id": 1}, {"id": 2}, {"id": 3}]"#;
+        let response = run_test_transform(&request(RULES, input, Some(2)));
+        assert_eq!(response.records, vec![json!({"id": 1}), json!({"id": 2})]);
+        assert!(response.truncated);
+        assert_eq!(response.total, Some(3));
+        assert!(response.errors.is_empty());
+    }
+
+    #[test]
+    fn failures_past_max_rows_leave_the_total_unknown() {
+        let rules = r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "ratio"
+    expr:
+      - "@input.total"
+      - divide: ["@input.by"]
+"#;
+        let input = r#"[{"total": 4, "by": 2}, {"total": 1, "by": 0}]"#;
+        let response = run_test_transform(&request(rules, input, Some(1)));
+        assert_eq!(response.records, vec![json!({"ratio": 2.0})]);
+        assert!(response.truncated);
+        assert_eq!(response.total, None);
+        assert!(response.errors.is_empty());
+    }
+
+    #[test]
+    fn finalize_rules_are_truncated_after_transform() {
+        let rules = r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "id"
+    source: "id"
+finalize:
+  sort:
+    by: "id"
+    order: desc
+"#;
+        let input = r#"[{"id": 1}, {"id": 2}, {"id": 3}]"#;
+        let response = run_test_transform(&request(rules, input, Some(1)));
+        assert_eq!(response.records, vec![json!({"id": 3})]);
+        assert!(response.truncated);
+        assert_eq!(response.total, Some(3));
+    }
+
+    #[test]
+    fn reports_validation_errors() {
+        let rules = r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "id"
+"#;
+        let response = run_test_transform(&request(rules, "[]", None));
+        assert!(response.records.is_empty());
+        assert_eq!(response.errors[0]["code"], "MissingMappingValue");
+    }
+
+    #[test]
+    fn reports_transform_errors() {
+        let response = run_test_transform(&request(RULES, "not json", None));
+        assert_eq!(response.errors[0]["code"], "InvalidInput");
+    }
+
+    #[test]
+    fn transform_errors_include_rule_lines() {
+        let rules = r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "ratio"
+    expr:
+      - "@input.total"
+      - divide: [0]
+"#;
+        let response = run_test_transform(&request(rules, r#"[{"total": 5}]"#, None));
+        assert_eq!(response.errors[0]["code"], "ExprError");
+        assert_eq!(response.errors[0]["line"], 10);
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use serde_json::Value as JsonValue;
+
+fn write_file(root: &Path, rel: &str, content: &str) {
+    let path = root.join(rel);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create parent dir");
+    }
+    fs::write(&path, content).expect("write file");
+}
+
+fn validate(rules_dir: &Path, format: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rulemorph-server"))
+        .arg("validate")
+        .arg("--rules-dir")
+        .arg(rules_dir)
+        .args(["--format", format])
+        .output()
+        .expect("run rulemorph-server")
+}
+
+fn write_endpoint(rules_dir: &Path, rule: &str) {
+    write_file(
+        rules_dir,
+        "endpoint.yaml",
+        &format!(
+            r#"
+version: 2
+type: endpoint
+endpoints:
+  - method: GET
+    path: /api/test
+    steps:
+      - rule: {}
+    reply:
+      status: 200
+"#,
+            rule
+        ),
+    );
+}
+
+#[test]
+fn validate_reports_broken_rules_dir_as_json() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    write_endpoint(temp.path(), "missing.yaml");
+
+    let output = validate(temp.path(), "json");
+    assert_eq!(output.status.code(), Some(2));
+    let errors: JsonValue = serde_json::from_slice(&output.stdout).expect("json output");
+    let errors = errors.as_array().expect("array of errors");
+    assert!(!errors.is_empty());
+    for error in errors {
+        let error = error.as_object().expect("error object");
+        let mut keys: Vec<&str> = error.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            vec!["code", "column", "file", "line", "message", "path"]
+        );
+        assert!(error["code"].is_string());
+        assert!(error["file"].is_string());
+        assert!(error["message"].is_string());
+    }
+    assert!(errors.iter().any(|error| error["code"] == "ReadFailed"));
+}
+
+#[test]
+fn validate_prints_text_and_succeeds_on_valid_rules_dir() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    write_endpoint(temp.path(), "missing.yaml");
+    let output = validate(temp.path(), "text");
+    assert_eq!(output.status.code(), Some(2));
+    let stdout = String::from_utf8(output.stdout).expect("utf-8 output");
+    assert!(stdout.starts_with("E ReadFailed"), "{}", stdout);
+
+    write_endpoint(temp.path(), "rules/ok.yaml");
+    write_file(
+        temp.path(),
+        "rules/ok.yaml",
+        r#"
+version: 2
+input:
+  format: json
+  json: {}
+mappings:
+  - target: "output.ok"
+    value: true
+"#,
+    );
+    let output = validate(temp.path(), "json");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "[]");
+}
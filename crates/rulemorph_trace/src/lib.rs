@@ -1,5 +1,7 @@
 mod trace_store;
 mod trace_watch;
 
-pub use trace_store::{ImportResult, RuleMeta, TraceMeta, TraceStore, TraceSummary};
+pub use trace_store::{
+    ImportResult, RuleMeta, TraceFilter, TraceMeta, TraceRetention, TraceStore, TraceSummary,
+};
 pub use trace_watch::start_trace_watcher;
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tokio::sync::RwLock;
@@ -41,6 +43,58 @@ pub struct ImportResult {
     pub rules_imported: usize,
 }
 
+/// Criteria for [`TraceStore::query`]; unset fields match every trace.
+#[derive(Debug, Clone, Default)]
+pub struct TraceFilter {
+    pub status: Option<String>,
+    /// Exact match on `rule.name`.
+    pub rule_name: Option<String>,
+    /// Inclusive lower bound on the trace timestamp.
+    pub from: Option<DateTime<FixedOffset>>,
+    /// Exclusive upper bound on the trace timestamp.
+    pub to: Option<DateTime<FixedOffset>>,
+    pub limit: Option<usize>,
+    pub offset: usize,
+}
+
+impl TraceFilter {
+    fn matches(&self, meta: &TraceMeta) -> bool {
+        if self
+            .status
+            .as_ref()
+            .is_some_and(|status| *status != meta.status)
+        {
+            return false;
+        }
+        if let Some(rule_name) = &self.rule_name {
+            let name = meta.rule.as_ref().and_then(|rule| rule.name.as_ref());
+            if name != Some(rule_name) {
+                return false;
+            }
+        }
+        if self.from.is_none() && self.to.is_none() {
+            return true;
+        }
+        let Some(timestamp) = meta
+            .timestamp
+            .as_deref()
+            .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+        else {
+            return false;
+        };
+        self.from.is_none_or(|from| timestamp >= from) && self.to.is_none_or(|to| timestamp < to)
+    }
+}
+
+/// Limits applied by [`TraceStore::prune`]; traces breaking either limit are deleted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TraceRetention {
+    /// Delete traces older than this.
+    pub max_age: Option<Duration>,
+    /// Keep only the newest this many traces.
+    pub max_count: Option<usize>,
+}
+
 #[derive(Debug, Clone)]
 pub struct TraceStore {
     data_dir: PathBuf,
@@ -68,6 +122,94 @@ impl TraceStore {
         Ok(items)
     }
 
+    /// Newest-first traces matching `filter`, after skipping `filter.offset` matches.
+    pub async fn query(&self, filter: &TraceFilter) -> Result<Vec<TraceMeta>> {
+        let matches = self
+            .list()
+            .await?
+            .into_iter()
+            .filter(|meta| filter.matches(meta))
+            .skip(filter.offset);
+        Ok(match filter.limit {
+            Some(limit) => matches.take(limit).collect(),
+            None => matches.collect(),
+        })
+    }
+
+    /// Delete traces outside `retention` and any day/month/year directories left empty.
+    /// Returns the number of trace files removed. A `max_age` reaching back past the
+    /// earliest representable time deletes nothing by age.
+    pub async fn prune(&self, retention: &TraceRetention) -> Result<usize> {
+        let cutoff = retention.max_age.and_then(|max_age| {
+            Utc::now().checked_sub_signed(chrono::Duration::from_std(max_age).ok()?)
+        });
+        let expired: Vec<PathBuf> = self
+            .list()
+            .await?
+            .into_iter()
+            .enumerate()
+            .filter(|(index, meta)| {
+                retention.max_count.is_some_and(|max| *index >= max)
+                    || cutoff.is_some_and(|cutoff| trace_time(meta) < cutoff)
+            })
+            .map(|(_, meta)| PathBuf::from(meta.path))
+            .collect();
+
+        let dir = traces_dir(&self.data_dir);
+        let removed = tokio::task::spawn_blocking(move || -> Result<usize> {
+            let mut removed = 0;
+            for path in &expired {
+                match std::fs::remove_file(path) {
+                    Ok(()) => removed += 1,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(err) => {
+                        return Err(err)
+                            .with_context(|| format!("failed to remove {}", path.display()));
+                    }
+                }
+            }
+            for entry in WalkDir::new(&dir)
+                .min_depth(1)
+                .contents_first(true)
+                .into_iter()
+                .filter_map(|e| e.ok())
+            {
+                if entry.file_type().is_dir() {
+                    // Fails while the directory still has files, which is what we want.
+                    let _ = std::fs::remove_dir(entry.path());
+                }
+            }
+            Ok(removed)
+        })
+        .await??;
+
+        self.refresh_index().await?;
+        Ok(removed)
+    }
+
+    /// Trace files written on `date`, from its `traces/YYYY/MM/DD` directory, sorted by
+    /// file name. Returns `None` when there is no directory for that day.
+    pub async fn day_trace_paths(&self, date: NaiveDate) -> Result<Option<Vec<PathBuf>>> {
+        let day_dir = traces_dir(&self.data_dir).join(date.format("%Y/%m/%d").to_string());
+        tokio::task::spawn_blocking(move || -> Result<Option<Vec<PathBuf>>> {
+            if !day_dir.is_dir() {
+                return Ok(None);
+            }
+            let paths = WalkDir::new(&day_dir)
+                .min_depth(1)
+                .max_depth(1)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|entry| entry.file_type().is_file())
+                .filter(|entry| entry.path().extension().and_then(|s| s.to_str()) == Some("json"))
+                .map(|entry| entry.into_path())
+                .collect();
+            Ok(Some(paths))
+        })
+        .await?
+    }
+
     pub async fn get(&self, trace_id: &str) -> Result<Option<Value>> {
         if !self.index.read().await.contains_key(trace_id) {
             self.refresh_index().await?;
@@ -179,6 +321,21 @@ impl TraceStore {
     // Sample seed disabled (data_dir-only workflow).
 }
 
+/// When the trace was written: its `timestamp`, else the file's modification time.
+fn trace_time(meta: &TraceMeta) -> DateTime<Utc> {
+    meta.timestamp
+        .as_deref()
+        .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .or_else(|| {
+            std::fs::metadata(&meta.path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .map(DateTime::<Utc>::from)
+        })
+        .unwrap_or_else(|| DateTime::<Utc>::from(SystemTime::UNIX_EPOCH))
+}
+
 fn traces_dir(data_dir: &Path) -> PathBuf {
     data_dir.join("traces")
 }
@@ -271,3 +428,158 @@ fn parse_trace_meta(path: &Path) -> Result<TraceMeta> {
 }
 
 // copy_dir_recursive was intentionally omitted to avoid counting existing files.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn write_trace(data_dir: &Path, id: &str, status: &str, rule: &str, timestamp: &str) {
+        let trace = json!({
+            "trace_id": id,
+            "status": status,
+            "timestamp": timestamp,
+            "rule": { "type": "endpoint", "name": rule },
+        });
+        std::fs::write(
+            traces_dir(data_dir).join(format!("{}.json", id)),
+            trace.to_string(),
+        )
+        .unwrap();
+    }
+
+    fn ids(traces: &[TraceMeta]) -> Vec<&str> {
+        traces.iter().map(|meta| meta.trace_id.as_str()).collect()
+    }
+
+    fn at(timestamp: &str) -> Option<DateTime<FixedOffset>> {
+        Some(DateTime::parse_from_rfc3339(timestamp).unwrap())
+    }
+
+    #[tokio::test]
+    async fn query_filters_by_status_rule_and_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TraceStore::new(dir.path().to_path_buf()).await.unwrap();
+        write_trace(dir.path(), "t1", "ok", "GET /a", "2026-01-01T00:00:00Z");
+        write_trace(dir.path(), "t2", "error", "GET /a", "2026-01-02T00:00:00Z");
+        write_trace(dir.path(), "t3", "error", "GET /b", "2026-01-03T00:00:00Z");
+        write_trace(
+            dir.path(),
+            "t4",
+            "error",
+            "GET /a",
+            "2026-01-04T09:00:00+09:00",
+        );
+
+        let errors = TraceFilter {
+            status: Some("error".to_string()),
+            ..TraceFilter::default()
+        };
+        assert_eq!(
+            ids(&store.query(&errors).await.unwrap()),
+            ["t4", "t3", "t2"]
+        );
+
+        let rule_a = TraceFilter {
+            rule_name: Some("GET /a".to_string()),
+            ..errors.clone()
+        };
+        assert_eq!(ids(&store.query(&rule_a).await.unwrap()), ["t4", "t2"]);
+
+        let window = TraceFilter {
+            from: at("2026-01-02T00:00:00Z"),
+            to: at("2026-01-04T00:00:00Z"),
+            ..TraceFilter::default()
+        };
+        assert_eq!(ids(&store.query(&window).await.unwrap()), ["t3", "t2"]);
+    }
+
+    fn write_dated_trace(data_dir: &Path, id: &str, timestamp: DateTime<Utc>) -> PathBuf {
+        let day_dir = traces_dir(data_dir).join(timestamp.format("%Y/%m/%d").to_string());
+        std::fs::create_dir_all(&day_dir).unwrap();
+        let path = day_dir.join(format!("{}.json", id));
+        let trace = json!({ "trace_id": id, "status": "ok", "timestamp": timestamp.to_rfc3339() });
+        std::fs::write(&path, trace.to_string()).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn prune_removes_old_traces_and_empty_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TraceStore::new(dir.path().to_path_buf()).await.unwrap();
+        let now = Utc::now();
+        let old = write_dated_trace(dir.path(), "old", now - chrono::Duration::days(40));
+        write_dated_trace(dir.path(), "recent", now - chrono::Duration::hours(1));
+        write_dated_trace(dir.path(), "newest", now);
+
+        let retention = TraceRetention {
+            max_age: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+            max_count: None,
+        };
+        assert_eq!(store.prune(&retention).await.unwrap(), 1);
+        assert_eq!(ids(&store.list().await.unwrap()), ["newest", "recent"]);
+        assert!(!old.parent().unwrap().exists());
+        assert!(traces_dir(dir.path()).exists());
+    }
+
+    #[tokio::test]
+    async fn prune_keeps_newest_traces_up_to_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TraceStore::new(dir.path().to_path_buf()).await.unwrap();
+        let now = Utc::now();
+        for hours in 0..4 {
+            write_dated_trace(
+                dir.path(),
+                &format!("t{}", hours),
+                now - chrono::Duration::hours(hours),
+            );
+        }
+
+        let retention = TraceRetention {
+            max_age: None,
+            max_count: Some(2),
+        };
+        assert_eq!(store.prune(&retention).await.unwrap(), 2);
+        assert_eq!(ids(&store.list().await.unwrap()), ["t0", "t1"]);
+        assert_eq!(store.prune(&retention).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn prune_keeps_everything_for_an_unrepresentable_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TraceStore::new(dir.path().to_path_buf()).await.unwrap();
+        write_dated_trace(dir.path(), "old", Utc::now() - chrono::Duration::days(400));
+
+        let retention = TraceRetention {
+            max_age: Some(Duration::MAX),
+            max_count: None,
+        };
+        assert_eq!(store.prune(&retention).await.unwrap(), 0);
+        assert_eq!(ids(&store.list().await.unwrap()), ["old"]);
+    }
+
+    #[tokio::test]
+    async fn query_paginates_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TraceStore::new(dir.path().to_path_buf()).await.unwrap();
+        for day in 1..=5 {
+            write_trace(
+                dir.path(),
+                &format!("t{}", day),
+                "ok",
+                "GET /a",
+                &format!("2026-01-0{}T00:00:00Z", day),
+            );
+        }
+
+        let page = |offset| TraceFilter {
+            limit: Some(2),
+            offset,
+            ..TraceFilter::default()
+        };
+        assert_eq!(ids(&store.query(&page(0)).await.unwrap()), ["t5", "t4"]);
+        assert_eq!(ids(&store.query(&page(2)).await.unwrap()), ["t3", "t2"]);
+        assert_eq!(ids(&store.query(&page(4)).await.unwrap()), ["t1"]);
+        assert!(store.query(&page(6)).await.unwrap().is_empty());
+    }
+}